@@ -0,0 +1,154 @@
+use crate::index_type::*;
+use crate::FreeList;
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::{Deref, DerefMut};
+
+/// A free list whose slots are aligned to (at least) the alignment `A`.
+///
+/// Every element is stored at an address that is a multiple of the alignment, and
+/// adjacent slots never share a block of that size. This allows using elements with
+/// aligned SIMD loads, and with [`CacheLine`] alignment avoids false sharing between
+/// elements that are accessed from different threads. The same works for the other
+/// lists, e.g. `AtomicFreeList<Aligned<T, CacheLine>>`.
+///
+/// ## Example
+/// ```rust
+/// use free_list::{AlignedFreeList, CacheLine};
+///
+/// let mut list = AlignedFreeList::<u32, CacheLine>::default();
+/// let first = list.push(1.into());
+/// let second = list.push(2.into());
+/// assert_eq!(*list[second], 2);
+///
+/// let address = |index| &list[index] as *const _ as usize;
+/// assert_eq!(address(first) % 64, 0);
+/// assert!(address(second).abs_diff(address(first)) >= 64);
+/// ```
+pub type AlignedFreeList<T, A = CacheLine, TIndex = DefaultIndex> = FreeList<Aligned<T, A>, TIndex>;
+
+/// A value that is aligned to (at least) the alignment `A`.
+///
+/// The alignment is chosen with one of the marker types [`Align16`], [`Align32`],
+/// [`Align64`] or [`Align128`]; the size of the wrapper is rounded up to a multiple of it.
+#[derive(Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(C)]
+pub struct Aligned<T, A: Alignment = CacheLine> {
+    /// Raises the alignment of the wrapper without taking any space.
+    _align: [A; 0],
+    value: T,
+}
+
+/// A marker type that specifies an alignment for [`Aligned`].
+///
+/// This trait is sealed; the alignment is the alignment of the implementing type.
+pub trait Alignment: private::Sealed + Copy + Default + Ord + std::hash::Hash {}
+
+mod private {
+    pub trait Sealed {}
+}
+
+macro_rules! alignments {
+    ($($(#[$meta:meta])* $name:ident = $align:literal;)*) => {
+        $(
+            $(#[$meta])*
+            #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+            #[repr(align($align))]
+            pub struct $name;
+
+            impl private::Sealed for $name {}
+            impl Alignment for $name {}
+        )*
+    };
+}
+
+alignments! {
+    /// Aligns values to 16 bytes, e.g. for SSE loads.
+    Align16 = 16;
+    /// Aligns values to 32 bytes, e.g. for AVX loads.
+    Align32 = 32;
+    /// Aligns values to 64 bytes, the size of a cache line on most current CPUs.
+    Align64 = 64;
+    /// Aligns values to 128 bytes, e.g. for CPUs that prefetch cache lines in pairs.
+    Align128 = 128;
+}
+
+/// The alignment of a cache line on most current CPUs.
+pub type CacheLine = Align64;
+
+impl<T, A: Alignment> Aligned<T, A> {
+    /// Wraps a value.
+    #[inline]
+    pub const fn new(value: T) -> Self {
+        Self { _align: [], value }
+    }
+
+    /// Unwraps the value.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T, A: Alignment> From<T> for Aligned<T, A> {
+    #[inline]
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T, A: Alignment> Deref for Aligned<T, A> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<T, A: Alignment> DerefMut for Aligned<T, A> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
+
+impl<T: Debug, A: Alignment> Debug for Aligned<T, A> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&self.value, f)
+    }
+}
+
+impl<T: Display, A: Alignment> Display for Aligned<T, A> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.value, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AtomicFreeList;
+    use std::mem::{align_of, size_of};
+
+    #[test]
+    fn slots_are_aligned_and_padded() {
+        assert_eq!(align_of::<Aligned<u8, Align16>>(), 16);
+        assert_eq!(size_of::<Aligned<u8, Align32>>(), 32);
+        assert_eq!(size_of::<Aligned<[u8; 65], Align64>>(), 128);
+        assert_eq!(align_of::<Aligned<u128, Align128>>(), 128);
+
+        let mut list = AlignedFreeList::<u16, Align32, u8>::default();
+        let indices: Vec<_> = (0..4).map(|value| list.push(value.into())).collect();
+        for index in indices {
+            assert_eq!(&list[index] as *const _ as usize % 32, 0);
+        }
+
+        // The slots of the concurrent list are padded as a whole.
+        let atomic = AtomicFreeList::<Aligned<u8>>::with_capacity(2);
+        let first = atomic.push(Aligned::new(1)).unwrap();
+        let second = atomic.push(Aligned::new(2)).unwrap();
+        let address = |index| unsafe { atomic.get(index).unwrap() } as *const _ as usize;
+        assert_eq!(address(first) % 64, 0);
+        assert!(address(second).abs_diff(address(first)) >= 64);
+    }
+}
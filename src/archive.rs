@@ -0,0 +1,219 @@
+use crate::index_type::*;
+use crate::{Allocator, FreeList, Global};
+use rkyv::munge::munge;
+use rkyv::option::ArchivedOption;
+use rkyv::rancor::Fallible;
+use rkyv::ser::{Allocator as SerializerAllocator, Writer};
+use rkyv::vec::{ArchivedVec, VecResolver};
+use rkyv::with::{Inline, Map, With};
+use rkyv::{Archive, Deserialize, Place, Portable, Serialize};
+
+/// The archived form of a [`FreeList`], which can be queried without deserializing it.
+///
+/// Every slot of the list is archived, so elements keep their indices and can be
+/// looked up in constant time. The free chain and the slot generations are not part
+/// of the archive; they are rebuilt when the list is deserialized.
+///
+/// ## Example
+/// ```rust
+/// use free_list::FreeList;
+///
+/// let mut list = FreeList::<u32, u8>::default();
+/// list.push(1);
+/// list.push(2);
+/// list.push(3);
+/// list.erase(1);
+///
+/// let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&list).unwrap();
+/// let archived = rkyv::access::<free_list::ArchivedFreeList<rkyv::Archived<u32>>, rkyv::rancor::Error>(
+///     &bytes,
+/// )
+/// .unwrap();
+/// assert_eq!(archived.get(0).map(|value| value.to_native()), Some(1));
+/// assert_eq!(archived.get(1), None);
+/// assert_eq!(archived.len(), 2);
+///
+/// let restored: FreeList<u32, u8> =
+///     rkyv::deserialize::<_, rkyv::rancor::Error>(archived).unwrap();
+/// assert_eq!(restored.iter().collect::<Vec<_>>(), [(0, &1), (2, &3)]);
+/// ```
+#[derive(Portable, rkyv::bytecheck::CheckBytes)]
+#[bytecheck(crate = rkyv::bytecheck)]
+#[repr(C)]
+pub struct ArchivedFreeList<T> {
+    /// The archived slots; vacant slots are archived as `None`.
+    slots: ArchivedVec<ArchivedOption<T>>,
+    /// The number of occupied slots.
+    len: rkyv::primitive::ArchivedUsize,
+}
+
+impl<T> ArchivedFreeList<T> {
+    /// Gets a reference to the element at the specified index, or `None` if the
+    /// slot was not occupied.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.slots.get(index)?.as_ref()
+    }
+
+    /// Determines whether the slot at the specified index was occupied.
+    #[inline]
+    pub fn contains(&self, index: usize) -> bool {
+        self.get(index).is_some()
+    }
+
+    /// Gets the number of elements.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len.to_native() as usize
+    }
+
+    /// Determines whether the archive contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Gets the number of archived slots, including vacant ones.
+    #[inline]
+    pub fn slot_count(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Gets an iterator over the indices and values of all elements, in ascending index order.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &T)> + '_ {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(index, slot)| Some((index, slot.as_ref()?)))
+    }
+}
+
+impl<T, TIndex, A> FreeList<T, TIndex, A>
+where
+    T: Default,
+    TIndex: IndexType,
+    A: Allocator,
+{
+    /// Gets the element of every slot, or `None` for vacant slots.
+    fn slot_refs(&self) -> Vec<Option<&T>> {
+        (0..self.slot_count())
+            .map(|index| {
+                self.occupied
+                    .contains(index)
+                    .then(|| unsafe { &**self.element(index) })
+            })
+            .collect()
+    }
+}
+
+impl<T, TIndex, A> Archive for FreeList<T, TIndex, A>
+where
+    T: Default + Archive,
+    TIndex: IndexType,
+    A: Allocator,
+{
+    type Archived = ArchivedFreeList<T::Archived>;
+    type Resolver = VecResolver;
+
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        munge!(let ArchivedFreeList { slots, len } = out);
+        ArchivedVec::resolve_from_len(self.slot_count(), resolver, slots);
+        self.len().resolve((), len);
+    }
+}
+
+impl<T, TIndex, A, S> Serialize<S> for FreeList<T, TIndex, A>
+where
+    T: Default + Serialize<S>,
+    TIndex: IndexType,
+    A: Allocator,
+    S: Fallible + SerializerAllocator + Writer + ?Sized,
+{
+    fn serialize(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+        let slots = self.slot_refs();
+        ArchivedVec::serialize_from_iter::<With<Option<&T>, Map<Inline>>, _, _>(
+            slots.iter().map(With::cast),
+            serializer,
+        )
+    }
+}
+
+impl<T, TIndex, D> Deserialize<FreeList<T, TIndex, Global>, D> for ArchivedFreeList<T::Archived>
+where
+    T: Default + Archive,
+    T::Archived: Deserialize<T, D>,
+    TIndex: IndexType,
+    D: Fallible + ?Sized,
+{
+    /// Restores the list with all elements at their original indices.
+    ///
+    /// ## Panics
+    /// Panics if the archive has more slots than `TIndex` can address.
+    fn deserialize(&self, deserializer: &mut D) -> Result<FreeList<T, TIndex, Global>, D::Error> {
+        let mut list = FreeList::default();
+        let mut vacant = Vec::new();
+        for slot in self.slots.iter() {
+            match slot.as_ref() {
+                Some(element) => {
+                    list.push(element.deserialize(deserializer)?);
+                }
+                None => vacant.push(list.push(T::default())),
+            }
+        }
+
+        // Erasing in descending order leaves the lowest index at the front of the free chain.
+        for index in vacant.into_iter().rev() {
+            list.erase(index);
+        }
+        Ok(list)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rkyv::rancor::Error;
+
+    #[test]
+    fn archived_lists_keep_their_indices() {
+        let mut list = FreeList::<String, u16>::default();
+        for i in 0..10 {
+            list.push(i.to_string());
+        }
+        for i in [0, 3, 9] {
+            list.erase(i);
+        }
+
+        let bytes = rkyv::to_bytes::<Error>(&list).unwrap();
+        let archived =
+            rkyv::access::<ArchivedFreeList<rkyv::string::ArchivedString>, Error>(&bytes).unwrap();
+        assert_eq!(archived.len(), 7);
+        assert_eq!(archived.slot_count(), 10);
+        assert!(!archived.contains(3));
+        assert_eq!(archived.get(4).map(|s| s.as_str()), Some("4"));
+        assert_eq!(
+            archived.iter().map(|(i, _)| i).collect::<Vec<_>>(),
+            [1, 2, 4, 5, 6, 7, 8]
+        );
+
+        let mut restored: FreeList<String, u16> = rkyv::deserialize::<_, Error>(archived).unwrap();
+        restored.check_invariants().unwrap();
+        assert_eq!(restored.len(), 7);
+        for (index, element) in list.iter() {
+            assert_eq!(restored.get(index), Some(element));
+        }
+        assert_eq!(restored.push("new".into()), 0);
+    }
+
+    #[test]
+    fn corrupted_archives_are_rejected() {
+        let mut list = FreeList::<u32, u8>::default();
+        list.push(1);
+        list.push(2);
+
+        let mut bytes = rkyv::to_bytes::<Error>(&list).unwrap();
+        // The tag of the first slot is neither `None` nor `Some`.
+        bytes[0] = 7;
+        assert!(rkyv::access::<ArchivedFreeList<u32>, Error>(&bytes).is_err());
+    }
+}
@@ -0,0 +1,333 @@
+use crate::index_type::*;
+use crate::{FreeList, Key, ParseKeyError};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
+
+/// A typed arena built on [`FreeList`] that only exposes safe operations.
+///
+/// Values are addressed by [`Id`]s, which combine the slot index with the slot
+/// generation and carry the value type. An id can therefore neither be used with an
+/// arena of a different type nor refer to a value that replaced a removed one, and
+/// ids cannot be built from or turned into plain indices.
+///
+/// ## Example
+/// ```rust
+/// use free_list::Arena;
+///
+/// let mut arena = Arena::<&str>::new();
+/// let first = arena.alloc("first");
+/// arena.alloc("second");
+/// assert_eq!(arena[first], "first");
+///
+/// assert_eq!(arena.remove(first), Some("first"));
+/// arena.alloc("third");
+/// assert_eq!(arena.get(first), None);
+/// assert_eq!(arena.iter().map(|(_, value)| *value).collect::<Vec<_>>(), ["third", "second"]);
+/// ```
+pub struct Arena<T, TIndex = DefaultIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    list: FreeList<T, TIndex>,
+}
+
+/// The id of a value in an [`Arena`].
+pub struct Id<T, TIndex = DefaultIndex>
+where
+    TIndex: IndexType,
+{
+    key: Key<TIndex>,
+    _type: PhantomData<fn() -> T>,
+}
+
+impl<T, TIndex> Id<T, TIndex>
+where
+    TIndex: IndexType,
+{
+    fn new(key: Key<TIndex>) -> Self {
+        Self {
+            key,
+            _type: PhantomData,
+        }
+    }
+}
+
+impl<T, TIndex> Clone for Id<T, TIndex>
+where
+    TIndex: IndexType,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, TIndex> Copy for Id<T, TIndex> where TIndex: IndexType {}
+
+impl<T, TIndex> PartialEq for Id<T, TIndex>
+where
+    TIndex: IndexType,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl<T, TIndex> Eq for Id<T, TIndex> where TIndex: IndexType {}
+
+impl<T, TIndex> PartialOrd for Id<T, TIndex>
+where
+    TIndex: IndexType,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T, TIndex> Ord for Id<T, TIndex>
+where
+    TIndex: IndexType,
+{
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+impl<T, TIndex> Hash for Id<T, TIndex>
+where
+    TIndex: IndexType,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key.index().into_usize().hash(state);
+        self.key.generation().hash(state);
+    }
+}
+
+impl<T, TIndex> fmt::Debug for Id<T, TIndex>
+where
+    TIndex: IndexType,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Id")
+            .field("index", &self.key.index())
+            .field("generation", &self.key.generation())
+            .finish()
+    }
+}
+
+/// Ids are displayed like their [`Key`], as `index@generation`.
+impl<T, TIndex> fmt::Display for Id<T, TIndex>
+where
+    TIndex: IndexType,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.key, f)
+    }
+}
+
+impl<T, TIndex> FromStr for Id<T, TIndex>
+where
+    TIndex: IndexType,
+{
+    type Err = ParseKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self::new)
+    }
+}
+
+impl<T, TIndex> Default for Arena<T, TIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, TIndex> Arena<T, TIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    /// Creates an empty arena.
+    pub fn new() -> Self {
+        Self {
+            list: FreeList::default(),
+        }
+    }
+
+    /// Moves a value into the arena and returns its id.
+    ///
+    /// ## Panics
+    /// Panics if the index type cannot address any more values.
+    pub fn alloc(&mut self, value: T) -> Id<T, TIndex> {
+        assert!(
+            self.list.len() < TIndex::MAX.into_usize() - 1,
+            "Attempted to insert more elements than can be addressed by the provided index type ({:?} allowed)",
+            TIndex::MAX
+        );
+        Id::new(self.list.push_key(value))
+    }
+
+    /// Gets a reference to the value of the specified id, or `None` if it was removed.
+    pub fn get(&self, id: Id<T, TIndex>) -> Option<&T> {
+        if self.list.contains_key(id.key) {
+            self.list.get(id.key.index())
+        } else {
+            None
+        }
+    }
+
+    /// Gets a mutable reference to the value of the specified id, or `None` if it was removed.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::Arena;
+    ///
+    /// let mut arena = Arena::<u32>::new();
+    /// let id = arena.alloc(1);
+    /// *arena.get_mut(id).unwrap() += 1;
+    /// assert_eq!(arena[id], 2);
+    /// ```
+    pub fn get_mut(&mut self, id: Id<T, TIndex>) -> Option<&mut T> {
+        if self.list.contains_key(id.key) {
+            self.list.get_mut(id.key.index())
+        } else {
+            None
+        }
+    }
+
+    /// Determines whether the specified id refers to a value.
+    #[inline]
+    pub fn contains(&self, id: Id<T, TIndex>) -> bool {
+        self.list.contains_key(id.key)
+    }
+
+    /// Removes the value of the specified id and returns it, or returns `None` if it
+    /// was already removed.
+    pub fn remove(&mut self, id: Id<T, TIndex>) -> Option<T> {
+        if self.list.contains_key(id.key) {
+            self.list.try_remove(id.key.index())
+        } else {
+            None
+        }
+    }
+
+    /// Gets the number of values in the arena.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Determines whether the arena contains no values.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Removes all values. Ids issued before remain invalid.
+    pub fn clear(&mut self) {
+        self.list.clear();
+    }
+
+    /// Gets an iterator over the ids and values of all entries, in slot order.
+    pub fn iter(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = (Id<T, TIndex>, &T)> + ExactSizeIterator + '_ {
+        self.list
+            .iter()
+            .map(|(index, value)| (self.id(index), value))
+    }
+
+    /// Gets an iterator over the ids and mutable values of all entries, in slot order.
+    pub fn iter_mut(
+        &mut self,
+    ) -> impl DoubleEndedIterator<Item = (Id<T, TIndex>, &mut T)> + ExactSizeIterator + '_ {
+        let ids: Vec<_> = self.list.keys().map(|index| self.id(index)).collect();
+        ids.into_iter()
+            .zip(self.list.iter_mut())
+            .map(|(id, (_, value))| (id, value))
+    }
+
+    /// Gets the id of the occupied slot at the specified index.
+    fn id(&self, index: TIndex) -> Id<T, TIndex> {
+        Id::new(self.list.key(index).expect("iterated slots are occupied"))
+    }
+}
+
+impl<T, TIndex> Index<Id<T, TIndex>> for Arena<T, TIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    type Output = T;
+
+    /// Gets a reference to the value of the specified id.
+    ///
+    /// ## Panics
+    /// Panics if the value was removed.
+    fn index(&self, id: Id<T, TIndex>) -> &Self::Output {
+        self.get(id)
+            .unwrap_or_else(|| panic!("Attempted to access a removed value ({id:?})"))
+    }
+}
+
+impl<T, TIndex> IndexMut<Id<T, TIndex>> for Arena<T, TIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    /// Gets a mutable reference to the value of the specified id.
+    ///
+    /// ## Panics
+    /// Panics if the value was removed.
+    fn index_mut(&mut self, id: Id<T, TIndex>) -> &mut Self::Output {
+        self.get_mut(id)
+            .unwrap_or_else(|| panic!("Attempted to access a removed value ({id:?})"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stale_ids_are_rejected() {
+        let mut arena = Arena::<String, u8>::new();
+        let a = arena.alloc("a".into());
+        let b = arena.alloc("b".into());
+        assert_eq!(arena.remove(a).as_deref(), Some("a"));
+        assert_eq!(arena.remove(a), None);
+
+        let c = arena.alloc("c".into());
+        assert_ne!(a, c);
+        assert!(!arena.contains(a));
+        assert_eq!(arena[c], "c");
+
+        for (_, value) in arena.iter_mut() {
+            value.push('!');
+        }
+        assert_eq!(
+            arena.iter().collect::<Vec<_>>(),
+            [(c, &"c!".to_string()), (b, &"b!".to_string())]
+        );
+
+        arena.clear();
+        assert!(arena.is_empty());
+        assert_eq!(arena.get(b), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn indexing_a_removed_value_panics() {
+        let mut arena = Arena::<u32, u8>::new();
+        let id = arena.alloc(1);
+        arena.remove(id);
+        let _ = arena[id];
+    }
+}
@@ -0,0 +1,528 @@
+use crate::index_type::*;
+use crate::macros::safety_check;
+use crate::{FreeElement, SlotStatus};
+use std::iter::{Enumerate, FusedIterator};
+use std::marker::PhantomData;
+use std::mem::ManuallyDrop;
+
+/// An indexed free list with a fixed capacity that does not allocate.
+///
+/// All `N` slots are stored inline, which makes this type suitable for embedded
+/// environments and hot paths where heap allocations are undesirable. Indices are
+/// handed out and reused exactly like in [`FreeList`](crate::FreeList).
+///
+/// ## Type parameters
+/// * `T` - The type of the element.
+/// * `N` - The number of slots. At most `TIndex::MAX - 1` slots can be used.
+/// * `TIndex` - The type of the index; see [`FreeList`](crate::FreeList) for details.
+pub struct ArrayFreeList<T, const N: usize, TIndex = DefaultIndex>
+where
+    TIndex: IndexType,
+{
+    /// The slots.
+    data: [FreeElement<T, TIndex>; N],
+    /// Whether the slot at the same position currently holds an element.
+    occupied: [bool; N],
+    /// The number of slots that were handed out so far.
+    slots: usize,
+    /// The index of the the most recently freed element, or `SENTINEL` if no
+    /// element is free.
+    first_free: TIndex,
+}
+
+impl<T, const N: usize, TIndex> Default for ArrayFreeList<T, N, TIndex>
+where
+    TIndex: IndexType,
+{
+    /// Creates an empty list.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::ArrayFreeList;
+    ///
+    /// let list = ArrayFreeList::<&str, 4, u8>::default();
+    /// assert_eq!(list.slot_count(), 0);
+    /// ```
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize, TIndex> ArrayFreeList<T, N, TIndex>
+where
+    TIndex: IndexType,
+{
+    /// The sentinel value indicates the absence of a valid value.
+    pub(crate) const SENTINEL: TIndex = TIndex::MAX;
+
+    /// Creates an empty list.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::ArrayFreeList;
+    ///
+    /// let list = ArrayFreeList::<&str, 4, u8>::new();
+    /// assert!(!list.is_full());
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            data: std::array::from_fn(|_| FreeElement {
+                next: Self::SENTINEL,
+            }),
+            occupied: [false; N],
+            slots: 0,
+            first_free: Self::SENTINEL,
+        }
+    }
+
+    /// Inserts an element to the list and returns an index to it.
+    ///
+    /// ## Errors
+    /// Returns the element back if all slots are occupied.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::ArrayFreeList;
+    ///
+    /// let mut list = ArrayFreeList::<&str, 2, u8>::new();
+    /// assert_eq!(list.push("first"), Ok(0));
+    /// assert_eq!(list.push("second"), Ok(1));
+    /// assert_eq!(list.push("third"), Err("third"));
+    ///
+    /// list.erase(0);
+    /// assert_eq!(list.push("third"), Ok(0));
+    /// ```
+    pub fn push(&mut self, element: T) -> Result<TIndex, T> {
+        if self.first_free != Self::SENTINEL {
+            let index = self.first_free;
+            let index_usize = index.into_usize();
+
+            // Set the "first free" pointer to the next free index.
+            self.first_free = unsafe { self.data[index_usize].next };
+
+            // Place the element into the previously free location.
+            self.data[index_usize].element = ManuallyDrop::new(element);
+            self.occupied[index_usize] = true;
+            Ok(index)
+        } else {
+            let index = self.slots;
+            if index >= N || index >= Self::SENTINEL.into_usize() - 1 {
+                return Err(element);
+            }
+
+            self.slots += 1;
+            self.data[index].element = ManuallyDrop::new(element);
+            self.occupied[index] = true;
+            Ok(index_from_usize(index))
+        }
+    }
+
+    /// Removes the nth element from the list.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::ArrayFreeList;
+    ///
+    /// let mut list = ArrayFreeList::<&str, 1, u8>::new();
+    /// list.push("first").unwrap();
+    /// assert!(list.is_full());
+    ///
+    /// list.erase(0);
+    /// assert!(!list.is_full());
+    /// ```
+    pub fn erase(&mut self, n: TIndex) {
+        safety_check!(
+            self.status(n).is_occupied(),
+            "Attempted to erase a slot that is not occupied ({:?})",
+            n
+        );
+        drop(self.remove(n));
+    }
+
+    /// Removes the nth element from the list and returns it, or returns `None` if the
+    /// slot is not occupied.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::ArrayFreeList;
+    ///
+    /// let mut list = ArrayFreeList::<&str, 2, u8>::new();
+    /// list.push("first").unwrap();
+    /// assert_eq!(list.remove(0), Some("first"));
+    /// assert_eq!(list.remove(0), None);
+    /// assert_eq!(list.push("second"), Ok(0));
+    /// ```
+    pub fn remove(&mut self, n: TIndex) -> Option<T> {
+        if !self.status(n).is_occupied() {
+            return None;
+        }
+
+        // SAFETY: The slot is occupied and is linked into the free chain right away.
+        let n_usize = n.into_usize();
+        let element = unsafe { ManuallyDrop::take(&mut self.data[n_usize].element) };
+        self.data[n_usize].next = self.first_free;
+        self.occupied[n_usize] = false;
+        self.first_free = n;
+        Some(element)
+    }
+
+    /// Removes all elements from the list.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::ArrayFreeList;
+    ///
+    /// let mut list = ArrayFreeList::<&str, 4, u8>::new();
+    /// list.push("first").unwrap();
+    /// list.clear();
+    /// assert_eq!(list.slot_count(), 0);
+    /// ```
+    pub fn clear(&mut self) {
+        // The list is emptied before the elements are dropped, so that a panicking
+        // destructor neither leaves dropped elements in the list nor drops them twice.
+        let slots = std::mem::take(&mut self.slots);
+        self.first_free = Self::SENTINEL;
+        ClearGuard {
+            data: &mut self.data[..slots],
+            occupied: &mut self.occupied[..slots],
+            next: 0,
+        }
+        .drop_elements();
+    }
+
+    /// Gets a reference to the value at the specified index.
+    ///
+    /// # Safety
+    ///
+    /// If the element at the specified index was erased, the slot now acts
+    /// as a pointer to the next free element. Accessing the same index again after that
+    /// is undefined behavior.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::ArrayFreeList;
+    ///
+    /// let mut list = ArrayFreeList::<&str, 4, u8>::new();
+    /// list.push("first").unwrap();
+    /// assert_eq!(unsafe { list.at(0) }, &"first");
+    /// ```
+    #[inline]
+    pub unsafe fn at(&self, index: TIndex) -> &T {
+        safety_check!(
+            self.status(index).is_occupied(),
+            "Attempted to access a slot that is not occupied ({:?})",
+            index
+        );
+        &self.data[index.into_usize()].element
+    }
+
+    /// Gets a mutable reference to the value at the specified index.
+    ///
+    /// # Safety
+    ///
+    /// If the element at the specified index was erased, the slot now acts
+    /// as a pointer to the next free element. Accessing the same index again after that
+    /// is undefined behavior.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::ArrayFreeList;
+    ///
+    /// let mut list = ArrayFreeList::<&str, 4, u8>::new();
+    /// list.push("first").unwrap();
+    /// *unsafe { list.at_mut(0) } = "changed";
+    /// assert_eq!(unsafe { list.at(0) }, &"changed");
+    /// ```
+    #[inline]
+    pub unsafe fn at_mut(&mut self, index: TIndex) -> &mut T {
+        safety_check!(
+            self.status(index).is_occupied(),
+            "Attempted to access a slot that is not occupied ({:?})",
+            index
+        );
+        &mut self.data[index.into_usize()].element
+    }
+
+    /// Determines the state of the slot at the specified index.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::{ArrayFreeList, SlotStatus};
+    ///
+    /// let mut list = ArrayFreeList::<&str, 4, u8>::new();
+    /// list.push("first").unwrap();
+    /// list.push("second").unwrap();
+    /// list.erase(0);
+    ///
+    /// assert_eq!(list.status(0), SlotStatus::Free);
+    /// assert_eq!(list.status(1), SlotStatus::Occupied);
+    /// assert_eq!(list.status(2), SlotStatus::OutOfRange);
+    /// ```
+    pub fn status(&self, index: TIndex) -> SlotStatus {
+        let index = index.into_usize();
+        if index >= self.slots {
+            SlotStatus::OutOfRange
+        } else if self.occupied[index] {
+            SlotStatus::Occupied
+        } else {
+            SlotStatus::Free
+        }
+    }
+
+    /// Gets an iterator over the indices of all occupied slots, in ascending order.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::ArrayFreeList;
+    ///
+    /// let mut list = ArrayFreeList::<&str, 4, u8>::new();
+    /// list.push("first").unwrap();
+    /// list.push("second").unwrap();
+    /// list.erase(0);
+    /// assert_eq!(list.keys().collect::<Vec<_>>(), [1]);
+    /// ```
+    pub fn keys(&self) -> ArrayKeys<'_, TIndex> {
+        let occupied = &self.occupied[..self.slots];
+        ArrayKeys {
+            remaining: occupied.iter().filter(|&&occupied| occupied).count(),
+            occupied: occupied.iter().enumerate(),
+            _index: PhantomData,
+        }
+    }
+
+    /// Gets the number of slots that were handed out so far.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::ArrayFreeList;
+    ///
+    /// let mut list = ArrayFreeList::<&str, 4, u8>::new();
+    /// list.push("first").unwrap();
+    /// list.erase(0);
+    /// assert_eq!(list.slot_count(), 1);
+    /// ```
+    pub fn slot_count(&self) -> usize {
+        self.slots
+    }
+
+    /// Gets the number of slots the list can hold, i.e. `N`.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Determines whether all slots are occupied, i.e. whether the next
+    /// [`push`](Self::push) would fail.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::ArrayFreeList;
+    ///
+    /// let mut list = ArrayFreeList::<&str, 1, u8>::new();
+    /// assert!(!list.is_full());
+    /// list.push("first").unwrap();
+    /// assert!(list.is_full());
+    /// ```
+    pub fn is_full(&self) -> bool {
+        self.first_free == Self::SENTINEL
+            && (self.slots >= N || self.slots >= Self::SENTINEL.into_usize() - 1)
+    }
+}
+
+impl<T, const N: usize, TIndex> Drop for ArrayFreeList<T, N, TIndex>
+where
+    TIndex: IndexType,
+{
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+/// Drops the elements of the slots of a cleared [`ArrayFreeList`] in ascending index
+/// order; if a destructor panics, the remaining elements are dropped while unwinding.
+struct ClearGuard<'a, T, TIndex>
+where
+    TIndex: IndexType,
+{
+    data: &'a mut [FreeElement<T, TIndex>],
+    occupied: &'a mut [bool],
+    /// The lowest slot that may still hold an element.
+    next: usize,
+}
+
+impl<T, TIndex> ClearGuard<'_, T, TIndex>
+where
+    TIndex: IndexType,
+{
+    /// Drops all remaining elements.
+    fn drop_elements(&mut self) {
+        while self.next < self.data.len() {
+            let index = self.next;
+            self.next += 1;
+            // The slot is marked as free first, so its element is never dropped twice.
+            if std::mem::take(&mut self.occupied[index]) {
+                unsafe { ManuallyDrop::drop(&mut self.data[index].element) };
+            }
+        }
+    }
+}
+
+impl<T, TIndex> Drop for ClearGuard<'_, T, TIndex>
+where
+    TIndex: IndexType,
+{
+    fn drop(&mut self) {
+        self.drop_elements();
+    }
+}
+
+/// An iterator over the indices of all occupied slots of an [`ArrayFreeList`],
+/// in ascending order.
+///
+/// This type is created by [`ArrayFreeList::keys`].
+#[derive(Debug, Clone)]
+pub struct ArrayKeys<'a, TIndex>
+where
+    TIndex: IndexType,
+{
+    occupied: Enumerate<std::slice::Iter<'a, bool>>,
+    /// The number of not yet yielded indices.
+    remaining: usize,
+    _index: PhantomData<TIndex>,
+}
+
+impl<'a, TIndex> Iterator for ArrayKeys<'a, TIndex>
+where
+    TIndex: IndexType,
+{
+    type Item = TIndex;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (index, _) = self.occupied.find(|(_, &occupied)| occupied)?;
+        self.remaining -= 1;
+        Some(index_from_usize(index))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, TIndex> DoubleEndedIterator for ArrayKeys<'a, TIndex>
+where
+    TIndex: IndexType,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (index, _) = self.occupied.rfind(|(_, &occupied)| occupied)?;
+        self.remaining -= 1;
+        Some(index_from_usize(index))
+    }
+}
+
+impl<'a, TIndex> ExactSizeIterator for ArrayKeys<'a, TIndex> where TIndex: IndexType {}
+
+impl<'a, TIndex> FusedIterator for ArrayKeys<'a, TIndex> where TIndex: IndexType {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn push_until_full() {
+        let mut list = ArrayFreeList::<u32, 3, u8>::new();
+        assert_eq!(list.push(1), Ok(0));
+        assert_eq!(list.push(2), Ok(1));
+        assert_eq!(list.push(3), Ok(2));
+        assert!(list.is_full());
+        assert_eq!(list.push(4), Err(4));
+    }
+
+    #[test]
+    fn capacity_is_limited_by_index_type() {
+        let mut list = ArrayFreeList::<u32, 300, u8>::new();
+        for i in 0..254 {
+            assert!(list.push(i).is_ok());
+        }
+        assert!(list.is_full());
+        assert_eq!(list.push(254), Err(254));
+    }
+
+    #[test]
+    fn erase_reuses_slots_in_lifo_order() {
+        let mut list = ArrayFreeList::<u32, 4, u8>::new();
+        for i in 0..4 {
+            list.push(i).unwrap();
+        }
+
+        list.erase(1);
+        list.erase(3);
+        assert_eq!(list.keys().collect::<Vec<_>>(), vec![0, 2]);
+        assert_eq!(list.push(10), Ok(3));
+        assert_eq!(list.push(11), Ok(1));
+        assert_eq!(unsafe { *list.at(1) }, 11);
+    }
+
+    #[test]
+    fn remove_moves_elements_out_and_frees_their_slots() {
+        #[derive(Debug, PartialEq)]
+        struct NoDefault(u32);
+
+        let mut list = ArrayFreeList::<NoDefault, 2, u8>::new();
+        list.push(NoDefault(1)).unwrap();
+        list.push(NoDefault(2)).unwrap();
+        assert_eq!(list.remove(1), Some(NoDefault(2)));
+        assert_eq!(list.remove(1), None);
+        assert_eq!(list.remove(2), None);
+        assert_eq!(list.status(1), SlotStatus::Free);
+        assert_eq!(list.push(NoDefault(3)), Ok(1));
+    }
+
+    #[test]
+    fn clear_and_drop_drop_live_elements() {
+        let value = Rc::new(());
+        let mut list = ArrayFreeList::<Rc<()>, 4, u8>::new();
+        for _ in 0..3 {
+            list.push(value.clone()).unwrap();
+        }
+        list.erase(0);
+        assert_eq!(Rc::strong_count(&value), 3);
+
+        list.clear();
+        assert_eq!(Rc::strong_count(&value), 1);
+
+        list.push(value.clone()).unwrap();
+        drop(list);
+        assert_eq!(Rc::strong_count(&value), 1);
+    }
+
+    #[test]
+    fn clear_drops_every_element_once_if_a_destructor_panics() {
+        use std::cell::Cell;
+
+        struct Bomb(u32, Rc<Cell<u32>>);
+
+        impl Drop for Bomb {
+            fn drop(&mut self) {
+                self.1.set(self.1.get() + 1);
+                if self.0 == 0 {
+                    panic!("destructor failed");
+                }
+            }
+        }
+
+        let drops = Rc::new(Cell::new(0));
+        let mut list = ArrayFreeList::<Bomb, 4, u8>::new();
+        for value in 0..3 {
+            list.push(Bomb(value, drops.clone())).ok().unwrap();
+        }
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| list.clear()));
+        assert!(result.is_err());
+        assert_eq!(drops.get(), 3);
+        assert_eq!(list.slot_count(), 0);
+
+        drop(list);
+        assert_eq!(drops.get(), 3);
+    }
+}
@@ -0,0 +1,429 @@
+use crate::PagedFreeList;
+use std::collections::VecDeque;
+use std::fmt::{Debug, Formatter};
+use std::future::Future;
+use std::ops::{Deref, DerefMut};
+use std::pin::Pin;
+use std::ptr::NonNull;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::task::{Context, Poll, Waker};
+
+/// A thread-safe object pool that holds at most a fixed number of values, e.g. database
+/// connections or large buffers, and lets tasks wait asynchronously for a value.
+///
+/// [`acquire`](Self::acquire) hands out an idle value, creates a new one while the pool
+/// is below its capacity, or waits until a value is returned by dropping its
+/// [`AsyncPoolGuard`] or discarded with [`AsyncPoolGuard::discard`]. Waiting tasks are
+/// served in the order in which they started waiting.
+///
+/// The pool does not depend on a particular runtime; its futures only use the
+/// [`Waker`] of their context, so they can be awaited on tokio, async-std or any other
+/// executor.
+///
+/// Values are stored in a [`PagedFreeList`], so they never move while guards to them exist.
+///
+/// ## Example
+/// ```rust
+/// use free_list::AsyncPool;
+///
+/// let pool = AsyncPool::new(2, || Vec::<u8>::with_capacity(4096));
+/// let first = pool.try_acquire().unwrap();
+/// let second = pool.try_acquire().unwrap();
+/// assert!(pool.try_acquire().is_none());
+///
+/// // Returning a value makes it available again.
+/// drop(first);
+/// assert!(pool.try_acquire().is_some());
+///
+/// async fn handle_request(pool: &AsyncPool<Vec<u8>>) {
+///     let mut buffer = pool.acquire().await;
+///     buffer.clear();
+/// }
+/// ```
+pub struct AsyncPool<T> {
+    state: Mutex<AsyncPoolState<T>>,
+    /// The maximum number of values.
+    capacity: usize,
+    /// Creates a value if none is idle and the pool is below its capacity.
+    create: Box<dyn Fn() -> T + Send + Sync>,
+}
+
+struct AsyncPoolState<T> {
+    /// All values owned by the pool, whether checked out or not.
+    values: PagedFreeList<T, usize>,
+    /// The number of values in `values`.
+    len: usize,
+    /// The indices of all values that are currently checked in, in LIFO order.
+    idle: Vec<usize>,
+    /// The tasks waiting for a value, in the order in which they started waiting.
+    waiters: VecDeque<Waiter>,
+    /// The identifier of the next waiting task.
+    next_waiter: u64,
+}
+
+/// A task waiting in [`AsyncPool::acquire`].
+struct Waiter {
+    id: u64,
+    waker: Waker,
+    /// Whether the task was woken since it last polled.
+    notified: bool,
+}
+
+/// A value checked out from an [`AsyncPool`].
+///
+/// The value is returned to the pool when the guard is dropped, which wakes the next
+/// waiting task.
+pub struct AsyncPoolGuard<'a, T> {
+    pool: &'a AsyncPool<T>,
+    value: NonNull<T>,
+    index: usize,
+}
+
+// SAFETY: The guard has exclusive access to its value, like a `&mut T`.
+unsafe impl<T: Send> Send for AsyncPoolGuard<'_, T> {}
+unsafe impl<T: Sync> Sync for AsyncPoolGuard<'_, T> {}
+
+/// The future returned by [`AsyncPool::acquire`].
+pub struct Acquire<'a, T> {
+    pool: &'a AsyncPool<T>,
+    /// The identifier under which the task waits, if it waited at all.
+    waiter: Option<u64>,
+    /// Whether a value was handed out.
+    done: bool,
+}
+
+impl<T> AsyncPool<T> {
+    /// Creates an empty pool that holds at most `capacity` values, which are created on
+    /// demand by the specified function.
+    ///
+    /// ## Panics
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize, create: impl Fn() -> T + Send + Sync + 'static) -> Self {
+        assert_ne!(capacity, 0, "At least one value is required");
+        Self {
+            state: Mutex::new(AsyncPoolState {
+                values: PagedFreeList::default(),
+                len: 0,
+                idle: Vec::new(),
+                waiters: VecDeque::new(),
+                next_waiter: 0,
+            }),
+            capacity,
+            create: Box::new(create),
+        }
+    }
+
+    /// Borrows a value from the pool, waiting until one is available.
+    pub fn acquire(&self) -> Acquire<'_, T> {
+        Acquire {
+            pool: self,
+            waiter: None,
+            done: false,
+        }
+    }
+
+    /// Borrows a value from the pool, or returns `None` if all values are checked out
+    /// and the pool is at its capacity.
+    ///
+    /// Tasks waiting in [`acquire`](Self::acquire) do not take precedence.
+    pub fn try_acquire(&self) -> Option<AsyncPoolGuard<'_, T>> {
+        let mut state = self.lock();
+        self.checkout(&mut state)
+    }
+
+    /// Gets the maximum number of values.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Gets the number of values owned by the pool, including checked out ones.
+    pub fn len(&self) -> usize {
+        self.lock().len
+    }
+
+    /// Determines whether the pool owns no values.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Gets the number of values that are available without creating a new one.
+    pub fn idle_count(&self) -> usize {
+        self.lock().idle.len()
+    }
+
+    /// Gets the number of tasks waiting for a value.
+    pub fn waiting_count(&self) -> usize {
+        self.lock().waiters.len()
+    }
+
+    /// Hands out an idle or new value, if possible.
+    fn checkout(&self, state: &mut AsyncPoolState<T>) -> Option<AsyncPoolGuard<'_, T>> {
+        let index = match state.idle.pop() {
+            Some(index) => index,
+            None if state.len < self.capacity => {
+                state.len += 1;
+                state.values.push((self.create)())
+            }
+            None => return None,
+        };
+
+        // SAFETY: The paged storage never moves the value, and the pool only
+        // erases values that are not checked out.
+        let value = NonNull::from(unsafe { state.values.at_mut(index) });
+        Some(AsyncPoolGuard {
+            pool: self,
+            value,
+            index,
+        })
+    }
+
+    /// Locks the state, ignoring poisoning since the pool is never left in an inconsistent state.
+    fn lock(&self) -> MutexGuard<'_, AsyncPoolState<T>> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T> AsyncPoolState<T> {
+    /// Gets the position of a waiting task in the queue.
+    fn position(&self, id: u64) -> Option<usize> {
+        self.waiters.iter().position(|waiter| waiter.id == id)
+    }
+
+    /// Wakes the task that has been waiting the longest and was not woken yet.
+    fn notify_one(&mut self) {
+        if let Some(waiter) = self.waiters.iter_mut().find(|waiter| !waiter.notified) {
+            waiter.notified = true;
+            waiter.waker.wake_by_ref();
+        }
+    }
+}
+
+impl<T> Debug for AsyncPool<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let state = self.lock();
+        f.debug_struct("AsyncPool")
+            .field("capacity", &self.capacity)
+            .field("len", &state.len)
+            .field("idle", &state.idle.len())
+            .field("waiting", &state.waiters.len())
+            .finish()
+    }
+}
+
+impl<'a, T> Future for Acquire<'a, T> {
+    type Output = AsyncPoolGuard<'a, T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let pool = self.pool;
+        let mut state = pool.lock();
+
+        // Values go to the task that has been waiting the longest.
+        let position = self.waiter.and_then(|id| state.position(id));
+        let first = match position {
+            Some(position) => position == 0,
+            None => state.waiters.is_empty(),
+        };
+        if first {
+            if let Some(guard) = pool.checkout(&mut state) {
+                if position.is_some() {
+                    state.waiters.pop_front();
+                }
+                // Another value may have been returned while this task was first in line.
+                if !state.idle.is_empty() || state.len < pool.capacity {
+                    state.notify_one();
+                }
+                self.done = true;
+                return Poll::Ready(guard);
+            }
+        }
+
+        match position {
+            Some(position) => {
+                let waiter = &mut state.waiters[position];
+                waiter.waker.clone_from(cx.waker());
+                waiter.notified = false;
+            }
+            None => {
+                let id = state.next_waiter;
+                state.next_waiter += 1;
+                state.waiters.push_back(Waiter {
+                    id,
+                    waker: cx.waker().clone(),
+                    notified: false,
+                });
+                self.waiter = Some(id);
+            }
+        }
+        Poll::Pending
+    }
+}
+
+impl<T> Drop for Acquire<'_, T> {
+    fn drop(&mut self) {
+        let Some(id) = self.waiter else {
+            return;
+        };
+        if self.done {
+            return;
+        }
+
+        let mut state = self.pool.lock();
+        let Some(position) = state.position(id) else {
+            return;
+        };
+        let waiter = state.waiters.remove(position).expect("position is valid");
+
+        // The task was woken for a value it will never take; pass the wakeup on.
+        if waiter.notified {
+            state.notify_one();
+        }
+    }
+}
+
+impl<T> Debug for Acquire<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Acquire")
+            .field("waiter", &self.waiter)
+            .field("done", &self.done)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a, T> AsyncPoolGuard<'a, T> {
+    /// Gets the index of the value within the pool.
+    #[inline]
+    pub fn index(this: &Self) -> usize {
+        this.index
+    }
+
+    /// Drops the value instead of returning it to the pool, e.g. because a connection
+    /// broke, so that a new value is created in its place.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::{AsyncPool, AsyncPoolGuard};
+    ///
+    /// let pool = AsyncPool::new(1, String::new);
+    /// let value = pool.try_acquire().unwrap();
+    /// AsyncPoolGuard::discard(value);
+    /// assert!(pool.is_empty());
+    /// assert!(pool.try_acquire().is_some());
+    /// ```
+    pub fn discard(this: Self) {
+        let this = std::mem::ManuallyDrop::new(this);
+        let mut state = this.pool.lock();
+        state.values.erase(this.index);
+        state.len -= 1;
+        state.notify_one();
+    }
+}
+
+impl<T> Deref for AsyncPoolGuard<'_, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: The guard has exclusive access to the value until it is dropped.
+        unsafe { self.value.as_ref() }
+    }
+}
+
+impl<T> DerefMut for AsyncPoolGuard<'_, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // SAFETY: The guard has exclusive access to the value until it is dropped.
+        unsafe { self.value.as_mut() }
+    }
+}
+
+impl<T> Drop for AsyncPoolGuard<'_, T> {
+    fn drop(&mut self) {
+        let mut state = self.pool.lock();
+        state.idle.push(self.index);
+        state.notify_one();
+    }
+}
+
+impl<T: Debug> Debug for AsyncPoolGuard<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AsyncPoolGuard")
+            .field("index", &self.index)
+            .field("value", &**self)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::task::Wake;
+
+    /// Counts how often it was woken.
+    #[derive(Default)]
+    struct CountingWaker(AtomicUsize);
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    fn poll<F: Future>(future: &mut Pin<Box<F>>, waker: &Arc<CountingWaker>) -> Poll<F::Output> {
+        let waker = Waker::from(waker.clone());
+        future.as_mut().poll(&mut Context::from_waker(&waker))
+    }
+
+    #[test]
+    fn waiters_are_woken_in_order() {
+        let pool = AsyncPool::new(1, || 0u32);
+        let mut held = pool.try_acquire().unwrap();
+        *held = 7;
+
+        let (first_waker, second_waker) = Default::default();
+        let mut first = Box::pin(pool.acquire());
+        let mut second = Box::pin(pool.acquire());
+        assert!(poll(&mut first, &first_waker).is_pending());
+        assert!(poll(&mut second, &second_waker).is_pending());
+        assert_eq!(pool.waiting_count(), 2);
+
+        drop(held);
+        assert_eq!(first_waker.0.load(Ordering::SeqCst), 1);
+        assert_eq!(second_waker.0.load(Ordering::SeqCst), 0);
+
+        // The second task cannot overtake the first one.
+        assert!(poll(&mut second, &second_waker).is_pending());
+        let Poll::Ready(guard) = poll(&mut first, &first_waker) else {
+            panic!("the first task should get the value");
+        };
+        assert_eq!(*guard, 7);
+
+        // Discarding the value lets the second task create a new one.
+        AsyncPoolGuard::discard(guard);
+        assert_eq!(second_waker.0.load(Ordering::SeqCst), 1);
+        let Poll::Ready(guard) = poll(&mut second, &second_waker) else {
+            panic!("the second task should get a new value");
+        };
+        assert_eq!(*guard, 0);
+    }
+
+    #[test]
+    fn cancelled_waiters_pass_their_wakeup_on() {
+        let pool = AsyncPool::new(1, String::new);
+        let held = pool.try_acquire().unwrap();
+
+        let (first_waker, second_waker) = Default::default();
+        let mut first = Box::pin(pool.acquire());
+        let mut second = Box::pin(pool.acquire());
+        assert!(poll(&mut first, &first_waker).is_pending());
+        assert!(poll(&mut second, &second_waker).is_pending());
+
+        drop(held);
+        drop(first);
+        assert_eq!(second_waker.0.load(Ordering::SeqCst), 1);
+        assert!(poll(&mut second, &second_waker).is_ready());
+        assert_eq!(pool.waiting_count(), 0);
+    }
+}
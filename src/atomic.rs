@@ -0,0 +1,649 @@
+use std::cell::UnsafeCell;
+use std::fmt::{Debug, Formatter};
+use std::mem::MaybeUninit;
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicU8, AtomicUsize, Ordering};
+
+/// A fixed-capacity free list that can be modified concurrently without locking.
+///
+/// All slots are reserved up front and linked into a free chain whose head is
+/// maintained as a lock-free (Treiber) stack. This allows [`push`](Self::push) and
+/// [`erase`](Self::erase) to be called from multiple threads at the same time.
+///
+/// The head of the free chain is tagged with a modification counter to prevent
+/// the ABA problem. Indices are `u32` values; at most `u32::MAX - 1` slots can be reserved.
+///
+/// ## Concurrent readers
+/// Readers that [`pin`](Self::pin) the list can access elements safely while other
+/// threads [`retire`](Self::retire) them. Retired elements are dropped and their slots
+/// recycled only once every reader that might still see them has dropped its
+/// [`EpochGuard`]. This uses epoch-based reclamation: the list keeps a global epoch that
+/// advances whenever no reader is pinned to the previous epoch, and an element retired in
+/// one epoch is reclaimed two epochs later.
+pub struct AtomicFreeList<T> {
+    /// The slots.
+    slots: Box<[AtomicSlot<T>]>,
+    /// The index of the first free slot in the lower 32 bits and a modification
+    /// tag in the upper 32 bits.
+    head: AtomicU64,
+    /// The number of live elements in the list.
+    length: AtomicUsize,
+    /// The global epoch.
+    epoch: AtomicUsize,
+    /// The number of readers pinned to an even and an odd epoch.
+    readers: [AtomicUsize; 2],
+    /// The index of the most recently retired slot that was not reclaimed yet;
+    /// retired slots are linked through their `next` field.
+    retired: AtomicU32,
+}
+
+/// A reader pinned to an [`AtomicFreeList`], created by [`AtomicFreeList::pin`].
+///
+/// Elements obtained through the guard stay valid until the guard is dropped, even if
+/// they are retired in the meantime.
+pub struct EpochGuard<'a, T> {
+    list: &'a AtomicFreeList<T>,
+    /// The parity of the epoch the reader is pinned to.
+    parity: usize,
+}
+
+/// A single slot of an [`AtomicFreeList`].
+struct AtomicSlot<T> {
+    /// The element; only initialized if the state is [`AtomicSlot::OCCUPIED`].
+    value: UnsafeCell<MaybeUninit<T>>,
+    /// The index of the next free slot, or `SENTINEL` if this is the last free slot.
+    next: AtomicU32,
+    /// The state of the slot.
+    state: AtomicU8,
+    /// The epoch in which the element was retired; only valid if the state is
+    /// [`AtomicSlot::RETIRED`].
+    retired_at: AtomicUsize,
+}
+
+impl<T> AtomicSlot<T> {
+    /// The slot is part of the free chain.
+    const FREE: u8 = 0;
+    /// The slot was taken from the free chain and is being written or erased.
+    const BUSY: u8 = 1;
+    /// The slot holds an element.
+    const OCCUPIED: u8 = 2;
+    /// The slot holds a retired element that may still be read.
+    const RETIRED: u8 = 3;
+}
+
+unsafe impl<T: Send> Send for AtomicFreeList<T> {}
+unsafe impl<T: Send + Sync> Sync for AtomicFreeList<T> {}
+
+impl<T> AtomicFreeList<T> {
+    /// The sentinel value indicates the absence of a valid index.
+    const SENTINEL: u32 = u32::MAX;
+
+    /// Creates a list with the specified number of pre-linked free slots.
+    ///
+    /// ## Panics
+    /// Panics if `capacity` is `u32::MAX` or larger.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::AtomicFreeList;
+    ///
+    /// let list = AtomicFreeList::<&str>::with_capacity(16);
+    /// assert_eq!(list.capacity(), 16);
+    /// assert_eq!(list.len(), 0);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        assert!(
+            capacity < Self::SENTINEL as usize,
+            "Attempted to reserve more slots than can be addressed ({:?} allowed)",
+            Self::SENTINEL - 1
+        );
+
+        let slots = (0..capacity)
+            .map(|index| AtomicSlot {
+                value: UnsafeCell::new(MaybeUninit::uninit()),
+                next: AtomicU32::new(if index + 1 < capacity {
+                    index as u32 + 1
+                } else {
+                    Self::SENTINEL
+                }),
+                state: AtomicU8::new(AtomicSlot::<T>::FREE),
+                retired_at: AtomicUsize::new(0),
+            })
+            .collect();
+
+        let first_free = if capacity > 0 { 0 } else { Self::SENTINEL };
+        Self {
+            slots,
+            head: AtomicU64::new(first_free as u64),
+            length: AtomicUsize::new(0),
+            epoch: AtomicUsize::new(0),
+            readers: [AtomicUsize::new(0), AtomicUsize::new(0)],
+            retired: AtomicU32::new(Self::SENTINEL),
+        }
+    }
+
+    /// Inserts an element into a free slot and returns the index of the slot.
+    ///
+    /// ## Errors
+    /// Returns the element back if all slots are occupied.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::AtomicFreeList;
+    ///
+    /// let list = AtomicFreeList::with_capacity(1);
+    /// assert_eq!(list.push("first"), Ok(0));
+    /// assert_eq!(list.push("second"), Err("second"));
+    /// ```
+    pub fn push(&self, element: T) -> Result<u32, T> {
+        let index = match self.pop_free() {
+            Some(index) => index,
+            None => {
+                // Retired slots may have become reclaimable.
+                self.collect();
+                let Some(index) = self.pop_free() else {
+                    return Err(element);
+                };
+                index
+            }
+        };
+
+        let slot = &self.slots[index as usize];
+        debug_assert_eq!(slot.state.load(Ordering::Relaxed), AtomicSlot::<T>::BUSY);
+
+        // SAFETY: The slot was removed from the free chain, so this thread has exclusive access.
+        unsafe { (*slot.value.get()).write(element) };
+        slot.state
+            .store(AtomicSlot::<T>::OCCUPIED, Ordering::Release);
+        self.length.fetch_add(1, Ordering::Relaxed);
+        Ok(index)
+    }
+
+    /// Removes the element at the specified index and returns it, or returns `None`
+    /// if the slot is not occupied.
+    ///
+    /// If multiple threads attempt to erase the same index concurrently, only one of
+    /// them obtains the element.
+    ///
+    /// The element is moved out right away; use [`retire`](Self::retire) to remove
+    /// elements that pinned readers may still access.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that no reference to the element is alive, neither one
+    /// obtained through an [`EpochGuard`] nor one returned by [`get`](Self::get).
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::AtomicFreeList;
+    ///
+    /// let list = AtomicFreeList::with_capacity(4);
+    /// let index = list.push("first").unwrap();
+    ///
+    /// // SAFETY: There are no readers.
+    /// assert_eq!(unsafe { list.erase(index) }, Some("first"));
+    /// assert_eq!(unsafe { list.erase(index) }, None);
+    /// ```
+    pub unsafe fn erase(&self, index: u32) -> Option<T> {
+        let slot = self.slots.get(index as usize)?;
+        slot.state
+            .compare_exchange(
+                AtomicSlot::<T>::OCCUPIED,
+                AtomicSlot::<T>::BUSY,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            )
+            .ok()?;
+
+        // SAFETY: The state transition grants exclusive access to the initialized value,
+        // and the caller guarantees that no reader refers to it.
+        let element = unsafe { (*slot.value.get()).assume_init_read() };
+        self.length.fetch_sub(1, Ordering::Relaxed);
+        self.push_free(index);
+        Some(element)
+    }
+
+    /// Removes the element at the specified index once no pinned reader can access it
+    /// anymore, and returns whether the slot was occupied.
+    ///
+    /// The element immediately stops being visible to new readers, but it is only dropped
+    /// and its slot only reused after all readers pinned at this time have dropped their
+    /// guards. If multiple threads attempt to retire the same index concurrently, only
+    /// one of them succeeds.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::AtomicFreeList;
+    ///
+    /// let list = AtomicFreeList::with_capacity(1);
+    /// let index = list.push(String::from("first")).unwrap();
+    ///
+    /// let guard = list.pin();
+    /// let value = guard.get(index).unwrap();
+    /// assert!(list.retire(index));
+    ///
+    /// // The element stays alive for the pinned reader, so its slot is not reused yet.
+    /// assert_eq!(value, "first");
+    /// assert!(guard.get(index).is_none());
+    /// assert!(list.push(String::from("second")).is_err());
+    ///
+    /// drop(guard);
+    /// assert_eq!(list.push(String::from("second")), Ok(index));
+    /// ```
+    pub fn retire(&self, index: u32) -> bool {
+        let Some(slot) = self.slots.get(index as usize) else {
+            return false;
+        };
+        if slot
+            .state
+            .compare_exchange(
+                AtomicSlot::<T>::OCCUPIED,
+                AtomicSlot::<T>::RETIRED,
+                Ordering::SeqCst,
+                Ordering::Relaxed,
+            )
+            .is_err()
+        {
+            return false;
+        }
+
+        // Readers pinned after this point cannot observe the element anymore.
+        slot.retired_at
+            .store(self.epoch.load(Ordering::SeqCst), Ordering::Relaxed);
+        self.length.fetch_sub(1, Ordering::Relaxed);
+        self.push_retired(index);
+        self.collect();
+        true
+    }
+
+    /// Pins the current thread as a reader, so that elements obtained through the guard
+    /// are not reclaimed until it is dropped.
+    ///
+    /// Pinned readers delay the reclamation of retired elements, so guards should be
+    /// short-lived.
+    pub fn pin(&self) -> EpochGuard<'_, T> {
+        loop {
+            let epoch = self.epoch.load(Ordering::SeqCst);
+            let parity = epoch % 2;
+            self.readers[parity].fetch_add(1, Ordering::SeqCst);
+            if self.epoch.load(Ordering::SeqCst) == epoch {
+                return EpochGuard { list: self, parity };
+            }
+
+            // The epoch advanced in between; pin to the new one instead.
+            self.readers[parity].fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Advances the epoch if possible and reclaims all retired slots that no pinned
+    /// reader can access anymore.
+    ///
+    /// This happens automatically on [`retire`](Self::retire) and when
+    /// [`push`](Self::push) finds no free slot.
+    pub fn collect(&self) {
+        // Readers pinned to the previous epoch share the parity of the next one.
+        let epoch = self.epoch.load(Ordering::SeqCst);
+        if self.readers[(epoch + 1) % 2].load(Ordering::SeqCst) == 0 {
+            let _ =
+                self.epoch
+                    .compare_exchange(epoch, epoch + 1, Ordering::SeqCst, Ordering::Relaxed);
+        }
+
+        let epoch = self.epoch.load(Ordering::SeqCst);
+        let mut index = self.retired.swap(Self::SENTINEL, Ordering::Acquire);
+        while index != Self::SENTINEL {
+            let slot = &self.slots[index as usize];
+            let next = slot.next.load(Ordering::Relaxed);
+            if slot.retired_at.load(Ordering::Relaxed) + 2 <= epoch {
+                // SAFETY: No reader pinned before the element was retired is left, and
+                // readers pinned afterwards cannot observe it.
+                unsafe { (*slot.value.get()).assume_init_drop() };
+                self.push_free(index);
+            } else {
+                self.push_retired(index);
+            }
+            index = next;
+        }
+    }
+
+    /// Gets a reference to the value at the specified index, or `None` if the slot
+    /// is not occupied.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that the element is neither erased nor retired by another
+    /// thread while the returned reference is alive. [`EpochGuard::get`] provides the same
+    /// access safely, also with respect to concurrent calls to [`retire`](Self::retire).
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::AtomicFreeList;
+    ///
+    /// let list = AtomicFreeList::with_capacity(4);
+    /// let index = list.push("first").unwrap();
+    /// assert_eq!(unsafe { list.get(index) }, Some(&"first"));
+    /// ```
+    pub unsafe fn get(&self, index: u32) -> Option<&T> {
+        let slot = self.slots.get(index as usize)?;
+        if slot.state.load(Ordering::Acquire) == AtomicSlot::<T>::OCCUPIED {
+            Some((*slot.value.get()).assume_init_ref())
+        } else {
+            None
+        }
+    }
+
+    /// Gets a mutable reference to the value at the specified index, or `None` if
+    /// the slot is not occupied.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::AtomicFreeList;
+    ///
+    /// let mut list = AtomicFreeList::with_capacity(4);
+    /// let index = list.push("first").unwrap();
+    /// *list.get_mut(index).unwrap() = "changed";
+    /// assert_eq!(unsafe { list.erase(index) }, Some("changed"));
+    /// ```
+    pub fn get_mut(&mut self, index: u32) -> Option<&mut T> {
+        let slot = self.slots.get_mut(index as usize)?;
+        if *slot.state.get_mut() == AtomicSlot::<T>::OCCUPIED {
+            Some(unsafe { slot.value.get_mut().assume_init_mut() })
+        } else {
+            None
+        }
+    }
+
+    /// Gets the number of live elements.
+    ///
+    /// In the presence of concurrent modifications, the value is only a snapshot.
+    pub fn len(&self) -> usize {
+        self.length.load(Ordering::Relaxed)
+    }
+
+    /// Determines whether the list contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Gets the number of reserved slots.
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Takes the first slot from the free chain and marks it as busy.
+    fn pop_free(&self) -> Option<u32> {
+        let mut head = self.head.load(Ordering::Acquire);
+        loop {
+            let index = head as u32;
+            if index == Self::SENTINEL {
+                return None;
+            }
+
+            // Slots are never deallocated, so reading the link of a slot that
+            // was concurrently taken is harmless; the tag detects the change.
+            let next = self.slots[index as usize].next.load(Ordering::Relaxed);
+            let new_head = Self::tagged(next, head);
+            match self.head.compare_exchange_weak(
+                head,
+                new_head,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => {
+                    self.slots[index as usize]
+                        .state
+                        .store(AtomicSlot::<T>::BUSY, Ordering::Relaxed);
+                    return Some(index);
+                }
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    /// Places a busy slot back into the free chain.
+    fn push_free(&self, index: u32) {
+        let slot = &self.slots[index as usize];
+        slot.state.store(AtomicSlot::<T>::FREE, Ordering::Relaxed);
+
+        let mut head = self.head.load(Ordering::Relaxed);
+        loop {
+            slot.next.store(head as u32, Ordering::Relaxed);
+            let new_head = Self::tagged(index, head);
+            match self.head.compare_exchange_weak(
+                head,
+                new_head,
+                Ordering::Release,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return,
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    /// Places a retired slot into the list of slots waiting to be reclaimed.
+    fn push_retired(&self, index: u32) {
+        let slot = &self.slots[index as usize];
+        let mut head = self.retired.load(Ordering::Relaxed);
+        loop {
+            slot.next.store(head, Ordering::Relaxed);
+            // Slots are only ever pushed onto this list or taken all at once, so the
+            // ABA problem cannot occur.
+            match self.retired.compare_exchange_weak(
+                head,
+                index,
+                Ordering::Release,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return,
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    /// Combines the specified index with the incremented tag of the previous head.
+    #[inline]
+    fn tagged(index: u32, previous_head: u64) -> u64 {
+        let tag = (previous_head >> 32).wrapping_add(1);
+        (tag << 32) | index as u64
+    }
+}
+
+impl<T> Drop for AtomicFreeList<T> {
+    fn drop(&mut self) {
+        for slot in self.slots.iter_mut() {
+            let state = *slot.state.get_mut();
+            if state == AtomicSlot::<T>::OCCUPIED || state == AtomicSlot::<T>::RETIRED {
+                unsafe { slot.value.get_mut().assume_init_drop() };
+            }
+        }
+    }
+}
+
+impl<'a, T> EpochGuard<'a, T> {
+    /// Gets a reference to the value at the specified index, or `None` if the slot
+    /// is not occupied.
+    ///
+    /// The reference stays valid while the guard is alive, even if the element is
+    /// retired concurrently, but it cannot outlive the guard:
+    /// ```compile_fail
+    /// use free_list::AtomicFreeList;
+    ///
+    /// let list = AtomicFreeList::with_capacity(1);
+    /// let index = list.push(String::from("first")).unwrap();
+    /// let value = {
+    ///     let guard = list.pin();
+    ///     guard.get(index).unwrap()
+    /// };
+    /// list.retire(index);
+    /// assert_eq!(value, "first");
+    /// ```
+    pub fn get(&self, index: u32) -> Option<&T> {
+        let slot = self.list.slots.get(index as usize)?;
+        if slot.state.load(Ordering::SeqCst) == AtomicSlot::<T>::OCCUPIED {
+            // SAFETY: The element was visible after the guard was pinned, so it is not
+            // reclaimed before the guard is dropped.
+            Some(unsafe { (*slot.value.get()).assume_init_ref() })
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> Drop for EpochGuard<'_, T> {
+    fn drop(&mut self) {
+        self.list.readers[self.parity].fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl<T> Debug for EpochGuard<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EpochGuard")
+            .field("parity", &self.parity)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::sync::Arc;
+
+    #[test]
+    fn reuses_slots_in_lifo_order() {
+        let list = AtomicFreeList::with_capacity(3);
+        assert_eq!(list.push(1), Ok(0));
+        assert_eq!(list.push(2), Ok(1));
+        assert_eq!(unsafe { list.erase(0) }, Some(1));
+        assert_eq!(list.push(3), Ok(0));
+        assert_eq!(list.push(4), Ok(2));
+        assert_eq!(list.push(5), Err(5));
+        assert_eq!(list.len(), 3);
+    }
+
+    #[test]
+    fn zero_capacity_is_always_full() {
+        let list = AtomicFreeList::with_capacity(0);
+        assert_eq!(list.push(1), Err(1));
+        assert_eq!(unsafe { list.erase(0) }, None);
+    }
+
+    #[test]
+    fn drop_drops_live_elements() {
+        let value = Arc::new(());
+        let list = AtomicFreeList::with_capacity(4);
+        list.push(value.clone()).unwrap();
+        list.push(value.clone()).unwrap();
+        drop(unsafe { list.erase(0) });
+        assert_eq!(Arc::strong_count(&value), 2);
+        drop(list);
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+
+    #[test]
+    fn concurrent_push_and_erase() {
+        const THREADS: usize = 8;
+        const ROUNDS: usize = 1000;
+
+        let list = AtomicFreeList::with_capacity(THREADS * 2);
+        std::thread::scope(|scope| {
+            for thread in 0..THREADS {
+                let list = &list;
+                scope.spawn(move || {
+                    for round in 0..ROUNDS {
+                        let value = thread * ROUNDS + round;
+                        let a = list.push(value).unwrap();
+                        let b = list.push(value + 1).unwrap();
+                        assert_ne!(a, b);
+                        // SAFETY: Every thread only erases its own elements and nobody reads them.
+                        assert_eq!(unsafe { list.erase(a) }, Some(value));
+                        assert_eq!(unsafe { list.erase(b) }, Some(value + 1));
+                    }
+                });
+            }
+        });
+
+        assert!(list.is_empty());
+
+        // All slots must have been returned to the free chain exactly once.
+        let mut indices = HashSet::new();
+        for _ in 0..THREADS * 2 {
+            assert!(indices.insert(list.push(0).unwrap()));
+        }
+        assert!(list.push(0).is_err());
+    }
+
+    #[test]
+    fn retired_elements_outlive_pinned_readers() {
+        let value = Arc::new(());
+        let list = AtomicFreeList::with_capacity(2);
+        let index = list.push(value.clone()).unwrap();
+
+        let guard = list.pin();
+        let element = guard.get(index).unwrap();
+        assert!(list.retire(index));
+        assert!(!list.retire(index));
+        assert!(list.is_empty());
+
+        // Neither further retirements nor collections may reclaim the element.
+        let other = list.push(value.clone()).unwrap();
+        assert!(list.retire(other));
+        list.collect();
+        list.collect();
+        assert!(Arc::ptr_eq(element, &value));
+        assert_eq!(Arc::strong_count(&value), 3);
+
+        drop(guard);
+        list.collect();
+        list.collect();
+        assert_eq!(Arc::strong_count(&value), 1);
+        assert!(list.push(value.clone()).is_ok());
+        assert!(list.push(value.clone()).is_ok());
+    }
+
+    #[test]
+    fn concurrent_get_and_retire() {
+        const THREADS: usize = 4;
+        const ROUNDS: usize = 1000;
+
+        let list = AtomicFreeList::with_capacity(THREADS);
+        let done = AtomicUsize::new(0);
+        std::thread::scope(|scope| {
+            for thread in 0..THREADS {
+                let (list, done) = (&list, &done);
+                scope.spawn(move || {
+                    for round in 0..ROUNDS {
+                        let value = vec![thread; round % 8 + 1];
+                        let index = loop {
+                            if let Ok(index) = list.push(value.clone()) {
+                                break index;
+                            }
+                            std::thread::yield_now();
+                        };
+                        assert!(list.retire(index));
+                    }
+                    done.fetch_add(1, Ordering::SeqCst);
+                });
+            }
+
+            // Readers verify that no element is reclaimed or overwritten while pinned.
+            for _ in 0..2 {
+                let (list, done) = (&list, &done);
+                scope.spawn(move || {
+                    while done.load(Ordering::SeqCst) < THREADS {
+                        let guard = list.pin();
+                        for index in 0..THREADS as u32 {
+                            if let Some(element) = guard.get(index) {
+                                let first = element[0];
+                                std::hint::spin_loop();
+                                assert!(element.iter().all(|&value| value == first));
+                            }
+                        }
+                    }
+                });
+            }
+        });
+
+        assert!(list.is_empty());
+    }
+}
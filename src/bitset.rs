@@ -0,0 +1,592 @@
+/// A compact, growable set of indices backed by a bitmap.
+///
+/// This is used to keep track of which slots of a list are currently occupied
+/// without having to walk the free chain.
+#[derive(Default, Debug, Clone)]
+pub(crate) struct BitSet {
+    /// The bits, in ascending order; bit `i` is stored in word `i / 64`.
+    words: Vec<u64>,
+    /// An optional Fenwick tree over the number of indices in each word, which allows to
+    /// compute ranks and select indices by rank in logarithmic time.
+    ranks: Option<Vec<usize>>,
+}
+
+impl BitSet {
+    /// The number of bits stored in each word.
+    const BITS: usize = u64::BITS as usize;
+
+    /// Creates a set from its words; bit `i` is stored in word `i / 64`.
+    pub fn from_words(words: Vec<u64>) -> Self {
+        Self { words, ranks: None }
+    }
+
+    /// Gets the words of the set; bit `i` is stored in word `i / 64`.
+    pub fn into_words(self) -> Vec<u64> {
+        self.words
+    }
+
+    /// Gets the words of the set; bit `i` is stored in word `i / 64`.
+    #[cfg_attr(not(feature = "bytemuck"), allow(dead_code))]
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// Determines whether the specified index is contained in the set.
+    #[inline]
+    pub fn contains(&self, index: usize) -> bool {
+        match self.words.get(index / Self::BITS) {
+            Some(word) => word & (1 << (index % Self::BITS)) != 0,
+            None => false,
+        }
+    }
+
+    /// Adds the specified index to the set, growing the bitmap if required.
+    #[inline]
+    pub fn insert(&mut self, index: usize) {
+        let word = index / Self::BITS;
+        while word >= self.words.len() {
+            self.words.push(0);
+            self.push_rank();
+        }
+        let bit = 1 << (index % Self::BITS);
+        if self.words[word] & bit == 0 {
+            self.words[word] |= bit;
+            self.add_rank(word, 1);
+        }
+    }
+
+    /// Removes the specified index from the set.
+    #[inline]
+    pub fn remove(&mut self, index: usize) {
+        let word = index / Self::BITS;
+        let bit = 1 << (index % Self::BITS);
+        if self.words.get(word).is_some_and(|&bits| bits & bit != 0) {
+            self.words[word] &= !bit;
+            self.add_rank(word, usize::MAX);
+        }
+    }
+
+    /// Enables or disables the rank index, which speeds up [`rank`](Self::rank) and
+    /// [`select`](Self::select) at the cost of updating it on every change.
+    pub fn set_rank_index(&mut self, enabled: bool) {
+        if !enabled {
+            self.ranks = None;
+        } else if self.ranks.is_none() {
+            self.ranks = Some(Vec::with_capacity(self.words.len()));
+            for _ in 0..self.words.len() {
+                self.push_rank();
+            }
+        }
+    }
+
+    /// Determines whether the rank index is enabled.
+    pub fn has_rank_index(&self) -> bool {
+        self.ranks.is_some()
+    }
+
+    /// Counts the indices in the set that are below `index`.
+    pub fn rank(&self, index: usize) -> usize {
+        let word = (index / Self::BITS).min(self.words.len());
+        let below = match &self.ranks {
+            Some(ranks) => Self::prefix(ranks, word),
+            None => self.words[..word]
+                .iter()
+                .map(|bits| bits.count_ones() as usize)
+                .sum(),
+        };
+        let mask = (1u64 << (index % Self::BITS)).wrapping_sub(1);
+        below + (self.words.get(word).copied().unwrap_or(0) & mask).count_ones() as usize
+    }
+
+    /// Finds the index in the set that has `rank` indices below it.
+    pub fn select(&self, mut rank: usize) -> Option<usize> {
+        let word = match &self.ranks {
+            Some(ranks) => {
+                // Descend the tree to the last word whose prefix count is at most `rank`.
+                let mut word = 0;
+                let mut step = ranks.len().checked_next_power_of_two()?;
+                while step > 0 {
+                    if word + step <= ranks.len() && ranks[word + step - 1] <= rank {
+                        word += step;
+                        rank -= ranks[word - 1];
+                    }
+                    step /= 2;
+                }
+                word
+            }
+            None => {
+                let mut word = 0;
+                while let Some(bits) = self.words.get(word) {
+                    let count = bits.count_ones() as usize;
+                    if rank < count {
+                        break;
+                    }
+                    rank -= count;
+                    word += 1;
+                }
+                word
+            }
+        };
+
+        let mut bits = *self.words.get(word)?;
+        if rank >= bits.count_ones() as usize {
+            return None;
+        }
+        for _ in 0..rank {
+            bits &= bits - 1;
+        }
+        Some(word * Self::BITS + bits.trailing_zeros() as usize)
+    }
+
+    /// Appends the count of the last word, which must be empty, to the rank index.
+    fn push_rank(&mut self) {
+        let Some(ranks) = &mut self.ranks else {
+            return;
+        };
+        // A node covers the words `(i - lowbit(i), i]`, 1-based.
+        let i = ranks.len() + 1;
+        let covered = Self::prefix(ranks, i - 1) - Self::prefix(ranks, i - (i & i.wrapping_neg()));
+        let count = self.words[i - 1].count_ones() as usize;
+        ranks.push(covered + count);
+    }
+
+    /// Adds `delta`, which wraps to subtract, to the count of the specified word.
+    fn add_rank(&mut self, word: usize, delta: usize) {
+        let Some(ranks) = &mut self.ranks else {
+            return;
+        };
+        let mut i = word + 1;
+        while i <= ranks.len() {
+            ranks[i - 1] = ranks[i - 1].wrapping_add(delta);
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    /// Sums the counts of the first `words` words.
+    fn prefix(ranks: &[usize], words: usize) -> usize {
+        let mut sum = 0;
+        let mut i = words;
+        while i > 0 {
+            sum += ranks[i - 1];
+            i &= i - 1;
+        }
+        sum
+    }
+
+    /// Finds the highest index below `index` that is not contained in the set.
+    pub fn last_zero_before(&self, index: usize) -> Option<usize> {
+        let mut word = index / Self::BITS;
+
+        // Only the bits below `index` are considered in its own word.
+        let mut mask = (1u64 << (index % Self::BITS)).wrapping_sub(1);
+        loop {
+            let zeros = !self.words.get(word).copied().unwrap_or(0) & mask;
+            if zeros != 0 {
+                let bit = Self::BITS - 1 - zeros.leading_zeros() as usize;
+                return Some(word * Self::BITS + bit);
+            }
+            if word == 0 {
+                return None;
+            }
+            word -= 1;
+            mask = u64::MAX;
+        }
+    }
+
+    /// Finds the highest index below `index` that is contained in the set.
+    pub fn last_one_before(&self, index: usize) -> Option<usize> {
+        let (mut word, mut mask) = if index / Self::BITS < self.words.len() {
+            // Only the bits below `index` are considered in its own word.
+            let mask = (1u64 << (index % Self::BITS)).wrapping_sub(1);
+            (index / Self::BITS, mask)
+        } else {
+            (self.words.len().checked_sub(1)?, u64::MAX)
+        };
+        loop {
+            let ones = self.words[word] & mask;
+            if ones != 0 {
+                let bit = Self::BITS - 1 - ones.leading_zeros() as usize;
+                return Some(word * Self::BITS + bit);
+            }
+            if word == 0 {
+                return None;
+            }
+            word -= 1;
+            mask = u64::MAX;
+        }
+    }
+
+    /// Finds the lowest index at or above `index` that is contained in the set.
+    pub fn next_one(&self, index: usize) -> Option<usize> {
+        let mut word = index / Self::BITS;
+        let mut bits = self.words.get(word)? & (u64::MAX << (index % Self::BITS));
+        while bits == 0 {
+            word += 1;
+            bits = *self.words.get(word)?;
+        }
+        Some(word * Self::BITS + bits.trailing_zeros() as usize)
+    }
+
+    /// Finds the lowest index at or above `index` that is not contained in the set.
+    pub fn next_zero(&self, index: usize) -> usize {
+        let mut word = index / Self::BITS;
+        let mut zeros =
+            !self.words.get(word).copied().unwrap_or(0) & (u64::MAX << (index % Self::BITS));
+        while zeros == 0 {
+            word += 1;
+            zeros = !self.words.get(word).copied().unwrap_or(0);
+        }
+        word * Self::BITS + zeros.trailing_zeros() as usize
+    }
+
+    /// Counts the indices contained in the set.
+    pub fn count(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Finds the highest index contained in the set.
+    pub fn last(&self) -> Option<usize> {
+        let (word, bits) = self
+            .words
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, &bits)| bits != 0)?;
+        Some(word * Self::BITS + Self::BITS - 1 - bits.leading_zeros() as usize)
+    }
+
+    /// Gets the number of bytes allocated for the bitmap.
+    pub fn memory_usage(&self) -> usize {
+        self.words.capacity() * std::mem::size_of::<u64>()
+            + self
+                .ranks
+                .as_ref()
+                .map_or(0, |ranks| ranks.capacity() * std::mem::size_of::<usize>())
+    }
+
+    /// Removes all indices from the set and frees the bitmap.
+    pub fn clear(&mut self) {
+        self.words = Vec::new();
+        if let Some(ranks) = &mut self.ranks {
+            *ranks = Vec::new();
+        }
+    }
+
+    /// Removes all indices from the set and returns them; the rank index stays enabled
+    /// if it was.
+    pub fn take(&mut self) -> Self {
+        Self {
+            words: std::mem::take(&mut self.words),
+            ranks: self.ranks.as_mut().map(std::mem::take),
+        }
+    }
+
+    /// Removes all indices from the set but keeps the bitmap allocated.
+    pub fn reset(&mut self) {
+        self.words.fill(0);
+        if let Some(ranks) = &mut self.ranks {
+            ranks.fill(0);
+        }
+    }
+
+    /// Iterates all indices contained in the set in ascending order.
+    pub fn ones(&self) -> Ones<'_> {
+        Ones {
+            words: &self.words,
+            offset: 0,
+            front: self.words.first().copied().unwrap_or(0),
+            back: self.words.last().copied().unwrap_or(0),
+            remaining: self.count(),
+        }
+    }
+
+    /// Iterates all indices contained in the set that are at or above `start`, in
+    /// ascending order.
+    pub fn ones_from(&self, start: usize) -> Ones<'_> {
+        let word = start / Self::BITS;
+        let Some(words) = self.words.get(word..).filter(|words| !words.is_empty()) else {
+            return Ones {
+                words: &[],
+                offset: 0,
+                front: 0,
+                back: 0,
+                remaining: 0,
+            };
+        };
+
+        let front = words[0] & (u64::MAX << (start % Self::BITS));
+        let remaining = front.count_ones() as usize
+            + words[1..]
+                .iter()
+                .map(|word| word.count_ones() as usize)
+                .sum::<usize>();
+        Ones {
+            words,
+            offset: word * Self::BITS,
+            front,
+            back: words[words.len() - 1],
+            remaining,
+        }
+    }
+}
+
+/// An iterator over the indices contained in a [`BitSet`], in ascending order.
+#[derive(Debug, Clone)]
+pub(crate) struct Ones<'a> {
+    /// The remaining words, from the one processed from the front to the one
+    /// processed from the back.
+    words: &'a [u64],
+    /// The index of the first bit of the first remaining word.
+    offset: usize,
+    /// The not yet yielded bits of the first word.
+    front: u64,
+    /// The not yet yielded bits of the last word; unused if only one word remains,
+    /// in which case `front` holds its bits.
+    back: u64,
+    /// The number of not yet yielded indices.
+    remaining: usize,
+}
+
+impl<'a> Iterator for Ones<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.front == 0 {
+            match self.words.len() {
+                0 | 1 => {
+                    self.words = &[];
+                    return None;
+                }
+                2 => self.front = self.back,
+                _ => self.front = self.words[1],
+            }
+            self.words = &self.words[1..];
+            self.offset += BitSet::BITS;
+        }
+
+        let bit = self.front.trailing_zeros() as usize;
+        self.front &= self.front - 1;
+        self.remaining -= 1;
+        Some(self.offset + bit)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a> DoubleEndedIterator for Ones<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            let last = match self.words.len() {
+                0 => return None,
+                1 => &mut self.front,
+                _ => &mut self.back,
+            };
+            if *last != 0 {
+                let bit = BitSet::BITS - 1 - last.leading_zeros() as usize;
+                *last &= !(1 << bit);
+                self.remaining -= 1;
+                return Some(self.offset + (self.words.len() - 1) * BitSet::BITS + bit);
+            }
+
+            if self.words.len() == 1 {
+                self.words = &[];
+                return None;
+            }
+            self.words = &self.words[..self.words.len() - 1];
+            if self.words.len() > 1 {
+                self.back = self.words[self.words.len() - 1];
+            }
+        }
+    }
+}
+
+impl<'a> ExactSizeIterator for Ones<'a> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_set_contains_nothing() {
+        let set = BitSet::default();
+        assert!(!set.contains(0));
+        assert!(!set.contains(1000));
+        assert_eq!(set.ones().count(), 0);
+    }
+
+    #[test]
+    fn insert_and_remove() {
+        let mut set = BitSet::default();
+        set.insert(3);
+        set.insert(64);
+        set.insert(200);
+        assert_eq!(set.ones().collect::<Vec<_>>(), vec![3, 64, 200]);
+        assert!(set.contains(64));
+        assert!(!set.contains(4));
+
+        set.remove(64);
+        set.remove(1000);
+        assert!(!set.contains(64));
+        assert_eq!(set.ones().collect::<Vec<_>>(), vec![3, 200]);
+    }
+
+    #[test]
+    fn ones_skips_empty_words() {
+        let mut set = BitSet::default();
+        set.insert(0);
+        set.insert(63);
+        set.insert(300);
+        assert_eq!(set.ones().collect::<Vec<_>>(), vec![0, 63, 300]);
+    }
+
+    #[test]
+    fn reset_keeps_the_allocation() {
+        let mut set = BitSet::default();
+        set.insert(100);
+        let usage = set.memory_usage();
+        set.reset();
+        assert!(!set.contains(100));
+        assert_eq!(set.count(), 0);
+        assert_eq!(set.memory_usage(), usage);
+    }
+
+    #[test]
+    fn clear_works() {
+        let mut set = BitSet::default();
+        set.insert(10);
+        set.clear();
+        assert_eq!(set.ones().count(), 0);
+    }
+
+    #[test]
+    fn last_zero_before_skips_full_words() {
+        let mut set = BitSet::default();
+        for index in 0..200 {
+            set.insert(index);
+        }
+        set.remove(5);
+        assert_eq!(set.last_zero_before(150), Some(5));
+        assert_eq!(set.last_zero_before(5), None);
+        assert_eq!(set.last_zero_before(0), None);
+        assert_eq!(set.last_zero_before(260), Some(259));
+    }
+
+    #[test]
+    fn last_one_before_skips_empty_words() {
+        let mut set = BitSet::default();
+        for index in [3, 64, 200] {
+            set.insert(index);
+        }
+        assert_eq!(set.last_one_before(usize::MAX), Some(200));
+        assert_eq!(set.last_one_before(200), Some(64));
+        assert_eq!(set.last_one_before(64), Some(3));
+        assert_eq!(set.last_one_before(3), None);
+        assert_eq!(BitSet::default().last_one_before(usize::MAX), None);
+    }
+
+    #[test]
+    fn ones_from_skips_lower_indices() {
+        let mut set = BitSet::default();
+        for index in [1, 63, 64, 130, 200] {
+            set.insert(index);
+        }
+        assert_eq!(set.ones_from(0).collect::<Vec<_>>(), [1, 63, 64, 130, 200]);
+        assert_eq!(set.ones_from(2).collect::<Vec<_>>(), [63, 64, 130, 200]);
+        assert_eq!(set.ones_from(65).len(), 2);
+        assert_eq!(set.ones_from(65).rev().collect::<Vec<_>>(), [200, 130]);
+        assert_eq!(set.ones_from(201).count(), 0);
+        assert_eq!(set.ones_from(1000).count(), 0);
+    }
+
+    #[test]
+    fn rank_and_select_agree_with_and_without_index() {
+        let mut plain = BitSet::default();
+        let mut indexed = BitSet::default();
+        indexed.set_rank_index(true);
+        for index in (0..1000).filter(|index| index % 3 == 0 || index % 7 == 0) {
+            plain.insert(index);
+            indexed.insert(index);
+        }
+        for index in (0..1000).step_by(5) {
+            plain.remove(index);
+            indexed.remove(index);
+        }
+
+        let ones: Vec<_> = plain.ones().collect();
+        for set in [&plain, &indexed] {
+            for (rank, &index) in ones.iter().enumerate() {
+                assert_eq!(set.select(rank), Some(index));
+                assert_eq!(set.rank(index), rank);
+            }
+            assert_eq!(set.select(ones.len()), None);
+            assert_eq!(set.rank(5000), ones.len());
+        }
+
+        // Enabling the index later builds it from the current bits.
+        plain.set_rank_index(true);
+        assert_eq!(plain.select(100), indexed.select(100));
+        plain.reset();
+        assert_eq!(plain.select(0), None);
+    }
+
+    #[test]
+    fn next_one_and_zero_find_run_bounds() {
+        let mut set = BitSet::default();
+        for index in (3..70).chain(128..192) {
+            set.insert(index);
+        }
+        assert_eq!(set.next_one(0), Some(3));
+        assert_eq!(set.next_zero(3), 70);
+        assert_eq!(set.next_one(70), Some(128));
+        assert_eq!(set.next_zero(128), 192);
+        assert_eq!(set.next_one(192), None);
+        assert_eq!(set.next_zero(500), 500);
+    }
+
+    #[test]
+    fn ones_meet_in_the_middle() {
+        let mut set = BitSet::default();
+        for index in [0, 3, 63, 64, 100, 191, 192, 250] {
+            set.insert(index);
+        }
+        assert_eq!(
+            set.ones().rev().collect::<Vec<_>>(),
+            [250, 192, 191, 100, 64, 63, 3, 0]
+        );
+
+        let mut ones = set.ones();
+        assert_eq!(ones.len(), 8);
+        assert_eq!(ones.next(), Some(0));
+        assert_eq!(ones.next_back(), Some(250));
+        assert_eq!(ones.next_back(), Some(192));
+        assert_eq!(ones.next(), Some(3));
+        assert_eq!(ones.next(), Some(63));
+        assert_eq!(ones.next_back(), Some(191));
+        assert_eq!(ones.len(), 2);
+        assert_eq!(ones.next_back(), Some(100));
+        assert_eq!(ones.next(), Some(64));
+        assert_eq!(ones.next(), None);
+        assert_eq!(ones.next_back(), None);
+        assert_eq!(ones.len(), 0);
+    }
+
+    #[test]
+    fn count_and_last() {
+        let mut set = BitSet::default();
+        assert_eq!(set.count(), 0);
+        assert_eq!(set.last(), None);
+
+        set.insert(1);
+        set.insert(70);
+        set.insert(130);
+        set.remove(130);
+        assert_eq!(set.count(), 2);
+        assert_eq!(set.last(), Some(70));
+    }
+}
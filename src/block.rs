@@ -0,0 +1,238 @@
+use crate::index_type::*;
+use std::collections::BTreeMap;
+
+/// An allocator of variable-length runs of indices, e.g. to suballocate ranges of a
+/// GPU buffer or of a slot array.
+///
+/// The allocator manages the index space `0..capacity` but stores no elements itself.
+/// Runs are allocated first-fit from the lowest free run that is large enough, and freed
+/// runs are coalesced with adjacent free runs so that the index space does not fragment
+/// more than necessary.
+///
+/// ## Type parameters
+/// * `TIndex` - The type of the indices; see [`FreeList`](crate::FreeList) for details.
+///
+/// ## Example
+/// ```rust
+/// use free_list::BlockAllocator;
+///
+/// let mut blocks = BlockAllocator::<u16>::with_capacity(16);
+/// let a = blocks.allocate(4).unwrap();
+/// let b = blocks.allocate(8).unwrap();
+/// assert_eq!((a, b), (0, 4));
+///
+/// // The freed run is coalesced with the free tail.
+/// assert_eq!(blocks.free(b), 8);
+/// assert_eq!(blocks.largest_free_run(), 12);
+/// assert_eq!(blocks.allocate(16), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BlockAllocator<TIndex = DefaultIndex>
+where
+    TIndex: IndexType,
+{
+    /// The number of managed indices.
+    capacity: usize,
+    /// The free runs by their first index, with their lengths; never adjacent.
+    free: BTreeMap<usize, usize>,
+    /// The allocated runs by their first index, with their lengths.
+    allocated: BTreeMap<usize, usize>,
+    _index: std::marker::PhantomData<TIndex>,
+}
+
+impl<TIndex> Default for BlockAllocator<TIndex>
+where
+    TIndex: IndexType,
+{
+    fn default() -> Self {
+        Self::with_capacity(0)
+    }
+}
+
+impl<TIndex> BlockAllocator<TIndex>
+where
+    TIndex: IndexType,
+{
+    /// Creates an allocator that manages the indices `0..capacity`, all of which are free.
+    ///
+    /// ## Panics
+    /// Panics if the index type cannot address the requested capacity.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut blocks = Self {
+            capacity: 0,
+            free: BTreeMap::new(),
+            allocated: BTreeMap::new(),
+            _index: std::marker::PhantomData,
+        };
+        blocks.grow(capacity);
+        blocks
+    }
+
+    /// Extends the managed index space by `additional` free indices at its end.
+    ///
+    /// ## Panics
+    /// Panics if the index type cannot address the requested capacity.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::BlockAllocator;
+    ///
+    /// let mut blocks = BlockAllocator::<u8>::with_capacity(4);
+    /// blocks.allocate(2).unwrap();
+    /// assert_eq!(blocks.allocate(4), None);
+    ///
+    /// blocks.grow(2);
+    /// assert_eq!(blocks.allocate(4), Some(2));
+    /// ```
+    pub fn grow(&mut self, additional: usize) {
+        if additional == 0 {
+            return;
+        }
+        let capacity = self.capacity + additional;
+        assert!(
+            capacity <= TIndex::MAX.into_usize(),
+            "Attempted to manage more indices than can be addressed by the provided index type ({:?} allowed)",
+            TIndex::MAX
+        );
+        self.insert_free(self.capacity, additional);
+        self.capacity = capacity;
+    }
+
+    /// Allocates a run of `len` adjacent indices and returns its first index, or `None`
+    /// if no free run is large enough.
+    ///
+    /// ## Panics
+    /// Panics if `len` is zero.
+    pub fn allocate(&mut self, len: usize) -> Option<TIndex> {
+        assert!(len > 0, "Attempted to allocate an empty block");
+        let (&start, &free) = self.free.iter().find(|(_, &free)| free >= len)?;
+        self.free.remove(&start);
+        if free > len {
+            self.free.insert(start + len, free - len);
+        }
+        self.allocated.insert(start, len);
+        Some(index_from_usize(start))
+    }
+
+    /// Frees the run starting at the specified index and returns its length.
+    ///
+    /// ## Panics
+    /// Panics if no run starts at the specified index.
+    pub fn free(&mut self, start: TIndex) -> usize {
+        let start = start.into_usize();
+        let len = self
+            .allocated
+            .remove(&start)
+            .unwrap_or_else(|| panic!("Attempted to free a block that is not allocated ({start})"));
+        self.insert_free(start, len);
+        len
+    }
+
+    /// Gets the length of the run starting at the specified index, or `None` if no run
+    /// starts there.
+    #[inline]
+    pub fn block_len(&self, start: TIndex) -> Option<usize> {
+        self.allocated.get(&start.into_usize()).copied()
+    }
+
+    /// Gets the number of managed indices.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Gets the number of allocated runs.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.allocated.len()
+    }
+
+    /// Determines whether no run is allocated.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.allocated.is_empty()
+    }
+
+    /// Gets the number of free indices, which may be spread over several runs.
+    pub fn free_len(&self) -> usize {
+        self.free.values().sum()
+    }
+
+    /// Gets the length of the largest free run, i.e. the largest run that can be
+    /// allocated.
+    pub fn largest_free_run(&self) -> usize {
+        self.free.values().copied().max().unwrap_or(0)
+    }
+
+    /// Gets an iterator over the first indices and lengths of all allocated runs, in
+    /// ascending order.
+    pub fn blocks(&self) -> impl DoubleEndedIterator<Item = (TIndex, usize)> + '_ {
+        self.allocated
+            .iter()
+            .map(|(&start, &len)| (index_from_usize(start), len))
+    }
+
+    /// Frees all runs.
+    pub fn clear(&mut self) {
+        self.allocated.clear();
+        self.free.clear();
+        if self.capacity > 0 {
+            self.free.insert(0, self.capacity);
+        }
+    }
+
+    /// Adds a free run, coalescing it with the adjacent free runs.
+    fn insert_free(&mut self, mut start: usize, mut len: usize) {
+        if let Some(next) = self.free.remove(&(start + len)) {
+            len += next;
+        }
+        if let Some((&previous, &previous_len)) = self.free.range(..start).next_back() {
+            if previous + previous_len == start {
+                start = previous;
+                len += previous_len;
+            }
+        }
+        self.free.insert(start, len);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn freed_runs_are_coalesced() {
+        let mut blocks = BlockAllocator::<u8>::with_capacity(10);
+        let a = blocks.allocate(3).unwrap();
+        let b = blocks.allocate(3).unwrap();
+        let c = blocks.allocate(3).unwrap();
+        assert_eq!(blocks.allocate(2), None);
+
+        assert_eq!(blocks.free(a), 3);
+        assert_eq!(blocks.free(c), 3);
+        assert_eq!(blocks.largest_free_run(), 4);
+        assert_eq!(blocks.free(b), 3);
+        assert_eq!(blocks.largest_free_run(), 10);
+        assert!(blocks.is_empty());
+
+        // First fit reuses the lowest run that is large enough.
+        let d = blocks.allocate(5).unwrap();
+        blocks.allocate(2).unwrap();
+        blocks.free(d);
+        assert_eq!(blocks.allocate(4), Some(0));
+        assert_eq!(blocks.allocate(1), Some(4));
+        assert_eq!(blocks.free_len(), 3);
+        assert_eq!(
+            blocks.blocks().collect::<Vec<_>>(),
+            [(0, 4), (4, 1), (5, 2)]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn freeing_an_unallocated_block_panics() {
+        let mut blocks = BlockAllocator::<u8>::with_capacity(4);
+        blocks.allocate(2);
+        blocks.free(1);
+    }
+}
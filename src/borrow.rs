@@ -0,0 +1,333 @@
+use crate::index_type::*;
+use crate::{BorrowError, FreeList, Key};
+use std::cell::{Cell, UnsafeCell};
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::{Deref, DerefMut};
+
+/// A free list whose elements can be borrowed individually through a shared reference,
+/// with the borrow state of every slot tracked at runtime like a [`RefCell`](std::cell::RefCell).
+///
+/// This allows holding mutable references to several different elements at once, e.g.
+/// while walking a graph of keys, where the borrow checker would only permit one. Borrowing
+/// an element mutably while it is borrowed elsewhere, or at all while it is borrowed
+/// mutably, panics (or fails with [`try_borrow`](Self::try_borrow) and
+/// [`try_borrow_mut`](Self::try_borrow_mut)). Inserting and removing elements takes
+/// `&mut self`, so no borrows can be outstanding then.
+///
+/// ## Type parameters
+/// * `T` - The type of the element.
+/// * `TIndex` - The type of the index; see [`FreeList`] for details.
+///
+/// ## Example
+/// ```rust
+/// use free_list::BorrowFreeList;
+///
+/// let mut list = BorrowFreeList::<u32>::default();
+/// let a = list.push(1);
+/// let b = list.push(2);
+///
+/// let list = &list;
+/// let mut first = list.borrow_mut(a).unwrap();
+/// let mut second = list.borrow_mut(b).unwrap();
+/// std::mem::swap(&mut *first, &mut *second);
+/// assert!(list.try_borrow(a).is_err());
+///
+/// drop((first, second));
+/// assert_eq!(*list.borrow(a).unwrap(), 2);
+/// ```
+pub struct BorrowFreeList<T, TIndex = DefaultIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    list: FreeList<UnsafeCell<T>, TIndex>,
+    /// The borrow state of each slot: the number of shared borrows, or `-1` if the
+    /// slot is mutably borrowed.
+    flags: Vec<Cell<isize>>,
+}
+
+/// A shared borrow of an element of a [`BorrowFreeList`], created by
+/// [`BorrowFreeList::borrow`].
+pub struct SlotRef<'a, T> {
+    value: &'a T,
+    flag: &'a Cell<isize>,
+}
+
+/// A mutable borrow of an element of a [`BorrowFreeList`], created by
+/// [`BorrowFreeList::borrow_mut`].
+pub struct SlotRefMut<'a, T> {
+    value: &'a mut T,
+    flag: &'a Cell<isize>,
+}
+
+impl<T, TIndex> Default for BorrowFreeList<T, TIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    fn default() -> Self {
+        Self {
+            list: FreeList::default(),
+            flags: Vec::new(),
+        }
+    }
+}
+
+impl<T, TIndex> BorrowFreeList<T, TIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    /// Inserts an element and returns its key.
+    pub fn push(&mut self, element: T) -> Key<TIndex> {
+        let key = self.list.push_key(UnsafeCell::new(element));
+        let index = key.index().into_usize();
+        if index >= self.flags.len() {
+            self.flags.resize_with(index + 1, Cell::default);
+        }
+        key
+    }
+
+    /// Removes the element of the specified key and returns it, or returns `None` if the
+    /// key does not refer to an element.
+    pub fn remove(&mut self, key: Key<TIndex>) -> Option<T> {
+        self.list
+            .contains_key(key)
+            .then(|| self.list.remove(key.index()).into_inner())
+    }
+
+    /// Borrows the element of the specified key, or returns `None` if the key does not
+    /// refer to an element.
+    ///
+    /// ## Panics
+    /// Panics if the element is mutably borrowed.
+    pub fn borrow(&self, key: Key<TIndex>) -> Option<SlotRef<'_, T>> {
+        match self.try_borrow(key) {
+            Ok(value) => Some(value),
+            Err(BorrowError::NotOccupied) => None,
+            Err(BorrowError::AlreadyBorrowed) => {
+                panic!("Attempted to borrow an element that is mutably borrowed ({key})")
+            }
+        }
+    }
+
+    /// Mutably borrows the element of the specified key, or returns `None` if the key
+    /// does not refer to an element.
+    ///
+    /// ## Panics
+    /// Panics if the element is borrowed.
+    pub fn borrow_mut(&self, key: Key<TIndex>) -> Option<SlotRefMut<'_, T>> {
+        match self.try_borrow_mut(key) {
+            Ok(value) => Some(value),
+            Err(BorrowError::NotOccupied) => None,
+            Err(BorrowError::AlreadyBorrowed) => {
+                panic!("Attempted to mutably borrow an element that is borrowed ({key})")
+            }
+        }
+    }
+
+    /// Borrows the element of the specified key.
+    ///
+    /// ## Errors
+    /// Fails if the key does not refer to an element or the element is mutably borrowed.
+    pub fn try_borrow(&self, key: Key<TIndex>) -> Result<SlotRef<'_, T>, BorrowError> {
+        let (cell, flag) = self.slot(key)?;
+        if flag.get() < 0 {
+            return Err(BorrowError::AlreadyBorrowed);
+        }
+        flag.set(flag.get() + 1);
+
+        // SAFETY: The flag rules out mutable borrows until the returned borrow is dropped.
+        let value = unsafe { &*cell.get() };
+        Ok(SlotRef { value, flag })
+    }
+
+    /// Mutably borrows the element of the specified key.
+    ///
+    /// ## Errors
+    /// Fails if the key does not refer to an element or the element is borrowed.
+    pub fn try_borrow_mut(&self, key: Key<TIndex>) -> Result<SlotRefMut<'_, T>, BorrowError> {
+        let (cell, flag) = self.slot(key)?;
+        if flag.get() != 0 {
+            return Err(BorrowError::AlreadyBorrowed);
+        }
+        flag.set(-1);
+
+        // SAFETY: The flag rules out any other borrow until the returned borrow is dropped.
+        let value = unsafe { &mut *cell.get() };
+        Ok(SlotRefMut { value, flag })
+    }
+
+    /// Gets a mutable reference to the element of the specified key without checking
+    /// borrows, since the access is exclusive; returns `None` if the key does not refer
+    /// to an element.
+    pub fn get_mut(&mut self, key: Key<TIndex>) -> Option<&mut T> {
+        self.list
+            .contains_key(key)
+            .then(|| self.list[key.index()].get_mut())
+    }
+
+    /// Determines whether the specified key refers to an element.
+    #[inline]
+    pub fn contains_key(&self, key: Key<TIndex>) -> bool {
+        self.list.contains_key(key)
+    }
+
+    /// Determines whether the element of the specified key is borrowed at all.
+    pub fn is_borrowed(&self, key: Key<TIndex>) -> bool {
+        self.slot(key).is_ok_and(|(_, flag)| flag.get() != 0)
+    }
+
+    /// Gets the number of elements.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Determines whether the list is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Gets the cell and borrow flag of an occupied slot.
+    fn slot(&self, key: Key<TIndex>) -> Result<(&UnsafeCell<T>, &Cell<isize>), BorrowError> {
+        if !self.list.contains_key(key) {
+            return Err(BorrowError::NotOccupied);
+        }
+        let index = key.index();
+        Ok((&self.list[index], &self.flags[index.into_usize()]))
+    }
+}
+
+impl<T, TIndex> Debug for BorrowFreeList<T, TIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BorrowFreeList")
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a, T> Deref for SlotRef<'a, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.value
+    }
+}
+
+impl<'a, T> SlotRef<'a, T> {
+    /// Borrows the element once more.
+    ///
+    /// Like [`Ref::clone`](std::cell::Ref::clone), this is an associated function so that
+    /// it does not shadow [`Clone`] of the element.
+    #[allow(clippy::should_implement_trait)]
+    pub fn clone(this: &Self) -> Self {
+        this.flag.set(this.flag.get() + 1);
+        Self {
+            value: this.value,
+            flag: this.flag,
+        }
+    }
+}
+
+impl<'a, T> Drop for SlotRef<'a, T> {
+    fn drop(&mut self) {
+        self.flag.set(self.flag.get() - 1);
+    }
+}
+
+impl<'a, T: Debug> Debug for SlotRef<'a, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<'a, T: Display> Display for SlotRef<'a, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&**self, f)
+    }
+}
+
+impl<'a, T> Deref for SlotRefMut<'a, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.value
+    }
+}
+
+impl<'a, T> DerefMut for SlotRefMut<'a, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.value
+    }
+}
+
+impl<'a, T> Drop for SlotRefMut<'a, T> {
+    fn drop(&mut self) {
+        self.flag.set(0);
+    }
+}
+
+impl<'a, T: Debug> Debug for SlotRefMut<'a, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<'a, T: Display> Display for SlotRefMut<'a, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&**self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn borrows_are_tracked_per_slot() {
+        let mut list = BorrowFreeList::<String, u8>::default();
+        let a = list.push("a".into());
+        let b = list.push("b".into());
+
+        {
+            let list = &list;
+            let first = list.borrow(a).unwrap();
+            let again = SlotRef::clone(&first);
+            assert_eq!(
+                list.try_borrow_mut(a).err(),
+                Some(BorrowError::AlreadyBorrowed)
+            );
+            list.borrow_mut(b).unwrap().push('!');
+            drop(first);
+            assert!(list.is_borrowed(a));
+            drop(again);
+            assert!(!list.is_borrowed(a));
+            assert_eq!(*list.borrow(b).unwrap(), "b!");
+        }
+
+        assert_eq!(list.remove(a).as_deref(), Some("a"));
+        assert_eq!(list.try_borrow(a).err(), Some(BorrowError::NotOccupied));
+        let c = list.push("c".into());
+        assert!(list.borrow_mut(a).is_none());
+        *list.get_mut(c).unwrap() += "c";
+        assert_eq!(*list.borrow(c).unwrap(), "cc");
+    }
+
+    #[test]
+    #[should_panic(expected = "Attempted to borrow an element that is mutably borrowed")]
+    fn borrowing_a_mutably_borrowed_element_panics() {
+        let mut list = BorrowFreeList::<u32>::default();
+        let key = list.push(1);
+        let _value = list.borrow_mut(key);
+        list.borrow(key);
+    }
+}
@@ -0,0 +1,209 @@
+use crate::PagedFreeList;
+use std::cell::{Cell, RefCell};
+use std::fmt::{Debug, Display, Formatter};
+use std::mem::ManuallyDrop;
+use std::ops::{Deref, DerefMut};
+use std::ptr::NonNull;
+
+/// A single-threaded pool that allocates values behind owning [`PoolBox`] pointers.
+///
+/// A [`PoolBox`] behaves like a [`Box`]: it owns its value, dereferences to it and drops
+/// it when it goes out of scope. Instead of the heap, the value lives in a slot of the
+/// pool, which is returned to the free chain on drop and reused by the next allocation.
+/// Every box also knows the index of its slot, so values can be addressed by index as well.
+///
+/// Values are stored in a [`PagedFreeList`], so they never move while boxes to them exist.
+///
+/// ## Example
+/// ```rust
+/// use free_list::{BoxPool, PoolBox};
+///
+/// let pool = BoxPool::default();
+/// let mut first = pool.alloc(String::from("first"));
+/// first.push_str(" value");
+/// assert_eq!(*first, "first value");
+///
+/// // Dropping the box frees its slot for the next allocation.
+/// let index = PoolBox::index(&first);
+/// drop(first);
+/// let second = pool.alloc(String::from("second"));
+/// assert_eq!(PoolBox::index(&second), index);
+/// ```
+pub struct BoxPool<T> {
+    /// The values of all live boxes; values are dropped by their boxes.
+    values: RefCell<PagedFreeList<ManuallyDrop<T>, usize>>,
+    /// The number of live boxes.
+    len: Cell<usize>,
+}
+
+/// An owning pointer to a value allocated in a [`BoxPool`].
+///
+/// This type is created by [`BoxPool::alloc`]. Like [`Box`], its associated functions
+/// take the box as an explicit argument so that they do not shadow methods of the value.
+pub struct PoolBox<'a, T> {
+    pool: &'a BoxPool<T>,
+    value: NonNull<T>,
+    index: usize,
+}
+
+impl<T> Default for BoxPool<T> {
+    /// Creates an empty pool.
+    fn default() -> Self {
+        Self {
+            values: RefCell::new(PagedFreeList::default()),
+            len: Cell::new(0),
+        }
+    }
+}
+
+impl<T> BoxPool<T> {
+    /// Moves a value into a free slot of the pool and returns a box owning it.
+    pub fn alloc(&self, value: T) -> PoolBox<'_, T> {
+        let mut values = self.values.borrow_mut();
+        let index = values.push(ManuallyDrop::new(value));
+        self.len.set(self.len.get() + 1);
+
+        // SAFETY: The paged storage never moves the value, and the slot is only
+        // erased by the box.
+        let value = NonNull::from(&mut **unsafe { values.at_mut(index) });
+        PoolBox {
+            pool: self,
+            value,
+            index,
+        }
+    }
+
+    /// Gets the number of live boxes.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::BoxPool;
+    ///
+    /// let pool = BoxPool::default();
+    /// let first = pool.alloc(1);
+    /// drop(pool.alloc(2));
+    /// assert_eq!(pool.len(), 1);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len.get()
+    }
+
+    /// Determines whether no boxes are live.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Determines whether the specified index refers to the slot of a live box.
+    pub fn contains(&self, index: usize) -> bool {
+        self.values.borrow().status(index).is_occupied()
+    }
+
+    /// Gets the number of slots that were handed out so far.
+    pub fn slot_count(&self) -> usize {
+        self.values.borrow().slot_count()
+    }
+
+    /// Returns the slot of a box whose value was dropped or moved out.
+    fn release(&self, index: usize) {
+        self.values.borrow_mut().erase(index);
+        self.len.set(self.len.get() - 1);
+    }
+}
+
+impl<'a, T> PoolBox<'a, T> {
+    /// Gets the index of the slot of the value within the pool.
+    #[inline]
+    pub fn index(this: &Self) -> usize {
+        this.index
+    }
+
+    /// Moves the value out of the box and frees its slot.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::{BoxPool, PoolBox};
+    ///
+    /// let pool = BoxPool::default();
+    /// let value = pool.alloc(vec![1, 2, 3]);
+    /// assert_eq!(PoolBox::into_inner(value), [1, 2, 3]);
+    /// assert!(pool.is_empty());
+    /// ```
+    pub fn into_inner(this: Self) -> T {
+        let this = ManuallyDrop::new(this);
+        // SAFETY: The box owns the value, and it is not dropped again since the box is
+        // forgotten.
+        let value = unsafe { this.value.as_ptr().read() };
+        this.pool.release(this.index);
+        value
+    }
+}
+
+impl<'a, T> Deref for PoolBox<'a, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: The box has exclusive access to the value until it is dropped.
+        unsafe { self.value.as_ref() }
+    }
+}
+
+impl<'a, T> DerefMut for PoolBox<'a, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // SAFETY: The box has exclusive access to the value until it is dropped.
+        unsafe { self.value.as_mut() }
+    }
+}
+
+impl<'a, T> Drop for PoolBox<'a, T> {
+    fn drop(&mut self) {
+        // SAFETY: The box owns the value; the storage does not drop it since it is
+        // wrapped in `ManuallyDrop`.
+        unsafe { self.value.as_ptr().drop_in_place() };
+        self.pool.release(self.index);
+    }
+}
+
+impl<'a, T: Debug> Debug for PoolBox<'a, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<'a, T: Display> Display for PoolBox<'a, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&**self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn values_are_dropped_exactly_once() {
+        let value = Rc::new(());
+        let pool = BoxPool::default();
+        let boxes: Vec<_> = (0..3).map(|_| pool.alloc(value.clone())).collect();
+        assert_eq!(Rc::strong_count(&value), 4);
+
+        let mut boxes = boxes.into_iter();
+        let first = boxes.next().unwrap();
+        let moved = PoolBox::into_inner(first);
+        assert!(!pool.contains(0));
+        assert_eq!(Rc::strong_count(&value), 4);
+        drop(moved);
+        drop(boxes);
+        assert_eq!(Rc::strong_count(&value), 1);
+        assert!(pool.is_empty());
+
+        // Freed slots are reused while the pool keeps its pages.
+        let reused = pool.alloc(value.clone());
+        assert!(PoolBox::index(&reused) < 3);
+        assert_eq!(pool.slot_count(), 3);
+    }
+}
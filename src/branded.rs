@@ -0,0 +1,226 @@
+use crate::index_type::*;
+use crate::{Allocator, FreeList, Global};
+use std::marker::PhantomData;
+use std::ops::{Index, IndexMut};
+
+/// An invariant lifetime that ties keys to the list they were issued by.
+type Brand<'id> = PhantomData<fn(&'id ()) -> &'id ()>;
+
+/// A view of a [`FreeList`] whose keys are branded with a unique lifetime.
+///
+/// A branded list is created by [`FreeList::branded`] and only lives for the duration of
+/// the closure passed to it. Every [`BrandedKey`] it hands out carries the same unique
+/// lifetime `'id`, so the compiler rejects keys that were issued by a different list.
+/// Since elements cannot be removed through the view, a branded key always refers to an
+/// occupied slot and elements can be accessed safely without any runtime checks.
+pub struct BrandedFreeList<'id, 'a, T, TIndex = DefaultIndex, A = Global>
+where
+    T: Default,
+    TIndex: IndexType,
+    A: Allocator,
+{
+    list: &'a mut FreeList<T, TIndex, A>,
+    _brand: Brand<'id>,
+}
+
+/// An index into a [`BrandedFreeList`] that is guaranteed to refer to an element.
+///
+/// The key can only be used with the list that issued it.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BrandedKey<'id, TIndex = DefaultIndex>
+where
+    TIndex: IndexType,
+{
+    index: TIndex,
+    _brand: Brand<'id>,
+}
+
+impl<'id, TIndex> Clone for BrandedKey<'id, TIndex>
+where
+    TIndex: IndexType,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'id, TIndex> Copy for BrandedKey<'id, TIndex> where TIndex: IndexType {}
+
+impl<'id, TIndex> BrandedKey<'id, TIndex>
+where
+    TIndex: IndexType,
+{
+    /// Gets the plain index of the element.
+    #[inline]
+    pub fn index(self) -> TIndex {
+        self.index
+    }
+}
+
+impl<T, TIndex, A> FreeList<T, TIndex, A>
+where
+    T: Default,
+    TIndex: IndexType,
+    A: Allocator,
+{
+    /// Calls the specified function with a branded view of the list, through which
+    /// elements can be inserted and accessed safely without runtime occupancy checks.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// list.push("existing");
+    ///
+    /// let total = list.branded(|mut list| {
+    ///     let first = list.push("first");
+    ///     let existing = list.key(0).unwrap();
+    ///     list[first] = "changed";
+    ///     list[first].len() + list[existing].len()
+    /// });
+    /// assert_eq!(total, 15);
+    /// ```
+    ///
+    /// Keys cannot be used with a different list:
+    /// ```compile_fail
+    /// use free_list::FreeList;
+    ///
+    /// let mut a = FreeList::<u32, u8>::default();
+    /// let mut b = FreeList::<u32, u8>::default();
+    /// a.branded(|mut a| {
+    ///     b.branded(|b| {
+    ///         let key = a.push(1);
+    ///         b[key]
+    ///     })
+    /// });
+    /// ```
+    pub fn branded<R>(
+        &mut self,
+        f: impl for<'id> FnOnce(BrandedFreeList<'id, '_, T, TIndex, A>) -> R,
+    ) -> R {
+        f(BrandedFreeList {
+            list: self,
+            _brand: PhantomData,
+        })
+    }
+}
+
+impl<'id, 'a, T, TIndex, A> BrandedFreeList<'id, 'a, T, TIndex, A>
+where
+    T: Default,
+    TIndex: IndexType,
+    A: Allocator,
+{
+    /// Inserts an element to the list and returns a branded key to it.
+    pub fn push(&mut self, element: T) -> BrandedKey<'id, TIndex> {
+        BrandedKey {
+            index: self.list.push(element),
+            _brand: PhantomData,
+        }
+    }
+
+    /// Validates the specified index and returns a branded key to it, or `None`
+    /// if the slot is not occupied.
+    pub fn key(&self, index: TIndex) -> Option<BrandedKey<'id, TIndex>> {
+        if self.list.contains(index) {
+            Some(BrandedKey {
+                index,
+                _brand: PhantomData,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Gets an iterator over the branded keys of all elements, in ascending index order.
+    pub fn keys(&self) -> impl Iterator<Item = BrandedKey<'id, TIndex>> + '_ {
+        self.list.keys().map(|index| BrandedKey {
+            index,
+            _brand: PhantomData,
+        })
+    }
+
+    /// Gets a reference to the element of the specified key.
+    #[inline]
+    pub fn get(&self, key: BrandedKey<'id, TIndex>) -> &T {
+        // SAFETY: Branded keys are only issued for occupied slots, and no element
+        // can be removed while the branded view exists.
+        unsafe { self.list.at(key.index) }
+    }
+
+    /// Gets a mutable reference to the element of the specified key.
+    #[inline]
+    pub fn get_mut(&mut self, key: BrandedKey<'id, TIndex>) -> &mut T {
+        // SAFETY: Branded keys are only issued for occupied slots, and no element
+        // can be removed while the branded view exists.
+        unsafe { self.list.at_mut(key.index) }
+    }
+
+    /// Gets the number of elements in the list.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Determines whether the list contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+}
+
+impl<'id, 'a, T, TIndex, A> Index<BrandedKey<'id, TIndex>>
+    for BrandedFreeList<'id, 'a, T, TIndex, A>
+where
+    T: Default,
+    TIndex: IndexType,
+    A: Allocator,
+{
+    type Output = T;
+
+    #[inline]
+    fn index(&self, key: BrandedKey<'id, TIndex>) -> &Self::Output {
+        self.get(key)
+    }
+}
+
+impl<'id, 'a, T, TIndex, A> IndexMut<BrandedKey<'id, TIndex>>
+    for BrandedFreeList<'id, 'a, T, TIndex, A>
+where
+    T: Default,
+    TIndex: IndexType,
+    A: Allocator,
+{
+    #[inline]
+    fn index_mut(&mut self, key: BrandedKey<'id, TIndex>) -> &mut Self::Output {
+        self.get_mut(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FreeList;
+
+    #[test]
+    fn keys_stay_valid_while_growing() {
+        let mut list = FreeList::<u32, u16>::default();
+        list.push(0);
+        list.push(1);
+        list.erase(0);
+
+        list.branded(|mut list| {
+            assert!(list.key(0).is_none());
+            let keys: Vec<_> = (0..100).map(|i| list.push(i)).collect();
+            for (i, key) in keys.iter().enumerate() {
+                list[*key] += i as u32;
+            }
+            for (i, key) in keys.into_iter().enumerate() {
+                assert_eq!(list[key], 2 * i as u32);
+            }
+            assert_eq!(list.keys().count(), 101);
+            assert_eq!(list.len(), 101);
+        });
+        assert_eq!(list.len(), 101);
+    }
+}
@@ -0,0 +1,354 @@
+use crate::bitset::BitSet;
+use crate::index_type::*;
+use crate::SlotStatus;
+
+/// An indexed free list that keeps its values packed in a contiguous array.
+///
+/// Values are stored densely in insertion order (modulo removals) while a sparse table
+/// maps the stable indices handed out by [`push`](Self::push) to their current position.
+/// Removing a value moves the last value into the gap, so iteration only ever touches
+/// live values, at the cost of an additional indirection on every lookup.
+///
+/// ## Type parameters
+/// * `T` - The type of the element.
+/// * `TIndex` - The type of the index; see [`FreeList`](crate::FreeList) for details.
+pub struct DenseFreeList<T, TIndex = DefaultIndex>
+where
+    TIndex: IndexType,
+{
+    /// The values, packed without gaps.
+    values: Vec<T>,
+    /// The index of the value at the same position in `values`.
+    indices: Vec<TIndex>,
+    /// For every occupied index, the position of its value in `values`; for
+    /// every free index, the next free index or `SENTINEL`.
+    sparse: Vec<TIndex>,
+    /// The set of indices that currently refer to a value.
+    occupied: BitSet,
+    /// The most recently freed index, or `SENTINEL` if no index is free.
+    first_free: TIndex,
+}
+
+impl<T, TIndex> Default for DenseFreeList<T, TIndex>
+where
+    TIndex: IndexType,
+{
+    /// Creates an empty list.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::DenseFreeList;
+    ///
+    /// let list = DenseFreeList::<&str, u8>::default();
+    /// assert!(list.is_empty());
+    /// ```
+    fn default() -> Self {
+        Self {
+            values: Vec::new(),
+            indices: Vec::new(),
+            sparse: Vec::new(),
+            occupied: BitSet::default(),
+            first_free: Self::SENTINEL,
+        }
+    }
+}
+
+impl<T, TIndex> DenseFreeList<T, TIndex>
+where
+    TIndex: IndexType,
+{
+    /// The sentinel value indicates the absence of a valid value.
+    pub(crate) const SENTINEL: TIndex = TIndex::MAX;
+
+    /// Inserts an element to the list and returns an index to it.
+    ///
+    /// ## Panics
+    /// Panics if the index type cannot address any more elements.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::DenseFreeList;
+    ///
+    /// let mut list = DenseFreeList::<&str, u8>::default();
+    /// assert_eq!(list.push("first"), 0);
+    /// assert_eq!(list.push("second"), 1);
+    /// ```
+    pub fn push(&mut self, element: T) -> TIndex {
+        let position = index_from_usize(self.values.len());
+        let index = if self.first_free != Self::SENTINEL {
+            let index = self.first_free;
+            let slot = &mut self.sparse[index.into_usize()];
+            self.first_free = *slot;
+            *slot = position;
+            index
+        } else {
+            let index = self.sparse.len();
+            assert!(
+                index < Self::SENTINEL.into_usize() - 1,
+                "Attempted to insert more elements than can be addressed by the provided index type ({:?} allowed)",
+                TIndex::MAX
+            );
+            self.sparse.push(position);
+            index_from_usize(index)
+        };
+
+        self.values.push(element);
+        self.indices.push(index);
+        self.occupied.insert(index.into_usize());
+        index
+    }
+
+    /// Removes the element at the specified index and returns it, or returns `None`
+    /// if the index does not refer to an element.
+    ///
+    /// The last element of the dense array is moved into the freed position.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::DenseFreeList;
+    ///
+    /// let mut list = DenseFreeList::<&str, u8>::default();
+    /// let first = list.push("first");
+    /// list.push("second");
+    ///
+    /// assert_eq!(list.erase(first), Some("first"));
+    /// assert_eq!(list.erase(first), None);
+    /// assert_eq!(list.values(), ["second"]);
+    /// ```
+    pub fn erase(&mut self, index: TIndex) -> Option<T> {
+        let index_usize = index.into_usize();
+        if !self.occupied.contains(index_usize) {
+            return None;
+        }
+
+        let position = self.sparse[index_usize].into_usize();
+        let element = self.values.swap_remove(position);
+        self.indices.swap_remove(position);
+        if let Some(&moved) = self.indices.get(position) {
+            self.sparse[moved.into_usize()] = index_from_usize(position);
+        }
+
+        self.occupied.remove(index_usize);
+        self.sparse[index_usize] = self.first_free;
+        self.first_free = index;
+        Some(element)
+    }
+
+    /// Removes all elements from the list.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::DenseFreeList;
+    ///
+    /// let mut list = DenseFreeList::<&str, u8>::default();
+    /// list.push("first");
+    /// list.clear();
+    /// assert!(list.is_empty());
+    /// assert_eq!(list.push("second"), 0);
+    /// ```
+    pub fn clear(&mut self) {
+        self.values.clear();
+        self.indices.clear();
+        self.sparse.clear();
+        self.occupied.clear();
+        self.first_free = Self::SENTINEL;
+    }
+
+    /// Gets a reference to the value at the specified index, or `None` if the
+    /// index does not refer to an element.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::DenseFreeList;
+    ///
+    /// let mut list = DenseFreeList::<&str, u8>::default();
+    /// let index = list.push("first");
+    /// assert_eq!(list.get(index), Some(&"first"));
+    /// assert_eq!(list.get(index + 1), None);
+    /// ```
+    pub fn get(&self, index: TIndex) -> Option<&T> {
+        let position = self.position(index)?;
+        Some(&self.values[position])
+    }
+
+    /// Gets a mutable reference to the value at the specified index, or `None` if
+    /// the index does not refer to an element.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::DenseFreeList;
+    ///
+    /// let mut list = DenseFreeList::<u32, u8>::default();
+    /// let index = list.push(1);
+    /// *list.get_mut(index).unwrap() += 1;
+    /// assert_eq!(list.get(index), Some(&2));
+    /// ```
+    pub fn get_mut(&mut self, index: TIndex) -> Option<&mut T> {
+        let position = self.position(index)?;
+        Some(&mut self.values[position])
+    }
+
+    /// Determines whether the specified index refers to an element.
+    #[inline]
+    pub fn contains(&self, index: TIndex) -> bool {
+        self.occupied.contains(index.into_usize())
+    }
+
+    /// Determines the state of the slot at the specified index.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::{DenseFreeList, SlotStatus};
+    ///
+    /// let mut list = DenseFreeList::<&str, u8>::default();
+    /// let index = list.push("first");
+    /// list.erase(index);
+    /// assert_eq!(list.status(index), SlotStatus::Free);
+    /// assert_eq!(list.status(index + 1), SlotStatus::OutOfRange);
+    /// ```
+    pub fn status(&self, index: TIndex) -> SlotStatus {
+        let index = index.into_usize();
+        if index >= self.sparse.len() {
+            SlotStatus::OutOfRange
+        } else if self.occupied.contains(index) {
+            SlotStatus::Occupied
+        } else {
+            SlotStatus::Free
+        }
+    }
+
+    /// Gets the number of elements in the list.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Determines whether the list contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Gets all values as a contiguous slice, in no particular order.
+    ///
+    /// The value at position `i` belongs to the index at position `i` of [`indices`](Self::indices).
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::DenseFreeList;
+    ///
+    /// let mut list = DenseFreeList::<u32, u8>::default();
+    /// for i in 0..4 {
+    ///     list.push(i);
+    /// }
+    /// list.erase(1);
+    /// assert_eq!(list.values().iter().sum::<u32>(), 5);
+    /// ```
+    #[inline]
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    /// Gets all values as a contiguous mutable slice, in no particular order.
+    #[inline]
+    pub fn values_mut(&mut self) -> &mut [T] {
+        &mut self.values
+    }
+
+    /// Gets the indices of all elements, in the same order as [`values`](Self::values).
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::DenseFreeList;
+    ///
+    /// let mut list = DenseFreeList::<&str, u8>::default();
+    /// list.push("first");
+    /// list.push("second");
+    /// list.push("third");
+    /// list.erase(0);
+    /// assert_eq!(list.indices(), [2, 1]);
+    /// ```
+    #[inline]
+    pub fn indices(&self) -> &[TIndex] {
+        &self.indices
+    }
+
+    /// Gets an iterator over the indices and values of all elements, in the order of
+    /// the dense array.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::DenseFreeList;
+    ///
+    /// let mut list = DenseFreeList::<&str, u8>::default();
+    /// list.push("first");
+    /// list.push("second");
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), [(0, &"first"), (1, &"second")]);
+    /// ```
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (TIndex, &T)> + ExactSizeIterator + '_ {
+        self.indices.iter().copied().zip(self.values.iter())
+    }
+
+    /// Gets an iterator over the indices and mutable values of all elements, in the
+    /// order of the dense array.
+    pub fn iter_mut(
+        &mut self,
+    ) -> impl DoubleEndedIterator<Item = (TIndex, &mut T)> + ExactSizeIterator + '_ {
+        self.indices.iter().copied().zip(self.values.iter_mut())
+    }
+
+    /// Gets the position of the value of the specified index in the dense array.
+    #[inline]
+    fn position(&self, index: TIndex) -> Option<usize> {
+        if self.contains(index) {
+            Some(self.sparse[index.into_usize()].into_usize())
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn erase_keeps_values_packed() {
+        let mut list = DenseFreeList::<u32, u8>::default();
+        for i in 0..5 {
+            list.push(i * 10);
+        }
+
+        assert_eq!(list.erase(1), Some(10));
+        assert_eq!(list.erase(4), Some(40));
+        assert_eq!(list.values(), [0, 30, 20]);
+        assert_eq!(list.indices(), [0, 3, 2]);
+        for index in [0, 2, 3] {
+            assert_eq!(list.get(index), Some(&(index as u32 * 10)));
+        }
+
+        assert_eq!(list.push(50), 4);
+        assert_eq!(list.push(60), 1);
+        assert_eq!(list.get(1), Some(&60));
+        assert_eq!(list.len(), 5);
+    }
+
+    #[test]
+    fn erasing_the_last_value_does_not_move_anything() {
+        let mut list = DenseFreeList::<u32, u8>::default();
+        let index = list.push(1);
+        assert_eq!(list.erase(index), Some(1));
+        assert!(list.is_empty());
+        assert_eq!(list.get(index), None);
+        assert_eq!(list.erase(index), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn inserting_too_many_panics() {
+        let mut list = DenseFreeList::<u32, u8>::default();
+        for i in 0..255 {
+            list.push(i);
+        }
+    }
+}
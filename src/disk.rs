@@ -0,0 +1,371 @@
+use crate::index_type::*;
+use crate::snapshot::invalid_data;
+use crate::FreeList;
+use allocator_api2::alloc::Allocator;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// The magic bytes at the start of every superblock.
+const MAGIC: [u8; 4] = *b"FLDB";
+
+/// The version of the file format.
+const VERSION: u32 = 1;
+
+/// The size reserved for each of the two superblocks; a single sector, so that
+/// writing a superblock is atomic on common storage.
+const SUPERBLOCK_SIZE: u64 = 512;
+
+/// The offset of the first snapshot.
+const DATA_START: u64 = 2 * SUPERBLOCK_SIZE;
+
+/// The size of the pages a snapshot is checksummed in.
+const PAGE_SIZE: usize = 4096;
+
+/// A file that stores snapshots of a [`FreeList`] such that a crash or power loss while
+/// saving never leaves it unreadable.
+///
+/// The file starts with two superblocks, each pointing to a snapshot and protected by a
+/// checksum, and snapshots are checksummed page by page. [`save`](Self::save) writes the
+/// new snapshot next to the current one and only then overwrites the older superblock,
+/// so that either the new or the previous snapshot is intact at any time.
+/// [`open`](Self::open) recovers the most recent snapshot that passes all checks.
+///
+/// Snapshots are written by [`FreeList::save_to`], so they preserve indices, the free
+/// chain and the slot generations.
+///
+/// ## Example
+/// ```rust
+/// use free_list::{DiskStore, FreeList};
+/// use std::io::{Read, Write};
+///
+/// let path = std::env::temp_dir().join(format!("free-list-doc-{}.db", std::process::id()));
+/// let mut store = DiskStore::open(&path).unwrap();
+///
+/// let mut list = FreeList::<u32, u8>::default();
+/// list.push(1);
+/// list.push(2);
+/// store.save(&list, |value, writer| writer.write_all(&value.to_le_bytes())).unwrap();
+///
+/// let mut store = DiskStore::open(&path).unwrap();
+/// let restored: FreeList<u32, u8> = store
+///     .load(|reader| {
+///         let mut bytes = [0; 4];
+///         reader.read_exact(&mut bytes)?;
+///         Ok(u32::from_le_bytes(bytes))
+///     })
+///     .unwrap()
+///     .unwrap();
+/// assert_eq!(restored.iter().collect::<Vec<_>>(), list.iter().collect::<Vec<_>>());
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct DiskStore {
+    file: File,
+    /// The most recent intact superblock.
+    current: Superblock,
+}
+
+/// A superblock, pointing to a snapshot.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+struct Superblock {
+    /// Increases with every saved snapshot; the superblock with the highest sequence
+    /// number is current.
+    sequence: u64,
+    /// The offset of the snapshot in the file.
+    offset: u64,
+    /// The length of the snapshot, including its page checksums; zero if there is none.
+    len: u64,
+}
+
+impl Superblock {
+    /// The number of encoded bytes, including the trailing checksum.
+    const ENCODED_LEN: usize = 4 + 4 + 8 + 8 + 8 + 4;
+
+    fn encode(&self) -> [u8; Self::ENCODED_LEN] {
+        let mut bytes = [0; Self::ENCODED_LEN];
+        bytes[..4].copy_from_slice(&MAGIC);
+        bytes[4..8].copy_from_slice(&VERSION.to_le_bytes());
+        bytes[8..16].copy_from_slice(&self.sequence.to_le_bytes());
+        bytes[16..24].copy_from_slice(&self.offset.to_le_bytes());
+        bytes[24..32].copy_from_slice(&self.len.to_le_bytes());
+        let checksum = crc32(&bytes[..32]);
+        bytes[32..].copy_from_slice(&checksum.to_le_bytes());
+        bytes
+    }
+
+    /// Decodes a superblock, or returns `None` if it is torn or was never written.
+    fn decode(bytes: &[u8; Self::ENCODED_LEN]) -> Option<Self> {
+        let u64_at = |at: usize| u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap());
+        let checksum = u32::from_le_bytes(bytes[32..].try_into().unwrap());
+        (bytes[..4] == MAGIC
+            && bytes[4..8] == VERSION.to_le_bytes()
+            && checksum == crc32(&bytes[..32]))
+        .then(|| Self {
+            sequence: u64_at(8),
+            offset: u64_at(16),
+            len: u64_at(24),
+        })
+    }
+
+    /// The superblock slot this superblock is stored in.
+    fn position(&self) -> u64 {
+        (self.sequence % 2) * SUPERBLOCK_SIZE
+    }
+}
+
+impl DiskStore {
+    /// Opens the store at the specified path, creating an empty one if the file does not
+    /// exist, and recovers the most recent intact snapshot.
+    ///
+    /// Fails with an error of kind [`io::ErrorKind::InvalidData`] if the file is not
+    /// empty but holds no intact superblock.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        let mut store = Self {
+            file,
+            current: Superblock::default(),
+        };
+
+        if store.file.metadata()?.len() == 0 {
+            store.write_superblock(&Superblock::default())?;
+            return Ok(store);
+        }
+
+        let mut candidates = Vec::with_capacity(2);
+        for slot in 0..2 {
+            let mut bytes = [0; Superblock::ENCODED_LEN];
+            store.file.seek(SeekFrom::Start(slot * SUPERBLOCK_SIZE))?;
+            match store.file.read_exact(&mut bytes) {
+                Ok(()) => candidates.extend(Superblock::decode(&bytes)),
+                Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => {}
+                Err(error) => return Err(error),
+            }
+        }
+        if candidates.is_empty() {
+            return Err(invalid_data("not a free list store"));
+        }
+
+        // Fall back to the older snapshot if the newer one was not completely written.
+        candidates.sort_by_key(|superblock| std::cmp::Reverse(superblock.sequence));
+        for superblock in candidates {
+            if store.read_snapshot(&superblock).is_ok() {
+                store.current = superblock;
+                return Ok(store);
+            }
+        }
+        Err(invalid_data("no intact snapshot in the store"))
+    }
+
+    /// Loads the current snapshot, or returns `None` if no snapshot was saved yet.
+    ///
+    /// Elements are decoded by the specified function; see [`FreeList::load_from`].
+    pub fn load<T, TIndex, A>(
+        &mut self,
+        read_element: impl FnMut(&mut &[u8]) -> io::Result<T>,
+    ) -> io::Result<Option<FreeList<T, TIndex, A>>>
+    where
+        T: Default,
+        TIndex: IndexType,
+        A: Allocator + Default,
+    {
+        if self.current.len == 0 {
+            return Ok(None);
+        }
+        let snapshot = self.read_snapshot(&self.current.clone())?;
+        FreeList::load_from(snapshot.as_slice(), read_element).map(Some)
+    }
+
+    /// Saves a snapshot of the list and makes it the current one once it is durably
+    /// written.
+    ///
+    /// Elements are encoded by the specified function; see [`FreeList::save_to`]. If the
+    /// save fails or is interrupted, the previous snapshot stays current.
+    pub fn save<T, TIndex, A>(
+        &mut self,
+        list: &FreeList<T, TIndex, A>,
+        mut write_element: impl FnMut(&T, &mut Vec<u8>) -> io::Result<()>,
+    ) -> io::Result<()>
+    where
+        T: Default,
+        TIndex: IndexType,
+        A: Allocator,
+    {
+        let mut snapshot = Vec::new();
+        list.save_to(&mut snapshot, |element, writer| {
+            write_element(element, writer)
+        })?;
+        let pages: Vec<_> = snapshot.chunks(PAGE_SIZE).collect();
+        let mut encoded = Vec::with_capacity(8 + 4 * pages.len() + snapshot.len());
+        encoded.extend_from_slice(&(snapshot.len() as u64).to_le_bytes());
+        for page in &pages {
+            encoded.extend_from_slice(&crc32(page).to_le_bytes());
+        }
+        encoded.extend_from_slice(&snapshot);
+
+        // Keep the current snapshot intact: use the space before it if the new one
+        // fits there, or the space after it otherwise.
+        let len = encoded.len() as u64;
+        let offset = if self.current.len == 0 || DATA_START + len <= self.current.offset {
+            DATA_START
+        } else {
+            self.current.offset + self.current.len
+        };
+        self.file.seek(SeekFrom::Start(offset))?;
+        self.file.write_all(&encoded)?;
+        self.file.sync_data()?;
+
+        let superblock = Superblock {
+            sequence: self.current.sequence + 1,
+            offset,
+            len,
+        };
+        self.write_superblock(&superblock)?;
+        self.current = superblock;
+        Ok(())
+    }
+
+    /// Writes a superblock into its slot and waits until it is durable.
+    fn write_superblock(&mut self, superblock: &Superblock) -> io::Result<()> {
+        self.file.seek(SeekFrom::Start(superblock.position()))?;
+        self.file.write_all(&superblock.encode())?;
+        self.file.sync_data()
+    }
+
+    /// Reads the snapshot of a superblock and verifies the checksums of all its pages.
+    fn read_snapshot(&mut self, superblock: &Superblock) -> io::Result<Vec<u8>> {
+        if superblock.len == 0 {
+            return Ok(Vec::new());
+        }
+        let len = usize::try_from(superblock.len)
+            .map_err(|_| invalid_data("snapshot length out of range"))?;
+        if superblock.offset + superblock.len > self.file.metadata()?.len() {
+            return Err(invalid_data("snapshot beyond the end of the file"));
+        }
+        let mut encoded = vec![0; len];
+        self.file.seek(SeekFrom::Start(superblock.offset))?;
+        self.file.read_exact(&mut encoded)?;
+
+        let (snapshot_len, rest) = encoded
+            .split_first_chunk::<8>()
+            .ok_or_else(|| invalid_data("truncated snapshot"))?;
+        let snapshot_len = u64::from_le_bytes(*snapshot_len) as usize;
+        let page_count = snapshot_len.div_ceil(PAGE_SIZE);
+        if rest.len() != page_count * 4 + snapshot_len {
+            return Err(invalid_data("truncated snapshot"));
+        }
+        let (checksums, snapshot) = rest.split_at(page_count * 4);
+        for (checksum, page) in checksums.chunks(4).zip(snapshot.chunks(PAGE_SIZE)) {
+            if checksum != crc32(page).to_le_bytes() {
+                return Err(invalid_data("snapshot page checksum mismatch"));
+            }
+        }
+        Ok(snapshot.to_vec())
+    }
+}
+
+/// Computes the CRC-32 (IEEE) checksum of the bytes.
+fn crc32(bytes: &[u8]) -> u32 {
+    const TABLE: [u32; 256] = {
+        let mut table = [0; 256];
+        let mut i = 0;
+        while i < 256 {
+            let mut crc = i as u32;
+            let mut bit = 0;
+            while bit < 8 {
+                crc = if crc & 1 != 0 {
+                    0xEDB8_8320 ^ (crc >> 1)
+                } else {
+                    crc >> 1
+                };
+                bit += 1;
+            }
+            table[i] = crc;
+            i += 1;
+        }
+        table
+    };
+
+    !bytes.iter().fold(!0u32, |crc, &byte| {
+        TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("free-list-{name}-{}.db", std::process::id()))
+    }
+
+    fn save(store: &mut DiskStore, list: &FreeList<u32, u8>) {
+        store
+            .save(list, |value, writer| writer.write_all(&value.to_le_bytes()))
+            .unwrap();
+    }
+
+    fn load(store: &mut DiskStore) -> Option<Vec<u32>> {
+        let list: Option<FreeList<u32, u8>> = store
+            .load(|reader| {
+                let mut bytes = [0; 4];
+                reader.read_exact(&mut bytes)?;
+                Ok(u32::from_le_bytes(bytes))
+            })
+            .unwrap();
+        list.map(|list| list.iter().map(|(_, &value)| value).collect())
+    }
+
+    #[test]
+    fn crc32_matches_reference() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn torn_snapshot_falls_back_to_previous_one() {
+        let path = temp_path("torn");
+        let _ = std::fs::remove_file(&path);
+        let mut store = DiskStore::open(&path).unwrap();
+        assert_eq!(load(&mut store), None);
+
+        let mut list = FreeList::<u32, u8>::default();
+        list.push(1);
+        save(&mut store, &list);
+        list.push(2);
+        save(&mut store, &list);
+        assert_eq!(load(&mut DiskStore::open(&path).unwrap()), Some(vec![1, 2]));
+
+        // Simulate a power loss that corrupted the newest snapshot after its superblock
+        // was written.
+        let current = store.current;
+        let mut file = OpenOptions::new().write(true).open(&path).unwrap();
+        file.seek(SeekFrom::Start(current.offset + current.len - 1))
+            .unwrap();
+        file.write_all(&[0xFF]).unwrap();
+        drop(file);
+        assert_eq!(load(&mut DiskStore::open(&path).unwrap()), Some(vec![1]));
+
+        // A torn superblock is ignored as well.
+        let mut file = OpenOptions::new().write(true).open(&path).unwrap();
+        file.seek(SeekFrom::Start(current.position() + 10)).unwrap();
+        file.write_all(&[0xFF]).unwrap();
+        drop(file);
+        let mut store = DiskStore::open(&path).unwrap();
+        assert_eq!(load(&mut store), Some(vec![1]));
+
+        // Saving again replaces the broken snapshot.
+        list.push(3);
+        save(&mut store, &list);
+        assert_eq!(
+            load(&mut DiskStore::open(&path).unwrap()),
+            Some(vec![1, 2, 3])
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,136 @@
+use crate::bitset::BitSet;
+use crate::index_type::*;
+use crate::FreeList;
+use allocator_api2::alloc::Allocator;
+use std::io::{self, Write};
+
+impl<T, TIndex, A> FreeList<T, TIndex, A>
+where
+    T: Default,
+    TIndex: IndexType,
+    A: Allocator,
+{
+    /// Renders the slots, their occupancy and the links of the free chain as a
+    /// [Graphviz](https://graphviz.org/) DOT graph.
+    ///
+    /// Occupied slots are filled green, free slots gray and quarantined slots yellow.
+    /// The rendering does not rely on the free chain being intact: cycles and links
+    /// beyond the end of the list are drawn in red instead of being followed.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// list.push("first");
+    /// list.push("second");
+    /// list.erase(0);
+    ///
+    /// let mut dot = Vec::new();
+    /// list.dump_dot(&mut dot).unwrap();
+    /// let dot = String::from_utf8(dot).unwrap();
+    /// assert!(dot.starts_with("digraph free_list {"));
+    /// assert!(dot.contains("first_free -> slot0;"));
+    /// ```
+    pub fn dump_dot<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "digraph free_list {{")?;
+        writeln!(writer, "    rankdir=LR;")?;
+        writeln!(writer, "    node [shape=box, style=filled];")?;
+
+        for index in 0..self.slot_count() {
+            let color = if self.occupied.contains(index) {
+                "palegreen"
+            } else if self.quarantine.iter().any(|&n| n.into_usize() == index) {
+                "khaki"
+            } else {
+                "lightgray"
+            };
+            writeln!(
+                writer,
+                "    slot{index} [label=\"{index}\", fillcolor={color}];"
+            )?;
+        }
+
+        writeln!(writer, "    first_free [shape=plaintext, style=solid];")?;
+        let mut from = String::from("first_free");
+        let mut visited = BitSet::default();
+        let mut token = self.first_free;
+        while token != Self::SENTINEL {
+            let index = token.into_usize();
+            if index >= self.slot_count() {
+                writeln!(
+                    writer,
+                    "    invalid{index} [label=\"{index}\", shape=plaintext, style=solid, fontcolor=red];"
+                )?;
+                writeln!(writer, "    {from} -> invalid{index} [color=red];")?;
+                break;
+            }
+
+            if visited.contains(index) || self.occupied.contains(index) {
+                writeln!(writer, "    {from} -> slot{index} [color=red];")?;
+                break;
+            }
+
+            writeln!(writer, "    {from} -> slot{index};")?;
+            visited.insert(index);
+            from = format!("slot{index}");
+            token = self.next_free(index);
+        }
+
+        writeln!(writer, "}}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FreeList;
+
+    fn render(list: &FreeList<u32, u8>) -> String {
+        let mut dot = Vec::new();
+        list.dump_dot(&mut dot).unwrap();
+        String::from_utf8(dot).unwrap()
+    }
+
+    #[test]
+    fn renders_slots_and_free_chain() {
+        let mut list = FreeList::<u32, u8>::default();
+        for i in 0..4 {
+            list.push(i);
+        }
+        list.erase(1);
+        list.erase(3);
+
+        let dot = render(&list);
+        assert!(dot.contains("slot0 [label=\"0\", fillcolor=palegreen];"));
+        assert!(dot.contains("slot1 [label=\"1\", fillcolor=lightgray];"));
+        assert!(dot.contains("first_free -> slot3;"));
+        assert!(dot.contains("slot3 -> slot1;"));
+        assert!(dot.trim_end().ends_with('}'));
+    }
+
+    #[test]
+    fn marks_cycles_in_red() {
+        let mut list = FreeList::<u32, u8>::default();
+        for i in 0..3 {
+            list.push(i);
+        }
+        list.erase(0);
+        list.erase(2);
+        list.data[0].next = 2;
+
+        let dot = render(&list);
+        assert!(dot.contains("slot0 -> slot2 [color=red];"));
+    }
+
+    #[test]
+    fn marks_quarantined_slots() {
+        let mut list = FreeList::<u32, u8>::default();
+        list.set_quarantine_len(1);
+        list.push(0);
+        list.erase(0);
+
+        let dot = render(&list);
+        assert!(dot.contains("slot0 [label=\"0\", fillcolor=khaki];"));
+        assert!(!dot.contains("first_free ->"));
+    }
+}
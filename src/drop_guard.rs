@@ -0,0 +1,97 @@
+use crate::bitset::BitSet;
+use crate::index_type::IndexType;
+use crate::{wipe, DropOrder, FreeElement};
+use allocator_api2::alloc::Allocator;
+use allocator_api2::vec::Vec;
+use std::mem::{needs_drop, ManuallyDrop};
+
+/// Drops the elements of slots that were already released by the list, and removes
+/// these slots from the slot storage afterwards.
+///
+/// If the destructor of an element panics, the guard drops the remaining elements while
+/// unwinding, so that every element is dropped exactly once and the slots are released
+/// either way; a second panic aborts the process, like it does for [`Vec`].
+pub(crate) struct DropGuard<'a, T, TIndex, A>
+where
+    TIndex: IndexType,
+    A: Allocator,
+{
+    data: &'a mut Vec<FreeElement<T, TIndex>, A>,
+    /// The number of slots that are kept.
+    len: usize,
+    /// The slots that still hold an element.
+    occupied: BitSet,
+    /// The order in which the elements are dropped.
+    order: DropOrder,
+    /// The lowest slot that may still hold an element in ascending order, or the slot
+    /// above the highest one in descending order.
+    cursor: usize,
+}
+
+impl<'a, T, TIndex, A> DropGuard<'a, T, TIndex, A>
+where
+    TIndex: IndexType,
+    A: Allocator,
+{
+    /// Creates a guard that removes all slots at and above `len`, of which the `occupied`
+    /// ones hold elements.
+    pub fn new(
+        data: &'a mut Vec<FreeElement<T, TIndex>, A>,
+        len: usize,
+        occupied: BitSet,
+        order: DropOrder,
+    ) -> Self {
+        let cursor = match order {
+            DropOrder::Ascending => 0,
+            DropOrder::Descending => usize::MAX,
+        };
+        Self {
+            data,
+            len,
+            occupied,
+            order,
+            cursor,
+        }
+    }
+
+    /// Drops all elements in the order of the guard.
+    pub fn drop_elements(&mut self) {
+        if !needs_drop::<T>() {
+            return;
+        }
+
+        let data = self.data.as_mut_ptr();
+        while let Some(index) = self.next_element() {
+            unsafe { ManuallyDrop::drop(&mut *FreeElement::element_ptr(data, index)) };
+        }
+    }
+
+    /// Finds the next slot holding an element and moves the cursor past it, so that the
+    /// element is never dropped twice.
+    fn next_element(&mut self) -> Option<usize> {
+        let index = match self.order {
+            DropOrder::Ascending => self.occupied.next_one(self.cursor)?,
+            DropOrder::Descending => self.occupied.last_one_before(self.cursor)?,
+        };
+        self.cursor = match self.order {
+            DropOrder::Ascending => index + 1,
+            DropOrder::Descending => index,
+        };
+        Some(index)
+    }
+}
+
+impl<'a, T, TIndex, A> Drop for DropGuard<'a, T, TIndex, A>
+where
+    TIndex: IndexType,
+    A: Allocator,
+{
+    fn drop(&mut self) {
+        self.drop_elements();
+
+        // Zero-sized elements are not stored, so there may be fewer slots than `len`.
+        let len = self.len.min(self.data.len());
+        wipe::wipe_slots(&mut self.data[len..]);
+        self.data.truncate(len);
+    }
+}
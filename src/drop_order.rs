@@ -0,0 +1,15 @@
+/// The order in which a [`FreeList`](crate::FreeList) drops its live elements when it is
+/// cleared, reset or dropped.
+///
+/// The order is set with [`FreeList::set_drop_order`](crate::FreeList::set_drop_order).
+/// It is part of the [raw parts](crate::RawParts) but not of snapshots.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum DropOrder {
+    /// Elements are dropped in ascending index order, e.g. parents before the children
+    /// that were inserted after them.
+    #[default]
+    Ascending,
+    /// Elements are dropped in descending index order, e.g. children before the parents
+    /// that were inserted before them.
+    Descending,
+}
@@ -0,0 +1,66 @@
+use crate::index_type::*;
+use crate::FreeList;
+use allocator_api2::alloc::{Allocator, Global};
+
+/// A handle to a vacant slot of a [`FreeList`].
+///
+/// This type is created by [`FreeList::vacant_entry`] and allows to obtain
+/// the index of an element before it is inserted, e.g. for elements that need
+/// to store their own index.
+pub struct VacantEntry<'a, T, TIndex = DefaultIndex, A = Global>
+where
+    T: Default,
+    TIndex: IndexType,
+    A: Allocator,
+{
+    list: &'a mut FreeList<T, TIndex, A>,
+    key: TIndex,
+}
+
+impl<'a, T, TIndex, A> VacantEntry<'a, T, TIndex, A>
+where
+    T: Default,
+    TIndex: IndexType,
+    A: Allocator,
+{
+    pub(crate) fn new(list: &'a mut FreeList<T, TIndex, A>) -> Self {
+        let key = list.next_index();
+        Self { list, key }
+    }
+
+    /// Gets the index at which the element will be stored.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<usize, u8>::default();
+    /// list.push(0);
+    ///
+    /// let entry = list.vacant_entry();
+    /// assert_eq!(entry.key(), 1);
+    /// ```
+    #[inline]
+    pub fn key(&self) -> TIndex {
+        self.key
+    }
+
+    /// Inserts the element into the vacant slot and returns a mutable reference to it.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<(u8, &str), u8>::default();
+    ///
+    /// let entry = list.vacant_entry();
+    /// let key = entry.key();
+    /// let element = entry.insert((key, "knows its own index"));
+    /// assert_eq!(element.0, key);
+    /// ```
+    pub fn insert(self, element: T) -> &'a mut T {
+        let index = self.list.push(element);
+        debug_assert_eq!(index, self.key);
+        unsafe { self.list.at_mut(index) }
+    }
+}
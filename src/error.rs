@@ -0,0 +1,241 @@
+use crate::index_type::*;
+use crate::Key;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// The error returned by [`FreeList::get_disjoint_mut`](crate::FreeList::get_disjoint_mut)
+/// and [`FreeList::get_disjoint_mut_slice`](crate::FreeList::get_disjoint_mut_slice).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum GetDisjointMutError {
+    /// At least one index does not refer to an occupied slot.
+    NotOccupied,
+    /// The same index was requested more than once.
+    OverlappingIndices,
+}
+
+impl Display for GetDisjointMutError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GetDisjointMutError::NotOccupied => {
+                write!(f, "an index does not refer to an occupied slot")
+            }
+            GetDisjointMutError::OverlappingIndices => {
+                write!(f, "an index was requested more than once")
+            }
+        }
+    }
+}
+
+impl Error for GetDisjointMutError {}
+
+/// The error returned by [`FreeList::try_erase`](crate::FreeList::try_erase).
+///
+/// Indices are reported as `usize` regardless of the index type of the list.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum EraseError {
+    /// The index refers to a slot beyond the end of the list.
+    OutOfRange { index: usize },
+    /// The index refers to a free slot, e.g. because its element was already erased.
+    NotOccupied { index: usize },
+}
+
+impl Display for EraseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EraseError::OutOfRange { index } => {
+                write!(f, "slot {index} is beyond the end of the list")
+            }
+            EraseError::NotOccupied { index } => {
+                write!(f, "slot {index} does not hold an element")
+            }
+        }
+    }
+}
+
+impl Error for EraseError {}
+
+/// The error returned when parsing a [`Key`](crate::Key) from its `index@generation` form.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ParseKeyError {
+    /// The text does not contain the `@` separating index and generation.
+    MissingSeparator,
+    /// The index is not a number that can be addressed by the index type.
+    InvalidIndex,
+    /// The generation is not a 32-bit unsigned number.
+    InvalidGeneration,
+}
+
+impl Display for ParseKeyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseKeyError::MissingSeparator => {
+                write!(f, "a key must have the form index@generation")
+            }
+            ParseKeyError::InvalidIndex => {
+                write!(f, "the index of the key is invalid for the index type")
+            }
+            ParseKeyError::InvalidGeneration => {
+                write!(f, "the generation of the key is invalid")
+            }
+        }
+    }
+}
+
+impl Error for ParseKeyError {}
+
+/// The error returned when a name is registered with a
+/// [`NameRegistry`](crate::NameRegistry) that already holds it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct NameTakenError<TIndex = DefaultIndex>
+where
+    TIndex: IndexType,
+{
+    /// The key of the value that holds the name.
+    pub existing: Key<TIndex>,
+}
+
+impl<TIndex> Display for NameTakenError<TIndex>
+where
+    TIndex: IndexType,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "the name is already taken by {}", self.existing)
+    }
+}
+
+impl<TIndex> Error for NameTakenError<TIndex> where TIndex: IndexType {}
+
+/// The error returned when a [`ShmFreeList`](crate::ShmFreeList) is created in or opened
+/// from a memory region.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ShmError {
+    /// The region is not aligned to the alignment of the header and the slots.
+    Misaligned,
+    /// The region is too small for the header or for the slots recorded in it.
+    TooSmall,
+    /// The region does not hold a list, or a list of another element type or layout
+    /// version.
+    Incompatible,
+}
+
+impl Display for ShmError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShmError::Misaligned => write!(f, "the memory region is misaligned"),
+            ShmError::TooSmall => write!(f, "the memory region is too small"),
+            ShmError::Incompatible => {
+                write!(f, "the memory region does not hold a compatible list")
+            }
+        }
+    }
+}
+
+impl Error for ShmError {}
+
+/// The error returned by [`FreeList::check_invariants`](crate::FreeList::check_invariants)
+/// when the internal bookkeeping of a list is inconsistent.
+///
+/// Indices are reported as `usize` regardless of the index type of the list.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum InvariantError {
+    /// A slot beyond the end of the list is marked as occupied.
+    OccupancyOutOfRange { index: usize },
+    /// The tracked number of live elements differs from the number of occupied slots.
+    LengthMismatch { tracked: usize, counted: usize },
+    /// The free chain or the quarantine refers to a slot beyond the end of the list.
+    LinkOutOfRange { index: usize },
+    /// The free chain or the quarantine refers to a slot that holds an element.
+    OccupiedSlotLinked { index: usize },
+    /// A slot appears more than once in the free chain or the quarantine,
+    /// e.g. because the free chain contains a cycle.
+    DuplicateFreeSlot { index: usize },
+    /// The free chain is not in ascending order although the reuse policy requires it.
+    UnsortedFreeChain { index: usize },
+    /// The recorded end of the free chain does not match its last slot.
+    LastFreeMismatch,
+    /// Not every slot has a generation.
+    MissingGenerations,
+    /// A free slot is neither part of the free chain nor of the quarantine and
+    /// can therefore never be reused.
+    UnlinkedFreeSlot { index: usize },
+    /// The list has more slots than the index type can address.
+    TooManySlots { slots: usize },
+}
+
+impl Display for InvariantError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InvariantError::OccupancyOutOfRange { index } => {
+                write!(
+                    f,
+                    "slot {index} is beyond the end of the list but marked as occupied"
+                )
+            }
+            InvariantError::LengthMismatch { tracked, counted } => {
+                write!(
+                    f,
+                    "the list tracks {tracked} live elements but {counted} slots are occupied"
+                )
+            }
+            InvariantError::LinkOutOfRange { index } => {
+                write!(
+                    f,
+                    "a free slot link refers to slot {index} beyond the end of the list"
+                )
+            }
+            InvariantError::OccupiedSlotLinked { index } => {
+                write!(f, "occupied slot {index} is linked as a free slot")
+            }
+            InvariantError::DuplicateFreeSlot { index } => {
+                write!(f, "free slot {index} is linked more than once")
+            }
+            InvariantError::UnsortedFreeChain { index } => {
+                write!(
+                    f,
+                    "free slot {index} breaks the ascending order of the free chain"
+                )
+            }
+            InvariantError::LastFreeMismatch => {
+                write!(
+                    f,
+                    "the recorded end of the free chain does not match its last slot"
+                )
+            }
+            InvariantError::MissingGenerations => {
+                write!(f, "not every slot has a generation")
+            }
+            InvariantError::UnlinkedFreeSlot { index } => {
+                write!(f, "free slot {index} is not linked and can never be reused")
+            }
+            InvariantError::TooManySlots { slots } => {
+                write!(
+                    f,
+                    "the list has {slots} slots, more than the index type can address"
+                )
+            }
+        }
+    }
+}
+
+impl Error for InvariantError {}
+
+/// The error returned by [`BorrowFreeList::try_borrow`](crate::BorrowFreeList::try_borrow)
+/// and [`BorrowFreeList::try_borrow_mut`](crate::BorrowFreeList::try_borrow_mut).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum BorrowError {
+    /// The key does not refer to an element.
+    NotOccupied,
+    /// The element is mutably borrowed, or borrowed at all when a mutable borrow was requested.
+    AlreadyBorrowed,
+}
+
+impl Display for BorrowError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BorrowError::NotOccupied => write!(f, "the key does not refer to an element"),
+            BorrowError::AlreadyBorrowed => write!(f, "the element is already borrowed"),
+        }
+    }
+}
+
+impl Error for BorrowError {}
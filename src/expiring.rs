@@ -0,0 +1,251 @@
+use crate::index_type::*;
+use crate::{FreeList, Key};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// A free list whose elements expire at a deadline, e.g. for session tables and caches.
+///
+/// Every element is inserted with a deadline of any ordered type, such as
+/// [`Instant`](std::time::Instant) or a tick counter. An element expires once the current
+/// time reaches its deadline. Expired elements are hidden from lookups right away and
+/// erased by [`sweep`](Self::sweep), which only visits expired deadlines.
+///
+/// Elements are addressed by [`Key`]s, so that a key of an expired element never
+/// refers to the element that reuses its slot.
+///
+/// ## Type parameters
+/// * `T` - The type of the element.
+/// * `D` - The type of the deadline.
+/// * `TIndex` - The type of the index; see [`FreeList`] for details.
+///
+/// ## Example
+/// ```rust
+/// use free_list::ExpiringFreeList;
+///
+/// let mut sessions = ExpiringFreeList::<&str, u64, u8>::default();
+/// let alice = sessions.push("alice", 10);
+/// let bob = sessions.push("bob", 20);
+///
+/// assert_eq!(sessions.get(alice, 5), Some(&"alice"));
+/// assert_eq!(sessions.get(alice, 10), None);
+///
+/// assert_eq!(sessions.sweep(15), [alice]);
+/// assert_eq!(sessions.len(), 1);
+/// assert_eq!(sessions.get(bob, 15), Some(&"bob"));
+/// ```
+pub struct ExpiringFreeList<T, D, TIndex = DefaultIndex>
+where
+    T: Default,
+    D: Ord + Copy,
+    TIndex: IndexType,
+{
+    list: FreeList<T, TIndex>,
+    /// The deadline of every slot that ever held an element; stale for free slots.
+    deadlines: Vec<D>,
+    /// The deadlines in ascending order. Entries whose key no longer refers to an element
+    /// or whose deadline was changed are stale and skipped.
+    queue: BinaryHeap<Reverse<(D, Key<TIndex>)>>,
+}
+
+impl<T, D, TIndex> Default for ExpiringFreeList<T, D, TIndex>
+where
+    T: Default,
+    D: Ord + Copy,
+    TIndex: IndexType,
+{
+    fn default() -> Self {
+        Self {
+            list: FreeList::default(),
+            deadlines: Vec::new(),
+            queue: BinaryHeap::new(),
+        }
+    }
+}
+
+impl<T, D, TIndex> ExpiringFreeList<T, D, TIndex>
+where
+    T: Default,
+    D: Ord + Copy,
+    TIndex: IndexType,
+{
+    /// Inserts an element that expires at the specified deadline and returns its key.
+    pub fn push(&mut self, element: T, deadline: D) -> Key<TIndex> {
+        let key = self.list.push_key(element);
+        let slot = key.index().into_usize();
+        if slot >= self.deadlines.len() {
+            self.deadlines.resize(slot + 1, deadline);
+        }
+        self.deadlines[slot] = deadline;
+        self.enqueue(deadline, key);
+        key
+    }
+
+    /// Removes the element of the specified key and returns it, regardless of whether
+    /// it expired, or returns `None` if the key does not refer to an element.
+    pub fn remove(&mut self, key: Key<TIndex>) -> Option<T> {
+        self.list
+            .contains_key(key)
+            .then(|| self.list.remove(key.index()))
+    }
+
+    /// Erases all elements whose deadline is reached at the specified time and returns
+    /// their keys, in the order of their deadlines.
+    pub fn sweep(&mut self, now: D) -> Vec<Key<TIndex>> {
+        let mut expired = Vec::new();
+        while let Some(&Reverse((deadline, key))) = self.queue.peek() {
+            if deadline > now {
+                break;
+            }
+            self.queue.pop();
+            if self.is_current(deadline, key) {
+                self.list.erase(key.index());
+                expired.push(key);
+            }
+        }
+        expired
+    }
+
+    /// Gets the earliest deadline of all elements, or `None` if the list is empty,
+    /// e.g. to schedule the next [`sweep`](Self::sweep).
+    pub fn next_deadline(&mut self) -> Option<D> {
+        while let Some(&Reverse((deadline, key))) = self.queue.peek() {
+            if self.is_current(deadline, key) {
+                return Some(deadline);
+            }
+            self.queue.pop();
+        }
+        None
+    }
+
+    /// Gets the deadline of the element of the specified key, or `None` if the key
+    /// does not refer to an element.
+    #[inline]
+    pub fn deadline(&self, key: Key<TIndex>) -> Option<D> {
+        self.list
+            .contains_key(key)
+            .then(|| self.deadlines[key.index().into_usize()])
+    }
+
+    /// Changes the deadline of the element of the specified key, e.g. to keep a session
+    /// alive, and returns whether the key refers to an element.
+    ///
+    /// This also revives an element that expired but was not swept yet.
+    pub fn set_deadline(&mut self, key: Key<TIndex>, deadline: D) -> bool {
+        if !self.list.contains_key(key) {
+            return false;
+        }
+        self.deadlines[key.index().into_usize()] = deadline;
+        self.enqueue(deadline, key);
+        true
+    }
+
+    /// Gets a reference to the element of the specified key, or `None` if the key does
+    /// not refer to an element or the element expired at the specified time.
+    #[inline]
+    pub fn get(&self, key: Key<TIndex>, now: D) -> Option<&T> {
+        self.is_live(key, now).then(|| &self.list[key.index()])
+    }
+
+    /// Gets a mutable reference to the element of the specified key, or `None` if the
+    /// key does not refer to an element or the element expired at the specified time.
+    #[inline]
+    pub fn get_mut(&mut self, key: Key<TIndex>, now: D) -> Option<&mut T> {
+        self.is_live(key, now).then(|| &mut self.list[key.index()])
+    }
+
+    /// Determines whether the specified key refers to an element that did not expire
+    /// at the specified time.
+    #[inline]
+    pub fn contains(&self, key: Key<TIndex>, now: D) -> bool {
+        self.is_live(key, now)
+    }
+
+    /// Gets the number of elements in the list, including expired elements that were
+    /// not swept yet.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Determines whether the list contains no elements, including expired elements that
+    /// were not swept yet.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Gets an iterator over the keys and values of all elements that did not expire at
+    /// the specified time, in ascending index order.
+    pub fn iter(&self, now: D) -> impl Iterator<Item = (Key<TIndex>, &T)> + '_ {
+        self.list
+            .iter()
+            .filter(move |&(index, _)| self.deadlines[index.into_usize()] > now)
+            .map(|(index, value)| (self.list.key(index).expect("index is occupied"), value))
+    }
+
+    /// Determines whether the key refers to an element that did not expire.
+    fn is_live(&self, key: Key<TIndex>, now: D) -> bool {
+        self.list.contains_key(key) && self.deadlines[key.index().into_usize()] > now
+    }
+
+    /// Determines whether a queued deadline is the current deadline of an element.
+    fn is_current(&self, deadline: D, key: Key<TIndex>) -> bool {
+        self.list.contains_key(key) && self.deadlines[key.index().into_usize()] == deadline
+    }
+
+    /// Queues a deadline, dropping stale entries once they outnumber the elements.
+    fn enqueue(&mut self, deadline: D, key: Key<TIndex>) {
+        self.queue.push(Reverse((deadline, key)));
+        if self.queue.len() > 2 * self.list.len() + 16 {
+            let queue = std::mem::take(&mut self.queue);
+            self.queue = queue
+                .into_iter()
+                .filter(|&Reverse((deadline, key))| self.is_current(deadline, key))
+                .collect();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changed_deadlines_and_removed_keys_are_not_swept() {
+        let mut list = ExpiringFreeList::<String, u32, u8>::default();
+        let a = list.push("a".into(), 5);
+        let b = list.push("b".into(), 5);
+        let c = list.push("c".into(), 7);
+        assert!(list.set_deadline(a, 10));
+        assert!(list.set_deadline(c, 3));
+        assert_eq!(list.remove(b), Some("b".into()));
+        assert_eq!(list.next_deadline(), Some(3));
+
+        assert_eq!(list.sweep(6), [c]);
+        assert_eq!(list.get(a, 6).map(String::as_str), Some("a"));
+        assert_eq!(list.deadline(c), None);
+        assert!(!list.set_deadline(c, 20));
+
+        // The slot of an expired element is reused under a new generation.
+        let d = list.push("d".into(), 8);
+        assert_eq!(d.index(), c.index());
+        assert_eq!(list.get(c, 0), None);
+        assert_eq!(list.iter(7).map(|(key, _)| key).collect::<Vec<_>>(), [a, d]);
+
+        assert_eq!(list.sweep(10), [d, a]);
+        assert!(list.is_empty());
+        assert_eq!(list.next_deadline(), None);
+    }
+
+    #[test]
+    fn stale_queue_entries_are_dropped() {
+        let mut list = ExpiringFreeList::<u8, u32, u8>::default();
+        let key = list.push(1, 0);
+        for deadline in 1..100 {
+            list.set_deadline(key, deadline);
+        }
+        assert!(list.queue.len() <= 2 * list.len() + 16);
+        assert_eq!(list.sweep(98), []);
+        assert_eq!(list.sweep(99), [key]);
+    }
+}
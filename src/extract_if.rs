@@ -0,0 +1,118 @@
+use crate::index_type::*;
+use crate::FreeList;
+use allocator_api2::alloc::{Allocator, Global};
+use std::iter::FusedIterator;
+
+/// An iterator that removes the elements matching a predicate from a [`FreeList`] and
+/// yields them together with their indices, in ascending index order.
+///
+/// Every matching element is erased from the list before it is yielded, so the list is
+/// consistent at all times. Dropping the iterator early keeps all elements that were not
+/// visited yet.
+///
+/// This type is created by [`FreeList::extract_if`].
+pub struct ExtractIf<'a, T, F, TIndex = DefaultIndex, A = Global>
+where
+    T: Default,
+    TIndex: IndexType,
+    A: Allocator,
+    F: FnMut(TIndex, &mut T) -> bool,
+{
+    list: &'a mut FreeList<T, TIndex, A>,
+    predicate: F,
+    /// The next slot to visit.
+    next: usize,
+}
+
+impl<'a, T, F, TIndex, A> ExtractIf<'a, T, F, TIndex, A>
+where
+    T: Default,
+    TIndex: IndexType,
+    A: Allocator,
+    F: FnMut(TIndex, &mut T) -> bool,
+{
+    pub(crate) fn new(list: &'a mut FreeList<T, TIndex, A>, predicate: F) -> Self {
+        Self {
+            list,
+            predicate,
+            next: 0,
+        }
+    }
+}
+
+impl<'a, T, F, TIndex, A> Iterator for ExtractIf<'a, T, F, TIndex, A>
+where
+    T: Default,
+    TIndex: IndexType,
+    A: Allocator,
+    F: FnMut(TIndex, &mut T) -> bool,
+{
+    type Item = (TIndex, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.next < self.list.slot_count() {
+            let slot = self.next;
+            self.next += 1;
+            if !self.list.occupied.contains(slot) {
+                continue;
+            }
+
+            let index = index_from_usize(slot);
+            // SAFETY: The slot is occupied.
+            let element = unsafe { self.list.at_mut(index) };
+            if (self.predicate)(index, element) {
+                return Some((index, self.list.take(index)));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.list.len()))
+    }
+}
+
+impl<'a, T, F, TIndex, A> FusedIterator for ExtractIf<'a, T, F, TIndex, A>
+where
+    T: Default,
+    TIndex: IndexType,
+    A: Allocator,
+    F: FnMut(TIndex, &mut T) -> bool,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FreeList, ReusePolicy};
+
+    #[test]
+    fn dropping_early_keeps_the_rest() {
+        for policy in [
+            ReusePolicy::Lifo,
+            ReusePolicy::Fifo,
+            ReusePolicy::LowestIndexFirst,
+        ] {
+            let mut list = FreeList::<u32, u8>::with_reuse_policy(policy);
+            for value in 0..10 {
+                list.push(value);
+            }
+            list.erase(4);
+
+            let mut odd = list.extract_if(|_, value| *value % 2 == 1);
+            assert_eq!(odd.next(), Some((1, 1)));
+            assert_eq!(odd.next(), Some((3, 3)));
+
+            assert_eq!(list.len(), 7);
+            assert_eq!(list.check_invariants(), Ok(()));
+            assert_eq!(
+                list.keys().collect::<Vec<_>>(),
+                [0, 2, 5, 6, 7, 8, 9],
+                "{policy:?}"
+            );
+
+            let rest: Vec<_> = list.extract_if(|index, _| index > 6).collect();
+            assert_eq!(rest, [(7, 7), (8, 8), (9, 9)]);
+            assert_eq!(list.check_invariants(), Ok(()));
+        }
+    }
+}
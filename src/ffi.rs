@@ -0,0 +1,158 @@
+//! A C interface to a free list of opaque pointers.
+//!
+//! The list is exposed as the opaque type [`CFreeList`] and elements are addressed
+//! through 64-bit handles that combine the slot index and its generation, so that
+//! handles to erased elements are rejected instead of aliasing a new element.
+//! The functions are designed to be picked up by [cbindgen](https://github.com/mozilla/cbindgen);
+//! the library can be linked into C or C++ code by building it as a static or
+//! dynamic library, e.g. with `cargo rustc --features ffi --crate-type staticlib`.
+//!
+//! ```c
+//! CFreeList *list = freelist_new();
+//! uint64_t handle = freelist_push(list, &value);
+//! assert(freelist_get(list, handle) == &value);
+//! freelist_erase(list, handle);
+//! freelist_delete(list);
+//! ```
+
+use crate::{FreeList, Key};
+use std::ffi::c_void;
+
+/// The handle returned by [`freelist_push`] if the element could not be inserted.
+pub const FREELIST_INVALID_HANDLE: u64 = u64::MAX;
+
+/// An opaque free list that stores untyped pointers owned by the caller.
+pub struct CFreeList {
+    list: FreeList<Element, u32>,
+}
+
+/// A pointer stored in the list; the list never dereferences it.
+struct Element(*mut c_void);
+
+impl Default for Element {
+    fn default() -> Self {
+        Self(std::ptr::null_mut())
+    }
+}
+
+impl CFreeList {
+    /// Converts a key into a handle.
+    fn handle(key: Key<u32>) -> u64 {
+        (u64::from(key.generation()) << 32) | u64::from(key.index())
+    }
+
+    /// Converts a handle into a key of an element in this list, if the element exists.
+    fn key(&self, handle: u64) -> Option<Key<u32>> {
+        let key = Key::new(handle as u32, (handle >> 32) as u32);
+        self.list.contains_key(key).then_some(key)
+    }
+}
+
+/// Creates an empty list. The list must be released with [`freelist_delete`].
+#[no_mangle]
+pub extern "C" fn freelist_new() -> *mut CFreeList {
+    Box::into_raw(Box::new(CFreeList {
+        list: FreeList::default(),
+    }))
+}
+
+/// Releases a list created by [`freelist_new`]. The stored pointers are not freed.
+///
+/// # Safety
+/// `list` must be null or a pointer returned by [`freelist_new`] that was not yet released.
+#[no_mangle]
+pub unsafe extern "C" fn freelist_delete(list: *mut CFreeList) {
+    if !list.is_null() {
+        drop(Box::from_raw(list));
+    }
+}
+
+/// Inserts a pointer into the list and returns a handle to it, or
+/// [`FREELIST_INVALID_HANDLE`] if the list is full.
+///
+/// # Safety
+/// `list` must be a valid pointer returned by [`freelist_new`].
+#[no_mangle]
+pub unsafe extern "C" fn freelist_push(list: *mut CFreeList, value: *mut c_void) -> u64 {
+    let list = &mut *list;
+    if list.list.len() >= u32::MAX as usize - 1 {
+        return FREELIST_INVALID_HANDLE;
+    }
+    CFreeList::handle(list.list.push_key(Element(value)))
+}
+
+/// Removes the element of the specified handle and returns whether it existed.
+///
+/// # Safety
+/// `list` must be a valid pointer returned by [`freelist_new`].
+#[no_mangle]
+pub unsafe extern "C" fn freelist_erase(list: *mut CFreeList, handle: u64) -> bool {
+    let list = &mut *list;
+    match list.key(handle) {
+        Some(key) => {
+            list.list.erase(key.index());
+            true
+        }
+        None => false,
+    }
+}
+
+/// Gets the pointer stored for the specified handle, or null if the handle is invalid.
+///
+/// # Safety
+/// `list` must be a valid pointer returned by [`freelist_new`].
+#[no_mangle]
+pub unsafe extern "C" fn freelist_get(list: *const CFreeList, handle: u64) -> *mut c_void {
+    let list = &*list;
+    match list.key(handle) {
+        Some(key) => list.list[key.index()].0,
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// Gets the number of elements in the list.
+///
+/// # Safety
+/// `list` must be a valid pointer returned by [`freelist_new`].
+#[no_mangle]
+pub unsafe extern "C" fn freelist_len(list: *const CFreeList) -> usize {
+    (*list).list.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handles_are_validated() {
+        let mut values = [1u32, 2, 3];
+        let pointers: Vec<*mut c_void> = values
+            .iter_mut()
+            .map(|value| value as *mut u32 as *mut c_void)
+            .collect();
+
+        unsafe {
+            let list = freelist_new();
+            let first = freelist_push(list, pointers[0]);
+            let second = freelist_push(list, pointers[1]);
+            assert_eq!(freelist_get(list, first), pointers[0]);
+            assert_eq!(freelist_len(list), 2);
+
+            assert!(freelist_erase(list, first));
+            assert!(!freelist_erase(list, first));
+            assert!(freelist_get(list, first).is_null());
+
+            // The slot is reused, but the stale handle stays invalid.
+            let third = freelist_push(list, pointers[2]);
+            assert_eq!(third as u32, first as u32);
+            assert_ne!(third, first);
+            assert!(freelist_get(list, first).is_null());
+            assert_eq!(freelist_get(list, third), pointers[2]);
+            assert_eq!(freelist_get(list, second), pointers[1]);
+            assert!(freelist_get(list, FREELIST_INVALID_HANDLE).is_null());
+
+            freelist_delete(list);
+            freelist_delete(std::ptr::null_mut());
+        }
+    }
+}
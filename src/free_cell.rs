@@ -0,0 +1,181 @@
+use crate::index_type::*;
+use crate::{FreeList, Key};
+use std::cell::Cell;
+use std::fmt::{Debug, Formatter};
+
+/// A free list of [`Copy`] values that can be read and written through a shared
+/// reference, with the semantics of [`Cell`].
+///
+/// Values are copied in and out instead of borrowed, so there is no borrow state to
+/// track and no way to fail at runtime, which makes this cheaper than a
+/// [`BorrowFreeList`](crate::BorrowFreeList) for small values. This suits single-threaded
+/// code that passes shared references around, e.g. callbacks of a user interface that
+/// update pooled state. Inserting and removing elements takes `&mut self`.
+///
+/// ## Type parameters
+/// * `T` - The type of the element.
+/// * `TIndex` - The type of the index; see [`FreeList`] for details.
+///
+/// ## Example
+/// ```rust
+/// use free_list::FreeCell;
+///
+/// let mut counters = FreeCell::<u32>::default();
+/// let clicks = counters.push(0);
+///
+/// let counters = &counters;
+/// let on_click = || counters.update(clicks, |count| count + 1);
+/// on_click();
+/// on_click();
+/// assert_eq!(counters.get(clicks), Some(2));
+/// ```
+pub struct FreeCell<T, TIndex = DefaultIndex>
+where
+    T: Copy + Default,
+    TIndex: IndexType,
+{
+    list: FreeList<Cell<T>, TIndex>,
+}
+
+impl<T, TIndex> Default for FreeCell<T, TIndex>
+where
+    T: Copy + Default,
+    TIndex: IndexType,
+{
+    fn default() -> Self {
+        Self {
+            list: FreeList::default(),
+        }
+    }
+}
+
+impl<T, TIndex> FreeCell<T, TIndex>
+where
+    T: Copy + Default,
+    TIndex: IndexType,
+{
+    /// Inserts a value and returns its key.
+    #[inline]
+    pub fn push(&mut self, value: T) -> Key<TIndex> {
+        self.list.push_key(Cell::new(value))
+    }
+
+    /// Removes the value of the specified key and returns it, or returns `None` if the
+    /// key does not refer to a value.
+    pub fn remove(&mut self, key: Key<TIndex>) -> Option<T> {
+        self.list
+            .contains_key(key)
+            .then(|| self.list.remove(key.index()).get())
+    }
+
+    /// Gets a copy of the value of the specified key, or `None` if the key does not
+    /// refer to a value.
+    #[inline]
+    pub fn get(&self, key: Key<TIndex>) -> Option<T> {
+        self.cell(key).map(Cell::get)
+    }
+
+    /// Sets the value of the specified key and returns whether the key referred to a value.
+    #[inline]
+    pub fn set(&self, key: Key<TIndex>, value: T) -> bool {
+        self.cell(key).map(|cell| cell.set(value)).is_some()
+    }
+
+    /// Sets the value of the specified key and returns the previous value, or returns
+    /// `None` if the key does not refer to a value.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeCell;
+    ///
+    /// let mut cells = FreeCell::<char>::default();
+    /// let key = cells.push('a');
+    /// assert_eq!(cells.replace(key, 'b'), Some('a'));
+    /// assert_eq!(cells.get(key), Some('b'));
+    /// ```
+    #[inline]
+    pub fn replace(&self, key: Key<TIndex>, value: T) -> Option<T> {
+        self.cell(key).map(|cell| cell.replace(value))
+    }
+
+    /// Replaces the value of the specified key by the result of the function and returns
+    /// the new value, or returns `None` if the key does not refer to a value.
+    pub fn update(&self, key: Key<TIndex>, f: impl FnOnce(T) -> T) -> Option<T> {
+        let cell = self.cell(key)?;
+        let value = f(cell.get());
+        cell.set(value);
+        Some(value)
+    }
+
+    /// Gets a mutable reference to the value of the specified key, or `None` if the key
+    /// does not refer to a value.
+    #[inline]
+    pub fn get_mut(&mut self, key: Key<TIndex>) -> Option<&mut T> {
+        self.list
+            .contains_key(key)
+            .then(|| self.list[key.index()].get_mut())
+    }
+
+    /// Determines whether the specified key refers to a value.
+    #[inline]
+    pub fn contains_key(&self, key: Key<TIndex>) -> bool {
+        self.list.contains_key(key)
+    }
+
+    /// Gets the number of values.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Determines whether the list is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Gets an iterator over the indices and copies of all values in ascending index order.
+    pub fn iter(&self) -> impl Iterator<Item = (TIndex, T)> + '_ {
+        self.list.iter().map(|(index, cell)| (index, cell.get()))
+    }
+
+    /// Gets the cell of the specified key.
+    #[inline]
+    fn cell(&self, key: Key<TIndex>) -> Option<&Cell<T>> {
+        self.list.contains_key(key).then(|| &self.list[key.index()])
+    }
+}
+
+impl<T, TIndex> Debug for FreeCell<T, TIndex>
+where
+    T: Copy + Default + Debug,
+    TIndex: IndexType + Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stale_keys_are_rejected_after_reuse() {
+        let mut cells = FreeCell::<u32, u8>::default();
+        let first = cells.push(1);
+        let second = cells.push(2);
+        assert_eq!(cells.remove(first), Some(1));
+
+        let third = cells.push(3);
+        assert_eq!(third.index(), first.index());
+        assert!(!cells.set(first, 4));
+        assert_eq!(cells.update(first, |value| value + 1), None);
+        assert_eq!(cells.get(first), None);
+
+        assert!(cells.set(second, 5));
+        *cells.get_mut(third).unwrap() *= 2;
+        assert_eq!(cells.iter().collect::<Vec<_>>(), [(0, 6), (1, 5)]);
+        assert_eq!(format!("{cells:?}"), "{0: 6, 1: 5}");
+    }
+}
@@ -0,0 +1,173 @@
+use crate::index_type::*;
+use crate::{Allocator, FreeList};
+
+/// A single step in the construction of an arbitrary list.
+#[derive(Debug, Clone)]
+enum Operation<T> {
+    /// Pushes the element.
+    Push(T),
+    /// Erases the element at the specified position among the occupied slots, modulo their count.
+    Erase(usize),
+}
+
+impl<T, TIndex, A> FreeList<T, TIndex, A>
+where
+    T: Default,
+    TIndex: IndexType,
+    A: Allocator + Default,
+{
+    /// Builds a list by replaying the specified operations on an empty list.
+    ///
+    /// Pushes that would exceed the capacity of the index type and erasures
+    /// on an empty list are skipped.
+    fn from_operations(operations: impl IntoIterator<Item = Operation<T>>) -> Self {
+        let mut list = Self::default();
+        for operation in operations {
+            list.apply(operation);
+        }
+        list
+    }
+
+    /// Applies a single operation to the list.
+    fn apply(&mut self, operation: Operation<T>) {
+        match operation {
+            Operation::Push(element) => {
+                if self.len() < TIndex::MAX.into_usize() - 1 {
+                    self.push(element);
+                }
+            }
+            Operation::Erase(selector) => {
+                if !self.is_empty() {
+                    let index = self
+                        .keys()
+                        .nth(selector % self.len())
+                        .expect("the list is not empty");
+                    self.erase(index);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T> arbitrary::Arbitrary<'a> for Operation<T>
+where
+    T: arbitrary::Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        if u.arbitrary()? {
+            Ok(Self::Push(u.arbitrary()?))
+        } else {
+            Ok(Self::Erase(u.arbitrary()?))
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T, TIndex, A> arbitrary::Arbitrary<'a> for FreeList<T, TIndex, A>
+where
+    T: Default + arbitrary::Arbitrary<'a>,
+    TIndex: IndexType,
+    A: Allocator + Default,
+{
+    /// Generates a list from an arbitrary interleaving of pushes and erasures.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use arbitrary::{Arbitrary, Unstructured};
+    /// use free_list::FreeList;
+    ///
+    /// let bytes = [1, 10, 1, 20, 0, 0, 1, 30];
+    /// let mut u = Unstructured::new(&bytes);
+    /// let list = FreeList::<u8, u8>::arbitrary(&mut u).unwrap();
+    /// list.check_invariants().unwrap();
+    /// ```
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let mut list = Self::default();
+        for operation in u.arbitrary_iter::<Operation<T>>()? {
+            list.apply(operation?);
+        }
+        Ok(list)
+    }
+}
+
+/// Creates a proptest strategy for lists built from up to `size` interleaved pushes and
+/// erasures, with the pushed elements drawn from `element`.
+///
+/// Shrinking removes operations, so failing cases shrink towards short histories.
+///
+/// ## Example
+/// ```rust
+/// use free_list::{free_list_strategy, FreeList};
+/// use proptest::prelude::*;
+///
+/// proptest!(|(list in free_list_strategy::<_, u8>(any::<u32>(), 0..64))| {
+///     prop_assert_eq!(list.iter().count(), list.len());
+/// });
+/// ```
+#[cfg(feature = "proptest")]
+pub fn free_list_strategy<T, TIndex>(
+    element: impl proptest::strategy::Strategy<Value = T>,
+    size: impl Into<proptest::collection::SizeRange>,
+) -> impl proptest::strategy::Strategy<Value = FreeList<T, TIndex>>
+where
+    T: Default + std::fmt::Debug + Clone,
+    TIndex: IndexType,
+{
+    use proptest::prelude::*;
+
+    let operation = prop_oneof![
+        2 => element.prop_map(Operation::Push),
+        1 => any::<usize>().prop_map(Operation::Erase),
+    ];
+    proptest::collection::vec(operation, size).prop_map(FreeList::from_operations)
+}
+
+#[cfg(feature = "proptest")]
+impl<T, TIndex> proptest::arbitrary::Arbitrary for FreeList<T, TIndex>
+where
+    T: Default + std::fmt::Debug + Clone + proptest::arbitrary::Arbitrary + 'static,
+    TIndex: IndexType + 'static,
+{
+    type Parameters = (proptest::collection::SizeRange, T::Parameters);
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with((size, element): Self::Parameters) -> Self::Strategy {
+        use proptest::strategy::Strategy;
+        free_list_strategy(proptest::arbitrary::any_with::<T>(element), size).boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn operations_are_replayed() {
+        let list = FreeList::<u32, u8>::from_operations([
+            Operation::Push(1),
+            Operation::Push(2),
+            Operation::Push(3),
+            Operation::Erase(4),
+            Operation::Push(4),
+            Operation::Erase(0),
+        ]);
+        assert_eq!(list.iter().collect::<Vec<_>>(), [(1, &4), (2, &3)]);
+        list.check_invariants().unwrap();
+    }
+
+    #[test]
+    fn pushes_beyond_the_index_capacity_are_skipped() {
+        let list = FreeList::<u32, u8>::from_operations((0..300).map(Operation::Push));
+        assert_eq!(list.len(), 254);
+    }
+
+    #[cfg(feature = "proptest")]
+    proptest::proptest! {
+        #[test]
+        fn generated_lists_are_consistent(list in proptest::prelude::any::<FreeList<u16, u8>>()) {
+            proptest::prop_assert!(list.check_invariants().is_ok());
+            proptest::prop_assert_eq!(list.iter().count(), list.len());
+        }
+    }
+}
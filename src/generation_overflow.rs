@@ -0,0 +1,58 @@
+/// What a [`FreeList`](crate::FreeList) does when the generation of a slot cannot be
+/// advanced any further because it reached `u32::MAX`.
+///
+/// The policy is set with
+/// [`FreeList::set_generation_overflow`](crate::FreeList::set_generation_overflow).
+/// It is part of the [raw parts](crate::RawParts) but not of snapshots.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum GenerationOverflow {
+    /// The generation wraps around to zero, so that keys of long-removed elements may
+    /// match new elements again.
+    ///
+    /// Every wrap is counted, see
+    /// [`FreeList::generation_wraps`](crate::FreeList::generation_wraps).
+    #[default]
+    Wrap,
+    /// Removing the element panics and leaves the list unchanged.
+    Panic,
+    /// The generation stays at `u32::MAX`, so that all keys handed out for the slot from
+    /// then on are equal.
+    Saturate,
+    /// The slot is retired and never reused, so that keys never match a different element.
+    ///
+    /// The last generation is reserved to mark retired slots; a slot is therefore retired
+    /// once its generation would reach `u32::MAX`. This costs one slot per four billion
+    /// reuses.
+    Retire,
+}
+
+impl GenerationOverflow {
+    /// Advances the generation of a slot whose element was removed and returns whether
+    /// the slot may be reused.
+    ///
+    /// With [`Panic`](Self::Panic), callers must have rejected exhausted generations
+    /// before modifying the list.
+    #[inline]
+    pub(crate) fn advance(self, generation: &mut u32, wraps: &mut u64) -> bool {
+        match (generation.checked_add(1), self) {
+            (Some(u32::MAX), GenerationOverflow::Retire) => {
+                *generation = u32::MAX;
+                false
+            }
+            (Some(next), _) => {
+                *generation = next;
+                true
+            }
+            (None, GenerationOverflow::Wrap) => {
+                *generation = 0;
+                *wraps += 1;
+                true
+            }
+            (None, GenerationOverflow::Saturate) => true,
+            (None, GenerationOverflow::Retire) => false,
+            (None, GenerationOverflow::Panic) => {
+                unreachable!("exhausted generations are rejected before the list is modified")
+            }
+        }
+    }
+}
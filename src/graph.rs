@@ -0,0 +1,357 @@
+use crate::index_type::*;
+use crate::FreeList;
+
+/// A directed graph whose vertices and edges are stored in [`FreeList`]s.
+///
+/// Every vertex keeps two doubly-linked lists of edges, one of its outgoing and one of
+/// its incoming edges, which are linked by index through the edges themselves. Removing
+/// an edge therefore takes constant time, and removing a vertex takes time proportional
+/// to the number of its edges. Vertex and edge handles stay valid until their vertex or
+/// edge is removed.
+///
+/// ## Type parameters
+/// * `V` - The type of the vertex weights.
+/// * `E` - The type of the edge weights.
+/// * `TIndex` - The type of the vertex and edge handles; see [`FreeList`] for details.
+///
+/// ## Example
+/// ```rust
+/// use free_list::Graph;
+///
+/// let mut graph = Graph::<&str, u32, u16>::default();
+/// let a = graph.add_vertex("a");
+/// let b = graph.add_vertex("b");
+/// let c = graph.add_vertex("c");
+/// let ab = graph.add_edge(a, b, 1);
+/// graph.add_edge(b, c, 2);
+/// graph.add_edge(c, a, 3);
+///
+/// assert_eq!(graph.remove_vertex(b), Some("b"));
+/// assert_eq!(graph.edge_count(), 1);
+/// assert!(!graph.contains_edge(ab));
+/// assert_eq!(graph.outgoing(c).map(|edge| graph.endpoints(edge)).collect::<Vec<_>>(), [Some((c, a))]);
+/// ```
+pub struct Graph<V, E, TIndex = DefaultIndex>
+where
+    V: Default,
+    E: Default,
+    TIndex: IndexType,
+{
+    vertices: FreeList<Vertex<V, TIndex>, TIndex>,
+    edges: FreeList<Edge<E, TIndex>, TIndex>,
+}
+
+/// A vertex of a [`Graph`].
+struct Vertex<V, TIndex> {
+    weight: V,
+    /// The first outgoing edge, or `SENTINEL` if there is none.
+    first_out: TIndex,
+    /// The first incoming edge, or `SENTINEL` if there is none.
+    first_in: TIndex,
+}
+
+/// An edge of a [`Graph`].
+struct Edge<E, TIndex> {
+    weight: E,
+    source: TIndex,
+    target: TIndex,
+    /// The neighbors in the outgoing list of the source; `SENTINEL` at the ends.
+    prev_out: TIndex,
+    next_out: TIndex,
+    /// The neighbors in the incoming list of the target; `SENTINEL` at the ends.
+    prev_in: TIndex,
+    next_in: TIndex,
+}
+
+impl<V, TIndex> Default for Vertex<V, TIndex>
+where
+    V: Default,
+    TIndex: IndexType,
+{
+    fn default() -> Self {
+        Self {
+            weight: V::default(),
+            first_out: TIndex::MAX,
+            first_in: TIndex::MAX,
+        }
+    }
+}
+
+impl<E, TIndex> Default for Edge<E, TIndex>
+where
+    E: Default,
+    TIndex: IndexType,
+{
+    fn default() -> Self {
+        Self {
+            weight: E::default(),
+            source: TIndex::MAX,
+            target: TIndex::MAX,
+            prev_out: TIndex::MAX,
+            next_out: TIndex::MAX,
+            prev_in: TIndex::MAX,
+            next_in: TIndex::MAX,
+        }
+    }
+}
+
+impl<V, E, TIndex> Default for Graph<V, E, TIndex>
+where
+    V: Default,
+    E: Default,
+    TIndex: IndexType,
+{
+    fn default() -> Self {
+        Self {
+            vertices: FreeList::default(),
+            edges: FreeList::default(),
+        }
+    }
+}
+
+impl<V, E, TIndex> Graph<V, E, TIndex>
+where
+    V: Default,
+    E: Default,
+    TIndex: IndexType,
+{
+    /// The sentinel value indicates the absence of an edge.
+    const SENTINEL: TIndex = TIndex::MAX;
+
+    /// Adds a vertex and returns its handle.
+    pub fn add_vertex(&mut self, weight: V) -> TIndex {
+        self.vertices.push(Vertex {
+            weight,
+            first_out: Self::SENTINEL,
+            first_in: Self::SENTINEL,
+        })
+    }
+
+    /// Adds an edge from `source` to `target` and returns its handle.
+    ///
+    /// ## Panics
+    /// Panics if either vertex does not exist.
+    pub fn add_edge(&mut self, source: TIndex, target: TIndex, weight: E) -> TIndex {
+        assert!(
+            self.vertices.contains(source) && self.vertices.contains(target),
+            "Attempted to connect a vertex that does not exist ({source:?} -> {target:?})"
+        );
+        let next_out = self.vertices[source].first_out;
+        let next_in = self.vertices[target].first_in;
+        let edge = self.edges.push(Edge {
+            weight,
+            source,
+            target,
+            prev_out: Self::SENTINEL,
+            next_out,
+            prev_in: Self::SENTINEL,
+            next_in,
+        });
+        if next_out != Self::SENTINEL {
+            self.edges[next_out].prev_out = edge;
+        }
+        if next_in != Self::SENTINEL {
+            self.edges[next_in].prev_in = edge;
+        }
+        self.vertices[source].first_out = edge;
+        self.vertices[target].first_in = edge;
+        edge
+    }
+
+    /// Removes an edge and returns its weight, or returns `None` if the edge does not exist.
+    pub fn remove_edge(&mut self, edge: TIndex) -> Option<E> {
+        let edge = self.edges.try_remove(edge)?;
+        if edge.prev_out == Self::SENTINEL {
+            self.vertices[edge.source].first_out = edge.next_out;
+        } else {
+            self.edges[edge.prev_out].next_out = edge.next_out;
+        }
+        if edge.next_out != Self::SENTINEL {
+            self.edges[edge.next_out].prev_out = edge.prev_out;
+        }
+        if edge.prev_in == Self::SENTINEL {
+            self.vertices[edge.target].first_in = edge.next_in;
+        } else {
+            self.edges[edge.prev_in].next_in = edge.next_in;
+        }
+        if edge.next_in != Self::SENTINEL {
+            self.edges[edge.next_in].prev_in = edge.prev_in;
+        }
+        Some(edge.weight)
+    }
+
+    /// Removes a vertex together with all its edges and returns its weight, or returns
+    /// `None` if the vertex does not exist.
+    pub fn remove_vertex(&mut self, vertex: TIndex) -> Option<V> {
+        if !self.vertices.contains(vertex) {
+            return None;
+        }
+        while self.vertices[vertex].first_out != Self::SENTINEL {
+            self.remove_edge(self.vertices[vertex].first_out);
+        }
+        while self.vertices[vertex].first_in != Self::SENTINEL {
+            self.remove_edge(self.vertices[vertex].first_in);
+        }
+        Some(self.vertices.remove(vertex).weight)
+    }
+
+    /// Gets a reference to the weight of the specified vertex.
+    #[inline]
+    pub fn vertex(&self, vertex: TIndex) -> Option<&V> {
+        self.vertices.get(vertex).map(|vertex| &vertex.weight)
+    }
+
+    /// Gets a mutable reference to the weight of the specified vertex.
+    #[inline]
+    pub fn vertex_mut(&mut self, vertex: TIndex) -> Option<&mut V> {
+        self.vertices
+            .get_mut(vertex)
+            .map(|vertex| &mut vertex.weight)
+    }
+
+    /// Gets a reference to the weight of the specified edge.
+    #[inline]
+    pub fn edge(&self, edge: TIndex) -> Option<&E> {
+        self.edges.get(edge).map(|edge| &edge.weight)
+    }
+
+    /// Gets a mutable reference to the weight of the specified edge.
+    #[inline]
+    pub fn edge_mut(&mut self, edge: TIndex) -> Option<&mut E> {
+        self.edges.get_mut(edge).map(|edge| &mut edge.weight)
+    }
+
+    /// Gets the source and target vertex of the specified edge.
+    #[inline]
+    pub fn endpoints(&self, edge: TIndex) -> Option<(TIndex, TIndex)> {
+        self.edges.get(edge).map(|edge| (edge.source, edge.target))
+    }
+
+    /// Determines whether the specified vertex exists.
+    #[inline]
+    pub fn contains_vertex(&self, vertex: TIndex) -> bool {
+        self.vertices.contains(vertex)
+    }
+
+    /// Determines whether the specified edge exists.
+    #[inline]
+    pub fn contains_edge(&self, edge: TIndex) -> bool {
+        self.edges.contains(edge)
+    }
+
+    /// Gets the number of vertices.
+    #[inline]
+    pub fn vertex_count(&self) -> usize {
+        self.vertices.len()
+    }
+
+    /// Gets the number of edges.
+    #[inline]
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Gets an iterator over the handles and weights of all vertices, in ascending handle order.
+    pub fn vertices(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = (TIndex, &V)> + ExactSizeIterator + '_ {
+        self.vertices
+            .iter()
+            .map(|(index, vertex)| (index, &vertex.weight))
+    }
+
+    /// Gets an iterator over the handles of the outgoing edges of the specified vertex,
+    /// most recently added first. The iterator is empty if the vertex does not exist.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::Graph;
+    ///
+    /// let mut graph = Graph::<(), (), u8>::default();
+    /// let a = graph.add_vertex(());
+    /// let b = graph.add_vertex(());
+    /// let first = graph.add_edge(a, b, ());
+    /// let second = graph.add_edge(a, a, ());
+    /// assert_eq!(graph.outgoing(a).collect::<Vec<_>>(), [second, first]);
+    /// assert_eq!(graph.incoming(a).collect::<Vec<_>>(), [second]);
+    /// ```
+    pub fn outgoing(&self, vertex: TIndex) -> impl Iterator<Item = TIndex> + '_ {
+        let first = self
+            .vertices
+            .get(vertex)
+            .map_or(Self::SENTINEL, |vertex| vertex.first_out);
+        self.walk(first, |edge| edge.next_out)
+    }
+
+    /// Gets an iterator over the handles of the incoming edges of the specified vertex,
+    /// most recently added first. The iterator is empty if the vertex does not exist.
+    pub fn incoming(&self, vertex: TIndex) -> impl Iterator<Item = TIndex> + '_ {
+        let first = self
+            .vertices
+            .get(vertex)
+            .map_or(Self::SENTINEL, |vertex| vertex.first_in);
+        self.walk(first, |edge| edge.next_in)
+    }
+
+    /// Follows an edge list from the specified edge.
+    fn walk(
+        &self,
+        first: TIndex,
+        next: fn(&Edge<E, TIndex>) -> TIndex,
+    ) -> impl Iterator<Item = TIndex> + '_ {
+        let mut edge = first;
+        std::iter::from_fn(move || {
+            if edge == Self::SENTINEL {
+                return None;
+            }
+            let current = edge;
+            edge = next(&self.edges[edge]);
+            Some(current)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edges_are_unlinked_from_both_lists() {
+        let mut graph = Graph::<u32, u32, u8>::default();
+        let a = graph.add_vertex(0);
+        let b = graph.add_vertex(1);
+        let ab1 = graph.add_edge(a, b, 1);
+        let ab2 = graph.add_edge(a, b, 2);
+        let ab3 = graph.add_edge(a, b, 3);
+        let ba = graph.add_edge(b, a, 4);
+
+        assert_eq!(graph.remove_edge(ab2), Some(2));
+        assert_eq!(graph.remove_edge(ab2), None);
+        assert_eq!(graph.outgoing(a).collect::<Vec<_>>(), [ab3, ab1]);
+        assert_eq!(graph.incoming(b).collect::<Vec<_>>(), [ab3, ab1]);
+        assert_eq!(graph.remove_edge(ab3), Some(3));
+        assert_eq!(graph.outgoing(a).collect::<Vec<_>>(), [ab1]);
+
+        // Reused edge slots are linked like new ones.
+        let aa = graph.add_edge(a, a, 5);
+        assert_eq!(graph.endpoints(aa), Some((a, a)));
+        assert_eq!(graph.outgoing(a).collect::<Vec<_>>(), [aa, ab1]);
+        assert_eq!(graph.incoming(a).collect::<Vec<_>>(), [aa, ba]);
+
+        assert_eq!(graph.remove_vertex(a), Some(0));
+        assert_eq!(graph.edge_count(), 0);
+        assert_eq!(graph.incoming(b).count(), 0);
+        assert_eq!(graph.outgoing(b).count(), 0);
+        assert_eq!(graph.vertices().collect::<Vec<_>>(), [(b, &1)]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn connecting_a_removed_vertex_panics() {
+        let mut graph = Graph::<u32, u32, u8>::default();
+        let a = graph.add_vertex(0);
+        let b = graph.add_vertex(1);
+        graph.remove_vertex(b);
+        graph.add_edge(a, b, 0);
+    }
+}
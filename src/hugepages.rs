@@ -0,0 +1,203 @@
+use allocator_api2::alloc::{AllocError, Allocator, Global};
+use std::alloc::Layout;
+use std::ptr::NonNull;
+
+/// The size of a huge page on x86-64 and AArch64 with 4 KiB base pages.
+const HUGE_PAGE_SIZE: usize = 2 << 20;
+
+/// An allocator that backs large allocations with huge pages on Linux, reducing TLB
+/// pressure for multi-gigabyte slot arrays.
+///
+/// Allocations of at least the [threshold](Self::with_threshold) (2 MiB by default) are
+/// mapped directly with `mmap`, rounded up to whole 2 MiB huge pages and aligned to them.
+/// Smaller allocations are served by the [`Global`] allocator.
+///
+/// * In [`HugePages::Transparent`] mode, the mapping is marked with
+///   `madvise(MADV_HUGEPAGE)`, so that the kernel backs it with transparent huge pages
+///   when available. This requires transparent huge pages to be enabled in `always`
+///   or `madvise` mode.
+/// * In [`HugePages::Explicit`] mode, the mapping is requested with `MAP_HUGETLB` from
+///   the pool of huge pages reserved in `/proc/sys/vm/nr_hugepages`. If the pool is
+///   exhausted, the allocator falls back to transparent huge pages.
+///
+/// ## Example
+/// ```rust
+/// use free_list::{FreeList, HugePageAllocator};
+///
+/// let mut list = FreeList::<[u64; 8], u32, _>::new_in(HugePageAllocator::transparent());
+/// list.reserve(1 << 16);
+/// assert_eq!(list.push([0; 8]), 0);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct HugePageAllocator {
+    mode: HugePages,
+    threshold: usize,
+}
+
+/// The kind of huge pages used by a [`HugePageAllocator`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum HugePages {
+    /// Transparent huge pages, assigned by the kernel on a best-effort basis.
+    Transparent,
+    /// Explicitly reserved huge pages, falling back to transparent huge pages.
+    Explicit,
+}
+
+impl Default for HugePageAllocator {
+    /// Creates an allocator that uses transparent huge pages.
+    fn default() -> Self {
+        Self::transparent()
+    }
+}
+
+impl HugePageAllocator {
+    /// Creates an allocator that uses transparent huge pages.
+    pub const fn transparent() -> Self {
+        Self {
+            mode: HugePages::Transparent,
+            threshold: HUGE_PAGE_SIZE,
+        }
+    }
+
+    /// Creates an allocator that uses explicitly reserved huge pages.
+    pub const fn explicit() -> Self {
+        Self {
+            mode: HugePages::Explicit,
+            threshold: HUGE_PAGE_SIZE,
+        }
+    }
+
+    /// Sets the size in bytes from which allocations are backed by huge pages.
+    ///
+    /// Lower thresholds waste up to a huge page of memory per allocation.
+    pub const fn with_threshold(mut self, threshold: usize) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Gets the kind of huge pages used.
+    pub const fn mode(&self) -> HugePages {
+        self.mode
+    }
+
+    /// Gets the size in bytes from which allocations are backed by huge pages.
+    pub const fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Determines whether an allocation of the specified layout is mapped directly.
+    fn is_mapped(&self, layout: Layout) -> bool {
+        layout.size() != 0 && layout.size() >= self.threshold && layout.align() <= HUGE_PAGE_SIZE
+    }
+
+    /// Maps memory of the specified length, which is a multiple of the huge page size.
+    fn map(&self, len: usize) -> Option<NonNull<u8>> {
+        if self.mode == HugePages::Explicit {
+            if let Some(ptr) = map_anonymous(len, libc::MAP_HUGETLB) {
+                return Some(ptr);
+            }
+        }
+
+        // Map an additional huge page so that the mapping can be aligned to one.
+        let padded = len.checked_add(HUGE_PAGE_SIZE)?;
+        let start = map_anonymous(padded, 0)?.as_ptr() as usize;
+        let aligned = start.next_multiple_of(HUGE_PAGE_SIZE);
+        let end = start + padded;
+        unsafe {
+            if aligned > start {
+                libc::munmap(start as *mut libc::c_void, aligned - start);
+            }
+            if end > aligned + len {
+                libc::munmap((aligned + len) as *mut libc::c_void, end - aligned - len);
+            }
+
+            // Failing to advise only loses the benefit, not the memory.
+            libc::madvise(aligned as *mut libc::c_void, len, libc::MADV_HUGEPAGE);
+        }
+        NonNull::new(aligned as *mut u8)
+    }
+}
+
+unsafe impl Allocator for HugePageAllocator {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        if !self.is_mapped(layout) {
+            return Global.allocate(layout);
+        }
+
+        let len = mapped_len(layout).ok_or(AllocError)?;
+        let ptr = self.map(len).ok_or(AllocError)?;
+        Ok(NonNull::slice_from_raw_parts(ptr, len))
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        if !self.is_mapped(layout) {
+            return Global.deallocate(ptr, layout);
+        }
+
+        // The length cannot overflow since it was computed for the allocation.
+        let len = mapped_len(layout).unwrap_or_default();
+        libc::munmap(ptr.as_ptr().cast(), len);
+    }
+}
+
+/// Gets the length of the mapping of the specified layout.
+fn mapped_len(layout: Layout) -> Option<usize> {
+    layout.size().checked_next_multiple_of(HUGE_PAGE_SIZE)
+}
+
+/// Maps zeroed, private anonymous memory with the specified additional flags.
+fn map_anonymous(len: usize, flags: libc::c_int) -> Option<NonNull<u8>> {
+    let ptr = unsafe {
+        libc::mmap(
+            std::ptr::null_mut(),
+            len,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS | flags,
+            -1,
+            0,
+        )
+    };
+    if ptr == libc::MAP_FAILED {
+        None
+    } else {
+        NonNull::new(ptr.cast())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FreeList;
+
+    #[test]
+    fn large_allocations_are_aligned_to_huge_pages() {
+        for alloc in [
+            HugePageAllocator::transparent(),
+            HugePageAllocator::explicit(),
+        ] {
+            let layout = Layout::from_size_align(3 << 20, 64).unwrap();
+            let memory = alloc.allocate(layout).unwrap();
+            assert_eq!(memory.len(), 4 << 20);
+            assert_eq!(memory.cast::<u8>().as_ptr() as usize % HUGE_PAGE_SIZE, 0);
+            unsafe {
+                memory.cast::<u8>().as_ptr().write_bytes(1, memory.len());
+                alloc.deallocate(memory.cast(), layout);
+            }
+        }
+    }
+
+    #[test]
+    fn lists_grow_across_the_threshold() {
+        let alloc = HugePageAllocator::transparent().with_threshold(1 << 16);
+        let mut list = FreeList::<u64, u32, _>::new_in(alloc);
+        for value in 0..100_000 {
+            assert_eq!(list.push(value), value as u32);
+        }
+        list.erase(7);
+        assert_eq!(
+            list.iter().map(|(_, &value)| value).sum::<u64>(),
+            4_999_949_993
+        );
+        assert_eq!(list.allocator().threshold(), 1 << 16);
+    }
+}
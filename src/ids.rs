@@ -0,0 +1,165 @@
+use crate::index_type::*;
+use crate::{EraseError, FreeList, Key, Keys, ReusePolicy};
+
+/// An allocator of recycled indices without any payload, e.g. for entity or connection
+/// IDs whose data lives elsewhere.
+///
+/// It uses the free chain of a [`FreeList`] of zero-sized elements, so it occupies no
+/// memory per allocated ID. Freed IDs are handed out again according to the
+/// [`ReusePolicy`]. Every ID also has a generation that changes when it is freed, so
+/// [`allocate_key`](Self::allocate_key) yields [`Key`]s that detect reuse; callers that
+/// do not need this can ignore generations entirely.
+///
+/// ## Type parameters
+/// * `TIndex` - The type of the index; see [`FreeList`] for details.
+///
+/// ## Example
+/// ```rust
+/// use free_list::IdAllocator;
+///
+/// let mut ids = IdAllocator::<u16>::default();
+/// let first = ids.allocate();
+/// let second = ids.allocate();
+/// assert_eq!((first, second), (0, 1));
+///
+/// ids.free(first);
+/// assert!(!ids.is_allocated(first));
+/// assert_eq!(ids.allocate(), first);
+/// ```
+pub struct IdAllocator<TIndex = DefaultIndex>
+where
+    TIndex: IndexType,
+{
+    ids: FreeList<(), TIndex>,
+}
+
+impl<TIndex> Default for IdAllocator<TIndex>
+where
+    TIndex: IndexType,
+{
+    fn default() -> Self {
+        Self {
+            ids: FreeList::default(),
+        }
+    }
+}
+
+impl<TIndex> IdAllocator<TIndex>
+where
+    TIndex: IndexType,
+{
+    /// Creates an allocator that hands out freed IDs again in the order given by the
+    /// specified policy.
+    pub fn with_reuse_policy(policy: ReusePolicy) -> Self {
+        Self {
+            ids: FreeList::with_reuse_policy(policy),
+        }
+    }
+
+    /// Allocates an ID, reusing a freed one if possible.
+    #[inline]
+    pub fn allocate(&mut self) -> TIndex {
+        self.ids.push(())
+    }
+
+    /// Allocates an ID and returns it together with its generation.
+    #[inline]
+    pub fn allocate_key(&mut self) -> Key<TIndex> {
+        self.ids.push_key(())
+    }
+
+    /// Frees an ID so that it can be allocated again.
+    ///
+    /// ## Panics
+    /// Panics if the ID is not allocated.
+    pub fn free(&mut self, id: TIndex) {
+        if let Err(error) = self.ids.try_erase(id) {
+            panic!("Attempted to free an ID that is not allocated ({error})");
+        }
+    }
+
+    /// Frees an ID so that it can be allocated again, or reports why it cannot be freed.
+    #[inline]
+    pub fn try_free(&mut self, id: TIndex) -> Result<(), EraseError> {
+        self.ids.try_erase(id)
+    }
+
+    /// Determines whether the specified ID is allocated.
+    #[inline]
+    pub fn is_allocated(&self, id: TIndex) -> bool {
+        self.ids.contains(id)
+    }
+
+    /// Determines whether the specified key refers to an allocated ID of the same
+    /// generation, i.e. whether the ID was not freed since the key was allocated.
+    #[inline]
+    pub fn is_current(&self, key: Key<TIndex>) -> bool {
+        self.ids.contains_key(key)
+    }
+
+    /// Gets the current key of an allocated ID, or `None` if the ID is not allocated.
+    #[inline]
+    pub fn key(&self, id: TIndex) -> Option<Key<TIndex>> {
+        self.ids.key(id)
+    }
+
+    /// Gets the number of allocated IDs.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Determines whether no IDs are allocated.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Frees all IDs.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.ids.clear();
+    }
+
+    /// Gets an iterator over all allocated IDs, in ascending order.
+    #[inline]
+    pub fn iter(&self) -> Keys<'_, TIndex> {
+        self.ids.keys()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn freed_ids_are_reused_under_new_generations() {
+        let mut ids = IdAllocator::<u8>::with_reuse_policy(ReusePolicy::Fifo);
+        let keys: Vec<_> = (0..4).map(|_| ids.allocate_key()).collect();
+        ids.free(keys[2].index());
+        assert_eq!(
+            ids.try_free(keys[2].index()),
+            Err(EraseError::NotOccupied { index: 2 })
+        );
+        ids.free(keys[0].index());
+
+        assert_eq!(ids.allocate(), keys[2].index());
+        assert!(!ids.is_current(keys[2]));
+        assert_ne!(ids.key(keys[2].index()), Some(keys[2]));
+        assert!(ids.is_current(keys[1]));
+        assert_eq!(ids.iter().collect::<Vec<_>>(), [1, 2, 3]);
+
+        ids.clear();
+        assert!(ids.is_empty());
+        assert_eq!(ids.try_free(9), Err(EraseError::OutOfRange { index: 9 }));
+    }
+
+    #[test]
+    #[should_panic(expected = "Attempted to free an ID that is not allocated")]
+    fn double_free_panics() {
+        let mut ids = IdAllocator::<u8>::default();
+        let id = ids.allocate();
+        ids.free(id);
+        ids.free(id);
+    }
+}
@@ -1,5 +1,17 @@
 use std::fmt::Debug;
 
+/// The index type used by the lists of this crate unless another one is specified.
+///
+/// This is `usize` by default, or `u32` if the `default-index-u32` feature is enabled.
+#[cfg(not(feature = "default-index-u32"))]
+pub type DefaultIndex = usize;
+
+/// The index type used by the lists of this crate unless another one is specified.
+///
+/// This is `usize` by default, or `u32` if the `default-index-u32` feature is enabled.
+#[cfg(feature = "default-index-u32")]
+pub type DefaultIndex = u32;
+
 /// A trait for the type that is used as an index into the list.
 /// The type needs to be convertible to `usize` and should generally
 /// be as small as possible; the list can store up to the maximum
@@ -19,14 +31,28 @@ impl<T> IndexType for T where
 {
 }
 
-/// A trait providing a conversion method into and from `usize` values.
+/// A trait providing conversions between the implementing type and `usize` values.
+///
+/// Converting from `usize` is fallible since not every value can be represented
+/// by smaller types; lists use it to detect when their index type is exhausted.
+pub trait FromAndIntoUsize: Sized {
+    /// Converts a `usize` value into the implementing type, or returns `None`
+    /// if the value cannot be represented.
+    fn try_from_usize(value: usize) -> Option<Self>;
+
+    /// Converts the value into a `usize`, saturating at `usize::MAX` if it
+    /// cannot be represented.
+    fn into_usize(self) -> usize;
+}
+
+/// Converts a slot position into an index.
 ///
-/// ## Safety
-/// This conversion can fail if the specified `value` is higher than
-/// the highest possible value of the underlying type, and vice versa.
-pub trait FromAndIntoUsize {
-    unsafe fn from(value: usize) -> Self;
-    unsafe fn into(self) -> usize;
+/// ## Panics
+/// Panics if the position cannot be represented by the index type.
+#[inline]
+pub(crate) fn index_from_usize<TIndex: FromAndIntoUsize>(value: usize) -> TIndex {
+    TIndex::try_from_usize(value)
+        .unwrap_or_else(|| panic!("The index type cannot address position {value}"))
 }
 
 /// Obtains the highest possible value of the implementing type.
@@ -59,113 +85,34 @@ impl MaxValue for usize {
     const MAX: usize = usize::MAX;
 }
 
-impl FromAndIntoUsize for u8 {
-    unsafe fn from(value: usize) -> Self {
-        debug_assert!(
-            value <= Self::MAX as usize,
-            "can address at most {} values",
-            Self::MAX
-        );
-        value as Self
-    }
-
-    unsafe fn into(self) -> usize {
-        debug_assert!(
-            self <= usize::MAX as Self,
-            "can address at most {} values",
-            usize::MAX
-        );
-        self as usize
-    }
-}
-
-impl FromAndIntoUsize for u16 {
-    unsafe fn from(value: usize) -> Self {
-        debug_assert!(
-            value <= Self::MAX as usize,
-            "can address at most {} values",
-            Self::MAX
-        );
-        value as Self
-    }
-
-    unsafe fn into(self) -> usize {
-        debug_assert!(
-            self <= usize::MAX as Self,
-            "can address at most {} values",
-            usize::MAX
-        );
-        self as usize
-    }
-}
-
-impl FromAndIntoUsize for u32 {
-    unsafe fn from(value: usize) -> Self {
-        debug_assert!(
-            value <= Self::MAX as usize,
-            "can address at most {} values",
-            Self::MAX
-        );
-        value as Self
-    }
-
-    unsafe fn into(self) -> usize {
-        debug_assert!(
-            self <= usize::MAX as Self,
-            "can address at most {} values",
-            usize::MAX
-        );
-        self as usize
-    }
-}
-
-impl FromAndIntoUsize for u64 {
-    unsafe fn from(value: usize) -> Self {
-        debug_assert!(
-            value <= Self::MAX as usize,
-            "can address at most {} values",
-            Self::MAX
-        );
-        value as Self
-    }
+macro_rules! impl_from_and_into_usize {
+    ($($primitive:ty),+) => {
+        $(
+            impl FromAndIntoUsize for $primitive {
+                #[inline]
+                fn try_from_usize(value: usize) -> Option<Self> {
+                    Self::try_from(value).ok()
+                }
 
-    unsafe fn into(self) -> usize {
-        debug_assert!(
-            self <= usize::MAX as Self,
-            "can address at most {} values",
-            usize::MAX
-        );
-        self as usize
-    }
+                #[inline]
+                fn into_usize(self) -> usize {
+                    usize::try_from(self).unwrap_or(usize::MAX)
+                }
+            }
+        )+
+    };
 }
 
-impl FromAndIntoUsize for u128 {
-    unsafe fn from(value: usize) -> Self {
-        debug_assert!(
-            value <= Self::MAX as usize,
-            "can address at most {} values",
-            Self::MAX
-        );
-        value as Self
-    }
-
-    unsafe fn into(self) -> usize {
-        debug_assert!(
-            self <= usize::MAX as Self,
-            "can address at most {} values",
-            usize::MAX
-        );
-        self as usize
-    }
-}
+impl_from_and_into_usize!(u8, u16, u32, u64, u128);
 
-/// The only safe implementation of `FromUnsafe`.
 impl FromAndIntoUsize for usize {
-    unsafe fn from(value: usize) -> Self {
-        value
+    #[inline]
+    fn try_from_usize(value: usize) -> Option<Self> {
+        Some(value)
     }
 
-    unsafe fn into(self) -> usize {
+    #[inline]
+    fn into_usize(self) -> usize {
         self
     }
 }
@@ -173,6 +120,7 @@ impl FromAndIntoUsize for usize {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::any::TypeId;
 
     #[test]
     fn u8_max() {
@@ -206,4 +154,25 @@ mod tests {
     fn usize_max() {
         assert_eq!(<usize as MaxValue>::MAX, usize::MAX);
     }
+
+    #[test]
+    fn conversions_are_checked() {
+        assert_eq!(u8::try_from_usize(254), Some(254));
+        assert_eq!(u8::try_from_usize(256), None);
+        assert_eq!(u16::try_from_usize(70000), None);
+        assert_eq!(u128::MAX.into_usize(), usize::MAX);
+        assert_eq!(42u8.into_usize(), 42);
+    }
+
+    #[test]
+    #[cfg(not(feature = "default-index-u32"))]
+    fn default_index_is_usize() {
+        assert_eq!(TypeId::of::<DefaultIndex>(), TypeId::of::<usize>());
+    }
+
+    #[test]
+    #[cfg(feature = "default-index-u32")]
+    fn default_index_is_u32() {
+        assert_eq!(TypeId::of::<DefaultIndex>(), TypeId::of::<u32>());
+    }
 }
@@ -0,0 +1,218 @@
+use crate::bitset::Ones;
+use crate::index_type::*;
+use crate::FreeElement;
+use std::iter::FusedIterator;
+use std::marker::PhantomData;
+
+/// An iterator over the indices of all occupied slots of a [`FreeList`](crate::FreeList),
+/// in ascending order.
+///
+/// This type is created by [`FreeList::keys`](crate::FreeList::keys).
+#[derive(Debug, Clone)]
+pub struct Keys<'a, TIndex>
+where
+    TIndex: IndexType,
+{
+    ones: Ones<'a>,
+    _index: PhantomData<TIndex>,
+}
+
+impl<'a, TIndex> Keys<'a, TIndex>
+where
+    TIndex: IndexType,
+{
+    pub(crate) fn new(ones: Ones<'a>) -> Self {
+        Self {
+            ones,
+            _index: PhantomData,
+        }
+    }
+}
+
+impl<'a, TIndex> Iterator for Keys<'a, TIndex>
+where
+    TIndex: IndexType,
+{
+    type Item = TIndex;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.ones.next().map(index_from_usize)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.ones.size_hint()
+    }
+}
+
+impl<'a, TIndex> DoubleEndedIterator for Keys<'a, TIndex>
+where
+    TIndex: IndexType,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.ones.next_back().map(index_from_usize)
+    }
+}
+
+impl<'a, TIndex> ExactSizeIterator for Keys<'a, TIndex> where TIndex: IndexType {}
+
+impl<'a, TIndex> FusedIterator for Keys<'a, TIndex> where TIndex: IndexType {}
+
+/// An iterator over the indices and values of all occupied slots of a
+/// [`FreeList`](crate::FreeList), in ascending index order.
+///
+/// This type is created by [`FreeList::iter`](crate::FreeList::iter).
+pub struct Iter<'a, T, TIndex>
+where
+    TIndex: IndexType,
+{
+    keys: Keys<'a, TIndex>,
+    data: &'a [FreeElement<T, TIndex>],
+}
+
+impl<'a, T, TIndex> Iter<'a, T, TIndex>
+where
+    TIndex: IndexType,
+{
+    /// Creates an iterator over the specified slots.
+    ///
+    /// All indices yielded by `ones` must refer to occupied slots of `data`.
+    pub(crate) fn new(ones: Ones<'a>, data: &'a [FreeElement<T, TIndex>]) -> Self {
+        Self {
+            keys: Keys::new(ones),
+            data,
+        }
+    }
+
+    /// Gets the element of an occupied slot yielded by `keys`.
+    fn element(&self, index: TIndex) -> &'a T {
+        // SAFETY: Only occupied slots are yielded.
+        unsafe { &*FreeElement::element_ptr(self.data.as_ptr().cast_mut(), index.into_usize()) }
+    }
+}
+
+impl<'a, T, TIndex> Clone for Iter<'a, T, TIndex>
+where
+    TIndex: IndexType,
+{
+    fn clone(&self) -> Self {
+        Self {
+            keys: self.keys.clone(),
+            data: self.data,
+        }
+    }
+}
+
+impl<'a, T, TIndex> Iterator for Iter<'a, T, TIndex>
+where
+    TIndex: IndexType,
+{
+    type Item = (TIndex, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.keys.next()?;
+        Some((index, self.element(index)))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.keys.size_hint()
+    }
+}
+
+impl<'a, T, TIndex> DoubleEndedIterator for Iter<'a, T, TIndex>
+where
+    TIndex: IndexType,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let index = self.keys.next_back()?;
+        Some((index, self.element(index)))
+    }
+}
+
+impl<'a, T, TIndex> ExactSizeIterator for Iter<'a, T, TIndex> where TIndex: IndexType {}
+
+impl<'a, T, TIndex> FusedIterator for Iter<'a, T, TIndex> where TIndex: IndexType {}
+
+/// An iterator over the indices and mutable values of all occupied slots of a
+/// [`FreeList`](crate::FreeList), in ascending index order.
+///
+/// This type is created by [`FreeList::iter_mut`](crate::FreeList::iter_mut).
+pub struct IterMut<'a, T, TIndex>
+where
+    TIndex: IndexType,
+{
+    keys: Keys<'a, TIndex>,
+    data: *mut FreeElement<T, TIndex>,
+    _data: PhantomData<&'a mut [FreeElement<T, TIndex>]>,
+}
+
+impl<'a, T, TIndex> IterMut<'a, T, TIndex>
+where
+    TIndex: IndexType,
+{
+    /// Creates an iterator over the specified slots.
+    ///
+    /// All indices yielded by `ones` must refer to occupied slots of `data`.
+    pub(crate) fn new(ones: Ones<'a>, data: &'a mut [FreeElement<T, TIndex>]) -> Self {
+        Self {
+            keys: Keys::new(ones),
+            data: data.as_mut_ptr(),
+            _data: PhantomData,
+        }
+    }
+
+    /// Gets the element of an occupied slot yielded by `keys`.
+    fn element(&mut self, index: TIndex) -> &'a mut T {
+        // SAFETY: Only occupied slots are yielded, and every index is yielded at most once,
+        // so no two references alias.
+        unsafe { &mut *FreeElement::element_ptr(self.data, index.into_usize()) }
+    }
+}
+
+impl<'a, T, TIndex> Iterator for IterMut<'a, T, TIndex>
+where
+    TIndex: IndexType,
+{
+    type Item = (TIndex, &'a mut T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.keys.next()?;
+        Some((index, self.element(index)))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.keys.size_hint()
+    }
+}
+
+impl<'a, T, TIndex> DoubleEndedIterator for IterMut<'a, T, TIndex>
+where
+    TIndex: IndexType,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let index = self.keys.next_back()?;
+        Some((index, self.element(index)))
+    }
+}
+
+impl<'a, T, TIndex> ExactSizeIterator for IterMut<'a, T, TIndex> where TIndex: IndexType {}
+
+impl<'a, T, TIndex> FusedIterator for IterMut<'a, T, TIndex> where TIndex: IndexType {}
+
+// SAFETY: The iterator hands out references to distinct elements like `&mut [T]` does.
+unsafe impl<'a, T, TIndex> Send for IterMut<'a, T, TIndex>
+where
+    T: Send,
+    TIndex: IndexType + Sync,
+{
+}
+
+// SAFETY: The iterator hands out references to distinct elements like `&mut [T]` does.
+unsafe impl<'a, T, TIndex> Sync for IterMut<'a, T, TIndex>
+where
+    T: Sync,
+    TIndex: IndexType + Sync,
+{
+}
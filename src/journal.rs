@@ -0,0 +1,197 @@
+use crate::index_type::*;
+use crate::{Key, Observer};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// A structural change of a [`FreeList`](crate::FreeList) recorded by a [`Journal`].
+///
+/// Every entry carries the tick at which it was recorded. Ticks start at one and
+/// increase by one per entry, so a gap between consecutive ticks reveals that entries
+/// were discarded because the journal was full.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum JournalEntry<TIndex = DefaultIndex>
+where
+    TIndex: IndexType,
+{
+    /// An element was inserted into the slot of the key.
+    Push { key: Key<TIndex>, tick: u64 },
+    /// The element of the key was removed.
+    Erase { key: Key<TIndex>, tick: u64 },
+    /// All elements were removed.
+    Clear { tick: u64 },
+}
+
+impl<TIndex> JournalEntry<TIndex>
+where
+    TIndex: IndexType,
+{
+    /// Gets the tick at which the entry was recorded.
+    #[inline]
+    pub fn tick(&self) -> u64 {
+        match *self {
+            JournalEntry::Push { tick, .. }
+            | JournalEntry::Erase { tick, .. }
+            | JournalEntry::Clear { tick } => tick,
+        }
+    }
+}
+
+/// A bounded record of the structural changes of a [`FreeList`](crate::FreeList), e.g. to
+/// replicate its contents to another thread, process or client.
+///
+/// The journal is an [`Observer`] and is registered with
+/// [`FreeList::set_observer`](crate::FreeList::set_observer). All clones share the same
+/// entries, so a clone can be kept to [`drain`](Self::drain) them, also from another thread.
+/// When the journal is full, the oldest entry is discarded for every new one.
+///
+/// ## Example
+/// ```rust
+/// use free_list::{FreeList, Journal, JournalEntry, Key};
+///
+/// let journal = Journal::with_capacity(16);
+/// let mut list = FreeList::<&str, u8>::default();
+/// list.set_observer(journal.clone());
+///
+/// list.push("first");
+/// list.erase(0);
+/// assert_eq!(
+///     journal.drain(),
+///     [
+///         JournalEntry::Push { key: Key::new(0, 0), tick: 1 },
+///         JournalEntry::Erase { key: Key::new(0, 0), tick: 2 },
+///     ]
+/// );
+/// assert!(journal.is_empty());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Journal<TIndex = DefaultIndex>
+where
+    TIndex: IndexType,
+{
+    state: Arc<Mutex<JournalState<TIndex>>>,
+}
+
+#[derive(Debug)]
+struct JournalState<TIndex>
+where
+    TIndex: IndexType,
+{
+    entries: VecDeque<JournalEntry<TIndex>>,
+    capacity: usize,
+    tick: u64,
+}
+
+impl<TIndex> Journal<TIndex>
+where
+    TIndex: IndexType,
+{
+    /// Creates an empty journal that keeps at most the specified number of entries.
+    ///
+    /// ## Panics
+    /// Panics if the capacity is zero.
+    pub fn with_capacity(capacity: usize) -> Self {
+        assert!(
+            capacity > 0,
+            "Attempted to create a journal that cannot hold any entries"
+        );
+        Self {
+            state: Arc::new(Mutex::new(JournalState {
+                entries: VecDeque::with_capacity(capacity),
+                capacity,
+                tick: 0,
+            })),
+        }
+    }
+
+    /// Removes all recorded entries and returns them, oldest first.
+    pub fn drain(&self) -> Vec<JournalEntry<TIndex>> {
+        self.lock().entries.drain(..).collect()
+    }
+
+    /// Gets the maximum number of entries the journal keeps.
+    pub fn capacity(&self) -> usize {
+        self.lock().capacity
+    }
+
+    /// Gets the number of recorded entries.
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    /// Determines whether the journal holds no entries.
+    pub fn is_empty(&self) -> bool {
+        self.lock().entries.is_empty()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, JournalState<TIndex>> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn record(&self, entry: impl FnOnce(u64) -> JournalEntry<TIndex>) {
+        let mut state = self.lock();
+        state.tick += 1;
+        let entry = entry(state.tick);
+        if state.entries.len() == state.capacity {
+            state.entries.pop_front();
+        }
+        state.entries.push_back(entry);
+    }
+}
+
+impl<TIndex> Observer<TIndex> for Journal<TIndex>
+where
+    TIndex: IndexType + Send,
+{
+    fn on_insert(&mut self, key: Key<TIndex>) {
+        self.record(|tick| JournalEntry::Push { key, tick });
+    }
+
+    fn on_erase(&mut self, key: Key<TIndex>) {
+        self.record(|tick| JournalEntry::Erase { key, tick });
+    }
+
+    fn on_clear(&mut self) {
+        self.record(|tick| JournalEntry::Clear { tick });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FreeList;
+
+    #[test]
+    fn full_journal_discards_oldest_entries() {
+        let journal = Journal::with_capacity(3);
+        let mut list = FreeList::<u32, u8>::default();
+        list.set_observer(journal.clone());
+        for value in 0..4 {
+            list.push(value);
+        }
+        list.clear();
+
+        let entries = journal.drain();
+        assert_eq!(
+            entries,
+            [
+                JournalEntry::Push {
+                    key: Key::new(2, 0),
+                    tick: 3
+                },
+                JournalEntry::Push {
+                    key: Key::new(3, 0),
+                    tick: 4
+                },
+                JournalEntry::Clear { tick: 5 },
+            ]
+        );
+
+        // Draining from another thread sees the entries recorded since.
+        list.push(4);
+        let drained = std::thread::spawn(move || journal.drain()).join().unwrap();
+        assert_eq!(
+            drained.iter().map(JournalEntry::tick).collect::<Vec<_>>(),
+            [6]
+        );
+    }
+}
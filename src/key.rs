@@ -0,0 +1,111 @@
+use crate::index_type::*;
+use crate::ParseKeyError;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// An index into a [`FreeList`](crate::FreeList) together with the generation of its slot.
+///
+/// The generation of a slot changes whenever its element is removed, so a key
+/// only matches the element it was obtained for, even after the slot was reused.
+/// Keys are obtained with [`FreeList::key`](crate::FreeList::key).
+///
+/// Keys are displayed as `index@generation` and can be parsed from that form again,
+/// e.g. to look up an element by a key taken from a log.
+///
+/// ## Example
+/// ```rust
+/// use free_list::Key;
+///
+/// let key = Key::<u16>::new(42, 3);
+/// assert_eq!(key.to_string(), "42@3");
+/// assert_eq!("42@3".parse(), Ok(key));
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Key<TIndex = DefaultIndex>
+where
+    TIndex: IndexType,
+{
+    index: TIndex,
+    generation: u32,
+}
+
+impl<TIndex> Key<TIndex>
+where
+    TIndex: IndexType,
+{
+    /// Creates a key from its parts.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::Key;
+    ///
+    /// let key = Key::<u8>::new(3, 1);
+    /// assert_eq!(key.index(), 3);
+    /// assert_eq!(key.generation(), 1);
+    /// ```
+    #[inline]
+    pub const fn new(index: TIndex, generation: u32) -> Self {
+        Self { index, generation }
+    }
+
+    /// Gets the index of the slot.
+    #[inline]
+    pub const fn index(self) -> TIndex {
+        self.index
+    }
+
+    /// Gets the generation of the slot at the time the key was obtained.
+    #[inline]
+    pub const fn generation(self) -> u32 {
+        self.generation
+    }
+}
+
+impl<TIndex> Display for Key<TIndex>
+where
+    TIndex: IndexType,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}@{}", self.index.into_usize(), self.generation)
+    }
+}
+
+impl<TIndex> FromStr for Key<TIndex>
+where
+    TIndex: IndexType,
+{
+    type Err = ParseKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (index, generation) = s.split_once('@').ok_or(ParseKeyError::MissingSeparator)?;
+        let index = index
+            .parse::<usize>()
+            .ok()
+            .and_then(TIndex::try_from_usize)
+            .filter(|&index| index != TIndex::MAX)
+            .ok_or(ParseKeyError::InvalidIndex)?;
+        let generation = generation
+            .parse()
+            .map_err(|_| ParseKeyError::InvalidGeneration)?;
+        Ok(Self::new(index, generation))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_round_trip_through_text() {
+        let key = Key::<u8>::new(254, u32::MAX);
+        assert_eq!(key.to_string().parse::<Key<u8>>(), Ok(key));
+
+        assert_eq!("3".parse::<Key<u8>>(), Err(ParseKeyError::MissingSeparator));
+        assert_eq!("255@0".parse::<Key<u8>>(), Err(ParseKeyError::InvalidIndex));
+        assert_eq!("-1@0".parse::<Key<u8>>(), Err(ParseKeyError::InvalidIndex));
+        assert_eq!(
+            "1@x".parse::<Key<u8>>(),
+            Err(ParseKeyError::InvalidGeneration)
+        );
+    }
+}
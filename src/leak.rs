@@ -0,0 +1,113 @@
+use std::fmt;
+use std::sync::RwLock;
+
+/// The elements that were still alive when a list with leak reporting was dropped.
+///
+/// Reports are only created in debug builds, and in release builds if the `checked`
+/// feature is enabled; see [`FreeList::set_report_leaks`](crate::FreeList::set_report_leaks).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeakReport {
+    /// The name of the element type, which identifies the list.
+    pub type_name: &'static str,
+    /// The indices of the slots that were still occupied, in ascending order.
+    pub indices: Vec<usize>,
+}
+
+impl fmt::Display for LeakReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} element(s) of type `{}` were still alive when the list was dropped (indices {:?})",
+            self.indices.len(),
+            self.type_name,
+            self.indices
+        )
+    }
+}
+
+/// The function leak reports are passed to; `None` prints them to the standard error.
+static HOOK: RwLock<Option<fn(&LeakReport)>> = RwLock::new(None);
+
+/// Sets the function that receives the leak reports of all lists, e.g. to forward them
+/// to a logger or to collect them in a test; `None` restores the default, which prints
+/// them to the standard error.
+///
+/// ## Example
+/// ```rust
+/// use free_list::{set_leak_hook, FreeList, LeakReport};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// static LEAKED: AtomicUsize = AtomicUsize::new(0);
+///
+/// fn count_leaks(report: &LeakReport) {
+///     LEAKED.fetch_add(report.indices.len(), Ordering::Relaxed);
+/// }
+///
+/// set_leak_hook(Some(count_leaks));
+/// let mut list = FreeList::<&str, u8>::default();
+/// list.set_report_leaks(true);
+/// list.push("forgotten");
+///
+/// // In debug builds, dropping the list passes a report with one index to the hook.
+/// drop(list);
+/// set_leak_hook(None);
+/// ```
+pub fn set_leak_hook(hook: Option<fn(&LeakReport)>) {
+    *HOOK.write().unwrap_or_else(|e| e.into_inner()) = hook;
+}
+
+/// Passes the report to the leak hook.
+pub(crate) fn report(report: &LeakReport) {
+    let hook = *HOOK.read().unwrap_or_else(|e| e.into_inner());
+    match hook {
+        Some(hook) => hook(report),
+        None => eprintln!("{report}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FreeList;
+    use std::cell::RefCell;
+
+    thread_local! {
+        static REPORTS: RefCell<Vec<LeakReport>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn collect(report: &LeakReport) {
+        REPORTS.with(|reports| reports.borrow_mut().push(report.clone()));
+    }
+
+    #[test]
+    #[cfg_attr(not(any(debug_assertions, feature = "checked")), ignore)]
+    fn live_elements_are_reported() {
+        set_leak_hook(Some(collect));
+
+        let mut list = FreeList::<String, u8>::default();
+        list.set_report_leaks(true);
+        for value in ["a", "b", "c"] {
+            list.push(value.into());
+        }
+        list.erase(1);
+        drop(list);
+
+        // Lists without reporting and lists without live elements stay silent.
+        let mut list = FreeList::<String, u8>::default();
+        list.push("d".into());
+        drop(list);
+        let mut list = FreeList::<String, u8>::default();
+        list.set_report_leaks(true);
+        drop(list);
+
+        let reports = REPORTS.with(|reports| reports.take());
+        assert_eq!(
+            reports,
+            [LeakReport {
+                type_name: std::any::type_name::<String>(),
+                indices: vec![0, 2],
+            }]
+        );
+        assert!(reports[0].to_string().starts_with("2 element(s)"));
+    }
+}
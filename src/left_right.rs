@@ -0,0 +1,351 @@
+use crate::index_type::*;
+use crate::{FreeList, Key};
+use std::cell::UnsafeCell;
+use std::fmt::{Debug, Formatter};
+use std::ops::Deref;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// A free list for many concurrent readers and occasional writers, where reading
+/// never takes a lock.
+///
+/// The list is kept in two copies. Readers [`read`](Self::read) the published copy
+/// without locking; writers modify the other copy and record their changes in an
+/// operation log. [`publish`](Self::publish) swaps the copies, waits until no reader
+/// is left on the previously published one, and replays the log on it, so that both
+/// copies are identical again. Changes are therefore only visible to readers after
+/// they are published, and every element is stored twice.
+///
+/// Writers are serialized by a lock. A thread that still holds a read guard when it
+/// publishes waits for itself and deadlocks, and long-lived read guards generally
+/// delay publishing.
+///
+/// ## Type parameters
+/// * `T` - The type of the element; values are cloned into both copies.
+/// * `TIndex` - The type of the index; see [`FreeList`] for details.
+///
+/// ## Example
+/// ```rust
+/// use free_list::LeftRightFreeList;
+///
+/// let routes = LeftRightFreeList::<&str>::default();
+/// let key = routes.push("10.0.0.0/8");
+/// assert!(!routes.read().contains_key(key));
+///
+/// routes.publish();
+/// std::thread::scope(|scope| {
+///     for _ in 0..4 {
+///         scope.spawn(|| assert_eq!(routes.read().get(key.index()), Some(&"10.0.0.0/8")));
+///     }
+/// });
+/// ```
+pub struct LeftRightFreeList<T, TIndex = DefaultIndex>
+where
+    T: Default + Clone,
+    TIndex: IndexType,
+{
+    /// The two copies of the list.
+    copies: [UnsafeCell<FreeList<T, TIndex>>; 2],
+    /// The index of the copy that readers use.
+    published: AtomicUsize,
+    /// The number of readers of each copy.
+    readers: [AtomicUsize; 2],
+    /// The operations applied to the unpublished copy only.
+    log: Mutex<Vec<Operation<T, TIndex>>>,
+}
+
+/// A change recorded for replaying it on the other copy.
+enum Operation<T, TIndex> {
+    Push(T),
+    Replace(TIndex, T),
+    Remove(TIndex),
+}
+
+/// Shared access to the published copy of a [`LeftRightFreeList`], created by
+/// [`LeftRightFreeList::read`].
+///
+/// The copy is not modified while the guard is alive.
+pub struct LeftRightReadGuard<'a, T, TIndex = DefaultIndex>
+where
+    T: Default + Clone,
+    TIndex: IndexType,
+{
+    list: &'a LeftRightFreeList<T, TIndex>,
+    /// The index of the copy that is read.
+    copy: usize,
+}
+
+// SAFETY: The list owns both copies and the pending operations, so moving it to another
+// thread only moves values of `T`, like moving a `Vec<T>` does.
+unsafe impl<T, TIndex> Send for LeftRightFreeList<T, TIndex>
+where
+    T: Default + Clone + Send,
+    TIndex: IndexType,
+{
+}
+
+// SAFETY: Readers on any thread hold `&T` into the published copy, which requires
+// `T: Sync`. The writer pushes values of `T` that were created on its own thread into the
+// other copy, and drops replaced and removed ones there, which requires `T: Send`. The
+// writer only mutates a copy after all readers of it have left, and the log is behind a
+// mutex, so a shared list never allows a data race.
+unsafe impl<T, TIndex> Sync for LeftRightFreeList<T, TIndex>
+where
+    T: Default + Clone + Send + Sync,
+    TIndex: IndexType,
+{
+}
+
+impl<T, TIndex> Default for LeftRightFreeList<T, TIndex>
+where
+    T: Default + Clone,
+    TIndex: IndexType,
+{
+    /// Creates an empty list.
+    fn default() -> Self {
+        Self {
+            copies: [
+                UnsafeCell::new(FreeList::default()),
+                UnsafeCell::new(FreeList::default()),
+            ],
+            published: AtomicUsize::new(0),
+            readers: [AtomicUsize::new(0), AtomicUsize::new(0)],
+            log: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl<T, TIndex> LeftRightFreeList<T, TIndex>
+where
+    T: Default + Clone,
+    TIndex: IndexType,
+{
+    /// Gets access to the published copy without locking.
+    pub fn read(&self) -> LeftRightReadGuard<'_, T, TIndex> {
+        loop {
+            let copy = self.published.load(Ordering::SeqCst);
+            self.readers[copy].fetch_add(1, Ordering::SeqCst);
+            if self.published.load(Ordering::SeqCst) == copy {
+                return LeftRightReadGuard { list: self, copy };
+            }
+
+            // The copies were swapped in between; read the new one instead.
+            self.readers[copy].fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Inserts an element and returns its key; the element becomes visible to readers
+    /// once the change is published.
+    pub fn push(&self, element: T) -> Key<TIndex> {
+        let copy = element.clone();
+        self.write(|list| list.push_key(copy), Operation::Push(element))
+    }
+
+    /// Replaces the element of the specified key and returns whether the key referred
+    /// to an element; the new value becomes visible to readers once the change is
+    /// published.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::LeftRightFreeList;
+    ///
+    /// let list = LeftRightFreeList::<u32>::default();
+    /// let key = list.push(1);
+    /// list.publish();
+    /// assert!(list.replace(key, 2));
+    /// assert_eq!(list.read()[key.index()], 1);
+    /// list.publish();
+    /// assert_eq!(list.read()[key.index()], 2);
+    /// ```
+    pub fn replace(&self, key: Key<TIndex>, element: T) -> bool {
+        let mut log = self.lock_log();
+        // SAFETY: No reader uses the unpublished copy, and the log lock excludes other writers.
+        let list = unsafe { &mut *self.unpublished() };
+        if !list.contains_key(key) {
+            return false;
+        }
+        list[key.index()] = element.clone();
+        log.push(Operation::Replace(key.index(), element));
+        true
+    }
+
+    /// Removes the element of the specified key and returns whether the key referred
+    /// to an element; readers see the element until the change is published.
+    pub fn remove(&self, key: Key<TIndex>) -> bool {
+        let mut log = self.lock_log();
+        // SAFETY: No reader uses the unpublished copy, and the log lock excludes other writers.
+        let list = unsafe { &mut *self.unpublished() };
+        if !list.contains_key(key) {
+            return false;
+        }
+        list.erase(key.index());
+        log.push(Operation::Remove(key.index()));
+        true
+    }
+
+    /// Makes all changes visible to readers.
+    ///
+    /// This waits until all readers of the previously published copy have dropped their
+    /// guards, and then applies the changes to that copy as well.
+    pub fn publish(&self) {
+        let mut log = self.lock_log();
+        if log.is_empty() {
+            return;
+        }
+
+        let previous = self.published.fetch_xor(1, Ordering::SeqCst);
+        while self.readers[previous].load(Ordering::SeqCst) != 0 {
+            std::thread::yield_now();
+        }
+
+        // SAFETY: Readers of the previous copy are gone and new readers use the other one.
+        let list = unsafe { &mut *self.copies[previous].get() };
+        for operation in log.drain(..) {
+            match operation {
+                Operation::Push(element) => {
+                    list.push(element);
+                }
+                Operation::Replace(index, element) => list[index] = element,
+                Operation::Remove(index) => list.erase(index),
+            }
+        }
+    }
+
+    /// Gets the number of changes that are not published yet.
+    pub fn pending(&self) -> usize {
+        self.lock_log().len()
+    }
+
+    /// Unwraps the list, including all unpublished changes.
+    pub fn into_inner(self) -> FreeList<T, TIndex> {
+        let [first, second] = self.copies;
+        if self.published.into_inner() == 0 {
+            second.into_inner()
+        } else {
+            first.into_inner()
+        }
+    }
+
+    /// Applies a change to the unpublished copy and records it for the other one.
+    fn write<R>(
+        &self,
+        apply: impl FnOnce(&mut FreeList<T, TIndex>) -> R,
+        operation: Operation<T, TIndex>,
+    ) -> R {
+        let mut log = self.lock_log();
+        // SAFETY: No reader uses the unpublished copy, and the log lock excludes other writers.
+        let result = apply(unsafe { &mut *self.unpublished() });
+        log.push(operation);
+        result
+    }
+
+    /// Gets the copy that readers do not use.
+    fn unpublished(&self) -> *mut FreeList<T, TIndex> {
+        self.copies[1 - self.published.load(Ordering::SeqCst)].get()
+    }
+
+    /// Locks the operation log; a poisoned lock is recovered since operations are
+    /// only recorded after they were applied.
+    fn lock_log(&self) -> MutexGuard<'_, Vec<Operation<T, TIndex>>> {
+        self.log.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T, TIndex> Debug for LeftRightFreeList<T, TIndex>
+where
+    T: Default + Clone,
+    TIndex: IndexType,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LeftRightFreeList")
+            .field("len", &self.read().len())
+            .field("pending", &self.pending())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a, T, TIndex> Deref for LeftRightReadGuard<'a, T, TIndex>
+where
+    T: Default + Clone,
+    TIndex: IndexType,
+{
+    type Target = FreeList<T, TIndex>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: Writers wait for all readers of a copy before modifying it.
+        unsafe { &*self.list.copies[self.copy].get() }
+    }
+}
+
+impl<'a, T, TIndex> Drop for LeftRightReadGuard<'a, T, TIndex>
+where
+    T: Default + Clone,
+    TIndex: IndexType,
+{
+    fn drop(&mut self) {
+        self.list.readers[self.copy].fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn both_copies_converge_after_publishing() {
+        let list = LeftRightFreeList::<u32, u8>::default();
+        let first = list.push(1);
+        let second = list.push(2);
+        list.publish();
+        assert!(list.remove(first));
+        assert!(!list.remove(first));
+        assert!(list.replace(second, 3));
+        let third = list.push(4);
+        assert_eq!(third.index(), first.index());
+        assert_eq!(list.pending(), 3);
+
+        let guard = list.read();
+        assert_eq!(guard.iter().collect::<Vec<_>>(), [(0, &1), (1, &2)]);
+        drop(guard);
+
+        // Publishing twice replays the changes on both copies.
+        list.publish();
+        assert_eq!(list.pending(), 0);
+        assert_eq!(list.read().iter().collect::<Vec<_>>(), [(0, &4), (1, &3)]);
+        let fourth = list.push(5);
+        list.publish();
+        assert!(list.read().contains_key(fourth));
+        assert!(list.read().contains_key(third));
+        assert!(!list.read().contains_key(first));
+        assert_eq!(list.into_inner().len(), 3);
+    }
+
+    #[test]
+    fn readers_see_consistent_snapshots() {
+        const ROUNDS: usize = 500;
+
+        let list = LeftRightFreeList::<usize>::default();
+        let keys = [list.push(0), list.push(0)];
+        list.publish();
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                for round in 1..=ROUNDS {
+                    list.replace(keys[0], round);
+                    list.replace(keys[1], round);
+                    list.publish();
+                }
+            });
+            for _ in 0..2 {
+                scope.spawn(|| loop {
+                    let guard = list.read();
+                    let (a, b) = (guard[keys[0].index()], guard[keys[1].index()]);
+                    assert_eq!(a, b);
+                    if a == ROUNDS {
+                        break;
+                    }
+                });
+            }
+        });
+    }
+}
@@ -1,8 +1,167 @@
+#![cfg_attr(feature = "nightly", feature(allocator_api))]
+
+mod aligned;
+#[cfg(feature = "rkyv")]
+mod archive;
+mod arena;
+mod array;
+#[cfg(feature = "async")]
+mod async_pool;
+mod atomic;
+mod bitset;
+mod block;
+mod borrow;
+mod boxed;
+mod branded;
+mod dense;
+mod disk;
+mod dot;
+mod drop_guard;
+mod drop_order;
+mod entry;
+mod error;
+mod expiring;
+mod extract_if;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod free_cell;
+#[cfg(any(feature = "arbitrary", feature = "proptest"))]
+mod fuzz;
+mod generation_overflow;
+mod graph;
+#[cfg(all(feature = "hugepages", target_os = "linux"))]
+mod hugepages;
+mod ids;
 mod index_type;
+mod iter;
+mod journal;
+mod key;
+mod leak;
+mod left_right;
+mod linked;
+mod lru;
+mod macros;
+mod map;
+mod meta;
+mod names;
+mod nested;
+mod node_pool;
+mod non_max;
+mod observer;
+mod paged;
+#[cfg(feature = "rayon")]
+mod par_iter;
+mod persistent;
+mod pinned;
+#[cfg(feature = "bytemuck")]
+mod pod;
+mod pool;
+mod raw;
+mod ref_counted;
+#[cfg(feature = "wasm")]
+mod registry;
+mod reuse_policy;
+mod secondary;
+mod sharded;
+mod shared;
+mod shm;
+mod slot_cell;
+mod slot_status;
+mod small;
+mod snapshot;
+mod soa;
+mod stats;
+mod sync;
+mod thread_local_pool;
+mod ticks;
+mod wal;
+mod wipe;
+mod zst;
+
+use crate::bitset::BitSet;
+use crate::drop_guard::DropGuard;
+use crate::index_type::index_from_usize;
+use crate::macros::safety_check;
+use crate::ticks::ChangeTicks;
+use crate::zst::ZstSlots;
 
-use crate::index_type::*;
+pub use crate::aligned::{
+    Align128, Align16, Align32, Align64, Aligned, AlignedFreeList, Alignment, CacheLine,
+};
+#[cfg(feature = "rkyv")]
+pub use crate::archive::ArchivedFreeList;
+pub use crate::arena::{Arena, Id};
+pub use crate::array::{ArrayFreeList, ArrayKeys};
+#[cfg(feature = "async")]
+pub use crate::async_pool::{Acquire, AsyncPool, AsyncPoolGuard};
+pub use crate::atomic::{AtomicFreeList, EpochGuard};
+pub use crate::block::BlockAllocator;
+pub use crate::borrow::{BorrowFreeList, SlotRef, SlotRefMut};
+pub use crate::boxed::{BoxPool, PoolBox};
+pub use crate::branded::{BrandedFreeList, BrandedKey};
+pub use crate::dense::DenseFreeList;
+pub use crate::disk::DiskStore;
+pub use crate::drop_order::DropOrder;
+pub use crate::entry::VacantEntry;
+pub use crate::error::{
+    BorrowError, EraseError, GetDisjointMutError, InvariantError, NameTakenError, ParseKeyError,
+    ShmError,
+};
+pub use crate::expiring::ExpiringFreeList;
+pub use crate::extract_if::ExtractIf;
+pub use crate::free_cell::FreeCell;
+#[cfg(feature = "proptest")]
+pub use crate::fuzz::free_list_strategy;
+pub use crate::generation_overflow::GenerationOverflow;
+pub use crate::graph::Graph;
+#[cfg(all(feature = "hugepages", target_os = "linux"))]
+pub use crate::hugepages::{HugePageAllocator, HugePages};
+pub use crate::ids::IdAllocator;
+pub use crate::index_type::{DefaultIndex, FromAndIntoUsize, IndexType, MaxValue};
+pub use crate::iter::{Iter, IterMut, Keys};
+pub use crate::journal::{Journal, JournalEntry};
+pub use crate::key::Key;
+pub use crate::leak::{set_leak_hook, LeakReport};
+pub use crate::left_right::{LeftRightFreeList, LeftRightReadGuard};
+pub use crate::linked::LinkedFreeList;
+pub use crate::lru::LruFreeList;
+pub use crate::map::FreeListMap;
+pub use crate::meta::MetaFreeList;
+pub use crate::names::NameRegistry;
+pub use crate::nested::{NestedFreeList, NestedKey};
+pub use crate::node_pool::NodePool;
+pub use crate::non_max::{NonMaxU16, NonMaxU32, NonMaxU64, NonMaxU8, NonMaxUsize};
+pub use crate::observer::Observer;
+pub use crate::paged::PagedFreeList;
+pub use crate::persistent::PersistentFreeList;
+pub use crate::pinned::PinnedFreeList;
+pub use crate::pool::{ObjectPool, PoolGuard};
+pub use crate::raw::RawParts;
+pub use crate::ref_counted::RefCountedFreeList;
+#[cfg(feature = "wasm")]
+pub use crate::registry::HandleRegistry;
+pub use crate::reuse_policy::ReusePolicy;
+pub use crate::secondary::SecondaryMap;
+pub use crate::sharded::ShardedFreeList;
+pub use crate::shared::{SharedHandle, SharedPool};
+pub use crate::shm::ShmFreeList;
+pub use crate::slot_cell::{SlotCell, SlotCellFreeList, SlotCellMut, SlotCellRef};
+pub use crate::slot_status::SlotStatus;
+pub use crate::small::SmallFreeList;
+pub use crate::soa::{SoaColumns, SoaFreeList};
+pub use crate::stats::FreeListStats;
+pub use crate::sync::{SyncFreeList, SyncReadGuard, SyncWriteGuard};
+pub use crate::thread_local_pool::{ThreadLocalPool, ThreadLocalPoolGuard};
+#[cfg(feature = "change-ticks")]
+pub use crate::ticks::SlotTicks;
+pub use crate::wal::LoggedFreeList;
 
-use std::mem::ManuallyDrop;
+pub use allocator_api2::alloc::{Allocator, Global};
+
+use std::collections::VecDeque;
+use std::mem::{needs_drop, ManuallyDrop};
+use std::ops::{Index, IndexMut};
+use std::panic::{RefUnwindSafe, UnwindSafe};
 
 /// Provides an indexed free list with constant-time removals from anywhere
 /// in the list without invalidating indices.
@@ -12,44 +171,105 @@ use std::mem::ManuallyDrop;
 ///   e.g. `254` when `TIndex` is substituted with a `u8`.
 /// - While the implementation of this type makes heavy use of debug-time assertions, the
 ///   user must make sure to never add more items to the list than the index type
-///   can maintain.
+///   can maintain. Enable the `checked` feature to keep these assertions in release builds.
 /// - At most `usize::MAX` elements can be stored in this vector.
 ///
 /// ## Type parameters
 /// * `T` - The type of the element. Must be trivially constructible and destructible.
+///   For zero-sized types, no slot storage is allocated; the list then only keeps track
+///   of the slots and the links between free slots.
 /// * `TIndex` - The type of the index; see safety considerations above. "Smaller" types (e.g. `u8`)
 ///   result in a more memory-efficient representation, while "larger" types (e.g. `usize`) allow
-///   for more data to be stored.
-pub struct FreeList<T, TIndex = usize>
+///   for more data to be stored. Defaults to [`DefaultIndex`].
+/// * `A` - The allocator used for the slot storage; see [`new_in`](Self::new_in). The occupancy
+///   metadata is always kept in the global allocator.
+pub struct FreeList<T, TIndex = DefaultIndex, A = Global>
 where
     T: Default,
     TIndex: IndexType,
+    A: Allocator,
 {
     /// The number of live elements in the list.
-    #[cfg(debug_assertions)]
     length: usize,
-    /// The actual data.
-    data: Vec<FreeElement<T, TIndex>>,
-    /// The index of the the most recently freed element, or `SENTINEL` if no
+    /// The highest number of live elements since creation or the last reset.
+    peak_length: usize,
+    /// The highest number of slots since creation or the last reset.
+    peak_slots: usize,
+    /// The actual data; always empty for zero-sized elements, see `zst`.
+    data: allocator_api2::vec::Vec<FreeElement<T, TIndex>, A>,
+    /// The slots of a list of zero-sized elements, which need no slot storage.
+    zst: ZstSlots<TIndex>,
+    /// The set of indices of all slots that currently hold an element.
+    occupied: BitSet,
+    /// The generation of every slot that was ever handed out; incremented whenever
+    /// the element of the slot is removed.
+    generations: Vec<u32>,
+    /// The index of the free slot that is reused next, or `SENTINEL` if no
     /// element is free.
     first_free: TIndex,
+    /// The index of the free slot that is reused last, or `SENTINEL` if no
+    /// element is free.
+    last_free: TIndex,
+    /// The order in which free slots are reused.
+    policy: ReusePolicy,
+    /// What happens when the generation of a slot is exhausted.
+    overflow: GenerationOverflow,
+    /// The number of times a generation wrapped around.
+    generation_wraps: u64,
+    /// The erased slots that are not yet part of the free chain, oldest first.
+    quarantine: VecDeque<TIndex>,
+    /// The number of erases a slot is held back for before it can be reused.
+    quarantine_len: usize,
+    /// Whether live elements are reported when the list is dropped.
+    report_leaks: bool,
+    /// The order in which live elements are dropped.
+    drop_order: DropOrder,
+    /// The observer notified about inserted and removed elements.
+    observer: Option<Box<dyn Observer<TIndex>>>,
+    /// The change ticks of all slots; empty without the `change-ticks` feature.
+    ticks: ChangeTicks,
 }
 
-union FreeElement<T, TIndex>
+/// A slot of a [`FreeList`], holding either an element or a link to the next free slot.
+///
+/// Which field is valid is tracked by the list itself; see [`RawParts`].
+pub union FreeElement<T, TIndex>
 where
     TIndex: IndexType,
 {
     /// This field contains the data as long as the element was not removed.
-    element: ManuallyDrop<T>,
+    pub element: ManuallyDrop<T>,
     /// If the element was "removed", this index is pointing to the next index
     /// of an element that is also freed, or `SENTINEL` if no other element is free.
-    next: TIndex,
+    pub next: TIndex,
+}
+
+impl<T, TIndex> FreeElement<T, TIndex>
+where
+    TIndex: IndexType,
+{
+    /// Gets a pointer to the element of the `n`th slot of the slots starting at `base`.
+    ///
+    /// Zero-sized elements are not stored, so for them, the pointer is dangling and
+    /// `base` is ignored.
+    ///
+    /// # Safety
+    /// Unless the element is zero-sized, `base` must point to at least `n + 1` slots.
+    #[inline]
+    pub(crate) unsafe fn element_ptr(base: *mut Self, n: usize) -> *mut ManuallyDrop<T> {
+        if std::mem::size_of::<T>() == 0 {
+            std::ptr::NonNull::dangling().as_ptr()
+        } else {
+            unsafe { std::ptr::addr_of_mut!((*base.add(n)).element) }
+        }
+    }
 }
 
-impl<T, TIndex> Default for FreeList<T, TIndex>
+impl<T, TIndex, A> Default for FreeList<T, TIndex, A>
 where
     T: Default,
     TIndex: IndexType,
+    A: Allocator + Default,
 {
     /// Creates an empty list.
     ///
@@ -58,453 +278,4106 @@ where
     /// use free_list::FreeList;
     ///
     /// let list = FreeList::<&str, u8>::default();
-    /// assert_eq!(list.capacity(), 0);
+    /// assert_eq!(list.slot_count(), 0);
     /// ```
     fn default() -> Self {
-        Self {
-            data: Vec::default(),
-            first_free: Self::SENTINEL,
-            #[cfg(debug_assertions)]
-            length: 0,
-        }
+        Self::new_in(A::default())
     }
 }
 
-impl<T, TIndex> FreeList<T, TIndex>
+impl<T, TIndex, A> FreeList<T, TIndex, A>
 where
     T: Default,
     TIndex: IndexType,
+    A: Allocator,
 {
     /// The sentinel value indicates the absence of a valid value.
     pub(crate) const SENTINEL: TIndex = TIndex::MAX;
 
-    /// Inserts an element to the free list and returns an index to it.
+    /// Whether the elements are zero-sized, in which case no slot storage is allocated.
+    const IS_ZST: bool = std::mem::size_of::<T>() == 0;
+
+    /// Whether occupied slots can be viewed as elements, i.e. a slot is no larger than its
+    /// element, so that adjacent occupied slots form a slice.
+    const SLOTS_ARE_ELEMENTS: bool =
+        Self::IS_ZST || std::mem::size_of::<FreeElement<T, TIndex>>() == std::mem::size_of::<T>();
+
+    /// Creates an empty list whose slots are allocated with the specified allocator.
     ///
     /// ## Example
     /// ```rust
-    /// use free_list::FreeList;
+    /// # #![cfg_attr(feature = "nightly", feature(allocator_api))]
+    /// use free_list::{FreeList, Global};
     ///
-    /// let mut list = FreeList::<&str, u8>::default();
-    /// assert_eq!(list.push("test"), 0);
-    /// assert_eq!(list.capacity(), 1);
+    /// let mut list = FreeList::<&str, u8, _>::new_in(Global);
+    /// list.push("first");
+    /// assert_eq!(list.slot_count(), 1);
     /// ```
-    pub fn push(&mut self, element: T) -> TIndex {
-        #[cfg(debug_assertions)]
-        {
-            if self.length >= usize::MAX {
-                panic!(
-                    "Attempted to insert more elements than can be addressed by the underlying index type ({:?} allowed)",
-                    usize::MAX
-                );
-            }
+    pub fn new_in(alloc: A) -> Self {
+        Self::with_reuse_policy_in(ReusePolicy::default(), alloc)
+    }
 
-            if self.length >= unsafe { Self::SENTINEL.into() } - 1 {
-                panic!(
-                    "Attempted to insert more elements than can be addressed by the provided index type ({:?} allowed)",
-                    TIndex::MAX
-                );
-            }
+    /// Creates an empty list that reuses erased slots in the order given by the specified policy.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::{FreeList, ReusePolicy};
+    ///
+    /// let mut list = FreeList::<&str, u8>::with_reuse_policy(ReusePolicy::Fifo);
+    /// list.push("first");
+    /// list.push("second");
+    /// list.erase(0);
+    /// list.erase(1);
+    ///
+    /// // The slot that was erased first is reused first.
+    /// assert_eq!(list.push("third"), 0);
+    /// ```
+    pub fn with_reuse_policy(policy: ReusePolicy) -> Self
+    where
+        A: Default,
+    {
+        Self::with_reuse_policy_in(policy, A::default())
+    }
 
-            self.length += 1;
+    /// Creates an empty list that reuses erased slots in the order given by the specified policy
+    /// and whose slots are allocated with the specified allocator.
+    ///
+    /// ## Example
+    /// ```rust
+    /// # #![cfg_attr(feature = "nightly", feature(allocator_api))]
+    /// use free_list::{FreeList, Global, ReusePolicy};
+    ///
+    /// let list = FreeList::<&str, u8, _>::with_reuse_policy_in(ReusePolicy::LowestIndexFirst, Global);
+    /// assert_eq!(list.reuse_policy(), ReusePolicy::LowestIndexFirst);
+    /// ```
+    pub fn with_reuse_policy_in(policy: ReusePolicy, alloc: A) -> Self {
+        Self {
+            data: allocator_api2::vec::Vec::new_in(alloc),
+            zst: ZstSlots::new(),
+            occupied: BitSet::default(),
+            generations: Vec::new(),
+            first_free: Self::SENTINEL,
+            last_free: Self::SENTINEL,
+            policy,
+            overflow: GenerationOverflow::default(),
+            generation_wraps: 0,
+            quarantine: VecDeque::new(),
+            quarantine_len: 0,
+            report_leaks: false,
+            drop_order: DropOrder::default(),
+            observer: None,
+            ticks: ChangeTicks::new(),
+            length: 0,
+            peak_length: 0,
+            peak_slots: 0,
         }
+    }
 
-        return if self.first_free != Self::SENTINEL {
-            let index = self.first_free;
-            let index_usize = unsafe { index.into() };
+    /// Gets the order in which erased slots are reused.
+    #[inline]
+    pub fn reuse_policy(&self) -> ReusePolicy {
+        self.policy
+    }
 
-            // Set the "first free" pointer to the next free index.
-            self.first_free = unsafe { self.data[index_usize].next };
+    /// Holds erased slots back for the specified number of subsequent erases before
+    /// they can be reused; zero disables the quarantine.
+    ///
+    /// This is meant for debugging: while a slot is quarantined, stale indices to it
+    /// are reported as [`SlotStatus::Free`] instead of silently referring to a new element.
+    /// If the quarantine is shortened, the oldest slots are released immediately.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::{FreeList, SlotStatus};
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// list.set_quarantine_len(1);
+    /// let stale = list.push("first");
+    /// list.erase(stale);
+    ///
+    /// // The erased slot is not reused yet.
+    /// assert_eq!(list.push("second"), 1);
+    /// assert_eq!(list.status(stale), SlotStatus::Free);
+    ///
+    /// // The next erase releases it from the quarantine.
+    /// list.erase(1);
+    /// assert_eq!(list.push("third"), stale);
+    /// ```
+    pub fn set_quarantine_len(&mut self, len: usize) {
+        self.quarantine_len = len;
+        while self.quarantine.len() > len {
+            self.release_quarantined();
+        }
+    }
 
-            // Place the element into the previously free location.
-            self.data[index_usize].element = ManuallyDrop::new(element);
-            index
-        } else {
-            let fe = FreeElement {
-                element: ManuallyDrop::new(element),
-            };
-            self.data.push(fe);
-            unsafe { <TIndex as FromAndIntoUsize>::from(self.data.len() - 1) }
-        };
+    /// Gets the number of erases a slot is held back for before it can be reused.
+    #[inline]
+    pub fn quarantine_len(&self) -> usize {
+        self.quarantine_len
     }
 
-    /// Removes the nth element from the free list.
+    /// Sets what happens when the generation of a slot is exhausted, i.e. when the
+    /// element of a slot is removed for the four billionth time.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::{FreeList, GenerationOverflow};
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// list.set_generation_overflow(GenerationOverflow::Retire);
+    /// assert_eq!(list.generation_overflow(), GenerationOverflow::Retire);
+    /// ```
+    pub fn set_generation_overflow(&mut self, overflow: GenerationOverflow) {
+        self.overflow = overflow;
+    }
+
+    /// Gets what happens when the generation of a slot is exhausted.
+    #[inline]
+    pub fn generation_overflow(&self) -> GenerationOverflow {
+        self.overflow
+    }
+
+    /// Gets the number of times the generation of a slot wrapped around to zero with
+    /// [`GenerationOverflow::Wrap`], after which stale keys may match new elements.
+    #[inline]
+    pub fn generation_wraps(&self) -> u64 {
+        self.generation_wraps
+    }
+
+    /// Reports the indices of all elements that are still alive when the list is dropped.
+    ///
+    /// This is meant for debugging handle leaks: elements that are never erased usually
+    /// mean that their index was lost. The report is passed to the hook installed with
+    /// [`set_leak_hook`], or printed to the standard error. Reports
+    /// are only created in debug builds, and in release builds if the `checked` feature
+    /// is enabled.
     ///
     /// ## Example
     /// ```rust
     /// use free_list::FreeList;
     ///
     /// let mut list = FreeList::<&str, u8>::default();
-    /// list.push("uses one slot");
+    /// list.set_report_leaks(true);
+    /// assert!(list.report_leaks());
+    /// ```
+    pub fn set_report_leaks(&mut self, enabled: bool) {
+        self.report_leaks = enabled;
+    }
+
+    /// Determines whether live elements are reported when the list is dropped.
+    #[inline]
+    pub fn report_leaks(&self) -> bool {
+        self.report_leaks
+    }
+
+    /// Sets the order in which live elements are dropped by [`clear`](Self::clear),
+    /// [`reset`](Self::reset) and when the list is dropped.
     ///
-    /// // After erasing the just-inserted element, the capacity stays at
-    /// // 1 because the list is not compacted.
-    /// list.erase(0);
-    /// assert_eq!(list.capacity(), 1);
+    /// Elements are dropped in ascending index order by default. Lists whose elements
+    /// depend on each other, e.g. children that refer to parents inserted before them,
+    /// can use [`DropOrder::Descending`] to tear down the dependents first.
     ///
-    /// // After inserting again, the capacity is still 1 because
-    /// // the slot was reused.
-    /// list.push("uses the same slot");
-    /// assert_eq!(list.capacity(), 1);
+    /// ## Example
+    /// ```rust
+    /// use free_list::{DropOrder, FreeList};
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// list.set_drop_order(DropOrder::Descending);
+    /// assert_eq!(list.drop_order(), DropOrder::Descending);
     /// ```
-    pub fn erase(&mut self, n: TIndex) {
-        if self.data.is_empty() {
-            return;
-        }
-        debug_assert!(!self.debug_is_in_free_list(n));
+    pub fn set_drop_order(&mut self, order: DropOrder) {
+        self.drop_order = order;
+    }
 
-        #[cfg(debug_assertions)]
-        debug_assert!(self.length > 0);
+    /// Gets the order in which live elements are dropped.
+    #[inline]
+    pub fn drop_order(&self) -> DropOrder {
+        self.drop_order
+    }
 
-        let n_usize = unsafe { n.into() };
-        unsafe { ManuallyDrop::drop(&mut self.data[n_usize].element) };
-        self.data[n_usize].next = self.first_free;
-        self.first_free = n;
+    /// Registers an observer that is notified about all inserted and removed elements,
+    /// replacing the previous one.
+    ///
+    /// The observer is notified after the list was updated. If a callback panics, the list
+    /// stays consistent and the observer is dropped, leaving the list without an observer.
+    ///
+    /// See [`Observer`] for an example.
+    pub fn set_observer(&mut self, observer: impl Observer<TIndex> + 'static) {
+        self.observer = Some(Box::new(observer));
+    }
 
-        #[cfg(debug_assertions)]
-        {
-            self.length -= 1;
-        }
+    /// Removes the observer and returns it.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::{FreeList, Observer};
+    ///
+    /// struct Ignore;
+    /// impl Observer<u8> for Ignore {}
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// list.set_observer(Ignore);
+    /// assert!(list.take_observer().is_some());
+    /// assert!(list.take_observer().is_none());
+    /// ```
+    pub fn take_observer(&mut self) -> Option<Box<dyn Observer<TIndex>>> {
+        self.observer.take()
     }
 
-    /// Removes all elements from the free list.
+    /// Gets a reference to the allocator used for the slot storage.
+    #[inline]
+    pub fn allocator(&self) -> &A {
+        self.data.allocator()
+    }
+
+    /// Inserts an element to the free list and returns an index to it.
+    ///
     /// ## Example
     /// ```rust
     /// use free_list::FreeList;
     ///
     /// let mut list = FreeList::<&str, u8>::default();
-    /// list.push("one");
-    /// list.push("two");
-    ///
-    /// list.clear();
-    /// assert_eq!(list.capacity(), 0);
+    /// assert_eq!(list.push("test"), 0);
+    /// assert_eq!(list.slot_count(), 1);
     /// ```
-    pub fn clear(&mut self) {
-        if self.data.is_empty() {
-            assert_eq!(self.first_free, Self::SENTINEL);
-            return;
-        }
+    pub fn push(&mut self, element: T) -> TIndex {
+        safety_check!(
+            self.length != usize::MAX,
+            "Attempted to insert more elements than can be addressed by the underlying index type ({:?} allowed)",
+            usize::MAX
+        );
+        safety_check!(
+            self.length < Self::SENTINEL.into_usize() - 1,
+            "Attempted to insert more elements than can be addressed by the provided index type ({:?} allowed)",
+            TIndex::MAX
+        );
 
-        // Collect all free indexes and sort them such that they
-        // are in ascending order.
-        let mut free_indexes = Vec::new();
-        let mut token = self.first_free;
-        while token != Self::SENTINEL {
-            free_indexes.push(token);
-            token = unsafe { self.data[token.into()].next };
-        }
-        free_indexes.sort();
-
-        // As long as there are free indexes, pop elements from the
-        // vector and ignore them if they correspond to a free index.
-        if !free_indexes.is_empty() {
-            for (i, entry) in self.data.iter_mut().enumerate() {
-                if free_indexes.is_empty()
-                    || *free_indexes.last().unwrap()
-                        != unsafe { <TIndex as FromAndIntoUsize>::from(i) }
-                {
-                    // This is not a pointer entry, drop required.
-                    unsafe { ManuallyDrop::drop(&mut entry.element) };
-                } else {
-                    // The entry only contains a index to another free spot; nothing to drop.
-                    let _ = free_indexes.pop();
-                }
+        // The element is only counted once it is placed, since growing the slot storage
+        // may panic; the observer is only notified once the list is consistent again.
+        let index = if self.first_free == Self::SENTINEL {
+            self.push_new_slot(element)
+        } else {
+            let index = self.first_free;
+            let index_usize = index.into_usize();
+
+            // Set the "first free" pointer to the next free index.
+            self.first_free = if Self::IS_ZST {
+                self.zst.unlink(index_usize)
+            } else {
+                unsafe { self.data[index_usize].next }
+            };
+            if self.first_free == Self::SENTINEL {
+                self.last_free = Self::SENTINEL;
             }
+
+            // Place the element into the previously free location.
+            unsafe { *self.element_mut(index_usize) = ManuallyDrop::new(element) };
+            self.occupied.insert(index_usize);
+            index
+        };
+
+        self.length += 1;
+        self.peak_length = self.peak_length.max(self.length);
+        self.ticks.added(index.into_usize());
+        self.notify_insert(index);
+        index
+    }
+
+    /// Appends a new slot holding the element; used by [`push`](Self::push) when the
+    /// free chain is empty.
+    #[cold]
+    #[inline(never)]
+    fn push_new_slot(&mut self, element: T) -> TIndex {
+        // Retired slots that were truncated before are never handed out again.
+        while self.is_retired(self.slot_count()) {
+            self.reserve_slots(1);
+            self.append_slot(FreeElement {
+                next: Self::SENTINEL,
+            });
         }
 
-        // At this point there are no free indexes anymore, so the
-        // list can be trivially cleared.
-        self.data.clear();
-        self.first_free = Self::SENTINEL;
+        // The quarantine and retired slots hold back slots that do not count towards the
+        // length, so the slot count needs to be checked separately.
+        assert!(
+            self.slot_count() < Self::SENTINEL.into_usize() - 1,
+            "Attempted to insert more elements than can be addressed by the provided index type ({:?} allowed)",
+            TIndex::MAX
+        );
 
-        #[cfg(debug_assertions)]
-        {
-            self.length = 0;
+        let fe = FreeElement {
+            element: ManuallyDrop::new(element),
+        };
+        self.reserve_slots(1);
+        self.append_slot(fe);
+        let slots = self.slot_count();
+        self.peak_slots = self.peak_slots.max(slots);
+        if self.generations.len() < slots {
+            self.generations.push(0);
         }
+        self.occupied.insert(slots - 1);
+        index_from_usize(slots - 1)
     }
 
-    /// Gets a reference to the value at the specified index.
+    /// Reserves free slots for at least `additional` more elements.
     ///
-    /// # Safety
+    /// The new slots are linked into the free chain right away, behind all slots that
+    /// are already free, so that the following pushes reuse them without growing the
+    /// slot storage. Slots held back by the quarantine do not count as available.
     ///
-    /// If the element at the specified index was erased, the union now acts
-    ///  as a pointer to the next free element. Accessing the same index again after that.
-    ///  is undefined behavior.
+    /// ## Panics
+    /// Panics if the index type cannot address the requested number of slots.
     ///
     /// ## Example
     /// ```rust
     /// use free_list::FreeList;
     ///
     /// let mut list = FreeList::<&str, u8>::default();
-    /// assert_eq!(list.push("first"), 0);
-    /// assert_eq!(list.push("second"), 1);
+    /// list.push("first");
+    /// list.reserve(3);
+    /// assert_eq!(list.slot_count(), 4);
     ///
-    /// let element = unsafe { list.at(0) };
-    /// assert_eq!(*element, "first");
+    /// // The reserved slots are used in ascending order.
+    /// assert_eq!(list.push("second"), 1);
+    /// assert_eq!(list.push("third"), 2);
+    /// assert_eq!(list.slot_count(), 4);
     /// ```
+    pub fn reserve(&mut self, additional: usize) {
+        let available = self.slot_count() - self.length - self.quarantine.len() - self.retired();
+        if additional <= available {
+            return;
+        }
+
+        let start = self.slot_count();
+        let end = start + (additional - available);
+        assert!(
+            end < Self::SENTINEL.into_usize(),
+            "Attempted to reserve more slots than can be addressed by the provided index type ({:?} allowed)",
+            TIndex::MAX
+        );
+
+        self.reserve_slots(end - start);
+        for index in start..end {
+            self.append_slot(FreeElement {
+                next: Self::SENTINEL,
+            });
+            if !self.is_retired(index) {
+                self.link_free_back(index_from_usize(index));
+            }
+        }
+        if self.generations.len() < end {
+            self.generations.resize(end, 0);
+        }
+        self.peak_slots = self.peak_slots.max(end);
+    }
+
+    /// Returns a handle to the slot that the next call to [`push`](Self::push) would use.
     ///
-    /// Note that accessing a previously erased item is undefined behavior:
+    /// This allows to learn the index of an element before it is inserted, which
+    /// is useful for elements that need to store their own index.
     ///
+    /// ## Example
     /// ```rust
     /// use free_list::FreeList;
     ///
-    /// let mut list = FreeList::<&str, u8>::default();
-    /// list.push("first");
+    /// let mut list = FreeList::<(u8, &str), u8>::default();
+    /// list.push((0, "first"));
     ///
-    /// // SAFETY: The code below is undefined behavior:
-    /// list.erase(0);
-    /// // let element = unsafe { list.at(0) };
-    /// // assert_eq!(*element, "first");
+    /// let entry = list.vacant_entry();
+    /// let key = entry.key();
+    /// assert_eq!(key, 1);
+    ///
+    /// entry.insert((key, "second"));
+    /// assert_eq!(unsafe { list.at(1) }, &(1, "second"));
     /// ```
-    #[inline]
-    pub unsafe fn at(&self, index: TIndex) -> &T {
-        debug_assert_ne!(index, Self::SENTINEL);
-        debug_assert!(!self.debug_is_in_free_list(index));
-        &self.data[index.into()].element
+    pub fn vacant_entry(&mut self) -> VacantEntry<'_, T, TIndex, A> {
+        VacantEntry::new(self)
     }
 
-    /// Gets a mutable reference to the value at the specified index.
-    ///
-    /// # Safety
+    /// Inserts the element created by the specified function, which receives the index
+    /// the element will be stored at, and returns that index.
     ///
-    /// If the element at the specified index was erased, the union now acts
-    /// as a pointer to the next free element. Accessing the same index again after that.
-    /// is undefined behavior.
+    /// If the function panics, the list is left unchanged.
     ///
     /// ## Example
     /// ```rust
     /// use free_list::FreeList;
     ///
-    /// let mut list = FreeList::<&str, u8>::default();
-    /// assert_eq!(list.push("first"), 0);
-    /// assert_eq!(list.push("second"), 1);
+    /// #[derive(Default)]
+    /// struct Node {
+    ///     id: u8,
+    ///     parent: Option<u8>,
+    /// }
     ///
-    /// let element = unsafe { list.at_mut(1) };
-    /// *element = "danger";
+    /// let mut tree = FreeList::<Node, u8>::default();
+    /// let root = tree.push_with(|id| Node { id, parent: None });
+    /// let child = tree.push_with(|id| Node { id, parent: Some(root) });
     ///
-    /// assert_eq!(unsafe { list.at(1) }, &"danger");
+    /// assert_eq!(tree[child].id, child);
+    /// assert_eq!(tree[child].parent, Some(root));
     /// ```
-    ///
-    /// As with `at()`, accessing a previously erased element is
-    /// undefined behavior.
-    #[inline]
-    pub unsafe fn at_mut(&mut self, index: TIndex) -> &mut T {
-        debug_assert_ne!(index, Self::SENTINEL);
-        debug_assert!(!self.debug_is_in_free_list(index));
-        &mut self.data[index.into()].element
+    pub fn push_with(&mut self, f: impl FnOnce(TIndex) -> T) -> TIndex {
+        let index = self.next_index();
+        let element = f(index);
+        let pushed = self.push(element);
+        debug_assert_eq!(pushed, index);
+        index
     }
 
-    /// Gets the current capacity of the list.
+    /// Inserts all elements of the iterator and returns their indices, in iteration order.
+    ///
+    /// Free slots are reused first; the slot storage for the remaining elements is
+    /// then reserved at once based on the iterator's size hint.
     ///
+    /// ## Example
     /// ```rust
     /// use free_list::FreeList;
     ///
     /// let mut list = FreeList::<&str, u8>::default();
-    ///
-    /// // The first elements increase the capacity.
     /// list.push("first");
     /// list.push("second");
-    /// assert_eq!(list.capacity(), 2);
-    ///
-    /// // Erasing elements does not decrease the capacity.
     /// list.erase(0);
-    /// list.erase(1);
-    /// assert_eq!(list.capacity(), 2);
     ///
-    /// // Adding elements after an erase does not increase capacity.
-    /// list.push("fourth");
-    /// list.push("fifth");
-    /// assert_eq!(list.capacity(), 2);
+    /// let indices = list.extend_and_collect(["a", "b", "c"]);
+    /// assert_eq!(indices, [0, 2, 3]);
+    /// assert_eq!(list[3], "c");
+    /// ```
+    pub fn extend_and_collect<I>(&mut self, iter: I) -> Vec<TIndex>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut iter = iter.into_iter();
+        let mut indices = Vec::with_capacity(iter.size_hint().0);
+        while self.first_free != Self::SENTINEL {
+            match iter.next() {
+                Some(element) => indices.push(self.push(element)),
+                None => return indices,
+            }
+        }
+
+        let remaining = iter.size_hint().0;
+        self.reserve_slots(remaining);
+        indices.reserve(remaining);
+        indices.extend(iter.map(|element| self.push(element)));
+        indices
+    }
+
+    /// Inserts the elements into adjacent slots and returns the index of the first one,
+    /// so that the element `i` can be addressed as `base + i`.
     ///
-    /// // Adding more elements increases capacity.
-    /// list.push("sixth");
-    /// list.push("seventh");
-    /// assert_eq!(list.capacity(), 4);
+    /// The lowest run of `N` adjacent free slots is used; if there is none, free slots at
+    /// the end of the list are extended by new slots. The remaining free slots keep their
+    /// order. Slots held back by the quarantine are not used.
     ///
-    /// // Clearing the list frees all resources.
-    /// list.clear();
-    /// assert_eq!(list.capacity(), 0);
+    /// ## Panics
+    /// Panics if `N` is zero or the index type cannot address the required slots.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// list.extend_and_collect(["a", "b", "c", "d"]);
+    /// list.erase(1);
+    /// list.erase(3);
+    ///
+    /// // Slot 1 is too small for the run; slot 3 is extended by a new slot.
+    /// let base = list.push_n_contiguous(["x", "y"]);
+    /// assert_eq!(base, 3);
+    /// assert_eq!(list[base + 1], "y");
+    /// assert_eq!(list.push("e"), 1);
     /// ```
-    #[allow(dead_code)]
-    pub fn capacity(&self) -> usize {
-        self.data.len()
-    }
+    pub fn push_n_contiguous<const N: usize>(&mut self, elements: [T; N]) -> TIndex {
+        assert!(N > 0, "Attempted to insert an empty run of elements");
 
-    /// Gets the number of elements in the list.
-    #[allow(dead_code)]
-    #[cfg(debug_assertions)]
-    fn debug_len(&self) -> usize {
-        #[cfg(debug_assertions)]
-        return self.length;
-        #[cfg(not(debug_assertions))]
-        unimplemented!()
-    }
+        // Only slots in the free chain can be used; quarantined and retired ones cannot.
+        let slots = self.slot_count();
+        let mut linked = BitSet::default();
+        let mut token = self.first_free;
+        while token != Self::SENTINEL {
+            linked.insert(token.into_usize());
+            token = self.next_free(token.into_usize());
+        }
 
-    #[allow(dead_code, unused_variables)]
-    #[cfg(debug_assertions)]
-    fn debug_is_in_free_list(&self, n: TIndex) -> bool {
-        #[cfg(any(debug_assertions, test))]
-        {
-            assert_ne!(n, Self::SENTINEL);
-            let mut token = self.first_free;
-            while token != Self::SENTINEL {
-                if n == token {
-                    return true;
-                }
-                token = unsafe { self.data[token.into()].next };
+        let mut base = 0;
+        for index in 0..slots {
+            if !linked.contains(index) {
+                base = index + 1;
+            } else if index + 1 - base == N {
+                break;
             }
-            return false;
         }
-        #[cfg(not(any(debug_assertions, test)))]
-        unimplemented!()
-    }
-}
+        while let Some(retired) = (slots.max(base)..base + N)
+            .rev()
+            .find(|&index| self.is_retired(index))
+        {
+            base = retired + 1;
+        }
+
+        let end = base + N;
+        assert!(
+            end < Self::SENTINEL.into_usize(),
+            "Attempted to insert more elements than can be addressed by the provided index type ({:?} allowed)",
+            TIndex::MAX
+        );
+
+        // Grow the slot storage first, so that a failure leaves the free chain untouched.
+        self.reserve_slots(end.saturating_sub(slots));
+
+        // Unlink the used slots while keeping the order of the remaining ones.
+        let mut token = self.first_free;
+        self.first_free = Self::SENTINEL;
+        self.last_free = Self::SENTINEL;
+        while token != Self::SENTINEL {
+            let next = self.next_free(token.into_usize());
+            if !(base..end).contains(&token.into_usize()) {
+                self.link_free_back(token);
+            } else {
+                self.set_next_free(token.into_usize(), Self::SENTINEL);
+            }
+            token = next;
+        }
+
+        // Slots between the end of the list and the run become free slots.
+        for index in slots..base {
+            self.append_slot(FreeElement {
+                next: Self::SENTINEL,
+            });
+            if !self.is_retired(index) {
+                self.link_free_back(index_from_usize(index));
+            }
+        }
+        if self.generations.len() < end {
+            self.generations.resize(end, 0);
+        }
+
+        for (index, element) in (base..end).zip(elements) {
+            if index < slots {
+                unsafe { *self.element_mut(index) = ManuallyDrop::new(element) };
+            } else {
+                self.append_slot(FreeElement {
+                    element: ManuallyDrop::new(element),
+                });
+            }
+            self.occupied.insert(index);
+            self.ticks.added(index);
+            self.length += 1;
+        }
+        self.peak_length = self.peak_length.max(self.length);
+        self.peak_slots = self.peak_slots.max(self.slot_count());
+
+        // The observer is notified last, so that it cannot interrupt the insertion.
+        for index in base..end {
+            self.notify_insert(index_from_usize(index));
+        }
+        index_from_usize(base)
+    }
+
+    /// Gets the index that the next call to [`push`](Self::push) will return.
+    pub(crate) fn next_index(&self) -> TIndex {
+        if self.first_free != Self::SENTINEL {
+            self.first_free
+        } else {
+            let mut index = self.slot_count();
+            while self.is_retired(index) {
+                index += 1;
+            }
+            index_from_usize(index)
+        }
+    }
+
+    /// Removes the nth element from the free list.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// list.push("uses one slot");
+    ///
+    /// // After erasing the just-inserted element, the slot count stays at
+    /// // 1 because the list is not compacted.
+    /// list.erase(0);
+    /// assert_eq!(list.slot_count(), 1);
+    ///
+    /// // After inserting again, the slot count is still 1 because
+    /// // the slot was reused.
+    /// list.push("uses the same slot");
+    /// assert_eq!(list.slot_count(), 1);
+    /// ```
+    pub fn erase(&mut self, n: TIndex) {
+        if self.slot_count() == 0 {
+            return;
+        }
+
+        // Elements without drop glue do not need to be read before the slot is released.
+        if needs_drop::<T>() {
+            drop(self.take(n));
+        } else {
+            self.check_erasable(n);
+            self.release(n);
+        }
+    }
+
+    /// Removes the nth element from the free list, or reports why it cannot be removed.
+    ///
+    /// Unlike [`erase`](Self::erase), the index is always validated, also in release
+    /// builds, so erasing the same index twice is reported instead of corrupting the list.
+    ///
+    /// ## Errors
+    /// Returns [`EraseError::OutOfRange`] if the index refers to a slot beyond the end of
+    /// the list, and [`EraseError::NotOccupied`] if the slot holds no element.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::{EraseError, FreeList};
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// list.push("first");
+    ///
+    /// assert_eq!(list.try_erase(0), Ok(()));
+    /// assert_eq!(list.try_erase(0), Err(EraseError::NotOccupied { index: 0 }));
+    /// assert_eq!(list.try_erase(1), Err(EraseError::OutOfRange { index: 1 }));
+    /// ```
+    pub fn try_erase(&mut self, n: TIndex) -> Result<(), EraseError> {
+        let index = n.into_usize();
+        match self.status(n) {
+            SlotStatus::Occupied => {
+                self.erase(n);
+                Ok(())
+            }
+            SlotStatus::Free => Err(EraseError::NotOccupied { index }),
+            SlotStatus::OutOfRange => Err(EraseError::OutOfRange { index }),
+        }
+    }
+
+    /// Removes the elements at all specified indices and returns the number of removed elements.
+    ///
+    /// The indices are sorted and deduplicated first, and indices that do not refer to an
+    /// element are ignored. All freed slots are then spliced into the free chain in a single
+    /// pass, in ascending index order, so they are reused front-to-back. With a quarantine,
+    /// the slots are quarantined one by one as with [`erase`](Self::erase).
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// for value in ["a", "b", "c", "d"] {
+    ///     list.push(value);
+    /// }
+    ///
+    /// assert_eq!(list.erase_many(&[3, 1, 3, 7]), 2);
+    /// assert_eq!(list.len(), 2);
+    /// assert_eq!(list.push("e"), 1);
+    /// assert_eq!(list.push("f"), 3);
+    /// ```
+    pub fn erase_many(&mut self, indices: &[TIndex]) -> usize {
+        let mut indices = indices.to_vec();
+        indices.sort_unstable();
+        indices.dedup();
+        indices.retain(|&index| self.occupied.contains(index.into_usize()));
+        self.check_generations(indices.iter().map(|index| index.into_usize()));
+        let count = indices.len();
+
+        // The elements are moved out before the list is updated and only dropped at the
+        // end, so that a panicking destructor cannot leave the list in an inconsistent state.
+        // Elements without drop glue cannot panic and are not moved out at all.
+        let mut removed = Vec::with_capacity(if needs_drop::<T>() { indices.len() } else { 0 });
+        if self.quarantine_len > 0 {
+            for &index in &indices {
+                if needs_drop::<T>() {
+                    removed.push(self.take(index));
+                } else {
+                    self.release(index);
+                }
+            }
+            return count;
+        }
+
+        let mut erased = Vec::with_capacity(if self.observer.is_some() { count } else { 0 });
+        for &index in &indices {
+            let slot = index.into_usize();
+            if needs_drop::<T>() {
+                removed.push(unsafe { ManuallyDrop::take(self.element_mut(slot)) });
+            }
+            self.wipe_slot(slot);
+            self.occupied.remove(slot);
+            if self.observer.is_some() {
+                erased.push((index, self.generations[slot]));
+            }
+            self.overflow
+                .advance(&mut self.generations[slot], &mut self.generation_wraps);
+        }
+        self.length -= count;
+        if self.overflow == GenerationOverflow::Retire {
+            indices.retain(|index| !self.is_retired(index.into_usize()));
+        }
+
+        match self.policy {
+            ReusePolicy::Lifo => {
+                for &index in indices.iter().rev() {
+                    self.link_free_front(index);
+                }
+            }
+            ReusePolicy::Fifo => {
+                for &index in &indices {
+                    self.link_free_back(index);
+                }
+            }
+            ReusePolicy::LowestIndexFirst => self.merge_free_chain(&indices),
+        }
+
+        for (index, generation) in erased {
+            self.notify_erase(index, generation);
+        }
+        count
+    }
+
+    /// Gets an iterator that removes all elements for which the predicate returns `true`
+    /// and yields them with their indices, in ascending index order.
+    ///
+    /// The predicate receives the index and a mutable reference to every element and may
+    /// modify the elements it keeps. Each extracted slot is released as it is yielded,
+    /// exactly as with [`erase`](Self::erase); if the iterator is dropped before it is
+    /// exhausted, the remaining elements are kept.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<u32, u8>::default();
+    /// for value in 1..=6 {
+    ///     list.push(value);
+    /// }
+    ///
+    /// let even: Vec<_> = list.extract_if(|_, value| *value % 2 == 0).collect();
+    /// assert_eq!(even, [(1, 2), (3, 4), (5, 6)]);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), [(0, &1), (2, &3), (4, &5)]);
+    /// ```
+    pub fn extract_if<F>(&mut self, predicate: F) -> ExtractIf<'_, T, F, TIndex, A>
+    where
+        F: FnMut(TIndex, &mut T) -> bool,
+    {
+        ExtractIf::new(self, predicate)
+    }
+
+    /// Moves the element out of the specified slot and releases the slot.
+    fn take(&mut self, n: TIndex) -> T {
+        self.check_erasable(n);
+        let element = unsafe { ManuallyDrop::take(self.element_mut(n.into_usize())) };
+        self.release(n);
+        element
+    }
+
+    /// Asserts that the specified slot holds an element that can be removed.
+    #[inline]
+    fn check_erasable(&self, n: TIndex) {
+        safety_check!(
+            self.occupied.contains(n.into_usize()),
+            "Attempted to erase a slot that is not occupied ({:?})",
+            n
+        );
+        debug_assert!(self.length > 0);
+        self.check_generations([n.into_usize()]);
+    }
+
+    /// Panics if the generation of any of the specified slots is exhausted and the
+    /// overflow policy forbids removing their elements.
+    #[inline]
+    fn check_generations(&self, indices: impl IntoIterator<Item = usize>) {
+        if self.overflow != GenerationOverflow::Panic {
+            return;
+        }
+        for index in indices {
+            assert!(
+                self.generations[index] != u32::MAX,
+                "Attempted to remove an element from a slot whose generation is exhausted ({index})"
+            );
+        }
+    }
+
+    /// Marks the specified occupied slot as free without dropping its element, which
+    /// must have been moved out before or have no drop glue.
+    fn release(&mut self, n: TIndex) {
+        let n_usize = n.into_usize();
+        let generation = self.generations[n_usize];
+        self.wipe_slot(n_usize);
+        self.occupied.remove(n_usize);
+        let reusable = self
+            .overflow
+            .advance(&mut self.generations[n_usize], &mut self.generation_wraps);
+        if !reusable {
+            // Retired slots are neither linked nor quarantined.
+        } else if self.quarantine_len == 0 {
+            self.link_free(n);
+        } else {
+            self.quarantine.push_back(n);
+            if self.quarantine.len() > self.quarantine_len {
+                self.release_quarantined();
+            }
+        }
+
+        self.length -= 1;
+        self.notify_erase(n, generation);
+    }
+
+    /// Removes all elements from the free list.
+    ///
+    /// The elements are dropped in ascending index order, or in the order set with
+    /// [`set_drop_order`](Self::set_drop_order). If the destructor of an element panics,
+    /// the remaining elements are still dropped and the list is left empty.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// list.push("one");
+    /// list.push("two");
+    ///
+    /// list.clear();
+    /// assert_eq!(list.slot_count(), 0);
+    /// ```
+    pub fn clear(&mut self) {
+        if self.slot_count() == 0 {
+            assert_eq!(self.first_free, Self::SENTINEL);
+            return;
+        }
+
+        // The list is emptied before the elements are dropped, so that a panicking
+        // destructor neither leaves dropped elements in the list nor drops them twice.
+        // The generations are kept so that keys to the dropped elements stay invalid.
+        self.check_generations(self.occupied.ones());
+        let occupied = self.occupied.take();
+        for index in occupied.ones() {
+            self.overflow
+                .advance(&mut self.generations[index], &mut self.generation_wraps);
+        }
+        self.zst.clear();
+        self.quarantine.clear();
+        self.first_free = Self::SENTINEL;
+        self.last_free = Self::SENTINEL;
+        let length = std::mem::take(&mut self.length);
+
+        // Only occupied slots hold an element that needs to be dropped;
+        // all other slots only contain an index to another free spot.
+        DropGuard::new(&mut self.data, 0, occupied, self.drop_order).drop_elements();
+        if length > 0 {
+            self.notify_clear();
+        }
+    }
+
+    /// Removes all elements from the free list but keeps all slots allocated.
+    ///
+    /// Unlike [`clear`](Self::clear), the slot storage is not released; instead, all slots
+    /// are linked into the free chain in ascending index order, so that the list can be
+    /// refilled, e.g. in the next frame, without reallocating. The elements are dropped
+    /// in the order set with [`set_drop_order`](Self::set_drop_order).
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// list.push("one");
+    /// list.push("two");
+    ///
+    /// list.reset();
+    /// assert!(list.is_empty());
+    /// assert_eq!(list.slot_count(), 2);
+    /// assert_eq!(list.push("three"), 0);
+    /// ```
+    pub fn reset(&mut self) {
+        // The elements are moved out first so that a panicking destructor cannot
+        // leave the list in an inconsistent state.
+        self.check_generations(self.occupied.ones());
+        let mut removed = Vec::with_capacity(if needs_drop::<T>() { self.length } else { 0 });
+        let data = self.data.as_mut_ptr();
+        for index in self.occupied.ones() {
+            if needs_drop::<T>() {
+                removed.push(unsafe {
+                    ManuallyDrop::take(&mut *FreeElement::element_ptr(data, index))
+                });
+            }
+            self.overflow
+                .advance(&mut self.generations[index], &mut self.generation_wraps);
+        }
+
+        // All slots are free now and are relinked below.
+        wipe::wipe_slots(&mut self.data);
+
+        self.occupied.reset();
+        self.quarantine.clear();
+        let length = std::mem::take(&mut self.length);
+        self.rebuild_free_chain();
+        if length > 0 {
+            self.notify_clear();
+        }
+        if self.drop_order == DropOrder::Descending {
+            removed.reverse();
+        }
+        drop(removed);
+    }
+
+    /// Reduces the number of slots towards the specified target by removing trailing free slots.
+    ///
+    /// Slots are only removed from the end of the list and only as long as they are free,
+    /// so the slot count never drops below the highest occupied index plus one. The
+    /// removed slots are unlinked from the free chain (or the quarantine), the order of
+    /// the remaining free slots is kept, and the slot storage is shrunk accordingly.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// for value in ["a", "b", "c", "d", "e"] {
+    ///     list.push(value);
+    /// }
+    /// list.erase(4);
+    /// list.erase(2);
+    /// list.erase(1);
+    ///
+    /// // Slot 3 is occupied, so only slot 4 can be removed.
+    /// list.shrink_to(2);
+    /// assert_eq!(list.slot_count(), 4);
+    ///
+    /// list.erase(3);
+    /// list.shrink_to(2);
+    /// assert_eq!(list.slot_count(), 2);
+    /// assert_eq!(list.push("f"), 1);
+    /// ```
+    pub fn shrink_to(&mut self, slots: usize) {
+        let slots = match self.occupied.last() {
+            Some(last) => slots.max(last + 1),
+            None => slots,
+        };
+        if slots >= self.slot_count() {
+            return;
+        }
+
+        // Unlink the removed slots while keeping the order of the remaining ones.
+        let mut token = self.first_free;
+        self.first_free = Self::SENTINEL;
+        self.last_free = Self::SENTINEL;
+        while token != Self::SENTINEL {
+            let next = self.next_free(token.into_usize());
+            if token.into_usize() < slots {
+                self.link_free_back(token);
+            }
+            token = next;
+        }
+
+        self.quarantine.retain(|&index| index.into_usize() < slots);
+        self.truncate_slots(slots);
+        wipe::shrink_to(&mut self.data, slots);
+    }
+
+    /// Removes all slots at and above `len`, dropping the elements they hold.
+    ///
+    /// Unlike [`shrink_to`](Self::shrink_to), occupied slots are removed as well. The
+    /// removed slots are unlinked from the free chain (or the quarantine) and the order
+    /// of the remaining free slots is kept. The generations of the removed slots are
+    /// kept, so that keys to the dropped elements stay invalid when the slots are added
+    /// again. The capacity of the slot storage is not changed.
+    ///
+    /// The elements are dropped in the order set with
+    /// [`set_drop_order`](Self::set_drop_order). If the destructor of an element panics,
+    /// the remaining elements are still dropped and the slots are removed.
+    ///
+    /// ## Panics
+    /// Panics if the generation of a removed element is exhausted and the list was set
+    /// to [`GenerationOverflow::Panic`]; the list is not changed then.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// for value in ["a", "b", "c", "d", "e"] {
+    ///     list.push(value);
+    /// }
+    /// list.erase(1);
+    /// list.erase(3);
+    ///
+    /// list.truncate(2);
+    /// assert_eq!(list.slot_count(), 2);
+    /// assert_eq!(list.len(), 1);
+    ///
+    /// // Slot 1 is still free, slot 2 is added again.
+    /// assert_eq!(list.push("f"), 1);
+    /// assert_eq!(list.push("g"), 2);
+    /// ```
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.slot_count() {
+            return;
+        }
+        self.check_generations(self.occupied.ones_from(len));
+
+        // Unlink the removed slots while keeping the order of the remaining ones.
+        let mut token = self.first_free;
+        self.first_free = Self::SENTINEL;
+        self.last_free = Self::SENTINEL;
+        while token != Self::SENTINEL {
+            let next = self.next_free(token.into_usize());
+            if token.into_usize() < len {
+                self.link_free_back(token);
+            }
+            token = next;
+        }
+        self.quarantine.retain(|&index| index.into_usize() < len);
+
+        // The slots are released before the elements are dropped, so that a panicking
+        // destructor neither leaves dropped elements in the list nor drops them twice.
+        let mut removed = BitSet::default();
+        let mut erased = Vec::new();
+        while let Some(index) = self.occupied.next_one(len) {
+            if self.observer.is_some() {
+                erased.push((index_from_usize(index), self.generations[index]));
+            }
+            self.overflow
+                .advance(&mut self.generations[index], &mut self.generation_wraps);
+            self.occupied.remove(index);
+            removed.insert(index);
+            self.length -= 1;
+        }
+        if Self::IS_ZST {
+            self.zst.truncate(len);
+        }
+
+        DropGuard::new(&mut self.data, len, removed, self.drop_order).drop_elements();
+        for (index, generation) in erased {
+            self.notify_erase(index, generation);
+        }
+    }
+
+    /// Reorders the free chain so that free slots are reused in ascending index order.
+    ///
+    /// After a burst of erases in random order, this makes subsequent insertions fill
+    /// the list front-to-back, which improves cache locality. Only free slots are
+    /// touched; the operation takes `O(f log f)` time for `f` free slots.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// for value in ["a", "b", "c", "d"] {
+    ///     list.push(value);
+    /// }
+    /// list.erase(1);
+    /// list.erase(3);
+    /// list.erase(0);
+    ///
+    /// list.sort_free_chain();
+    /// assert_eq!(list.push("e"), 0);
+    /// assert_eq!(list.push("f"), 1);
+    /// assert_eq!(list.push("g"), 3);
+    /// ```
+    pub fn sort_free_chain(&mut self) {
+        let mut free = Vec::new();
+        let mut token = self.first_free;
+        while token != Self::SENTINEL {
+            free.push(token);
+            token = self.next_free(token.into_usize());
+        }
+        free.sort_unstable();
+
+        self.first_free = Self::SENTINEL;
+        self.last_free = Self::SENTINEL;
+        for index in free {
+            self.link_free_back(index);
+        }
+    }
+
+    /// Moves all elements at indices greater than or equal to `at` into a new list.
+    ///
+    /// Elements keep their indices: the new list has free slots below `at`, and this
+    /// list is truncated to `at` slots. The free chains of both lists are rebuilt in
+    /// ascending index order. The new list uses the same allocator, reuse policy and
+    /// quarantine length as this list.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// for value in ["a", "b", "c", "d"] {
+    ///     list.push(value);
+    /// }
+    /// list.erase(1);
+    ///
+    /// let other = list.split_off(2);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), [(0, &"a")]);
+    /// assert_eq!(other.iter().collect::<Vec<_>>(), [(2, &"c"), (3, &"d")]);
+    ///
+    /// // Free slots of both lists are reused front-to-back.
+    /// assert_eq!(list.push("e"), 1);
+    /// ```
+    pub fn split_off(&mut self, at: TIndex) -> Self
+    where
+        A: Clone,
+    {
+        let slots = self.slot_count();
+        let at = at.into_usize().min(slots);
+        let mut other = Self::with_reuse_policy_in(self.policy, self.allocator().clone());
+        other.quarantine_len = self.quarantine_len;
+        other.overflow = self.overflow;
+        other.drop_order = self.drop_order;
+        other.generations = self.generations.clone();
+        other.reserve_slots(slots);
+        self.check_generations(self.occupied.ones());
+
+        for index in 0..slots {
+            if index >= at && self.occupied.contains(index) {
+                // Move the element; the slot in this list is truncated below.
+                let element = unsafe { ManuallyDrop::take(self.element_mut(index)) };
+                other.append_slot(FreeElement {
+                    element: ManuallyDrop::new(element),
+                });
+                other.occupied.insert(index);
+                other.ticks.added(index);
+                other.length += 1;
+                self.occupied.remove(index);
+                let generation = self.generations[index];
+                self.overflow
+                    .advance(&mut self.generations[index], &mut self.generation_wraps);
+                self.length -= 1;
+                self.notify_erase(index_from_usize(index), generation);
+            } else {
+                other.append_slot(FreeElement {
+                    next: Self::SENTINEL,
+                });
+                if self.occupied.contains(index) {
+                    other
+                        .overflow
+                        .advance(&mut other.generations[index], &mut other.generation_wraps);
+                }
+            }
+        }
+
+        self.truncate_slots(at);
+        self.quarantine.retain(|&index| index.into_usize() < at);
+        self.rebuild_free_chain();
+        other.rebuild_free_chain();
+        other.peak_length = other.length;
+        other.peak_slots = other.slot_count();
+        other
+    }
+
+    /// Gets a reference to the value at the specified index.
+    ///
+    /// # Safety
+    ///
+    /// If the element at the specified index was erased, the union now acts
+    ///  as a pointer to the next free element. Accessing the same index again after that.
+    ///  is undefined behavior.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// assert_eq!(list.push("first"), 0);
+    /// assert_eq!(list.push("second"), 1);
+    ///
+    /// let element = unsafe { list.at(0) };
+    /// assert_eq!(*element, "first");
+    /// ```
+    ///
+    /// Note that accessing a previously erased item is undefined behavior:
+    ///
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// list.push("first");
+    ///
+    /// // SAFETY: The code below is undefined behavior:
+    /// list.erase(0);
+    /// // let element = unsafe { list.at(0) };
+    /// // assert_eq!(*element, "first");
+    /// ```
+    #[inline]
+    pub unsafe fn at(&self, index: TIndex) -> &T {
+        safety_check!(
+            self.occupied.contains(index.into_usize()),
+            "Attempted to access a slot that is not occupied ({:?})",
+            index
+        );
+        self.element(index.into_usize())
+    }
+
+    /// Gets a mutable reference to the value at the specified index.
+    ///
+    /// # Safety
+    ///
+    /// If the element at the specified index was erased, the union now acts
+    /// as a pointer to the next free element. Accessing the same index again after that.
+    /// is undefined behavior.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// assert_eq!(list.push("first"), 0);
+    /// assert_eq!(list.push("second"), 1);
+    ///
+    /// let element = unsafe { list.at_mut(1) };
+    /// *element = "danger";
+    ///
+    /// assert_eq!(unsafe { list.at(1) }, &"danger");
+    /// ```
+    ///
+    /// As with `at()`, accessing a previously erased element is
+    /// undefined behavior.
+    #[inline]
+    pub unsafe fn at_mut(&mut self, index: TIndex) -> &mut T {
+        safety_check!(
+            self.occupied.contains(index.into_usize()),
+            "Attempted to access a slot that is not occupied ({:?})",
+            index
+        );
+        self.ticks.changed([index.into_usize()]);
+        self.element_mut(index.into_usize())
+    }
+
+    /// Gets mutable references to the values at multiple distinct indices at once.
+    ///
+    /// ## Errors
+    /// Returns [`GetDisjointMutError::NotOccupied`] if any index does not refer to
+    /// an occupied slot, and [`GetDisjointMutError::OverlappingIndices`] if the same
+    /// index is specified more than once.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::{FreeList, GetDisjointMutError};
+    ///
+    /// let mut list = FreeList::<u32, u8>::default();
+    /// list.push(1);
+    /// list.push(2);
+    ///
+    /// let [a, b] = list.get_disjoint_mut([0, 1]).unwrap();
+    /// std::mem::swap(a, b);
+    /// assert_eq!(unsafe { list.at(0) }, &2);
+    ///
+    /// assert_eq!(list.get_disjoint_mut([0, 0]).err(), Some(GetDisjointMutError::OverlappingIndices));
+    /// assert_eq!(list.get_disjoint_mut([0, 2]).err(), Some(GetDisjointMutError::NotOccupied));
+    /// ```
+    pub fn get_disjoint_mut<const N: usize>(
+        &mut self,
+        indices: [TIndex; N],
+    ) -> Result<[&mut T; N], GetDisjointMutError> {
+        // Every index is converted once, so that the checks and the references agree
+        // even if the index type converts inconsistently.
+        let indices = indices.map(|index| index.into_usize());
+        for (i, &index) in indices.iter().enumerate() {
+            if !self.is_occupied_slot(index) {
+                return Err(GetDisjointMutError::NotOccupied);
+            }
+            if indices[..i].contains(&index) {
+                return Err(GetDisjointMutError::OverlappingIndices);
+            }
+        }
+
+        self.ticks.changed(indices);
+
+        // SAFETY: All indices were verified to be distinct and occupied.
+        let base = self.data.as_mut_ptr();
+        Ok(indices.map(|index| unsafe { &mut **FreeElement::element_ptr(base, index) }))
+    }
+
+    /// Gets mutable references to the values at multiple distinct indices at once.
+    ///
+    /// This is the slice-based counterpart of [`get_disjoint_mut`](Self::get_disjoint_mut)
+    /// for when the number of indices is not known at compile time.
+    ///
+    /// ## Errors
+    /// Returns [`GetDisjointMutError::NotOccupied`] if any index does not refer to
+    /// an occupied slot, and [`GetDisjointMutError::OverlappingIndices`] if the same
+    /// index is specified more than once.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<u32, u8>::default();
+    /// list.push(1);
+    /// list.push(2);
+    /// list.push(3);
+    ///
+    /// for value in list.get_disjoint_mut_slice(&[2, 0]).unwrap() {
+    ///     *value *= 10;
+    /// }
+    /// assert_eq!(unsafe { list.at(0) }, &10);
+    /// assert_eq!(unsafe { list.at(1) }, &2);
+    /// assert_eq!(unsafe { list.at(2) }, &30);
+    /// ```
+    pub fn get_disjoint_mut_slice(
+        &mut self,
+        indices: &[TIndex],
+    ) -> Result<Vec<&mut T>, GetDisjointMutError> {
+        // Every index is converted once, so that the checks and the references agree
+        // even if the index type converts inconsistently.
+        let indices: Vec<usize> = indices.iter().map(|index| index.into_usize()).collect();
+        if indices.iter().any(|&index| !self.is_occupied_slot(index)) {
+            return Err(GetDisjointMutError::NotOccupied);
+        }
+
+        let mut sorted = indices.clone();
+        sorted.sort_unstable();
+        if sorted.windows(2).any(|pair| pair[0] == pair[1]) {
+            return Err(GetDisjointMutError::OverlappingIndices);
+        }
+
+        self.ticks.changed(indices.iter().copied());
+
+        // SAFETY: All indices were verified to be distinct and occupied.
+        let base = self.data.as_mut_ptr();
+        Ok(indices
+            .into_iter()
+            .map(|index| unsafe { &mut **FreeElement::element_ptr(base, index) })
+            .collect())
+    }
+
+    /// Swaps the values stored at two occupied slots in place.
+    ///
+    /// ## Panics
+    /// Panics if either index does not refer to an occupied slot.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// list.push("first");
+    /// list.push("second");
+    ///
+    /// list.swap(0, 1);
+    /// assert_eq!(unsafe { list.at(0) }, &"second");
+    /// assert_eq!(unsafe { list.at(1) }, &"first");
+    /// ```
+    pub fn swap(&mut self, a: TIndex, b: TIndex) {
+        assert!(
+            self.status(a).is_occupied(),
+            "Attempted to swap a slot that is not occupied ({:?})",
+            a
+        );
+        assert!(
+            self.status(b).is_occupied(),
+            "Attempted to swap a slot that is not occupied ({:?})",
+            b
+        );
+
+        if a != b {
+            let [a, b] = self
+                .get_disjoint_mut([a, b])
+                .expect("indices were validated");
+            std::mem::swap(a, b);
+        }
+    }
+
+    /// Gets the number of slots, occupied or free, that were handed out so far.
+    ///
+    /// This is the exclusive upper bound of all indices issued by the list.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    ///
+    /// // The first elements increase the slot count.
+    /// list.push("first");
+    /// list.push("second");
+    /// assert_eq!(list.slot_count(), 2);
+    ///
+    /// // Erasing elements does not decrease the slot count.
+    /// list.erase(0);
+    /// list.erase(1);
+    /// assert_eq!(list.slot_count(), 2);
+    ///
+    /// // Adding elements after an erase reuses the free slots.
+    /// list.push("fourth");
+    /// list.push("fifth");
+    /// assert_eq!(list.slot_count(), 2);
+    ///
+    /// // Adding more elements increases the slot count.
+    /// list.push("sixth");
+    /// list.push("seventh");
+    /// assert_eq!(list.slot_count(), 4);
+    ///
+    /// // Clearing the list frees all resources.
+    /// list.clear();
+    /// assert_eq!(list.slot_count(), 0);
+    /// ```
+    #[inline]
+    pub fn slot_count(&self) -> usize {
+        if Self::IS_ZST {
+            self.zst.len
+        } else {
+            self.data.len()
+        }
+    }
+
+    /// Gets the number of slots the list can hold without reallocating its slot storage.
+    ///
+    /// Unlike [`slot_count`](Self::slot_count), this includes allocated memory that was
+    /// never handed out as a slot, and unlike [`len`](Self::len), it is unaffected by erases.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// list.push("first");
+    /// list.push("second");
+    /// list.erase(0);
+    ///
+    /// assert_eq!(list.len(), 1);
+    /// assert_eq!(list.slot_count(), 2);
+    /// assert!(list.capacity() >= 2);
+    /// ```
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        if Self::IS_ZST {
+            usize::MAX
+        } else {
+            self.data.capacity()
+        }
+    }
+
+    /// Gets a snapshot of the slot usage of the list, including the high-water marks
+    /// since the list was created or [`reset_high_water_marks`](Self::reset_high_water_marks)
+    /// was last called.
+    ///
+    /// Determining the span walks the occupancy metadata and is therefore linear in the
+    /// number of slots.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// for value in ["a", "b", "c", "d"] {
+    ///     list.push(value);
+    /// }
+    /// list.erase(1);
+    /// list.erase(3);
+    ///
+    /// let stats = list.stats();
+    /// assert_eq!(stats.live, 2);
+    /// assert_eq!(stats.free, 2);
+    /// assert_eq!(stats.total, 4);
+    /// assert_eq!(stats.span, 3);
+    /// assert!((stats.fragmentation() - 1.0 / 3.0).abs() < 1e-9);
+    /// ```
+    pub fn stats(&self) -> FreeListStats {
+        debug_assert_eq!(self.length, self.occupied.count());
+        FreeListStats {
+            live: self.length,
+            free: self.slot_count() - self.length,
+            total: self.slot_count(),
+            span: self.occupied.last().map_or(0, |index| index + 1),
+            peak_live: self.peak_length,
+            peak_total: self.peak_slots,
+        }
+    }
+
+    /// Resets the high-water marks reported by [`stats`](Self::stats) to the current usage.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// list.push("first");
+    /// list.push("second");
+    /// list.clear();
+    /// assert_eq!(list.stats().peak_live, 2);
+    /// assert_eq!(list.stats().peak_total, 2);
+    ///
+    /// list.reset_high_water_marks();
+    /// assert_eq!(list.stats().peak_live, 0);
+    /// ```
+    pub fn reset_high_water_marks(&mut self) {
+        self.peak_length = self.length;
+        self.peak_slots = self.slot_count();
+    }
+
+    /// Validates the internal bookkeeping of the list.
+    ///
+    /// This walks the free chain, the quarantine and the occupancy metadata and reports
+    /// the first inconsistency found, such as cycles, out-of-range links or a mismatching
+    /// element count. It is available in release builds and is meant to be used when the
+    /// list is manipulated through FFI or other unsafe code.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// list.push("first");
+    /// list.push("second");
+    /// list.erase(0);
+    /// assert_eq!(list.check_invariants(), Ok(()));
+    /// ```
+    pub fn check_invariants(&self) -> Result<(), InvariantError> {
+        let slots = self.slot_count();
+        if slots >= Self::SENTINEL.into_usize() {
+            return Err(InvariantError::TooManySlots { slots });
+        }
+        if let Some(index) = self.occupied.last().filter(|&index| index >= slots) {
+            return Err(InvariantError::OccupancyOutOfRange { index });
+        }
+
+        if self.generations.len() < slots {
+            return Err(InvariantError::MissingGenerations);
+        }
+
+        let counted = self.occupied.count();
+        if counted != self.length {
+            return Err(InvariantError::LengthMismatch {
+                tracked: self.length,
+                counted,
+            });
+        }
+
+        // Every slot linked as free is recorded so that cycles and duplicates are detected.
+        let mut linked = BitSet::default();
+        let mut link = |index: TIndex| {
+            let index = index.into_usize();
+            if index >= slots {
+                Err(InvariantError::LinkOutOfRange { index })
+            } else if self.occupied.contains(index) {
+                Err(InvariantError::OccupiedSlotLinked { index })
+            } else if linked.contains(index) {
+                Err(InvariantError::DuplicateFreeSlot { index })
+            } else {
+                linked.insert(index);
+                Ok(index)
+            }
+        };
+
+        let mut last = Self::SENTINEL;
+        let mut token = self.first_free;
+        while token != Self::SENTINEL {
+            let index = link(token)?;
+            if self.policy == ReusePolicy::LowestIndexFirst
+                && last != Self::SENTINEL
+                && last > token
+            {
+                return Err(InvariantError::UnsortedFreeChain { index });
+            }
+            last = token;
+            token = self.next_free(index);
+        }
+
+        if last != self.last_free {
+            return Err(InvariantError::LastFreeMismatch);
+        }
+
+        for &token in &self.quarantine {
+            link(token)?;
+        }
+
+        match (0..slots).find(|&index| {
+            !self.occupied.contains(index) && !linked.contains(index) && !self.is_retired(index)
+        }) {
+            Some(index) => Err(InvariantError::UnlinkedFreeSlot { index }),
+            None => Ok(()),
+        }
+    }
+
+    /// Gets the number of bytes currently allocated by the list, including the slot
+    /// storage and all metadata.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<u64, u8>::default();
+    /// assert_eq!(list.memory_usage(), 0);
+    ///
+    /// list.push(1);
+    /// assert!(list.memory_usage() >= std::mem::size_of::<u64>());
+    /// ```
+    pub fn memory_usage(&self) -> usize {
+        self.data.capacity() * std::mem::size_of::<FreeElement<T, TIndex>>()
+            + self.occupied.memory_usage()
+            + self.quarantine.capacity() * std::mem::size_of::<TIndex>()
+            + self.generations.capacity() * std::mem::size_of::<u32>()
+            + self.zst.memory_usage()
+    }
+
+    /// Determines the state of the slot at the specified index.
+    ///
+    /// This is a constant-time operation that can be used to validate indices
+    /// obtained from untrusted sources before accessing the slot.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::{FreeList, SlotStatus};
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// list.push("first");
+    /// list.push("second");
+    /// list.erase(0);
+    ///
+    /// assert_eq!(list.status(0), SlotStatus::Free);
+    /// assert_eq!(list.status(1), SlotStatus::Occupied);
+    /// assert_eq!(list.status(2), SlotStatus::OutOfRange);
+    /// ```
+    pub fn status(&self, index: TIndex) -> SlotStatus {
+        let index = index.into_usize();
+        if index >= self.slot_count() {
+            SlotStatus::OutOfRange
+        } else if self.occupied.contains(index) {
+            SlotStatus::Occupied
+        } else {
+            SlotStatus::Free
+        }
+    }
+
+    /// Gets an iterator over the indices of all occupied slots, in ascending order.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// list.push("first");
+    /// list.push("second");
+    /// list.push("third");
+    /// list.erase(1);
+    ///
+    /// let keys: Vec<_> = list.keys().collect();
+    /// assert_eq!(keys, [0, 2]);
+    /// ```
+    pub fn keys(&self) -> Keys<'_, TIndex> {
+        Keys::new(self.occupied.ones())
+    }
+
+    /// Gets an iterator over the indices and values of all occupied slots, in ascending
+    /// index order.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// list.push("first");
+    /// list.push("second");
+    /// list.push("third");
+    /// list.erase(0);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), [(1, &"second"), (2, &"third")]);
+    ///
+    /// // The iterator knows its length and can be traversed from both ends.
+    /// assert_eq!(list.iter().len(), 2);
+    /// assert_eq!(list.iter().rev().next(), Some((2, &"third")));
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T, TIndex> {
+        Iter::new(self.occupied.ones(), &self.data)
+    }
+
+    /// Determines whether every slot holds an element, i.e. the list has no holes.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// list.push("first");
+    /// list.push("second");
+    /// assert!(list.is_dense());
+    ///
+    /// list.erase(0);
+    /// assert!(!list.is_dense());
+    /// ```
+    #[inline]
+    pub fn is_dense(&self) -> bool {
+        self.length == self.slot_count()
+    }
+
+    /// Gets all elements as a contiguous slice, in index order, if the list is dense.
+    ///
+    /// Returns `None` if the list has holes, see [`is_dense`](Self::is_dense), or if a
+    /// slot is larger than an element, which is the case if the element is smaller than
+    /// the index type or less aligned than it.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<f32, u32>::default();
+    /// list.extend_and_collect([1.0, 2.0, 3.0]);
+    /// assert_eq!(list.as_slice(), Some(&[1.0, 2.0, 3.0][..]));
+    ///
+    /// list.erase(1);
+    /// assert_eq!(list.as_slice(), None);
+    /// ```
+    pub fn as_slice(&self) -> Option<&[T]> {
+        let ptr = self.dense_ptr(self.data.as_ptr().cast_mut())?;
+        // SAFETY: All slots are occupied and have the layout of their element.
+        Some(unsafe { std::slice::from_raw_parts(ptr, self.length) })
+    }
+
+    /// Gets all elements as a contiguous mutable slice, in index order, if the list is
+    /// dense; see [`as_slice`](Self::as_slice).
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<u32, u32>::default();
+    /// list.extend_and_collect([1, 2, 3]);
+    /// list.as_mut_slice().unwrap().reverse();
+    /// assert_eq!(list[0], 3);
+    /// ```
+    pub fn as_mut_slice(&mut self) -> Option<&mut [T]> {
+        let base = self.data.as_mut_ptr();
+        let ptr = self.dense_ptr(base)?;
+        self.ticks.changed(0..self.length);
+        // SAFETY: All slots are occupied and have the layout of their element.
+        Some(unsafe { std::slice::from_raw_parts_mut(ptr, self.length) })
+    }
+
+    /// Gets an iterator over the runs of adjacent occupied slots, in ascending index
+    /// order, as the index of the first slot and a slice of the elements of the run.
+    ///
+    /// This allows to process fragmented lists with bulk slice operations. Like
+    /// [`as_slice`](Self::as_slice), this returns `None` if a slot is larger than an
+    /// element.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<u32, u32>::default();
+    /// list.extend_and_collect(0..6);
+    /// list.erase(2);
+    /// list.erase(3);
+    ///
+    /// let runs: Vec<_> = list.iter_occupied_runs().unwrap().collect();
+    /// assert_eq!(runs, [(0, &[0, 1][..]), (4, &[4, 5][..])]);
+    /// ```
+    pub fn iter_occupied_runs(&self) -> Option<impl Iterator<Item = (TIndex, &[T])> + '_> {
+        if !Self::SLOTS_ARE_ELEMENTS {
+            return None;
+        }
+
+        let base = self.data.as_ptr().cast_mut();
+        let mut next = 0;
+        Some(std::iter::from_fn(move || {
+            let start = self.occupied.next_one(next)?;
+            next = self.occupied.next_zero(start);
+            // SAFETY: The slots of the run are occupied and have the layout of their
+            // element; `ManuallyDrop` is transparent.
+            let run = unsafe {
+                let ptr = FreeElement::element_ptr(base, start).cast::<T>();
+                std::slice::from_raw_parts(ptr, next - start)
+            };
+            Some((index_from_usize(start), run))
+        }))
+    }
+
+    /// Clones the elements into the buffer in ascending index order and returns the
+    /// number of elements written, which is limited by the length of the buffer.
+    ///
+    /// Runs of adjacent elements are cloned slice-wise, which compiles to a plain copy
+    /// for `Copy` types.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<u32, u32>::default();
+    /// list.extend_and_collect([1, 2, 3, 4]);
+    /// list.erase(1);
+    ///
+    /// let mut buffer = [0; 8];
+    /// assert_eq!(list.gather_into(&mut buffer), 3);
+    /// assert_eq!(buffer[..3], [1, 3, 4]);
+    /// ```
+    pub fn gather_into(&self, buffer: &mut [T]) -> usize
+    where
+        T: Clone,
+    {
+        let mut written = 0;
+        if let Some(runs) = self.iter_occupied_runs() {
+            for (_, run) in runs {
+                let count = run.len().min(buffer.len() - written);
+                buffer[written..written + count].clone_from_slice(&run[..count]);
+                written += count;
+                if written == buffer.len() {
+                    break;
+                }
+            }
+        } else {
+            for (target, (_, element)) in buffer.iter_mut().zip(self.iter()) {
+                target.clone_from(element);
+                written += 1;
+            }
+        }
+        written
+    }
+
+    /// Moves the elements with the lowest indices into the buffer, in ascending index
+    /// order, and returns the number of elements moved, which is limited by the length
+    /// of the buffer.
+    ///
+    /// The moved elements are removed from the list as if by [`remove`](Self::remove);
+    /// the values they replace in the buffer are dropped.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<String, u8>::default();
+    /// list.extend_and_collect(["a".to_string(), "b".to_string(), "c".to_string()]);
+    ///
+    /// let mut buffer = vec![String::new(); 2];
+    /// assert_eq!(list.drain_into(&mut buffer), 2);
+    /// assert_eq!(buffer, ["a", "b"]);
+    /// assert_eq!(list.len(), 1);
+    /// ```
+    pub fn drain_into(&mut self, buffer: &mut [T]) -> usize {
+        let mut written = 0;
+        let mut next = 0;
+        while written < buffer.len() {
+            let Some(index) = self.occupied.next_one(next) else {
+                break;
+            };
+            buffer[written] = self.take(index_from_usize(index));
+            written += 1;
+            next = index + 1;
+        }
+        written
+    }
+
+    /// Gets a pointer to the first element if all slots are occupied and each slot has
+    /// the size of an element, so that the elements form a slice. Zero-sized elements
+    /// are not stored and always form a slice. `base` must point to the slots.
+    fn dense_ptr(&self, base: *mut FreeElement<T, TIndex>) -> Option<*mut T> {
+        if !Self::SLOTS_ARE_ELEMENTS || !self.is_dense() {
+            return None;
+        }
+        if self.length == 0 {
+            return Some(std::ptr::NonNull::dangling().as_ptr());
+        }
+        // SAFETY: There is at least one slot; `ManuallyDrop` is transparent.
+        let ptr = unsafe { FreeElement::element_ptr(base, 0) };
+        Some(ptr.cast())
+    }
+
+    /// Gets an iterator over the indices and values of all occupied slots above the
+    /// specified index, in ascending index order.
+    ///
+    /// This allows to process a large list in batches, e.g. spread over several frames,
+    /// by remembering the index of the last processed element and resuming after it.
+    /// The index does not need to refer to an element anymore; elements inserted or
+    /// removed in between are visited or skipped according to their index.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<u32, u8>::default();
+    /// list.extend_and_collect(0..6);
+    ///
+    /// let batch: Vec<_> = list.iter().take(3).collect();
+    /// let (cursor, _) = *batch.last().unwrap();
+    /// list.erase(2);
+    /// list.erase(3);
+    ///
+    /// let rest: Vec<_> = list.iter_after(cursor).map(|(index, _)| index).collect();
+    /// assert_eq!(rest, [4, 5]);
+    /// ```
+    pub fn iter_after(&self, index: TIndex) -> Iter<'_, T, TIndex> {
+        Iter::new(
+            self.occupied
+                .ones_from(index.into_usize().saturating_add(1)),
+            &self.data,
+        )
+    }
+
+    /// Enables or disables the rank index over the occupied slots.
+    ///
+    /// With the index, [`rank`](Self::rank) and [`select`](Self::select) take logarithmic
+    /// time; without it, they scan the occupancy bitmap, which takes linear time but is
+    /// fast for small lists. The index is updated on every insertion and removal, costing
+    /// one `usize` per 64 slots. Whether it is enabled is part of the raw parts but not of
+    /// snapshots.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<u32, u16>::default();
+    /// list.set_rank_index(true);
+    /// list.extend_and_collect(0..1000);
+    /// list.erase(10);
+    /// assert_eq!(list.rank(500), 499);
+    /// assert_eq!(list.select(499), Some(500));
+    /// ```
+    pub fn set_rank_index(&mut self, enabled: bool) {
+        self.occupied.set_rank_index(enabled);
+    }
+
+    /// Determines whether the rank index is enabled; see [`set_rank_index`](Self::set_rank_index).
+    #[inline]
+    pub fn has_rank_index(&self) -> bool {
+        self.occupied.has_rank_index()
+    }
+
+    /// Gets the number of elements whose index is below the specified index, i.e. the
+    /// position of the element at that index in iteration order.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// list.extend_and_collect(["a", "b", "c"]);
+    /// list.erase(0);
+    /// assert_eq!(list.rank(2), 1);
+    /// assert_eq!(list.rank(200), 2);
+    /// ```
+    pub fn rank(&self, index: TIndex) -> usize {
+        self.occupied.rank(index.into_usize())
+    }
+
+    /// Gets the index of the element with the specified rank, i.e. the index of the
+    /// element at that position in iteration order, or `None` if there are not that
+    /// many elements. This is the inverse of [`rank`](Self::rank).
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// list.extend_and_collect(["a", "b", "c"]);
+    /// list.erase(0);
+    /// assert_eq!(list.select(0), Some(1));
+    /// assert_eq!(list.select(2), None);
+    /// ```
+    pub fn select(&self, rank: usize) -> Option<TIndex> {
+        self.occupied.select(rank).map(index_from_usize)
+    }
+
+    /// Gets the index and value of the `n`-th element in iteration order, or `None` if
+    /// there are not that many elements.
+    ///
+    /// This uses the rank index if it is enabled, see [`set_rank_index`](Self::set_rank_index),
+    /// and scans the occupancy bitmap otherwise, which is still faster than skipping
+    /// elements of [`iter`](Self::iter).
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// list.extend_and_collect(["a", "b", "c"]);
+    /// list.erase(1);
+    /// assert_eq!(list.nth_occupied(1), Some((2, &"c")));
+    /// assert_eq!(list.nth_occupied(2), None);
+    /// ```
+    pub fn nth_occupied(&self, n: usize) -> Option<(TIndex, &T)> {
+        let slot = self.occupied.select(n)?;
+        Some((index_from_usize(slot), self.occupied_element(slot)?))
+    }
+
+    /// Gets the index and a mutable reference to the value of the `n`-th element in
+    /// iteration order, or `None` if there are not that many elements.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<u32, u8>::default();
+    /// list.extend_and_collect([1, 2, 3]);
+    /// list.erase(0);
+    /// *list.nth_occupied_mut(0).unwrap().1 += 10;
+    /// assert_eq!(list[1], 12);
+    /// ```
+    pub fn nth_occupied_mut(&mut self, n: usize) -> Option<(TIndex, &mut T)> {
+        let slot = self.occupied.select(n)?;
+        Some((index_from_usize(slot), self.occupied_element_mut(slot)?))
+    }
+
+    /// Gets an iterator over the indices and mutable values of all occupied slots, in
+    /// ascending index order.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<u32, u8>::default();
+    /// list.push(1);
+    /// list.push(2);
+    /// for (_, value) in list.iter_mut() {
+    ///     *value *= 10;
+    /// }
+    /// assert_eq!(list.get(1), Some(&20));
+    /// ```
+    pub fn iter_mut(&mut self) -> IterMut<'_, T, TIndex> {
+        self.ticks.changed(self.occupied.ones());
+        IterMut::new(self.occupied.ones(), &mut self.data)
+    }
+
+    /// Inserts an element to the list and returns an index to it.
+    ///
+    /// This is an alias of [`push`](Self::push) for compatibility with the `slab` crate.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// let key = list.insert("first");
+    /// assert_eq!(list[key], "first");
+    /// ```
+    #[inline]
+    pub fn insert(&mut self, element: T) -> TIndex {
+        self.push(element)
+    }
+
+    /// Removes the element at the specified index and returns it.
+    ///
+    /// ## Panics
+    /// Panics if the slot is not occupied.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// let key = list.insert("first");
+    /// assert_eq!(list.remove(key), "first");
+    /// assert!(!list.contains(key));
+    /// ```
+    pub fn remove(&mut self, index: TIndex) -> T {
+        self.try_remove(index).unwrap_or_else(|| {
+            panic!("Attempted to remove a slot that is not occupied ({index:?})")
+        })
+    }
+
+    /// Removes the element at the specified index and returns it, or returns `None`
+    /// if the slot is not occupied.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// let key = list.insert("first");
+    /// assert_eq!(list.try_remove(key), Some("first"));
+    /// assert_eq!(list.try_remove(key), None);
+    /// ```
+    pub fn try_remove(&mut self, index: TIndex) -> Option<T> {
+        if self.contains(index) {
+            Some(self.take(index))
+        } else {
+            None
+        }
+    }
+
+    /// Removes the element with the highest index and returns it together with its
+    /// index, or returns `None` if the list is empty.
+    ///
+    /// As long as elements are only pushed to slots at the end of the list, this removes
+    /// the most recently pushed element, which allows to use the list as a stack of
+    /// scratch objects without tracking their indices.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// list.push("first");
+    /// list.push("second");
+    /// list.push("third");
+    /// list.erase(2);
+    ///
+    /// assert_eq!(list.pop(), Some((1, "second")));
+    /// assert_eq!(list.pop(), Some((0, "first")));
+    /// assert_eq!(list.pop(), None);
+    /// ```
+    pub fn pop(&mut self) -> Option<(TIndex, T)> {
+        let index = index_from_usize(self.occupied.last()?);
+        Some((index, self.take(index)))
+    }
+
+    /// Gets a reference to the value at the specified index, or `None` if the
+    /// slot is not occupied.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// let key = list.insert("first");
+    /// assert_eq!(list.get(key), Some(&"first"));
+    /// assert_eq!(list.get(key + 1), None);
+    /// ```
+    pub fn get(&self, index: TIndex) -> Option<&T> {
+        self.occupied_element(index.into_usize())
+    }
+
+    /// Gets a mutable reference to the value at the specified index, or `None` if
+    /// the slot is not occupied.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// let key = list.insert("first");
+    /// *list.get_mut(key).unwrap() = "changed";
+    /// assert_eq!(list[key], "changed");
+    /// ```
+    pub fn get_mut(&mut self, index: TIndex) -> Option<&mut T> {
+        self.occupied_element_mut(index.into_usize())
+    }
+
+    /// Determines whether the specified index refers to an occupied slot.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// let key = list.insert("first");
+    /// assert!(list.contains(key));
+    /// list.erase(key);
+    /// assert!(!list.contains(key));
+    /// ```
+    #[inline]
+    pub fn contains(&self, index: TIndex) -> bool {
+        self.status(index).is_occupied()
+    }
+
+    /// Gets the generational key of the element at the specified index, or `None`
+    /// if the slot is not occupied.
+    ///
+    /// Unlike the bare index, the key does not match a different element that is
+    /// later stored in the same slot.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// let index = list.push("first");
+    /// let first = list.key(index).unwrap();
+    /// list.erase(index);
+    ///
+    /// let index = list.push("second");
+    /// let second = list.key(index).unwrap();
+    /// assert_eq!(first.index(), second.index());
+    /// assert!(!list.contains_key(first));
+    /// assert!(list.contains_key(second));
+    /// ```
+    pub fn key(&self, index: TIndex) -> Option<Key<TIndex>> {
+        let n = index.into_usize();
+        self.is_occupied_slot(n)
+            .then(|| Key::new(index, self.generations[n]))
+    }
+
+    /// Inserts an element to the list and returns its generational key.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// let key = list.push_key("first");
+    /// assert_eq!(key.index(), 0);
+    /// assert_eq!(list.key(0), Some(key));
+    /// ```
+    pub fn push_key(&mut self, element: T) -> Key<TIndex> {
+        let index = self.push(element);
+        Key::new(index, self.generations[index.into_usize()])
+    }
+
+    /// Determines whether the specified key refers to an element of the list.
+    #[inline]
+    pub fn contains_key(&self, key: Key<TIndex>) -> bool {
+        self.key(key.index()) == Some(key)
+    }
+
+    /// Gets the number of elements in the list.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// list.insert("first");
+    /// list.insert("second");
+    /// list.remove(0);
+    /// assert_eq!(list.len(), 1);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    /// Determines whether the list contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// Determines whether the slot at the specified index exists and is occupied.
+    #[inline]
+    fn is_occupied_slot(&self, index: usize) -> bool {
+        index < self.slot_count() && self.occupied.contains(index)
+    }
+
+    /// Determines whether the specified slot is free and never reused again because its
+    /// generation is exhausted; see [`GenerationOverflow::Retire`].
+    #[inline]
+    fn is_retired(&self, index: usize) -> bool {
+        self.overflow == GenerationOverflow::Retire
+            && self.generations.get(index) == Some(&u32::MAX)
+            && !self.occupied.contains(index)
+    }
+
+    /// Gets the number of retired slots within the slot storage.
+    fn retired(&self) -> usize {
+        if self.overflow != GenerationOverflow::Retire {
+            return 0;
+        }
+        (0..self.slot_count())
+            .filter(|&index| self.is_retired(index))
+            .count()
+    }
+
+    /// Links all free slots that are not quarantined into the free chain, in ascending order.
+    fn rebuild_free_chain(&mut self) {
+        let mut quarantined = BitSet::default();
+        for &index in &self.quarantine {
+            quarantined.insert(index.into_usize());
+        }
+
+        self.first_free = Self::SENTINEL;
+        self.last_free = Self::SENTINEL;
+        for index in 0..self.slot_count() {
+            if !self.occupied.contains(index)
+                && !quarantined.contains(index)
+                && !self.is_retired(index)
+            {
+                self.link_free_back(index_from_usize(index));
+            }
+        }
+    }
+
+    /// Links a free slot into the free chain according to the reuse policy.
+    fn link_free(&mut self, n: TIndex) {
+        match self.policy {
+            ReusePolicy::Lifo => self.link_free_front(n),
+            ReusePolicy::Fifo => self.link_free_back(n),
+            ReusePolicy::LowestIndexFirst => self.link_free_sorted(n),
+        }
+    }
+
+    /// Merges the specified free slots, sorted in ascending order, into the sorted free chain.
+    fn merge_free_chain(&mut self, indices: &[TIndex]) {
+        let mut previous = Self::SENTINEL;
+        let mut current = self.first_free;
+        for &index in indices {
+            while current != Self::SENTINEL && current < index {
+                previous = current;
+                current = self.next_free(current.into_usize());
+            }
+
+            self.set_next_free(index.into_usize(), current);
+            if previous == Self::SENTINEL {
+                self.first_free = index;
+            } else {
+                self.set_next_free(previous.into_usize(), index);
+            }
+            if current == Self::SENTINEL {
+                self.last_free = index;
+            }
+            previous = index;
+        }
+    }
+
+    /// Moves the oldest quarantined slot into the free chain.
+    fn release_quarantined(&mut self) {
+        if let Some(n) = self.quarantine.pop_front() {
+            self.link_free(n);
+        }
+    }
+
+    /// Links a free slot into the front of the free chain.
+    fn link_free_front(&mut self, n: TIndex) {
+        self.set_next_free(n.into_usize(), self.first_free);
+        if self.first_free == Self::SENTINEL {
+            self.last_free = n;
+        }
+        self.first_free = n;
+    }
+
+    /// Links a free slot into the back of the free chain.
+    fn link_free_back(&mut self, n: TIndex) {
+        self.set_next_free(n.into_usize(), Self::SENTINEL);
+        if self.last_free == Self::SENTINEL {
+            self.first_free = n;
+        } else {
+            self.set_next_free(self.last_free.into_usize(), n);
+        }
+        self.last_free = n;
+    }
+
+    /// Links a free slot into an ascending free chain, keeping it sorted.
+    fn link_free_sorted(&mut self, n: TIndex) {
+        let n_usize = n.into_usize();
+
+        // Every slot below the length that is neither occupied, quarantined nor retired is
+        // part of the free chain, so the predecessor of the slot is the closest such slot
+        // before it.
+        let mut below = n_usize;
+        let previous = loop {
+            match self.occupied.last_zero_before(below) {
+                Some(index)
+                    if self.quarantine.contains(&index_from_usize(index))
+                        || self.is_retired(index) =>
+                {
+                    below = index
+                }
+                previous => break previous,
+            }
+        };
+
+        match previous {
+            Some(previous) => {
+                let next = self.next_free(previous);
+                self.set_next_free(previous, n);
+                self.set_next_free(n_usize, next);
+                if next == Self::SENTINEL {
+                    self.last_free = n;
+                }
+            }
+            None => self.link_free_front(n),
+        }
+    }
+
+    #[allow(dead_code)]
+    fn debug_is_in_free_list(&self, n: TIndex) -> bool {
+        assert_ne!(n, Self::SENTINEL);
+        let mut token = self.first_free;
+        while token != Self::SENTINEL {
+            if n == token {
+                return true;
+            }
+            token = self.next_free(token.into_usize());
+        }
+        false
+    }
+
+    /// Notifies the observer about an inserted element.
+    #[inline]
+    fn notify_insert(&mut self, index: TIndex) {
+        if self.observer.is_some() {
+            let key = Key::new(index, self.generations[index.into_usize()]);
+            self.notify(|observer| observer.on_insert(key));
+        }
+    }
+
+    /// Notifies the observer about an element removed from a slot of the specified generation.
+    #[inline]
+    fn notify_erase(&mut self, index: TIndex, generation: u32) {
+        self.notify(|observer| observer.on_erase(Key::new(index, generation)));
+    }
+
+    /// Notifies the observer that all elements were removed.
+    fn notify_clear(&mut self) {
+        self.notify(|observer| observer.on_clear());
+    }
+
+    /// Calls the observer, if any.
+    ///
+    /// The observer is taken out of the list for the duration of the call and only put back
+    /// if the call returns, so an observer that panics is dropped instead of staying
+    /// registered with partly updated state.
+    #[inline]
+    fn notify(&mut self, notify: impl FnOnce(&mut dyn Observer<TIndex>)) {
+        if let Some(mut observer) = self.observer.take() {
+            notify(observer.as_mut());
+            self.observer = Some(observer);
+        }
+    }
+
+    /// Gets the link of a free slot to the next free slot.
+    #[inline]
+    pub(crate) fn next_free(&self, n: usize) -> TIndex {
+        if Self::IS_ZST {
+            self.zst.next(n)
+        } else {
+            unsafe { self.data[n].next }
+        }
+    }
+
+    /// Sets the link of a free slot to the next free slot.
+    #[inline]
+    pub(crate) fn set_next_free(&mut self, n: usize, next: TIndex) {
+        if Self::IS_ZST {
+            self.zst.set_next(n, next);
+        } else {
+            self.data[n].next = next;
+        }
+    }
+
+    /// Gets the element of the slot at the specified position, or `None` if the slot is
+    /// not occupied.
+    ///
+    /// Safe accessors convert an index only once and pass the position on, so that the
+    /// check and the access agree even if the index type converts inconsistently.
+    #[inline]
+    fn occupied_element(&self, n: usize) -> Option<&T> {
+        // SAFETY: The slot is occupied.
+        self.is_occupied_slot(n)
+            .then(|| unsafe { &**self.element(n) })
+    }
+
+    /// Gets the element of the slot at the specified position mutably, or `None` if the
+    /// slot is not occupied; see [`occupied_element`](Self::occupied_element).
+    #[inline]
+    fn occupied_element_mut(&mut self, n: usize) -> Option<&mut T> {
+        if !self.is_occupied_slot(n) {
+            return None;
+        }
+        self.ticks.changed([n]);
+        // SAFETY: The slot is occupied.
+        Some(unsafe { &mut **self.element_mut(n) })
+    }
+
+    /// Gets the element of an occupied slot.
+    ///
+    /// # Safety
+    /// The slot must be occupied.
+    #[inline]
+    pub(crate) unsafe fn element(&self, n: usize) -> &ManuallyDrop<T> {
+        if Self::IS_ZST {
+            unsafe { &*std::ptr::NonNull::dangling().as_ptr() }
+        } else {
+            unsafe { &self.data[n].element }
+        }
+    }
+
+    /// Gets the element of an occupied slot mutably.
+    ///
+    /// # Safety
+    /// The slot must be occupied, or the element must be overwritten without being read.
+    #[inline]
+    pub(crate) unsafe fn element_mut(&mut self, n: usize) -> &mut ManuallyDrop<T> {
+        if Self::IS_ZST {
+            unsafe { &mut *std::ptr::NonNull::dangling().as_ptr() }
+        } else {
+            unsafe { &mut self.data[n].element }
+        }
+    }
+
+    /// Appends a slot; zero-sized elements are not stored, so only the slot is counted.
+    pub(crate) fn append_slot(&mut self, slot: FreeElement<T, TIndex>) {
+        if Self::IS_ZST {
+            self.zst.len += 1;
+        } else {
+            self.data.push(slot);
+        }
+    }
+
+    /// Reserves slot storage for at least `additional` more slots.
+    pub(crate) fn reserve_slots(&mut self, additional: usize) {
+        if !Self::IS_ZST {
+            wipe::reserve(&mut self.data, additional);
+        }
+    }
+
+    /// Removes all slots at and above `len`, which must hold no elements.
+    fn truncate_slots(&mut self, len: usize) {
+        if Self::IS_ZST {
+            self.zst.truncate(len);
+        } else {
+            wipe::wipe_slots(&mut self.data[len..]);
+            self.data.truncate(len);
+        }
+    }
+
+    /// Wipes the slot at the specified index, which must hold no element.
+    #[inline]
+    fn wipe_slot(&mut self, n: usize) {
+        if !Self::IS_ZST {
+            wipe::wipe_slots(&mut self.data[n..=n]);
+        }
+    }
+}
+
+impl<T, TIndex, A> Index<TIndex> for FreeList<T, TIndex, A>
+where
+    T: Default,
+    TIndex: IndexType,
+    A: Allocator,
+{
+    type Output = T;
+
+    /// Gets a reference to the value at the specified index.
+    ///
+    /// ## Panics
+    /// Panics if the slot is not occupied.
+    fn index(&self, index: TIndex) -> &Self::Output {
+        self.get(index).unwrap_or_else(|| {
+            panic!("Attempted to access a slot that is not occupied ({index:?})")
+        })
+    }
+}
+
+impl<T, TIndex, A> IndexMut<TIndex> for FreeList<T, TIndex, A>
+where
+    T: Default,
+    TIndex: IndexType,
+    A: Allocator,
+{
+    /// Gets a mutable reference to the value at the specified index.
+    ///
+    /// ## Panics
+    /// Panics if the slot is not occupied.
+    fn index_mut(&mut self, index: TIndex) -> &mut Self::Output {
+        self.get_mut(index).unwrap_or_else(|| {
+            panic!("Attempted to access a slot that is not occupied ({index:?})")
+        })
+    }
+}
+
+impl<'a, T, TIndex, A> IntoIterator for &'a FreeList<T, TIndex, A>
+where
+    T: Default,
+    TIndex: IndexType,
+    A: Allocator,
+{
+    type Item = (TIndex, &'a T);
+    type IntoIter = Iter<'a, T, TIndex>;
+
+    /// Gets an iterator over the indices and values of all occupied slots, in ascending
+    /// index order; see [`FreeList::iter`].
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<u32, u8>::default();
+    /// list.push(1);
+    /// list.push(2);
+    ///
+    /// let mut sum = 0;
+    /// for (_, value) in &list {
+    ///     sum += value;
+    /// }
+    /// assert_eq!(sum, 3);
+    /// ```
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, TIndex, A> IntoIterator for &'a mut FreeList<T, TIndex, A>
+where
+    T: Default,
+    TIndex: IndexType,
+    A: Allocator,
+{
+    type Item = (TIndex, &'a mut T);
+    type IntoIter = IterMut<'a, T, TIndex>;
+
+    /// Gets an iterator over the indices and mutable values of all occupied slots, in
+    /// ascending index order; see [`FreeList::iter_mut`].
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<u32, u8>::default();
+    /// list.push(1);
+    /// list.push(2);
+    ///
+    /// for (index, value) in &mut list {
+    ///     *value += u32::from(index);
+    /// }
+    /// assert_eq!(list[1], 3);
+    /// ```
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T, TIndex, A> std::fmt::Debug for FreeList<T, TIndex, A>
+where
+    T: Default + std::fmt::Debug,
+    TIndex: IndexType,
+    A: Allocator,
+{
+    /// Formats the elements as a map from their index to their value.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<T, TIndex, A> Drop for FreeList<T, TIndex, A>
+where
+    T: Default,
+    TIndex: IndexType,
+    A: Allocator,
+{
+    fn drop(&mut self) {
+        if cfg!(any(debug_assertions, feature = "checked")) && self.report_leaks && self.length > 0
+        {
+            leak::report(&LeakReport {
+                type_name: std::any::type_name::<T>(),
+                indices: self.occupied.ones().collect(),
+            });
+        }
+        // Dropping must not panic because of an exhausted generation.
+        self.overflow = GenerationOverflow::Wrap;
+        self.clear();
+    }
+}
+
+// A panic in the destructor of an element or a user closure leaves the list consistent, so the
+// list is unwind safe if its elements and allocator are. The boxed observer is not required to
+// be unwind safe because it cannot be observed in a broken state: observers are only notified
+// once a change is complete, so a panicking callback merely skips the remaining notifications,
+// and `notify` drops an observer that panicked instead of putting it back. A shared reference
+// to the list never reaches the observer at all.
+impl<T, TIndex, A> UnwindSafe for FreeList<T, TIndex, A>
+where
+    T: Default + UnwindSafe,
+    TIndex: IndexType,
+    A: Allocator + UnwindSafe,
+{
+}
+
+impl<T, TIndex, A> RefUnwindSafe for FreeList<T, TIndex, A>
+where
+    T: Default + RefUnwindSafe,
+    TIndex: IndexType,
+    A: Allocator + RefUnwindSafe,
+{
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Default, Debug, PartialEq, PartialOrd)]
+    struct Complex(f64, f64);
+
+    impl Drop for Complex {
+        fn drop(&mut self) {
+            self.0 = 42.;
+            self.1 = 1337.0;
+        }
+    }
+
+    #[test]
+    fn after_construction_has_no_first_free() {
+        let list = FreeList::<Complex>::default();
+        assert_eq!(list.first_free, FreeList::<Complex>::SENTINEL);
+        assert_eq!(list.slot_count(), 0);
+    }
+
+    #[test]
+    fn after_insertion_has_no_first_free() {
+        let mut list = FreeList::<Complex>::default();
+        assert_eq!(list.push(Complex::default()), 0);
+        assert_eq!(list.first_free, FreeList::<Complex>::SENTINEL);
+        assert_eq!(list.slot_count(), 1);
+    }
+
+    #[test]
+    fn after_deletion_has_a_first_free() {
+        let mut list = FreeList::<Complex>::default();
+        list.push(Complex::default());
+        list.erase(0);
+        assert_eq!(list.first_free, 0);
+        assert_eq!(list.slot_count(), 1);
+    }
+
+    #[test]
+    fn insert_after_delete_has_no_free() {
+        let mut list = FreeList::<Complex>::default();
+        list.push(Complex::default());
+        list.erase(0);
+        list.push(Complex::default());
+        assert_eq!(list.first_free, FreeList::<Complex>::SENTINEL);
+        assert_eq!(list.slot_count(), 1);
+    }
+
+    #[test]
+    fn first_free_points_to_last_freed_index() {
+        let mut list = FreeList::<Complex>::default();
+        insert_some(&mut list, 2);
+        list.erase(0);
+        list.erase(1);
+        assert_eq!(list.first_free, 1);
+        assert_eq!(list.slot_count(), 2);
+    }
+
+    #[test]
+    fn erase_in_ascending_order() {
+        let mut list = FreeList::<Complex>::default();
+        insert_some(&mut list, 4);
+        list.erase(0);
+        list.erase(1);
+        list.erase(2);
+        list.erase(3);
+        assert_eq!(list.first_free, 3);
+        assert_eq!(list.slot_count(), 4);
+    }
+
+    #[test]
+    fn erase_in_descending_order() {
+        let mut list = FreeList::<Complex>::default();
+        insert_some(&mut list, 4);
+        list.erase(3);
+        list.erase(2);
+        list.erase(1);
+        list.erase(0);
+        assert_eq!(list.first_free, 0);
+        assert_eq!(list.slot_count(), 4);
+    }
+
+    #[test]
+    fn erase_in_mixed_order() {
+        let mut list = FreeList::<Complex>::default();
+        insert_some(&mut list, 4);
+        list.erase(0);
+        list.erase(3);
+        list.erase(1);
+        list.erase(2);
+        assert_eq!(list.first_free, 2);
+        assert_eq!(list.slot_count(), 4);
+    }
+
+    #[test]
+    fn clear_works() {
+        let mut list = FreeList::<Complex>::default();
+        insert_some(&mut list, 4);
+        list.erase(1);
+        list.clear();
+        list.clear();
+        assert_eq!(list.first_free, FreeList::<Complex>::SENTINEL);
+        assert_eq!(list.slot_count(), 0);
+    }
+
+    #[test]
+    fn is_in_free_list_works() {
+        let mut list = FreeList::<Complex>::default();
+        insert_some(&mut list, 2);
+        list.erase(0);
+        assert!(list.debug_is_in_free_list(0));
+        assert!(!list.debug_is_in_free_list(1));
+    }
+
+    #[test]
+    fn at_works() {
+        let mut list = FreeList::<Complex>::default();
+        list.push(Complex(1., 2.));
+        list.push(Complex::default());
+        let element = unsafe { list.at(0) };
+        assert_eq!(*element, Complex(1., 2.));
+    }
+
+    #[test]
+    fn at_mut_works() {
+        let mut list = FreeList::<Complex>::default();
+        list.push(Complex(1., 2.));
+        list.push(Complex::default());
+
+        // Mutably access the element and exchange it.
+        let element = unsafe { list.at_mut(0) };
+        *element = Complex::default();
+
+        // Get a new reference and verify.
+        let element = unsafe { list.at(0) };
+        assert_eq!(*element, Complex(0., 0.));
+    }
+
+    #[test]
+    fn keys_skips_free_slots() {
+        let mut list = FreeList::<Complex>::default();
+        insert_some(&mut list, 4);
+        list.erase(0);
+        list.erase(2);
+        assert_eq!(list.keys().collect::<Vec<_>>(), vec![1, 3]);
+
+        list.push(Complex::default());
+        assert_eq!(list.keys().collect::<Vec<_>>(), vec![1, 2, 3]);
+
+        list.clear();
+        assert_eq!(list.keys().count(), 0);
+    }
+
+    #[test]
+    fn vacant_entry_reuses_first_free() {
+        let mut list = FreeList::<Complex>::default();
+        insert_some(&mut list, 3);
+        list.erase(1);
+
+        let entry = list.vacant_entry();
+        assert_eq!(entry.key(), 1);
+        entry.insert(Complex(1., 2.));
+        assert_eq!(list.first_free, FreeList::<Complex>::SENTINEL);
+        assert_eq!(unsafe { list.at(1) }, &Complex(1., 2.));
+        assert_eq!(list.vacant_entry().key(), 3);
+    }
+
+    #[test]
+    fn status_works() {
+        let mut list = FreeList::<Complex>::default();
+        assert_eq!(list.status(0), SlotStatus::OutOfRange);
+        assert_eq!(
+            list.status(FreeList::<Complex>::SENTINEL),
+            SlotStatus::OutOfRange
+        );
+
+        insert_some(&mut list, 2);
+        list.erase(1);
+        assert_eq!(list.status(0), SlotStatus::Occupied);
+        assert_eq!(list.status(1), SlotStatus::Free);
+        assert_eq!(list.status(2), SlotStatus::OutOfRange);
+
+        list.clear();
+        assert_eq!(list.status(0), SlotStatus::OutOfRange);
+    }
+
+    #[test]
+    fn get_disjoint_mut_works() {
+        let mut list = FreeList::<Complex>::default();
+        insert_some(&mut list, 3);
+        list.erase(1);
+
+        let [a, b] = list.get_disjoint_mut([2, 0]).unwrap();
+        a.0 = 2.;
+        b.0 = 1.;
+        assert_eq!(unsafe { list.at(0) }, &Complex(1., 0.));
+        assert_eq!(unsafe { list.at(2) }, &Complex(2., 0.));
+
+        assert_eq!(
+            list.get_disjoint_mut([0, 1]).err(),
+            Some(GetDisjointMutError::NotOccupied)
+        );
+        assert_eq!(
+            list.get_disjoint_mut([0, 3]).err(),
+            Some(GetDisjointMutError::NotOccupied)
+        );
+        assert_eq!(
+            list.get_disjoint_mut([2, 0, 2]).err(),
+            Some(GetDisjointMutError::OverlappingIndices)
+        );
+    }
+
+    #[test]
+    fn get_disjoint_mut_slice_works() {
+        let mut list = FreeList::<Complex>::default();
+        insert_some(&mut list, 3);
+
+        let values = list.get_disjoint_mut_slice(&[1, 2]).unwrap();
+        assert_eq!(values.len(), 2);
+        assert!(list.get_disjoint_mut_slice(&[]).unwrap().is_empty());
+        assert_eq!(
+            list.get_disjoint_mut_slice(&[1, 0, 1]).err(),
+            Some(GetDisjointMutError::OverlappingIndices)
+        );
+        list.erase(0);
+        assert_eq!(
+            list.get_disjoint_mut_slice(&[1, 0]).err(),
+            Some(GetDisjointMutError::NotOccupied)
+        );
+    }
+
+    #[test]
+    fn swap_works() {
+        let mut list = FreeList::<Complex>::default();
+        list.push(Complex(1., 2.));
+        list.push(Complex(3., 4.));
+
+        list.swap(0, 1);
+        assert_eq!(unsafe { list.at(0) }, &Complex(3., 4.));
+        assert_eq!(unsafe { list.at(1) }, &Complex(1., 2.));
+
+        list.swap(1, 1);
+        assert_eq!(unsafe { list.at(1) }, &Complex(1., 2.));
+    }
+
+    #[test]
+    #[should_panic]
+    fn swap_with_free_slot_panics() {
+        let mut list = FreeList::<Complex>::default();
+        insert_some(&mut list, 2);
+        list.erase(1);
+        list.swap(0, 1);
+    }
+
+    #[test]
+    fn fifo_reuses_oldest_free_slot() {
+        let mut list = FreeList::<Complex>::with_reuse_policy(ReusePolicy::Fifo);
+        insert_some(&mut list, 4);
+        list.erase(2);
+        list.erase(0);
+        list.erase(3);
+        assert_eq!(list.push(Complex::default()), 2);
+        list.erase(1);
+        assert_eq!(list.push(Complex::default()), 0);
+        assert_eq!(list.push(Complex::default()), 3);
+        assert_eq!(list.push(Complex::default()), 1);
+        assert_eq!(list.first_free, FreeList::<Complex>::SENTINEL);
+        assert_eq!(list.last_free, FreeList::<Complex>::SENTINEL);
+    }
+
+    #[test]
+    fn lowest_index_first_reuses_lowest_free_slot() {
+        let mut list = FreeList::<Complex>::with_reuse_policy(ReusePolicy::LowestIndexFirst);
+        insert_some(&mut list, 200);
+        for index in [150, 3, 70, 199, 64] {
+            list.erase(index);
+        }
+        assert_eq!(list.push(Complex::default()), 3);
+        list.erase(0);
+        for expected in [0, 64, 70, 150, 199, 200] {
+            assert_eq!(list.push(Complex::default()), expected);
+        }
+    }
+
+    #[test]
+    fn sort_free_chain_orders_free_slots() {
+        let mut list = FreeList::<Complex>::default();
+        insert_some(&mut list, 10);
+        for index in [7, 2, 9, 4] {
+            list.erase(index);
+        }
+        list.sort_free_chain();
+        assert_eq!(list.first_free, 2);
+        assert_eq!(list.last_free, 9);
+        assert!(list.debug_is_in_free_list(4));
+        assert!(list.debug_is_in_free_list(7));
+
+        for expected in [2, 4, 7, 9, 10] {
+            assert_eq!(list.push(Complex::default()), expected);
+        }
+    }
+
+    #[test]
+    fn sort_free_chain_on_empty_chain_does_nothing() {
+        let mut list = FreeList::<Complex>::default();
+        list.sort_free_chain();
+        insert_some(&mut list, 2);
+        list.sort_free_chain();
+        assert_eq!(list.first_free, FreeList::<Complex>::SENTINEL);
+        assert_eq!(list.push(Complex::default()), 2);
+    }
+
+    #[test]
+    fn quarantine_delays_reuse() {
+        let mut list = FreeList::<Complex>::default();
+        list.set_quarantine_len(2);
+        insert_some(&mut list, 4);
+        list.erase(0);
+        list.erase(1);
+        assert_eq!(list.first_free, FreeList::<Complex>::SENTINEL);
+        assert_eq!(list.status(0), SlotStatus::Free);
+        assert_eq!(list.push(Complex::default()), 4);
+
+        list.erase(2);
+        assert_eq!(list.first_free, 0);
+        assert_eq!(list.push(Complex::default()), 0);
+
+        list.set_quarantine_len(0);
+        assert_eq!(list.push(Complex::default()), 2);
+        assert_eq!(list.push(Complex::default()), 1);
+    }
+
+    #[test]
+    fn quarantine_with_lowest_index_first_skips_quarantined_slots() {
+        let mut list = FreeList::<Complex>::with_reuse_policy(ReusePolicy::LowestIndexFirst);
+        list.set_quarantine_len(1);
+        insert_some(&mut list, 5);
+        list.erase(1);
+        list.erase(4);
+        list.erase(2);
+        for expected in [1, 4, 5] {
+            assert_eq!(list.push(Complex::default()), expected);
+        }
+
+        list.set_quarantine_len(0);
+        assert_eq!(list.push(Complex::default()), 2);
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg_attr(not(any(debug_assertions, feature = "checked")), ignore)]
+    fn erasing_quarantined_slot_panics() {
+        let mut list = FreeList::<Complex>::default();
+        list.set_quarantine_len(1);
+        insert_some(&mut list, 1);
+        list.erase(0);
+        list.erase(0);
+    }
+
+    #[test]
+    fn stats_work() {
+        let mut list = FreeList::<Complex>::default();
+        assert_eq!(list.stats(), FreeListStats::default());
+        assert_eq!(list.stats().fragmentation(), 0.0);
+
+        insert_some(&mut list, 10);
+        list.erase(9);
+        list.erase(2);
+        list.erase(5);
+        let stats = list.stats();
+        assert_eq!(stats.live, 7);
+        assert_eq!(stats.free, 3);
+        assert_eq!(stats.total, 10);
+        assert_eq!(stats.span, 9);
+    }
+
+    #[test]
+    fn high_water_marks_are_tracked() {
+        let mut list = FreeList::<Complex>::default();
+        insert_some(&mut list, 5);
+        list.erase(0);
+        list.erase(1);
+        insert_some(&mut list, 1);
+        let stats = list.stats();
+        assert_eq!((stats.peak_live, stats.peak_total), (5, 5));
+
+        list.clear();
+        insert_some(&mut list, 2);
+        assert_eq!(list.stats().peak_live, 5);
+
+        list.reset_high_water_marks();
+        let stats = list.stats();
+        assert_eq!((stats.peak_live, stats.peak_total), (2, 2));
+        insert_some(&mut list, 1);
+        assert_eq!(list.stats().peak_total, 3);
+    }
+
+    #[test]
+    fn check_invariants_accepts_valid_lists() {
+        for policy in [
+            ReusePolicy::Lifo,
+            ReusePolicy::Fifo,
+            ReusePolicy::LowestIndexFirst,
+        ] {
+            let mut list = FreeList::<Complex>::with_reuse_policy(policy);
+            list.set_quarantine_len(2);
+            assert_eq!(list.check_invariants(), Ok(()));
+
+            insert_some(&mut list, 10);
+            for index in [4, 8, 1, 0, 6] {
+                list.erase(index);
+                assert_eq!(list.check_invariants(), Ok(()));
+            }
+            insert_some(&mut list, 2);
+            assert_eq!(list.check_invariants(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn check_invariants_detects_corruption() {
+        let mut list = FreeList::<Complex>::default();
+        insert_some(&mut list, 4);
+        list.erase(1);
+        list.erase(2);
+
+        // A cycle in the free chain.
+        list.data[1].next = 2;
+        assert_eq!(
+            list.check_invariants(),
+            Err(InvariantError::DuplicateFreeSlot { index: 2 })
+        );
+
+        // A link to an occupied slot.
+        list.data[1].next = 3;
+        assert_eq!(
+            list.check_invariants(),
+            Err(InvariantError::OccupiedSlotLinked { index: 3 })
+        );
+
+        // A link beyond the end of the list.
+        list.data[1].next = 10;
+        assert_eq!(
+            list.check_invariants(),
+            Err(InvariantError::LinkOutOfRange { index: 10 })
+        );
+
+        // A free slot that was dropped from the chain.
+        list.data[2].next = FreeList::<Complex>::SENTINEL;
+        list.last_free = 2;
+        assert_eq!(
+            list.check_invariants(),
+            Err(InvariantError::UnlinkedFreeSlot { index: 1 })
+        );
+
+        list.length += 1;
+        assert_eq!(
+            list.check_invariants(),
+            Err(InvariantError::LengthMismatch {
+                tracked: 3,
+                counted: 2
+            })
+        );
+        list.length -= 1;
+    }
+
+    #[test]
+    fn memory_usage_accounts_for_slots_and_metadata() {
+        let mut list = FreeList::<Complex>::default();
+        assert_eq!(list.memory_usage(), 0);
+
+        insert_some(&mut list, 100);
+        let slots = list.data.capacity() * std::mem::size_of::<Complex>();
+        assert!(slots >= 100 * std::mem::size_of::<Complex>());
+        assert!(list.occupied.memory_usage() >= 2 * std::mem::size_of::<u64>());
+        let generations = list.generations.capacity() * std::mem::size_of::<u32>();
+        assert_eq!(
+            list.memory_usage(),
+            slots + list.occupied.memory_usage() + generations
+        );
+
+        // Clearing keeps the slot storage and generations allocated for reuse.
+        list.clear();
+        assert_eq!(list.memory_usage(), slots + generations);
+    }
+
+    #[test]
+    fn slab_api_works() {
+        let mut list = FreeList::<Complex>::default();
+        let a = list.insert(Complex(1., 2.));
+        let b = list.insert(Complex(3., 4.));
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[b], Complex(3., 4.));
+
+        list[a].0 = 5.;
+        assert_eq!(list.get(a), Some(&Complex(5., 2.)));
+        assert_eq!(list.remove(a), Complex(5., 2.));
+        assert_eq!(list.try_remove(a), None);
+        assert!(!list.contains(a));
+        assert!(list.get_mut(a).is_none());
+        assert_eq!(list.len(), 1);
+
+        assert_eq!(list.insert(Complex::default()), a);
+        assert!(!list.is_empty());
+    }
+
+    #[test]
+    fn iter_skips_free_slots() {
+        let mut list = FreeList::<Complex>::default();
+        for i in 0..5 {
+            list.push(Complex(i as f64, 0.));
+        }
+        list.erase(0);
+        list.erase(3);
+
+        for (index, value) in list.iter_mut() {
+            value.1 = index as f64;
+        }
+        assert_eq!(
+            list.iter().collect::<Vec<_>>(),
+            [
+                (1, &Complex(1., 1.)),
+                (2, &Complex(2., 2.)),
+                (4, &Complex(4., 4.))
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn remove_vacant_panics() {
+        let mut list = FreeList::<Complex>::default();
+        insert_some(&mut list, 1);
+        list.remove(0);
+        list.remove(0);
+    }
+
+    #[test]
+    fn split_off_moves_upper_elements() {
+        let mut list = FreeList::<Complex>::default();
+        for i in 0..6 {
+            list.push(Complex(i as f64, 0.));
+        }
+        list.erase(1);
+        list.erase(4);
+        let moved = list.key(5).unwrap();
+
+        let mut other = list.split_off(3);
+        assert_eq!(list.check_invariants(), Ok(()));
+        assert_eq!(other.check_invariants(), Ok(()));
+        assert_eq!(list.keys().collect::<Vec<_>>(), [0, 2]);
+        assert_eq!(other.keys().collect::<Vec<_>>(), [3, 5]);
+        assert_eq!((list.len(), other.len()), (2, 2));
+        assert_eq!(list.slot_count(), 3);
+        assert_eq!(other.slot_count(), 6);
+        assert!(other.contains_key(moved));
+        assert_eq!(other[5], Complex(5., 0.));
+
+        assert_eq!(list.push(Complex::default()), 1);
+        assert_eq!(list.push(Complex::default()), 3);
+        for expected in [0, 1, 2, 4, 6] {
+            assert_eq!(other.push(Complex::default()), expected);
+        }
+    }
+
+    #[test]
+    fn split_off_beyond_end_yields_empty_list() {
+        let mut list = FreeList::<Complex>::default();
+        insert_some(&mut list, 2);
+        let other = list.split_off(10);
+        assert_eq!(list.len(), 2);
+        assert!(other.is_empty());
+        assert_eq!(other.slot_count(), 2);
+    }
+
+    #[test]
+    fn push_with_passes_the_reused_index() {
+        let mut list = FreeList::<(u8, Complex), u8>::default();
+        for _ in 0..3 {
+            list.push_with(|index| (index, Complex(0.0, 0.0)));
+        }
+        list.erase(1);
+
+        assert_eq!(list.push_with(|index| (index, Complex(1.0, 1.0))), 1);
+        assert_eq!(list[1].0, 1);
+        assert_eq!(list.push_with(|index| (index, Complex(2.0, 2.0))), 3);
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            list.push_with(|_| panic!("constructor failed"));
+        }));
+        assert!(result.is_err());
+        assert_eq!(list.len(), 4);
+        assert_eq!(list.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn clear_drops_every_element_once_if_a_destructor_panics() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        #[derive(Default)]
+        struct Tracked(Option<Rc<Cell<usize>>>, bool);
+
+        impl Drop for Tracked {
+            fn drop(&mut self) {
+                if let Some(drops) = &self.0 {
+                    drops.set(drops.get() + 1);
+                }
+                if self.1 {
+                    panic!("destructor failed");
+                }
+            }
+        }
+
+        let drops = Rc::new(Cell::new(0));
+        let mut list = FreeList::<Tracked, u8>::default();
+        for panics in [false, true, false, false] {
+            list.push(Tracked(Some(drops.clone()), panics));
+        }
+        list.erase(3);
+        assert_eq!(drops.get(), 1);
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| list.clear()));
+        assert!(result.is_err());
+        assert_eq!(drops.get(), 4);
+        assert!(list.is_empty());
+        assert_eq!(list.slot_count(), 0);
+        assert_eq!(list.check_invariants(), Ok(()));
+
+        list.push(Tracked(Some(drops.clone()), false));
+        drop(list);
+        assert_eq!(drops.get(), 5);
+    }
+
+    #[test]
+    fn elements_are_dropped_in_the_configured_order() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        #[derive(Default)]
+        struct Recorded(Option<(u8, Rc<RefCell<Vec<u8>>>)>);
+
+        impl Drop for Recorded {
+            fn drop(&mut self) {
+                if let Some((value, drops)) = &self.0 {
+                    drops.borrow_mut().push(*value);
+                }
+            }
+        }
+
+        let drops = Rc::new(RefCell::new(Vec::new()));
+        let fill = |list: &mut FreeList<Recorded, u8>| {
+            for value in 0..5 {
+                list.push(Recorded(Some((value, drops.clone()))));
+            }
+            list.remove(2);
+            drops.borrow_mut().clear();
+        };
+
+        let mut list = FreeList::default();
+        fill(&mut list);
+        list.clear();
+        assert_eq!(drops.take(), [0, 1, 3, 4]);
+
+        list.set_drop_order(DropOrder::Descending);
+        fill(&mut list);
+        list.clear();
+        assert_eq!(drops.take(), [4, 3, 1, 0]);
+
+        fill(&mut list);
+        list.reset();
+        assert_eq!(drops.take(), [4, 3, 1, 0]);
+
+        fill(&mut list);
+        list.truncate(3);
+        assert_eq!(drops.take(), [4, 3]);
+        drop(list);
+        assert_eq!(drops.take(), [1, 0]);
+    }
+
+    #[test]
+    fn panicking_observers_leave_the_list_consistent() {
+        struct Failing;
+
+        impl Observer<u8> for Failing {
+            fn on_insert(&mut self, _key: Key<u8>) {
+                panic!("observer failed");
+            }
+        }
+
+        let mut list = FreeList::<Complex, u8>::default();
+        insert_some_in(&mut list, 3);
+        list.erase(1);
+        list.set_observer(Failing);
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            list.push_n_contiguous([Complex(1.0, 0.0), Complex(2.0, 0.0)])
+        }));
+        assert!(result.is_err());
+        assert_eq!(list.len(), 4);
+        assert_eq!(list.check_invariants(), Ok(()));
+        assert!(list.take_observer().is_none());
+
+        list.set_observer(Failing);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            list.push(Complex(3.0, 0.0))
+        }));
+        assert!(result.is_err());
+        assert_eq!(list[1], Complex(3.0, 0.0));
+        assert_eq!(list.check_invariants(), Ok(()));
+        assert!(list.take_observer().is_none());
+
+        list.push(Complex(4.0, 0.0));
+        assert_eq!(list.len(), 6);
+    }
+
+    #[test]
+    fn lists_are_unwind_safe_if_their_elements_are() {
+        fn assert_unwind_safe<T: UnwindSafe + RefUnwindSafe>() {}
+        assert_unwind_safe::<FreeList<String>>();
+        assert_unwind_safe::<FreeList<Complex, u8>>();
+    }
+
+    #[test]
+    fn extend_and_collect_fills_free_slots_first() {
+        let mut list = FreeList::<Complex, u8>::default();
+        insert_some_in(&mut list, 5);
+        list.erase(3);
+        list.erase(1);
+
+        let indices = list.extend_and_collect((0..5).map(|i| Complex(i as f64, 0.0)));
+        assert_eq!(indices[..2], [1, 3]);
+        assert_eq!(indices.len(), 5);
+        for (i, index) in indices.into_iter().enumerate() {
+            assert_eq!(list[index], Complex(i as f64, 0.0));
+        }
+        assert_eq!(list.check_invariants(), Ok(()));
+    }
 
-impl<T, TIndex> Drop for FreeList<T, TIndex>
-where
-    T: Default,
-    TIndex: IndexType,
-{
-    fn drop(&mut self) {
-        self.clear();
+    #[test]
+    fn erase_many_respects_the_reuse_policy() {
+        for policy in [
+            ReusePolicy::Lifo,
+            ReusePolicy::Fifo,
+            ReusePolicy::LowestIndexFirst,
+        ] {
+            let mut list = FreeList::<Complex, u8>::with_reuse_policy(policy);
+            insert_some_in(&mut list, 10);
+            list.erase(4);
+            list.erase(8);
+
+            assert_eq!(list.erase_many(&[7, 1, 8, 1, 5, 200]), 3);
+            assert_eq!(list.len(), 5);
+            assert_eq!(list.check_invariants(), Ok(()));
+
+            let reused: Vec<_> = (0..5).map(|_| list.push(Complex::default())).collect();
+            let expected = match policy {
+                ReusePolicy::Lifo => [1, 5, 7, 8, 4],
+                ReusePolicy::Fifo => [4, 8, 1, 5, 7],
+                ReusePolicy::LowestIndexFirst => [1, 4, 5, 7, 8],
+            };
+            assert_eq!(reused, expected, "{policy:?}");
+        }
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
+    #[test]
+    fn erase_many_quarantines_slots() {
+        let mut list = FreeList::<Complex, u8>::default();
+        list.set_quarantine_len(2);
+        insert_some_in(&mut list, 4);
+        assert_eq!(list.erase_many(&[2, 0, 1]), 3);
+        assert_eq!(list.check_invariants(), Ok(()));
+        assert_eq!(list.push(Complex::default()), 0);
+        assert_eq!(list.push(Complex::default()), 4);
+    }
 
-    #[derive(Default, Debug, PartialEq, PartialOrd)]
-    struct Complex(f64, f64);
+    #[test]
+    fn reset_keeps_the_slots() {
+        let mut list = FreeList::<Complex, u8>::default();
+        list.set_quarantine_len(1);
+        insert_some_in(&mut list, 5);
+        list.erase(2);
+        let key = list.key(3).unwrap();
+        let usage = list.memory_usage();
 
-    impl Drop for Complex {
-        fn drop(&mut self) {
-            self.0 = 42.;
-            self.1 = 1337.0;
+        list.reset();
+        assert!(list.is_empty());
+        assert!(!list.contains_key(key));
+        assert_eq!(list.memory_usage(), usage);
+        assert_eq!(list.check_invariants(), Ok(()));
+        for index in 0..5 {
+            assert_eq!(list.push(Complex::default()), index);
         }
+        assert_eq!(list.push(Complex::default()), 5);
     }
 
     #[test]
-    fn after_construction_has_no_first_free() {
-        let list = FreeList::<Complex>::default();
-        assert_eq!(list.first_free, FreeList::<Complex>::SENTINEL);
-        assert_eq!(list.capacity(), 0);
+    fn shrink_to_keeps_the_free_chain_order() {
+        let mut list = FreeList::<Complex, u8>::default();
+        list.set_quarantine_len(1);
+        insert_some_in(&mut list, 8);
+        for index in [1, 6, 3, 7, 5] {
+            list.erase(index);
+        }
+
+        list.shrink_to(0);
+        assert_eq!(list.slot_count(), 5);
+        assert_eq!(list.check_invariants(), Ok(()));
+        assert_eq!(list.push(Complex::default()), 3);
+        assert_eq!(list.push(Complex::default()), 1);
+        assert_eq!(list.push(Complex::default()), 5);
+
+        list.shrink_to(100);
+        assert_eq!(list.slot_count(), 6);
     }
 
     #[test]
-    fn after_insertion_has_no_first_free() {
-        let mut list = FreeList::<Complex>::default();
-        assert_eq!(list.push(Complex::default()), 0);
-        assert_eq!(list.first_free, FreeList::<Complex>::SENTINEL);
-        assert_eq!(list.capacity(), 1);
+    fn truncate_removes_occupied_slots_and_keeps_the_free_chain_order() {
+        let mut list = FreeList::<String, u8>::default();
+        list.set_quarantine_len(1);
+        for value in 0..8 {
+            list.push(value.to_string());
+        }
+        let stale = list.key(4).unwrap();
+        for index in [1, 6, 3, 7, 5] {
+            list.erase(index);
+        }
+
+        list.truncate(4);
+        assert_eq!(list.slot_count(), 4);
+        assert_eq!(list.len(), 2);
+        assert_eq!(list.check_invariants(), Ok(()));
+        assert_eq!(list.push("a".into()), 3);
+        assert_eq!(list.push("b".into()), 1);
+        assert_eq!(list.push("c".into()), 4);
+        assert!(!list.contains_key(stale));
+
+        list.truncate(100);
+        assert_eq!(list.slot_count(), 5);
+
+        let mut zst = FreeList::<(), u8>::default();
+        for _ in 0..3 {
+            zst.push(());
+        }
+        zst.erase(0);
+        zst.truncate(1);
+        assert_eq!(zst.len(), 0);
+        assert_eq!(zst.slot_count(), 1);
+        assert_eq!(zst.check_invariants(), Ok(()));
+        assert_eq!(zst.push(()), 0);
     }
 
     #[test]
-    fn after_deletion_has_a_first_free() {
-        let mut list = FreeList::<Complex>::default();
-        list.push(Complex::default());
-        list.erase(0);
-        assert_eq!(list.first_free, 0);
-        assert_eq!(list.capacity(), 1);
+    fn iterators_are_double_ended() {
+        let mut list = FreeList::<u32, u8>::default();
+        for value in 0..200 {
+            list.push(value);
+        }
+        for index in (0..200).step_by(3) {
+            list.erase(index);
+        }
+
+        let mut iter = list.iter_mut();
+        assert_eq!(iter.len(), 133);
+        let (first, _) = iter.next().unwrap();
+        let (last, _) = iter.next_back().unwrap();
+        assert_eq!((first, last), (1, 199));
+        assert_eq!(iter.len(), 131);
+        for (_, value) in iter.rev() {
+            *value += 1000;
+        }
+
+        let mut expected: Vec<_> = list.keys().collect();
+        expected.reverse();
+        assert_eq!(
+            list.iter()
+                .rev()
+                .map(|(index, _)| index)
+                .collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(list[1], 1);
+        assert_eq!(list[2], 1002);
+        assert_eq!(list[199], 199);
     }
 
     #[test]
-    fn insert_after_delete_has_no_free() {
-        let mut list = FreeList::<Complex>::default();
+    fn references_can_be_iterated() {
+        let mut list = FreeList::<Complex, u8>::default();
+        insert_some_in(&mut list, 4);
+        list.erase(2);
+
+        for (index, value) in &mut list {
+            value.0 = index.into();
+        }
+        let mut indices = Vec::new();
+        for (index, value) in &list {
+            assert_eq!(value.0, f64::from(index));
+            indices.push(index);
+        }
+        assert_eq!(indices, [0, 1, 3]);
+    }
+
+    #[test]
+    fn elements_without_drop_glue_are_released() {
+        let mut list = FreeList::<u32, u8>::default();
+        list.set_quarantine_len(2);
+        for value in 0..10 {
+            list.push(value);
+        }
+        let key = list.key(3).unwrap();
+
+        list.erase(3);
+        assert_eq!(list.erase_many(&[1, 5, 7]), 3);
+        assert_eq!(list.len(), 6);
+        assert!(!list.contains_key(key));
+        assert_eq!(list.check_invariants(), Ok(()));
+
+        list.set_quarantine_len(0);
+        assert_eq!(list.erase_many(&[0, 2]), 2);
+        assert_eq!(list.check_invariants(), Ok(()));
+
+        list.clear();
+        assert!(list.is_empty());
+        assert_eq!(list.check_invariants(), Ok(()));
+        list.clear();
+        assert_eq!(list.push(42), 0);
+    }
+
+    #[test]
+    fn zero_sized_elements_allocate_no_slots() {
+        static DROPPED: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+        #[derive(Default)]
+        struct Marker;
+
+        impl Drop for Marker {
+            fn drop(&mut self) {
+                DROPPED.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            }
+        }
+
+        for policy in [
+            ReusePolicy::Lifo,
+            ReusePolicy::Fifo,
+            ReusePolicy::LowestIndexFirst,
+        ] {
+            DROPPED.store(0, std::sync::atomic::Ordering::Relaxed);
+            let mut list = FreeList::<Marker, u16>::with_reuse_policy(policy);
+            for _ in 0..100 {
+                list.push(Marker);
+            }
+            assert_eq!(list.slot_count(), 100);
+            assert_eq!(list.data.capacity(), 0);
+
+            list.erase(10);
+            list.erase(5);
+            assert_eq!(list.erase_many(&[20, 30, 30]), 2);
+            assert_eq!(DROPPED.load(std::sync::atomic::Ordering::Relaxed), 4);
+            assert_eq!(list.check_invariants(), Ok(()));
+
+            let reused = [list.push(Marker), list.push(Marker)];
+            let expected = match policy {
+                ReusePolicy::Lifo => [20, 30],
+                ReusePolicy::Fifo => [10, 5],
+                ReusePolicy::LowestIndexFirst => [5, 10],
+            };
+            assert_eq!(reused, expected, "{policy:?}");
+            assert_eq!(list.iter().count(), 98);
+            assert_eq!(list.check_invariants(), Ok(()));
+
+            list.reserve(10);
+            let other = list.split_off(50);
+            assert_eq!(other.len(), 50);
+            list.shrink_to(0);
+            assert_eq!(list.slot_count(), 50);
+            assert_eq!(list.check_invariants(), Ok(()));
+            assert_eq!(other.check_invariants(), Ok(()));
+            drop(other);
+
+            let mut list = unsafe { FreeList::from_raw_parts(list.into_raw_parts()) };
+            assert_eq!(list.data.capacity(), 0);
+            assert_eq!(list.check_invariants(), Ok(()));
+            list.reset();
+            assert_eq!(list.push(Marker), 0);
+            list.clear();
+            assert_eq!(DROPPED.load(std::sync::atomic::Ordering::Relaxed), 103);
+        }
+    }
+
+    #[test]
+    fn try_erase_reports_invalid_indices() {
+        let mut list = FreeList::<Complex, u8>::default();
+        insert_some_in(&mut list, 3);
+        list.set_quarantine_len(1);
+
+        assert_eq!(list.try_erase(1), Ok(()));
+        assert_eq!(list.try_erase(1), Err(EraseError::NotOccupied { index: 1 }));
+        assert_eq!(list.try_erase(3), Err(EraseError::OutOfRange { index: 3 }));
+        assert_eq!(list.len(), 2);
+        assert_eq!(list.check_invariants(), Ok(()));
+
+        assert_eq!(
+            EraseError::NotOccupied { index: 1 }.to_string(),
+            "slot 1 does not hold an element"
+        );
+    }
+
+    #[test]
+    fn pop_removes_from_the_back() {
+        let mut list = FreeList::<u32, u8>::default();
+        for value in 0..4 {
+            list.push(value);
+        }
+        list.erase(1);
+
+        assert_eq!(list.pop(), Some((3, 3)));
+        assert_eq!(list.push(4), 3);
+        assert_eq!(list.pop(), Some((3, 4)));
+        assert_eq!(list.pop(), Some((2, 2)));
+        assert_eq!(list.pop(), Some((0, 0)));
+        assert_eq!(list.pop(), None);
+        assert_eq!(list.slot_count(), 4);
+        assert_eq!(list.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn observer_is_notified_about_changes() {
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Debug, PartialEq)]
+        enum Event {
+            Insert(Key<u8>),
+            Erase(Key<u8>),
+            Clear,
+        }
+
+        struct Recorder(Arc<Mutex<Vec<Event>>>);
+
+        impl Observer<u8> for Recorder {
+            fn on_insert(&mut self, key: Key<u8>) {
+                self.0.lock().unwrap().push(Event::Insert(key));
+            }
+
+            fn on_erase(&mut self, key: Key<u8>) {
+                self.0.lock().unwrap().push(Event::Erase(key));
+            }
+
+            fn on_clear(&mut self) {
+                self.0.lock().unwrap().push(Event::Clear);
+            }
+        }
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let mut list = FreeList::<Complex, u8>::default();
+        list.set_observer(Recorder(events.clone()));
+        insert_some_in(&mut list, 4);
+        list.erase(1);
+        list.erase_many(&[0, 2, 5]);
         list.push(Complex::default());
-        list.erase(0);
+        list.reset();
+        list.reset();
         list.push(Complex::default());
-        assert_eq!(list.first_free, FreeList::<Complex>::SENTINEL);
-        assert_eq!(list.capacity(), 1);
+        drop(list);
+
+        use Event::*;
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                Insert(Key::new(0, 0)),
+                Insert(Key::new(1, 0)),
+                Insert(Key::new(2, 0)),
+                Insert(Key::new(3, 0)),
+                Erase(Key::new(1, 0)),
+                Erase(Key::new(0, 0)),
+                Erase(Key::new(2, 0)),
+                Insert(Key::new(0, 1)),
+                Clear,
+                Insert(Key::new(0, 2)),
+                Clear,
+            ]
+        );
     }
 
     #[test]
-    fn first_free_points_to_last_freed_index() {
-        let mut list = FreeList::<Complex>::default();
-        insert_some(&mut list, 2);
+    fn generation_overflow_policies() {
+        let mut list = FreeList::<u32, u8>::default();
+        list.push(0);
+        list.generations[0] = u32::MAX;
+        list.erase(0);
+        let index = list.push(1);
+        assert_eq!(list.key(index), Some(Key::new(0, 0)));
+        assert_eq!(list.generation_wraps(), 1);
+
+        list.set_generation_overflow(GenerationOverflow::Saturate);
+        list.generations[0] = u32::MAX;
+        list.erase(0);
+        let index = list.push(2);
+        assert_eq!(list.key(index), Some(Key::new(0, u32::MAX)));
+
+        list.set_generation_overflow(GenerationOverflow::Retire);
+        list.push(3);
+        list.generations[0] = u32::MAX - 1;
         list.erase(0);
+        assert_eq!(list.push(4), 2);
+        assert!(!list.contains(0));
         list.erase(1);
-        assert_eq!(list.first_free, 1);
-        assert_eq!(list.capacity(), 2);
+        list.erase(2);
+        list.reset();
+        assert_eq!(list.push(5), 1);
+        list.clear();
+        assert_eq!(list.push(6), 1);
+        assert_eq!(list.check_invariants(), Ok(()));
+
+        let parts = list.into_raw_parts();
+        let mut list = unsafe { FreeList::from_raw_parts(parts) };
+        assert_eq!(list.push(7), 2);
+        assert_eq!(list.try_erase(0), Err(EraseError::NotOccupied { index: 0 }));
     }
 
     #[test]
-    fn erase_in_ascending_order() {
-        let mut list = FreeList::<Complex>::default();
-        insert_some(&mut list, 4);
-        list.erase(0);
+    fn disjoint_access_uses_the_converted_indices() {
+        /// An index type whose conversion maps different indices to the same slot.
+        #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+        struct Aliasing(u16);
+
+        impl MaxValue for Aliasing {
+            const MAX: Self = Aliasing(u16::MAX);
+        }
+
+        impl FromAndIntoUsize for Aliasing {
+            fn try_from_usize(value: usize) -> Option<Self> {
+                u16::try_from(value).ok().map(Aliasing)
+            }
+
+            fn into_usize(self) -> usize {
+                usize::from(self.0 % 4)
+            }
+        }
+
+        let mut list = FreeList::<u32, Aliasing>::default();
+        list.push(1);
+        assert_eq!(
+            list.get_disjoint_mut([Aliasing(0), Aliasing(4)]).err(),
+            Some(GetDisjointMutError::OverlappingIndices)
+        );
+        assert_eq!(
+            list.get_disjoint_mut_slice(&[Aliasing(4), Aliasing(0)])
+                .err(),
+            Some(GetDisjointMutError::OverlappingIndices)
+        );
+    }
+
+    #[test]
+    fn quarantined_slots_count_towards_the_addressable_slots() {
+        let mut list = FreeList::<u32, u8>::default();
+        list.set_quarantine_len(10);
+        for value in 0..240 {
+            list.push(value);
+        }
+        for index in 0..10 {
+            list.erase(index);
+        }
+        for value in 0..14 {
+            list.push(value);
+        }
+        assert_eq!(list.slot_count(), 254);
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| list.push(0)));
+        assert!(result.is_err());
+        assert_eq!(list.slot_count(), 254);
+        assert_eq!(list.len(), 244);
+        assert_eq!(list.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn safe_accessors_use_the_converted_index() {
+        /// An index type whose conversion changes between calls.
+        #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+        struct Flipping(u8);
+
+        thread_local! {
+            static CALLS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+        }
+
+        impl MaxValue for Flipping {
+            const MAX: Self = Flipping(u8::MAX);
+        }
+
+        impl FromAndIntoUsize for Flipping {
+            fn try_from_usize(value: usize) -> Option<Self> {
+                u8::try_from(value).ok().map(Flipping)
+            }
+
+            fn into_usize(self) -> usize {
+                // Every other conversion points to the free slot 1.
+                let calls = CALLS.replace(CALLS.get() + 1);
+                if self.0 == 0 && calls % 2 == 1 {
+                    1
+                } else {
+                    usize::from(self.0)
+                }
+            }
+        }
+
+        let mut list = FreeList::<String, Flipping>::default();
+        list.push("first".into());
+        list.push("second".into());
+        list.erase(Flipping(1));
+
+        // The slot may be reported as free, but its link is never read as an element.
+        for _ in 0..4 {
+            let value = list.get(Flipping(0)).map(String::as_str);
+            assert!(matches!(value, None | Some("first")));
+            let length = list.get_mut(Flipping(0)).map(|value| value.len());
+            assert!(matches!(length, None | Some(5)));
+            assert_eq!(
+                list.nth_occupied(0).map(|(_, value)| value.as_str()),
+                Some("first")
+            );
+        }
+    }
+
+    #[test]
+    fn sorted_free_chain_skips_retired_slots() {
+        let mut list = FreeList::<u32, u8>::with_reuse_policy(ReusePolicy::LowestIndexFirst);
+        list.set_generation_overflow(GenerationOverflow::Retire);
+        for value in 0..4 {
+            list.push(value);
+        }
+        list.generations[1] = u32::MAX - 1;
         list.erase(1);
         list.erase(2);
-        list.erase(3);
-        assert_eq!(list.first_free, 3);
-        assert_eq!(list.capacity(), 4);
+        list.erase(0);
+        assert_eq!(list.check_invariants(), Ok(()));
+        assert_eq!(list.push(4), 0);
+        assert_eq!(list.push(5), 2);
+        assert_eq!(list.push(6), 4);
     }
 
     #[test]
-    fn erase_in_descending_order() {
-        let mut list = FreeList::<Complex>::default();
-        insert_some(&mut list, 4);
-        list.erase(3);
-        list.erase(2);
+    #[should_panic]
+    fn exhausted_generation_panics_before_erasing() {
+        let mut list = FreeList::<u32, u8>::default();
+        list.set_generation_overflow(GenerationOverflow::Panic);
+        list.push(0);
+        list.push(1);
+        list.generations[1] = u32::MAX;
+        list.erase_many(&[0, 1]);
+    }
+
+    #[test]
+    fn push_n_contiguous_uses_the_lowest_free_run() {
+        let mut list = FreeList::<u32, u8>::with_reuse_policy(ReusePolicy::Fifo);
+        list.extend_and_collect(0..8);
+        for index in [6, 2, 3, 0, 4] {
+            list.erase(index);
+        }
+
+        assert_eq!(list.push_n_contiguous([10, 11]), 2);
+        assert_eq!(list.check_invariants(), Ok(()));
+        assert_eq!(list.push(12), 6);
+        assert_eq!(list.push(13), 0);
+        assert_eq!(list.push(14), 4);
+
+        // Without a free run, the run is appended.
+        assert_eq!(list.push_n_contiguous([20, 21, 22]), 8);
+        assert_eq!(list.slot_count(), 11);
+        assert_eq!(
+            list.iter().skip(8).collect::<Vec<_>>(),
+            [(8, &20), (9, &21), (10, &22)]
+        );
+        assert_eq!(list.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn nth_occupied_matches_iteration_order() {
+        let mut list = FreeList::<u32, u16>::default();
+        list.extend_and_collect(0..300);
+        list.erase_many(&(0..300).filter(|i| i % 4 != 1).collect::<Vec<_>>());
+        for enabled in [false, true] {
+            list.set_rank_index(enabled);
+            for (n, expected) in list.iter().enumerate() {
+                assert_eq!(list.nth_occupied(n), Some(expected));
+            }
+            assert_eq!(list.nth_occupied(list.len()), None);
+        }
+
+        // The index follows later changes.
         list.erase(1);
-        list.erase(0);
-        assert_eq!(list.first_free, 0);
-        assert_eq!(list.capacity(), 4);
+        list.push(1000);
+        assert_eq!(list.nth_occupied(0), Some((1, &1000)));
+        assert_eq!(list.nth_occupied(1), Some((5, &5)));
     }
 
     #[test]
-    fn erase_in_mixed_order() {
-        let mut list = FreeList::<Complex>::default();
-        insert_some(&mut list, 4);
-        list.erase(0);
+    fn dense_lists_expose_slices() {
+        let mut list = FreeList::<u64, u32>::default();
+        assert_eq!(list.as_slice(), Some(&[][..]));
+        list.extend_and_collect([1, 2, 3, 4]);
         list.erase(3);
-        list.erase(1);
-        list.erase(2);
-        assert_eq!(list.first_free, 2);
-        assert_eq!(list.capacity(), 4);
+        assert_eq!(list.as_mut_slice(), None);
+        list.push(5);
+        assert_eq!(list.as_slice(), Some(&[1, 2, 3, 5][..]));
+
+        // Slots of small elements hold an index and are larger than the element.
+        let mut list = FreeList::<u8, u32>::default();
+        list.push(1);
+        assert!(list.is_dense());
+        assert_eq!(list.as_slice(), None);
+
+        let mut list = FreeList::<(), u8>::default();
+        list.push(());
+        list.push(());
+        assert_eq!(list.as_slice().map(<[()]>::len), Some(2));
     }
 
     #[test]
-    fn clear_works() {
-        let mut list = FreeList::<Complex>::default();
-        insert_some(&mut list, 4);
+    fn gather_into_copies_runs_and_single_elements() {
+        let mut list = FreeList::<u64, u32>::default();
+        list.extend_and_collect(0..10);
+        list.erase_many(&[2, 3, 7]);
+        let mut buffer = [0; 5];
+        assert_eq!(list.gather_into(&mut buffer), 5);
+        assert_eq!(buffer, [0, 1, 4, 5, 6]);
+
+        // Slots of small elements are not slices.
+        let mut list = FreeList::<u8, u32>::default();
+        list.extend_and_collect(0..4);
+        list.erase(0);
+        let mut buffer = [0; 8];
+        assert_eq!(list.gather_into(&mut buffer), 3);
+        assert_eq!(buffer[..3], [1, 2, 3]);
+
+        let mut buffer = [0; 2];
+        assert_eq!(list.drain_into(&mut buffer), 2);
+        assert_eq!(buffer, [1, 2]);
+        assert_eq!(list.iter().collect::<Vec<_>>(), [(3, &3)]);
+        assert_eq!(list.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn reserve_links_slots_behind_the_free_chain() {
+        let mut list = FreeList::<Complex, u8>::with_reuse_policy(ReusePolicy::Lifo);
+        list.set_quarantine_len(1);
+        insert_some_in(&mut list, 4);
         list.erase(1);
-        list.clear();
-        list.clear();
-        assert_eq!(list.first_free, FreeList::<Complex>::SENTINEL);
-        assert_eq!(list.capacity(), 0);
+        list.erase(2);
+
+        // Slot 1 is free, slot 2 is quarantined.
+        list.reserve(1);
+        assert_eq!(list.slot_count(), 4);
+        list.reserve(3);
+        assert_eq!(list.slot_count(), 6);
+        assert_eq!(list.check_invariants(), Ok(()));
+
+        let indices: Vec<_> = (0..3).map(|_| list.push(Complex::default())).collect();
+        assert_eq!(indices, [1, 4, 5]);
+        assert_eq!(list.slot_count(), 6);
+        assert_eq!(list.check_invariants(), Ok(()));
     }
 
     #[test]
-    fn is_in_free_list_works() {
-        let mut list = FreeList::<Complex>::default();
-        insert_some(&mut list, 2);
+    fn reserve_keeps_the_chain_sorted() {
+        let mut list = FreeList::<Complex, u8>::with_reuse_policy(ReusePolicy::LowestIndexFirst);
+        insert_some_in(&mut list, 4);
+        list.reserve(2);
+        list.erase(3);
         list.erase(0);
-        assert!(list.debug_is_in_free_list(0));
-        assert!(!list.debug_is_in_free_list(1));
+        assert_eq!(list.check_invariants(), Ok(()));
+
+        let indices: Vec<_> = (0..4).map(|_| list.push(Complex::default())).collect();
+        assert_eq!(indices, [0, 3, 4, 5]);
+        assert_eq!(list.push(Complex::default()), 6);
     }
 
     #[test]
-    fn at_works() {
-        let mut list = FreeList::<Complex>::default();
-        list.push(Complex(1., 2.));
-        list.push(Complex::default());
-        let element = unsafe { list.at(0) };
-        assert_eq!(*element, Complex(1., 2.));
+    #[should_panic]
+    fn reserving_too_many_slots_panics() {
+        let mut list = FreeList::<Complex, u8>::default();
+        list.reserve(255);
     }
 
     #[test]
-    fn at_mut_works() {
-        let mut list = FreeList::<Complex>::default();
-        list.push(Complex(1., 2.));
-        list.push(Complex::default());
+    fn custom_allocator_is_used() {
+        use std::alloc::Layout;
+        use std::cell::Cell;
+        use std::ptr::NonNull;
 
-        // Mutably access the element and exchange it.
-        let element = unsafe { list.at_mut(0) };
-        *element = Complex::default();
+        #[derive(Default)]
+        struct Counting(Cell<usize>);
 
-        // Get a new reference and verify.
-        let element = unsafe { list.at(0) };
-        assert_eq!(*element, Complex(0., 0.));
+        unsafe impl Allocator for &Counting {
+            fn allocate(
+                &self,
+                layout: Layout,
+            ) -> Result<NonNull<[u8]>, allocator_api2::alloc::AllocError> {
+                self.0.set(self.0.get() + 1);
+                Global.allocate(layout)
+            }
+
+            unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+                Global.deallocate(ptr, layout)
+            }
+        }
+
+        let alloc = Counting::default();
+        let mut list = FreeList::<Complex, u8, _>::new_in(&alloc);
+        insert_some_in(&mut list, 10);
+        assert!(alloc.0.get() > 0);
+        assert!(std::ptr::eq(*list.allocator(), &alloc));
     }
 
     #[test]
@@ -568,4 +4441,13 @@ mod test {
             list.push(Complex::default());
         }
     }
+
+    fn insert_some_in<TIndex: IndexType, A: Allocator>(
+        list: &mut FreeList<Complex, TIndex, A>,
+        n: usize,
+    ) {
+        for _ in 0..n {
+            list.push(Complex::default());
+        }
+    }
 }
@@ -0,0 +1,300 @@
+use crate::index_type::*;
+use crate::FreeList;
+
+/// A doubly-linked list whose nodes are stored in a [`FreeList`] and linked by index.
+///
+/// Every element is addressed by a stable handle, which stays valid until the element
+/// is removed, regardless of insertions and removals elsewhere in the list. Inserting
+/// next to a handle and removing a handle take constant time, and nodes do not require
+/// individual heap allocations.
+///
+/// ## Type parameters
+/// * `T` - The type of the element.
+/// * `TIndex` - The type of the handles; see [`FreeList`] for details.
+///
+/// ## Example
+/// ```rust
+/// use free_list::LinkedFreeList;
+///
+/// let mut list = LinkedFreeList::<&str, u8>::default();
+/// let b = list.push_back("b");
+/// list.push_front("a");
+/// let d = list.push_back("d");
+/// list.insert_before(d, "c");
+///
+/// assert_eq!(list.remove(b), Some("b"));
+/// assert_eq!(list.iter().map(|(_, value)| *value).collect::<Vec<_>>(), ["a", "c", "d"]);
+/// ```
+pub struct LinkedFreeList<T, TIndex = DefaultIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    nodes: FreeList<Node<T, TIndex>, TIndex>,
+    /// The first node, or `SENTINEL` if the list is empty.
+    head: TIndex,
+    /// The last node, or `SENTINEL` if the list is empty.
+    tail: TIndex,
+}
+
+/// A node of a [`LinkedFreeList`].
+struct Node<T, TIndex> {
+    value: T,
+    /// The previous node, or `SENTINEL` for the first node.
+    prev: TIndex,
+    /// The next node, or `SENTINEL` for the last node.
+    next: TIndex,
+}
+
+impl<T, TIndex> Default for Node<T, TIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    fn default() -> Self {
+        Self {
+            value: T::default(),
+            prev: TIndex::MAX,
+            next: TIndex::MAX,
+        }
+    }
+}
+
+impl<T, TIndex> Default for LinkedFreeList<T, TIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    fn default() -> Self {
+        Self {
+            nodes: FreeList::default(),
+            head: Self::SENTINEL,
+            tail: Self::SENTINEL,
+        }
+    }
+}
+
+impl<T, TIndex> LinkedFreeList<T, TIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    /// The sentinel value indicates the absence of a node.
+    const SENTINEL: TIndex = TIndex::MAX;
+
+    /// Inserts an element at the front of the list and returns its handle.
+    pub fn push_front(&mut self, value: T) -> TIndex {
+        self.link(value, Self::SENTINEL, self.head)
+    }
+
+    /// Inserts an element at the back of the list and returns its handle.
+    pub fn push_back(&mut self, value: T) -> TIndex {
+        self.link(value, self.tail, Self::SENTINEL)
+    }
+
+    /// Inserts an element before the element of the specified handle and returns its handle.
+    ///
+    /// ## Panics
+    /// Panics if the handle does not refer to an element.
+    pub fn insert_before(&mut self, handle: TIndex, value: T) -> TIndex {
+        let prev = self.node(handle).prev;
+        self.link(value, prev, handle)
+    }
+
+    /// Inserts an element after the element of the specified handle and returns its handle.
+    ///
+    /// ## Panics
+    /// Panics if the handle does not refer to an element.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::LinkedFreeList;
+    ///
+    /// let mut list = LinkedFreeList::<u32, u8>::default();
+    /// let first = list.push_back(1);
+    /// list.push_back(3);
+    /// let second = list.insert_after(first, 2);
+    /// assert_eq!(list.next(first), Some(second));
+    /// ```
+    pub fn insert_after(&mut self, handle: TIndex, value: T) -> TIndex {
+        let next = self.node(handle).next;
+        self.link(value, handle, next)
+    }
+
+    /// Removes the element of the specified handle and returns it, or returns `None`
+    /// if the handle does not refer to an element.
+    pub fn remove(&mut self, handle: TIndex) -> Option<T> {
+        let node = self.nodes.try_remove(handle)?;
+        if node.prev == Self::SENTINEL {
+            self.head = node.next;
+        } else {
+            self.nodes[node.prev].next = node.next;
+        }
+        if node.next == Self::SENTINEL {
+            self.tail = node.prev;
+        } else {
+            self.nodes[node.next].prev = node.prev;
+        }
+        Some(node.value)
+    }
+
+    /// Removes the first element and returns it.
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.remove(self.head)
+    }
+
+    /// Removes the last element and returns it.
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.remove(self.tail)
+    }
+
+    /// Gets the handle of the first element.
+    #[inline]
+    pub fn front(&self) -> Option<TIndex> {
+        (self.head != Self::SENTINEL).then_some(self.head)
+    }
+
+    /// Gets the handle of the last element.
+    #[inline]
+    pub fn back(&self) -> Option<TIndex> {
+        (self.tail != Self::SENTINEL).then_some(self.tail)
+    }
+
+    /// Gets the handle of the element following the element of the specified handle.
+    pub fn next(&self, handle: TIndex) -> Option<TIndex> {
+        let next = self.nodes.get(handle)?.next;
+        (next != Self::SENTINEL).then_some(next)
+    }
+
+    /// Gets the handle of the element preceding the element of the specified handle.
+    pub fn prev(&self, handle: TIndex) -> Option<TIndex> {
+        let prev = self.nodes.get(handle)?.prev;
+        (prev != Self::SENTINEL).then_some(prev)
+    }
+
+    /// Gets a reference to the element of the specified handle.
+    #[inline]
+    pub fn get(&self, handle: TIndex) -> Option<&T> {
+        self.nodes.get(handle).map(|node| &node.value)
+    }
+
+    /// Gets a mutable reference to the element of the specified handle.
+    #[inline]
+    pub fn get_mut(&mut self, handle: TIndex) -> Option<&mut T> {
+        self.nodes.get_mut(handle).map(|node| &mut node.value)
+    }
+
+    /// Determines whether the specified handle refers to an element.
+    #[inline]
+    pub fn contains(&self, handle: TIndex) -> bool {
+        self.nodes.contains(handle)
+    }
+
+    /// Gets the number of elements in the list.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Determines whether the list contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Removes all elements from the list.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.head = Self::SENTINEL;
+        self.tail = Self::SENTINEL;
+    }
+
+    /// Gets an iterator over the handles and values of all elements, from front to back.
+    pub fn iter(&self) -> impl Iterator<Item = (TIndex, &T)> + '_ {
+        let mut handle = self.head;
+        std::iter::from_fn(move || {
+            if handle == Self::SENTINEL {
+                return None;
+            }
+            let node = &self.nodes[handle];
+            let current = handle;
+            handle = node.next;
+            Some((current, &node.value))
+        })
+    }
+
+    /// Gets the node of the specified handle.
+    fn node(&self, handle: TIndex) -> &Node<T, TIndex> {
+        self.nodes.get(handle).unwrap_or_else(|| {
+            panic!("Attempted to insert next to a slot that is not occupied ({handle:?})")
+        })
+    }
+
+    /// Stores a node between the specified neighbors and returns its handle.
+    fn link(&mut self, value: T, prev: TIndex, next: TIndex) -> TIndex {
+        let handle = self.nodes.push(Node { value, prev, next });
+        if prev == Self::SENTINEL {
+            self.head = handle;
+        } else {
+            self.nodes[prev].next = handle;
+        }
+        if next == Self::SENTINEL {
+            self.tail = handle;
+        } else {
+            self.nodes[next].prev = handle;
+        }
+        handle
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(list: &LinkedFreeList<u32, u8>) -> Vec<u32> {
+        list.iter().map(|(_, value)| *value).collect()
+    }
+
+    #[test]
+    fn links_are_maintained() {
+        let mut list = LinkedFreeList::<u32, u8>::default();
+        let two = list.push_back(2);
+        let one = list.push_front(1);
+        let four = list.push_back(4);
+        let three = list.insert_after(two, 3);
+        assert_eq!(values(&list), [1, 2, 3, 4]);
+        assert_eq!(list.front(), Some(one));
+        assert_eq!(list.back(), Some(four));
+        assert_eq!(list.prev(three), Some(two));
+
+        assert_eq!(list.remove(one), Some(1));
+        assert_eq!(list.remove(four), Some(4));
+        assert_eq!(list.remove(four), None);
+        assert_eq!(values(&list), [2, 3]);
+        assert_eq!(list.front(), Some(two));
+        assert_eq!(list.back(), Some(three));
+
+        // Removed handles are reused, while existing handles stay valid.
+        let zero = list.insert_before(two, 0);
+        assert!(zero == one || zero == four);
+        *list.get_mut(three).unwrap() = 30;
+        assert_eq!(values(&list), [0, 2, 30]);
+
+        assert_eq!(list.pop_back(), Some(30));
+        assert_eq!(list.pop_front(), Some(0));
+        assert_eq!(list.pop_front(), Some(2));
+        assert_eq!(list.pop_front(), None);
+        assert!(list.is_empty());
+        assert_eq!(list.front(), None);
+        assert_eq!(list.back(), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn inserting_next_to_a_removed_element_panics() {
+        let mut list = LinkedFreeList::<u32, u8>::default();
+        let handle = list.push_back(1);
+        list.remove(handle);
+        list.insert_after(handle, 2);
+    }
+}
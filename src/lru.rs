@@ -0,0 +1,242 @@
+use crate::index_type::*;
+use crate::{FreeList, Key};
+
+/// A fixed-capacity free list that tracks the order in which its elements were used,
+/// so that it can serve as the storage of a least-recently-used cache.
+///
+/// The recency order is a doubly linked list threaded through the slots by index, so
+/// [`touch`](Self::touch) and [`evict_lru`](Self::evict_lru) take constant time and need
+/// no allocation. Inserting into a full list evicts the least recently used element.
+///
+/// Elements are addressed by [`Key`]s, so that the key of an evicted element never
+/// refers to the element that reuses its slot.
+///
+/// ## Type parameters
+/// * `T` - The type of the element.
+/// * `TIndex` - The type of the index; see [`FreeList`] for details.
+///
+/// ## Example
+/// ```rust
+/// use free_list::LruFreeList;
+///
+/// let mut cache = LruFreeList::<&str, u8>::new(2);
+/// let (a, _) = cache.push("a");
+/// let (b, _) = cache.push("b");
+/// cache.touch(a);
+///
+/// // The cache is full, so the least recently used element is evicted.
+/// let (c, evicted) = cache.push("c");
+/// assert_eq!(evicted, Some((b, "b")));
+/// assert_eq!(c.index(), b.index());
+/// assert_eq!(cache.get(b), None);
+/// assert_eq!(cache.evict_lru(), Some((a, "a")));
+/// ```
+pub struct LruFreeList<T, TIndex = DefaultIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    list: FreeList<T, TIndex>,
+    capacity: usize,
+    /// The neighbors of every slot in the recency order, as `(more recent, less recent)`;
+    /// stale for free slots.
+    links: Vec<(TIndex, TIndex)>,
+    /// The most recently used element.
+    head: TIndex,
+    /// The least recently used element.
+    tail: TIndex,
+}
+
+impl<T, TIndex> LruFreeList<T, TIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    /// Marks the end of the recency order.
+    const SENTINEL: TIndex = TIndex::MAX;
+
+    /// Creates an empty list that holds up to the specified number of elements.
+    ///
+    /// ## Panics
+    /// Panics if the capacity is zero or exceeds the number of indices of the index type.
+    pub fn new(capacity: usize) -> Self {
+        assert!(
+            capacity > 0,
+            "Attempted to create an LRU list without capacity"
+        );
+        assert!(
+            capacity < TIndex::MAX.into_usize(),
+            "Attempted to create an LRU list larger than the index type can address"
+        );
+        let mut list = FreeList::default();
+        list.reserve(capacity);
+        Self {
+            list,
+            capacity,
+            links: Vec::with_capacity(capacity),
+            head: Self::SENTINEL,
+            tail: Self::SENTINEL,
+        }
+    }
+
+    /// Inserts an element as the most recently used one and returns its key.
+    ///
+    /// If the list is full, the least recently used element is evicted first and
+    /// returned with its key.
+    pub fn push(&mut self, element: T) -> (Key<TIndex>, Option<(Key<TIndex>, T)>) {
+        let evicted = if self.list.len() == self.capacity {
+            self.evict_lru()
+        } else {
+            None
+        };
+        let key = self.list.push_key(element);
+        let slot = key.index().into_usize();
+        if slot >= self.links.len() {
+            self.links
+                .resize(slot + 1, (Self::SENTINEL, Self::SENTINEL));
+        }
+        self.link_front(key.index());
+        (key, evicted)
+    }
+
+    /// Marks the element of the specified key as the most recently used one and returns
+    /// whether the key refers to an element.
+    pub fn touch(&mut self, key: Key<TIndex>) -> bool {
+        if !self.list.contains_key(key) {
+            return false;
+        }
+        if self.head != key.index() {
+            self.unlink(key.index());
+            self.link_front(key.index());
+        }
+        true
+    }
+
+    /// Removes the least recently used element and returns it with its key, or returns
+    /// `None` if the list is empty.
+    pub fn evict_lru(&mut self) -> Option<(Key<TIndex>, T)> {
+        let key = self.peek_lru()?;
+        self.unlink(key.index());
+        Some((key, self.list.remove(key.index())))
+    }
+
+    /// Gets the key of the least recently used element, or `None` if the list is empty.
+    #[inline]
+    pub fn peek_lru(&self) -> Option<Key<TIndex>> {
+        self.list.key(self.tail)
+    }
+
+    /// Removes the element of the specified key and returns it, or returns `None` if
+    /// the key does not refer to an element.
+    pub fn remove(&mut self, key: Key<TIndex>) -> Option<T> {
+        if !self.list.contains_key(key) {
+            return None;
+        }
+        self.unlink(key.index());
+        Some(self.list.remove(key.index()))
+    }
+
+    /// Gets a reference to the element of the specified key without marking it as used,
+    /// or `None` if the key does not refer to an element.
+    #[inline]
+    pub fn get(&self, key: Key<TIndex>) -> Option<&T> {
+        self.list.contains_key(key).then(|| &self.list[key.index()])
+    }
+
+    /// Gets a mutable reference to the element of the specified key without marking it
+    /// as used, or `None` if the key does not refer to an element.
+    #[inline]
+    pub fn get_mut(&mut self, key: Key<TIndex>) -> Option<&mut T> {
+        self.list
+            .contains_key(key)
+            .then(|| &mut self.list[key.index()])
+    }
+
+    /// Gets the number of elements in the list.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Determines whether the list contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Gets the maximum number of elements in the list.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Gets an iterator over the keys and values of all elements, from the most to the
+    /// least recently used one.
+    pub fn iter(&self) -> impl Iterator<Item = (Key<TIndex>, &T)> + '_ {
+        let mut current = self.head;
+        std::iter::from_fn(move || {
+            let key = self.list.key(current)?;
+            current = self.links[current.into_usize()].1;
+            Some((key, &self.list[key.index()]))
+        })
+    }
+
+    /// Links an element as the most recently used one.
+    fn link_front(&mut self, index: TIndex) {
+        self.links[index.into_usize()] = (Self::SENTINEL, self.head);
+        if self.head == Self::SENTINEL {
+            self.tail = index;
+        } else {
+            self.links[self.head.into_usize()].0 = index;
+        }
+        self.head = index;
+    }
+
+    /// Unlinks an element from the recency order.
+    fn unlink(&mut self, index: TIndex) {
+        let (previous, next) = self.links[index.into_usize()];
+        if previous == Self::SENTINEL {
+            self.head = next;
+        } else {
+            self.links[previous.into_usize()].1 = next;
+        }
+        if next == Self::SENTINEL {
+            self.tail = previous;
+        } else {
+            self.links[next.into_usize()].0 = previous;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recency_order_follows_touches_and_removals() {
+        let mut cache = LruFreeList::<u32, u8>::new(3);
+        let keys: Vec<_> = (0..3).map(|value| cache.push(value).0).collect();
+        assert!(cache.touch(keys[0]));
+        assert!(cache.touch(keys[0]));
+        assert_eq!(cache.remove(keys[1]), Some(1));
+        assert!(!cache.touch(keys[1]));
+
+        let order = |cache: &LruFreeList<u32, u8>| {
+            cache.iter().map(|(_, &value)| value).collect::<Vec<_>>()
+        };
+        assert_eq!(order(&cache), [0, 2]);
+
+        let (_, evicted) = cache.push(3);
+        assert_eq!(evicted, None);
+        let (_, evicted) = cache.push(4);
+        assert_eq!(evicted, Some((keys[2], 2)));
+        assert_eq!(order(&cache), [4, 3, 0]);
+        assert_eq!(cache.peek_lru(), Some(keys[0]));
+
+        while cache.evict_lru().is_some() {}
+        assert!(cache.is_empty());
+        assert_eq!(cache.iter().count(), 0);
+        cache.push(5);
+        assert_eq!(order(&cache), [5]);
+    }
+}
@@ -0,0 +1,83 @@
+/// Defines one or more newtypes around a primitive index type that can be used
+/// as the `TIndex` of the lists of this crate.
+///
+/// This allows handles to be domain-typed instead of bare integers, so that an
+/// index into one list cannot accidentally be used with another. The generated type
+/// derives `Copy`, `Clone`, `PartialEq`, `Eq`, `PartialOrd`, `Ord`, `Hash` and `Debug`
+/// and can be converted from and into the wrapped type.
+///
+/// ## Example
+/// ```rust
+/// use free_list::{define_index_type, FreeList};
+///
+/// define_index_type! {
+///     /// The index of an enemy.
+///     pub struct EnemyId(u32);
+///
+///     /// The index of a projectile.
+///     pub struct ProjectileId(pub u16);
+/// }
+///
+/// let mut enemies = FreeList::<&str, EnemyId>::default();
+/// let id: EnemyId = enemies.push("goblin");
+/// assert_eq!(id, EnemyId::from(0));
+/// assert_eq!(u32::from(id), 0);
+/// assert_eq!(unsafe { enemies.at(id) }, &"goblin");
+///
+/// let projectile = ProjectileId(7);
+/// assert_eq!(projectile.0, 7);
+/// ```
+#[macro_export]
+macro_rules! define_index_type {
+    ($($(#[$meta:meta])* $vis:vis struct $name:ident($inner_vis:vis $inner:ty);)+) => {
+        $(
+            $(#[$meta])*
+            #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+            $vis struct $name($inner_vis $inner);
+
+            impl $crate::MaxValue for $name {
+                const MAX: Self = $name(<$inner as $crate::MaxValue>::MAX);
+            }
+
+            impl $crate::FromAndIntoUsize for $name {
+                #[inline]
+                fn try_from_usize(value: usize) -> ::core::option::Option<Self> {
+                    <$inner as $crate::FromAndIntoUsize>::try_from_usize(value).map($name)
+                }
+
+                #[inline]
+                fn into_usize(self) -> usize {
+                    <$inner as $crate::FromAndIntoUsize>::into_usize(self.0)
+                }
+            }
+
+            impl ::core::convert::From<$inner> for $name {
+                #[inline]
+                fn from(value: $inner) -> Self {
+                    $name(value)
+                }
+            }
+
+            impl ::core::convert::From<$name> for $inner {
+                #[inline]
+                fn from(value: $name) -> Self {
+                    value.0
+                }
+            }
+        )+
+    };
+}
+
+/// Asserts a condition that guards against undefined behavior.
+///
+/// The check is performed in debug builds, and in release builds if the `checked`
+/// feature is enabled; otherwise it compiles to nothing.
+macro_rules! safety_check {
+    ($($arg:tt)+) => {
+        if cfg!(any(debug_assertions, feature = "checked")) {
+            assert!($($arg)+);
+        }
+    };
+}
+
+pub(crate) use safety_check;
@@ -0,0 +1,216 @@
+use crate::index_type::*;
+use crate::FreeList;
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A hash map that assigns every key a stable index into a free list.
+///
+/// Values can be looked up by their key or by their index. An index stays valid until
+/// its key is removed, after which it may be reused for another key, just like the
+/// indices of a [`FreeList`]. This makes the indices suitable as small handles to
+/// pass around instead of the keys.
+///
+/// ## Type parameters
+/// * `K` - The type of the key.
+/// * `V` - The type of the value.
+/// * `TIndex` - The type of the index; see [`FreeList`] for details.
+///
+/// ## Example
+/// ```rust
+/// use free_list::FreeListMap;
+///
+/// let mut textures = FreeListMap::<String, u32, u16>::default();
+/// let grass = textures.insert("grass.png".into(), 7);
+/// let stone = textures.insert("stone.png".into(), 8);
+///
+/// assert_eq!(textures.get("grass.png"), Some(&7));
+/// assert_eq!(textures.get_by_index(stone), Some((&"stone.png".to_string(), &8)));
+///
+/// // Inserting an existing key replaces its value and keeps its index.
+/// assert_eq!(textures.insert("grass.png".into(), 9), grass);
+/// assert_eq!(textures.get_by_index(grass).map(|(_, value)| *value), Some(9));
+/// ```
+pub struct FreeListMap<K, V, TIndex = DefaultIndex>
+where
+    V: Default,
+    TIndex: IndexType,
+{
+    indices: HashMap<K, TIndex>,
+    values: FreeList<V, TIndex>,
+    /// The key of every slot; `None` for free slots.
+    keys: Vec<Option<K>>,
+}
+
+impl<K, V, TIndex> Default for FreeListMap<K, V, TIndex>
+where
+    V: Default,
+    TIndex: IndexType,
+{
+    fn default() -> Self {
+        Self {
+            indices: HashMap::new(),
+            values: FreeList::default(),
+            keys: Vec::new(),
+        }
+    }
+}
+
+impl<K, V, TIndex> FreeListMap<K, V, TIndex>
+where
+    K: Eq + Hash + Clone,
+    V: Default,
+    TIndex: IndexType,
+{
+    /// Inserts a value for the specified key and returns the index of the key.
+    ///
+    /// If the key is already present, its value is replaced and its index is kept.
+    pub fn insert(&mut self, key: K, value: V) -> TIndex {
+        if let Some(&index) = self.indices.get(&key) {
+            self.values[index] = value;
+            return index;
+        }
+
+        let index = self.values.push(value);
+        let slot = index.into_usize();
+        if slot >= self.keys.len() {
+            self.keys.resize_with(slot + 1, || None);
+        }
+        self.keys[slot] = Some(key.clone());
+        self.indices.insert(key, index);
+        index
+    }
+
+    /// Removes the specified key and returns its value, or returns `None` if the key is
+    /// not present.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let index = self.indices.remove(key)?;
+        self.keys[index.into_usize()] = None;
+        Some(self.values.remove(index))
+    }
+
+    /// Removes the key at the specified index and returns it with its value, or returns
+    /// `None` if the index does not refer to a key.
+    pub fn remove_by_index(&mut self, index: TIndex) -> Option<(K, V)> {
+        let key = self.keys.get_mut(index.into_usize())?.take()?;
+        self.indices.remove(&key);
+        Some((key, self.values.remove(index)))
+    }
+
+    /// Gets a reference to the value of the specified key, or `None` if the key is not
+    /// present.
+    #[inline]
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.indices.get(key).map(|&index| &self.values[index])
+    }
+
+    /// Gets a mutable reference to the value of the specified key, or `None` if the key
+    /// is not present.
+    #[inline]
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.indices.get(key).map(|&index| &mut self.values[index])
+    }
+
+    /// Gets the index of the specified key, or `None` if the key is not present.
+    #[inline]
+    pub fn index_of<Q>(&self, key: &Q) -> Option<TIndex>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.indices.get(key).copied()
+    }
+
+    /// Gets references to the key and value at the specified index, or `None` if the
+    /// index does not refer to a key.
+    #[inline]
+    pub fn get_by_index(&self, index: TIndex) -> Option<(&K, &V)> {
+        let key = self.keys.get(index.into_usize())?.as_ref()?;
+        Some((key, &self.values[index]))
+    }
+
+    /// Gets a reference to the key and a mutable reference to the value at the specified
+    /// index, or `None` if the index does not refer to a key.
+    #[inline]
+    pub fn get_by_index_mut(&mut self, index: TIndex) -> Option<(&K, &mut V)> {
+        let key = self.keys.get(index.into_usize())?.as_ref()?;
+        Some((key, &mut self.values[index]))
+    }
+
+    /// Determines whether the specified key is present.
+    #[inline]
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.indices.contains_key(key)
+    }
+
+    /// Gets the number of keys in the map.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Determines whether the map contains no keys.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Gets an iterator over the indices, keys and values of all entries, in ascending
+    /// index order.
+    pub fn iter(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = (TIndex, &K, &V)> + ExactSizeIterator + '_ {
+        self.values.iter().map(|(index, value)| {
+            let key = self.keys[index.into_usize()]
+                .as_ref()
+                .expect("occupied slots have a key");
+            (index, key, value)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removed_indices_are_reused_for_new_keys() {
+        let mut map = FreeListMap::<&str, u32, u8>::default();
+        let a = map.insert("a", 1);
+        let b = map.insert("b", 2);
+        assert_eq!(map.index_of("b"), Some(b));
+
+        assert_eq!(map.remove("a"), Some(1));
+        assert_eq!(map.remove("a"), None);
+        assert_eq!(map.get_by_index(a), None);
+
+        let c = map.insert("c", 3);
+        assert_eq!(c, a);
+        *map.get_mut("c").unwrap() += 10;
+        assert_eq!(
+            map.iter().collect::<Vec<_>>(),
+            [(c, &"c", &13), (b, &"b", &2)]
+        );
+
+        assert_eq!(map.remove_by_index(b), Some(("b", 2)));
+        assert!(!map.contains_key("b"));
+        assert_eq!(map.remove_by_index(b), None);
+        assert_eq!(map.len(), 1);
+    }
+}
@@ -0,0 +1,166 @@
+use crate::index_type::*;
+use crate::FreeList;
+
+/// A free list that attaches a small metadata value, e.g. flags, a type tag or an owner
+/// id, to every slot.
+///
+/// The metadata is kept in a separate column next to the slots, so it neither enlarges
+/// the slots of the elements nor needs to be part of the element type. Scanning the
+/// metadata of all elements touches only the metadata column.
+///
+/// ## Type parameters
+/// * `T` - The type of the element.
+/// * `M` - The type of the metadata.
+/// * `TIndex` - The type of the index; see [`FreeList`] for details.
+///
+/// ## Example
+/// ```rust
+/// use free_list::MetaFreeList;
+///
+/// let mut list = MetaFreeList::<&str, u8, u8>::default();
+/// let player = list.push("player", 0b01);
+/// let enemy = list.push("enemy", 0b10);
+///
+/// *list.meta_mut(enemy).unwrap() |= 0b01;
+/// assert_eq!(list.meta(player), Some(&0b01));
+/// assert_eq!(list.meta(enemy), Some(&0b11));
+///
+/// assert_eq!(list.remove(player), ("player", 0b01));
+/// assert_eq!(list.meta(player), None);
+/// ```
+pub struct MetaFreeList<T, M, TIndex = DefaultIndex>
+where
+    T: Default,
+    M: Copy,
+    TIndex: IndexType,
+{
+    list: FreeList<T, TIndex>,
+    /// The metadata of every slot that ever held an element; stale for free slots.
+    meta: Vec<M>,
+}
+
+impl<T, M, TIndex> Default for MetaFreeList<T, M, TIndex>
+where
+    T: Default,
+    M: Copy,
+    TIndex: IndexType,
+{
+    fn default() -> Self {
+        Self {
+            list: FreeList::default(),
+            meta: Vec::new(),
+        }
+    }
+}
+
+impl<T, M, TIndex> MetaFreeList<T, M, TIndex>
+where
+    T: Default,
+    M: Copy,
+    TIndex: IndexType,
+{
+    /// Inserts an element with the specified metadata and returns an index to it.
+    pub fn push(&mut self, element: T, meta: M) -> TIndex {
+        let index = self.list.push(element);
+        let slot = index.into_usize();
+        if slot >= self.meta.len() {
+            self.meta.resize(slot + 1, meta);
+        }
+        self.meta[slot] = meta;
+        index
+    }
+
+    /// Removes the element at the specified index and returns it with its metadata.
+    ///
+    /// ## Panics
+    /// Panics if the slot is not occupied.
+    pub fn remove(&mut self, index: TIndex) -> (T, M) {
+        let element = self.list.remove(index);
+        (element, self.meta[index.into_usize()])
+    }
+
+    /// Gets a reference to the metadata of the element at the specified index, or
+    /// `None` if the slot is not occupied.
+    #[inline]
+    pub fn meta(&self, index: TIndex) -> Option<&M> {
+        self.list
+            .contains(index)
+            .then(|| &self.meta[index.into_usize()])
+    }
+
+    /// Gets a mutable reference to the metadata of the element at the specified index,
+    /// or `None` if the slot is not occupied.
+    #[inline]
+    pub fn meta_mut(&mut self, index: TIndex) -> Option<&mut M> {
+        self.list
+            .contains(index)
+            .then(|| &mut self.meta[index.into_usize()])
+    }
+
+    /// Gets a reference to the element at the specified index, or `None` if the
+    /// slot is not occupied.
+    #[inline]
+    pub fn get(&self, index: TIndex) -> Option<&T> {
+        self.list.get(index)
+    }
+
+    /// Gets a mutable reference to the element at the specified index, or `None` if
+    /// the slot is not occupied.
+    #[inline]
+    pub fn get_mut(&mut self, index: TIndex) -> Option<&mut T> {
+        self.list.get_mut(index)
+    }
+
+    /// Determines whether the specified index refers to an element.
+    #[inline]
+    pub fn contains(&self, index: TIndex) -> bool {
+        self.list.contains(index)
+    }
+
+    /// Gets the number of elements in the list.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Determines whether the list contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Gets an iterator over the indices, values and metadata of all elements, in
+    /// ascending index order.
+    pub fn iter(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = (TIndex, &T, M)> + ExactSizeIterator + '_ {
+        self.list
+            .iter()
+            .map(|(index, value)| (index, value, self.meta[index.into_usize()]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reused_slots_take_the_new_metadata() {
+        let mut list = MetaFreeList::<String, (u8, bool), u8>::default();
+        let first = list.push("first".into(), (1, false));
+        let second = list.push("second".into(), (2, true));
+        list.meta_mut(first).unwrap().1 = true;
+
+        assert_eq!(list.remove(first), ("first".to_string(), (1, true)));
+        assert_eq!(list.meta_mut(first), None);
+
+        let reused = list.push("reused".into(), (3, false));
+        assert_eq!(reused, first);
+        assert_eq!(
+            list.iter()
+                .map(|(index, _, meta)| (index, meta))
+                .collect::<Vec<_>>(),
+            [(reused, (3, false)), (second, (2, true))]
+        );
+    }
+}
@@ -0,0 +1,275 @@
+use crate::index_type::*;
+use crate::{FreeList, Key, NameTakenError};
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A registry of values that are addressed both by a unique name and by a [`Key`], e.g.
+/// the assets of an asset manager or the plugins of a plugin system.
+///
+/// Names can be human-readable strings or external IDs of any hashable type. Every
+/// name refers to at most one value, and registering a name that is already taken
+/// fails with a [`NameTakenError`] naming the value that holds it. Values can be
+/// looked up by name and by key, and the name of a key can be looked up in turn.
+///
+/// ## Type parameters
+/// * `T` - The type of the value.
+/// * `N` - The type of the name.
+/// * `TIndex` - The type of the index; see [`FreeList`] for details.
+///
+/// ## Example
+/// ```rust
+/// use free_list::NameRegistry;
+///
+/// let mut assets = NameRegistry::<Vec<u8>>::default();
+/// let logo = assets.register("logo.png".into(), vec![0x89, 0x50]).unwrap();
+///
+/// assert_eq!(assets.key_of("logo.png"), Some(logo));
+/// assert_eq!(assets.name_of(logo).map(String::as_str), Some("logo.png"));
+///
+/// let error = assets.register("logo.png".into(), Vec::new()).unwrap_err();
+/// assert_eq!(error.existing, logo);
+/// ```
+pub struct NameRegistry<T, N = String, TIndex = DefaultIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    keys: HashMap<N, Key<TIndex>>,
+    values: FreeList<T, TIndex>,
+    /// The name of every slot; `None` for free slots.
+    names: Vec<Option<N>>,
+}
+
+impl<T, N, TIndex> Default for NameRegistry<T, N, TIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    fn default() -> Self {
+        Self {
+            keys: HashMap::new(),
+            values: FreeList::default(),
+            names: Vec::new(),
+        }
+    }
+}
+
+impl<T, N, TIndex> NameRegistry<T, N, TIndex>
+where
+    T: Default,
+    N: Eq + Hash + Clone,
+    TIndex: IndexType,
+{
+    /// Registers a value under the specified name and returns its key.
+    ///
+    /// If the name is already taken, the value is dropped and the key of the value that
+    /// holds the name is returned in the error.
+    pub fn register(&mut self, name: N, value: T) -> Result<Key<TIndex>, NameTakenError<TIndex>> {
+        if let Some(&existing) = self.keys.get(&name) {
+            return Err(NameTakenError { existing });
+        }
+
+        let key = self.values.push_key(value);
+        let slot = key.index().into_usize();
+        if slot >= self.names.len() {
+            self.names.resize_with(slot + 1, || None);
+        }
+        self.names[slot] = Some(name.clone());
+        self.keys.insert(name, key);
+        Ok(key)
+    }
+
+    /// Registers a value under the specified name, replacing the value that holds the
+    /// name, and returns the key and the replaced value.
+    ///
+    /// A replaced value keeps its key.
+    pub fn register_or_replace(&mut self, name: N, value: T) -> (Key<TIndex>, Option<T>) {
+        match self.keys.get(&name) {
+            Some(&key) => {
+                let previous = std::mem::replace(&mut self.values[key.index()], value);
+                (key, Some(previous))
+            }
+            None => match self.register(name, value) {
+                Ok(key) => (key, None),
+                Err(_) => unreachable!("the name is not taken"),
+            },
+        }
+    }
+
+    /// Changes the name of the value of the specified key and returns the previous name.
+    ///
+    /// Fails if the new name is held by another value; renaming a value to its own name
+    /// succeeds.
+    ///
+    /// ## Panics
+    /// Panics if the key does not refer to a value.
+    pub fn rename(&mut self, key: Key<TIndex>, name: N) -> Result<N, NameTakenError<TIndex>> {
+        assert!(
+            self.values.contains_key(key),
+            "Attempted to rename a value that is not registered ({key})"
+        );
+        if let Some(&existing) = self.keys.get(&name) {
+            if existing != key {
+                return Err(NameTakenError { existing });
+            }
+        }
+
+        let previous = self.names[key.index().into_usize()]
+            .replace(name.clone())
+            .expect("registered values have a name");
+        self.keys.remove(&previous);
+        self.keys.insert(name, key);
+        Ok(previous)
+    }
+
+    /// Removes the value of the specified key and returns it with its name, or returns
+    /// `None` if the key does not refer to a value.
+    pub fn unregister(&mut self, key: Key<TIndex>) -> Option<(N, T)> {
+        if !self.values.contains_key(key) {
+            return None;
+        }
+        let name = self.names[key.index().into_usize()]
+            .take()
+            .expect("registered values have a name");
+        self.keys.remove(&name);
+        Some((name, self.values.remove(key.index())))
+    }
+
+    /// Removes the value of the specified name and returns it with its key, or returns
+    /// `None` if the name is not taken.
+    pub fn unregister_name<Q>(&mut self, name: &Q) -> Option<(Key<TIndex>, T)>
+    where
+        N: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let key = self.keys.remove(name)?;
+        self.names[key.index().into_usize()] = None;
+        Some((key, self.values.remove(key.index())))
+    }
+
+    /// Gets the key of the value of the specified name, or `None` if the name is not
+    /// taken.
+    #[inline]
+    pub fn key_of<Q>(&self, name: &Q) -> Option<Key<TIndex>>
+    where
+        N: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.keys.get(name).copied()
+    }
+
+    /// Gets the name of the value of the specified key, or `None` if the key does not
+    /// refer to a value.
+    #[inline]
+    pub fn name_of(&self, key: Key<TIndex>) -> Option<&N> {
+        if self.values.contains_key(key) {
+            self.names[key.index().into_usize()].as_ref()
+        } else {
+            None
+        }
+    }
+
+    /// Gets a reference to the value of the specified key, or `None` if the key does not
+    /// refer to a value.
+    #[inline]
+    pub fn get(&self, key: Key<TIndex>) -> Option<&T> {
+        self.values
+            .contains_key(key)
+            .then(|| &self.values[key.index()])
+    }
+
+    /// Gets a mutable reference to the value of the specified key, or `None` if the key
+    /// does not refer to a value.
+    #[inline]
+    pub fn get_mut(&mut self, key: Key<TIndex>) -> Option<&mut T> {
+        self.values
+            .contains_key(key)
+            .then(|| &mut self.values[key.index()])
+    }
+
+    /// Gets a reference to the value of the specified name, or `None` if the name is not
+    /// taken.
+    #[inline]
+    pub fn get_by_name<Q>(&self, name: &Q) -> Option<&T>
+    where
+        N: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.keys.get(name).map(|key| &self.values[key.index()])
+    }
+
+    /// Gets a mutable reference to the value of the specified name, or `None` if the
+    /// name is not taken.
+    #[inline]
+    pub fn get_by_name_mut<Q>(&mut self, name: &Q) -> Option<&mut T>
+    where
+        N: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.keys.get(name).map(|key| &mut self.values[key.index()])
+    }
+
+    /// Gets the number of registered values.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Determines whether no values are registered.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Gets an iterator over the keys, names and values of all registered values, in
+    /// ascending index order.
+    pub fn iter(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = (Key<TIndex>, &N, &T)> + ExactSizeIterator + '_ {
+        self.values.iter().map(|(index, value)| {
+            let key = self.values.key(index).expect("index is occupied");
+            let name = self.names[index.into_usize()]
+                .as_ref()
+                .expect("registered values have a name");
+            (key, name, value)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_and_keys_stay_in_sync() {
+        let mut registry = NameRegistry::<u32, u64, u8>::default();
+        let a = registry.register(100, 1).unwrap();
+        let b = registry.register(200, 2).unwrap();
+        assert_eq!(
+            registry.register(100, 3),
+            Err(NameTakenError { existing: a })
+        );
+
+        assert_eq!(registry.rename(a, 200), Err(NameTakenError { existing: b }));
+        assert_eq!(registry.rename(a, 100), Ok(100));
+        assert_eq!(registry.rename(a, 300), Ok(100));
+        assert_eq!(registry.key_of(&100), None);
+        assert_eq!(registry.get_by_name(&300), Some(&1));
+
+        assert_eq!(registry.register_or_replace(200, 4), (b, Some(2)));
+        assert_eq!(registry.unregister_name(&300), Some((a, 1)));
+        assert_eq!(registry.unregister(a), None);
+
+        // The slot is reused under a new key, so the old key no longer resolves.
+        let (c, replaced) = registry.register_or_replace(100, 5);
+        assert_eq!((c.index(), replaced), (a.index(), None));
+        assert_eq!(registry.name_of(a), None);
+        assert_eq!(
+            registry.iter().collect::<Vec<_>>(),
+            [(c, &100, &5), (b, &200, &4)]
+        );
+        assert_eq!(registry.unregister(b), Some((200, 4)));
+        assert_eq!(registry.len(), 1);
+    }
+}
@@ -0,0 +1,234 @@
+use crate::index_type::*;
+use crate::{FreeList, Key};
+
+/// A two-level free list: a parent list of child pools, each of which is a
+/// [`FreeList`] itself.
+///
+/// This suits resources whose lifetime is bound to a group, such as a level or scene:
+/// every group allocates from its own pool, and [`free_pool`](Self::free_pool) releases
+/// all of its elements at once. Elements are addressed by [`NestedKey`]s combining the
+/// key of the pool with the key of the slot, so handles into a freed pool are rejected
+/// even after the pool was reused for another group.
+///
+/// ## Type parameters
+/// * `T` - The type of the element.
+/// * `TIndex` - The type of the index of both pools and slots; see [`FreeList`] for details.
+///
+/// ## Example
+/// ```rust
+/// use free_list::NestedFreeList;
+///
+/// let mut pools = NestedFreeList::<&str, u16>::default();
+/// let level = pools.create_pool();
+/// let hero = pools.push(level, "hero");
+/// let door = pools.push(level, "door");
+/// assert_eq!(pools.get(hero), Some(&"hero"));
+///
+/// // Freeing the pool releases all of its elements.
+/// let freed = pools.free_pool(level).unwrap();
+/// assert_eq!(freed.len(), 2);
+/// assert_eq!(pools.get(door), None);
+/// ```
+pub struct NestedFreeList<T, TIndex = DefaultIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    pools: FreeList<FreeList<T, TIndex>, TIndex>,
+}
+
+/// The key of an element of a [`NestedFreeList`], made of the key of its pool and the
+/// key of its slot within the pool.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NestedKey<TIndex = DefaultIndex>
+where
+    TIndex: IndexType,
+{
+    pool: Key<TIndex>,
+    slot: Key<TIndex>,
+}
+
+impl<TIndex> NestedKey<TIndex>
+where
+    TIndex: IndexType,
+{
+    /// Creates a key from its parts.
+    #[inline]
+    pub const fn new(pool: Key<TIndex>, slot: Key<TIndex>) -> Self {
+        Self { pool, slot }
+    }
+
+    /// Gets the key of the pool.
+    #[inline]
+    pub const fn pool(self) -> Key<TIndex> {
+        self.pool
+    }
+
+    /// Gets the key of the slot within the pool.
+    #[inline]
+    pub const fn slot(self) -> Key<TIndex> {
+        self.slot
+    }
+}
+
+impl<T, TIndex> Default for NestedFreeList<T, TIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    fn default() -> Self {
+        Self {
+            pools: FreeList::default(),
+        }
+    }
+}
+
+impl<T, TIndex> NestedFreeList<T, TIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    /// Creates an empty pool and returns its key.
+    #[inline]
+    pub fn create_pool(&mut self) -> Key<TIndex> {
+        self.pools.push_key(FreeList::default())
+    }
+
+    /// Removes a pool with all of its elements and returns it, or returns `None` if the
+    /// key does not refer to a pool.
+    ///
+    /// Keys of the elements of the pool are rejected afterwards.
+    pub fn free_pool(&mut self, pool: Key<TIndex>) -> Option<FreeList<T, TIndex>> {
+        self.pools
+            .contains_key(pool)
+            .then(|| self.pools.remove(pool.index()))
+    }
+
+    /// Inserts an element into the specified pool and returns its key.
+    ///
+    /// ## Panics
+    /// Panics if the key does not refer to a pool.
+    pub fn push(&mut self, pool: Key<TIndex>, element: T) -> NestedKey<TIndex> {
+        let Some(list) = self.pool_mut(pool) else {
+            panic!("Attempted to insert into a pool that does not exist ({pool})");
+        };
+        NestedKey::new(pool, list.push_key(element))
+    }
+
+    /// Removes the element of the specified key and returns it, or returns `None` if the
+    /// key does not refer to an element.
+    pub fn remove(&mut self, key: NestedKey<TIndex>) -> Option<T> {
+        let list = self.pool_mut(key.pool)?;
+        list.contains_key(key.slot)
+            .then(|| list.remove(key.slot.index()))
+    }
+
+    /// Gets a reference to the element of the specified key, or `None` if the key does
+    /// not refer to an element.
+    #[inline]
+    pub fn get(&self, key: NestedKey<TIndex>) -> Option<&T> {
+        let list = self.pool(key.pool)?;
+        list.contains_key(key.slot).then(|| &list[key.slot.index()])
+    }
+
+    /// Gets a mutable reference to the element of the specified key, or `None` if the
+    /// key does not refer to an element.
+    #[inline]
+    pub fn get_mut(&mut self, key: NestedKey<TIndex>) -> Option<&mut T> {
+        let list = self.pool_mut(key.pool)?;
+        list.contains_key(key.slot)
+            .then(|| &mut list[key.slot.index()])
+    }
+
+    /// Determines whether the specified key refers to an element.
+    #[inline]
+    pub fn contains_key(&self, key: NestedKey<TIndex>) -> bool {
+        self.pool(key.pool)
+            .is_some_and(|list| list.contains_key(key.slot))
+    }
+
+    /// Gets a reference to the specified pool, or `None` if the key does not refer to
+    /// a pool.
+    #[inline]
+    pub fn pool(&self, pool: Key<TIndex>) -> Option<&FreeList<T, TIndex>> {
+        self.pools
+            .contains_key(pool)
+            .then(|| &self.pools[pool.index()])
+    }
+
+    /// Gets a mutable reference to the specified pool, or `None` if the key does not
+    /// refer to a pool.
+    #[inline]
+    pub fn pool_mut(&mut self, pool: Key<TIndex>) -> Option<&mut FreeList<T, TIndex>> {
+        self.pools
+            .contains_key(pool)
+            .then(|| &mut self.pools[pool.index()])
+    }
+
+    /// Gets the number of pools.
+    #[inline]
+    pub fn pool_count(&self) -> usize {
+        self.pools.len()
+    }
+
+    /// Gets the number of elements in all pools.
+    pub fn len(&self) -> usize {
+        self.pools.iter().map(|(_, list)| list.len()).sum()
+    }
+
+    /// Determines whether all pools are empty.
+    pub fn is_empty(&self) -> bool {
+        self.pools.iter().all(|(_, list)| list.is_empty())
+    }
+
+    /// Gets an iterator over the keys of all elements and their values, pool by pool
+    /// and in ascending index order within each pool.
+    pub fn iter(&self) -> impl Iterator<Item = (NestedKey<TIndex>, &T)> + '_ {
+        self.pools.iter().flat_map(move |(pool, list)| {
+            let pool = self.pools.key(pool).expect("index is occupied");
+            list.iter().map(move |(slot, value)| {
+                let slot = list.key(slot).expect("index is occupied");
+                (NestedKey::new(pool, slot), value)
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_of_freed_pools_are_rejected_after_reuse() {
+        let mut pools = NestedFreeList::<u32, u8>::default();
+        let a = pools.create_pool();
+        let b = pools.create_pool();
+        let a0 = pools.push(a, 1);
+        let b0 = pools.push(b, 2);
+        let b1 = pools.push(b, 3);
+        assert_eq!(pools.remove(b0), Some(2));
+        assert_eq!(pools.remove(b0), None);
+
+        assert!(pools.free_pool(a).is_some());
+        assert!(pools.free_pool(a).is_none());
+        let c = pools.create_pool();
+        assert_eq!(c.index(), a.index());
+        let c0 = pools.push(c, 4);
+        assert_eq!(c0.slot(), a0.slot());
+        assert_eq!(pools.get(a0), None);
+        assert!(!pools.contains_key(a0));
+
+        *pools.get_mut(c0).unwrap() += 10;
+        assert_eq!(pools.iter().collect::<Vec<_>>(), [(c0, &14), (b1, &3)]);
+        assert_eq!((pools.pool_count(), pools.len()), (2, 2));
+    }
+
+    #[test]
+    #[should_panic(expected = "Attempted to insert into a pool that does not exist")]
+    fn pushing_into_a_freed_pool_panics() {
+        let mut pools = NestedFreeList::<u32, u8>::default();
+        let pool = pools.create_pool();
+        pools.free_pool(pool);
+        pools.push(pool, 1);
+    }
+}
@@ -0,0 +1,377 @@
+use crate::index_type::*;
+use crate::FreeList;
+
+/// A pool of tree nodes stored in a [`FreeList`] and linked by index.
+///
+/// Every node knows its parent, its first child and its next sibling, which is enough
+/// to represent trees of any degree, such as quadtrees or octrees. Whole subtrees can be
+/// freed at once, and their slots are reused by subsequently added nodes. The pool may
+/// hold any number of trees; nodes without a parent are roots.
+///
+/// ## Type parameters
+/// * `T` - The type of the node values.
+/// * `TIndex` - The type of the node handles; see [`FreeList`] for details.
+///
+/// ## Example
+/// ```rust
+/// use free_list::NodePool;
+///
+/// let mut pool = NodePool::<&str, u16>::default();
+/// let root = pool.add_root("root");
+/// let north = pool.add_child(root, "north");
+/// let south = pool.add_child(root, "south");
+/// pool.add_child(north, "north-west");
+/// pool.add_child(north, "north-east");
+///
+/// assert_eq!(pool.children(root).collect::<Vec<_>>(), [north, south]);
+/// assert_eq!(pool.remove_subtree(north), 3);
+/// assert_eq!(pool.children(root).collect::<Vec<_>>(), [south]);
+/// assert_eq!(pool.len(), 2);
+/// ```
+pub struct NodePool<T, TIndex = DefaultIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    nodes: FreeList<Node<T, TIndex>, TIndex>,
+}
+
+/// A node of a [`NodePool`].
+struct Node<T, TIndex> {
+    value: T,
+    /// The parent node, or `SENTINEL` for roots.
+    parent: TIndex,
+    /// The first child node, or `SENTINEL` for leaves.
+    first_child: TIndex,
+    /// The next child of the same parent, or `SENTINEL` for the last child and roots.
+    next_sibling: TIndex,
+}
+
+impl<T, TIndex> Default for Node<T, TIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    fn default() -> Self {
+        Self {
+            value: T::default(),
+            parent: TIndex::MAX,
+            first_child: TIndex::MAX,
+            next_sibling: TIndex::MAX,
+        }
+    }
+}
+
+impl<T, TIndex> Default for NodePool<T, TIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    fn default() -> Self {
+        Self {
+            nodes: FreeList::default(),
+        }
+    }
+}
+
+impl<T, TIndex> NodePool<T, TIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    /// The sentinel value indicates the absence of a node.
+    const SENTINEL: TIndex = TIndex::MAX;
+
+    /// Adds a node without a parent and returns its handle.
+    pub fn add_root(&mut self, value: T) -> TIndex {
+        self.nodes.push(Node {
+            value,
+            parent: Self::SENTINEL,
+            first_child: Self::SENTINEL,
+            next_sibling: Self::SENTINEL,
+        })
+    }
+
+    /// Adds a node as the last child of the specified parent and returns its handle.
+    ///
+    /// Takes time proportional to the number of children the parent already has.
+    ///
+    /// ## Panics
+    /// Panics if the parent does not exist.
+    pub fn add_child(&mut self, parent: TIndex, value: T) -> TIndex {
+        assert!(
+            self.nodes.contains(parent),
+            "Attempted to add a child to a node that does not exist ({parent:?})"
+        );
+        let last = self.children(parent).last();
+        let node = self.nodes.push(Node {
+            value,
+            parent,
+            first_child: Self::SENTINEL,
+            next_sibling: Self::SENTINEL,
+        });
+        match last {
+            Some(last) => self.nodes[last].next_sibling = node,
+            None => self.nodes[parent].first_child = node,
+        }
+        node
+    }
+
+    /// Removes the specified node together with all its descendants and returns the
+    /// number of removed nodes, or zero if the node does not exist.
+    pub fn remove_subtree(&mut self, node: TIndex) -> usize {
+        if !self.nodes.contains(node) {
+            return 0;
+        }
+        self.unlink(node);
+        self.free(node)
+    }
+
+    /// Removes all descendants of the specified node and returns their number, keeping
+    /// the node itself. This is how a quadtree cell is collapsed back into a leaf.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::NodePool;
+    ///
+    /// let mut pool = NodePool::<u32, u8>::default();
+    /// let root = pool.add_root(0);
+    /// for quadrant in 1..=4 {
+    ///     pool.add_child(root, quadrant);
+    /// }
+    /// assert_eq!(pool.remove_children(root), 4);
+    /// assert!(pool.is_leaf(root));
+    /// ```
+    pub fn remove_children(&mut self, node: TIndex) -> usize {
+        let Some(parent) = self.nodes.get_mut(node) else {
+            return 0;
+        };
+        let mut child = std::mem::replace(&mut parent.first_child, Self::SENTINEL);
+        let mut removed = 0;
+        while child != Self::SENTINEL {
+            let next = self.nodes[child].next_sibling;
+            removed += self.free(child);
+            child = next;
+        }
+        removed
+    }
+
+    /// Gets a reference to the value of the specified node.
+    #[inline]
+    pub fn get(&self, node: TIndex) -> Option<&T> {
+        self.nodes.get(node).map(|node| &node.value)
+    }
+
+    /// Gets a mutable reference to the value of the specified node.
+    #[inline]
+    pub fn get_mut(&mut self, node: TIndex) -> Option<&mut T> {
+        self.nodes.get_mut(node).map(|node| &mut node.value)
+    }
+
+    /// Determines whether the specified node exists.
+    #[inline]
+    pub fn contains(&self, node: TIndex) -> bool {
+        self.nodes.contains(node)
+    }
+
+    /// Gets the parent of the specified node, or `None` for roots and removed nodes.
+    pub fn parent(&self, node: TIndex) -> Option<TIndex> {
+        let parent = self.nodes.get(node)?.parent;
+        (parent != Self::SENTINEL).then_some(parent)
+    }
+
+    /// Gets the first child of the specified node.
+    pub fn first_child(&self, node: TIndex) -> Option<TIndex> {
+        let child = self.nodes.get(node)?.first_child;
+        (child != Self::SENTINEL).then_some(child)
+    }
+
+    /// Gets the next sibling of the specified node.
+    pub fn next_sibling(&self, node: TIndex) -> Option<TIndex> {
+        let sibling = self.nodes.get(node)?.next_sibling;
+        (sibling != Self::SENTINEL).then_some(sibling)
+    }
+
+    /// Determines whether the specified node exists and has no children.
+    #[inline]
+    pub fn is_leaf(&self, node: TIndex) -> bool {
+        self.nodes
+            .get(node)
+            .is_some_and(|node| node.first_child == Self::SENTINEL)
+    }
+
+    /// Gets the number of nodes in the pool.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Determines whether the pool contains no nodes.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Removes all nodes from the pool.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+    }
+
+    /// Gets an iterator over the children of the specified node, in insertion order.
+    /// The iterator is empty if the node does not exist.
+    pub fn children(&self, node: TIndex) -> impl Iterator<Item = TIndex> + '_ {
+        let mut child = self
+            .nodes
+            .get(node)
+            .map_or(Self::SENTINEL, |node| node.first_child);
+        std::iter::from_fn(move || {
+            if child == Self::SENTINEL {
+                return None;
+            }
+            let current = child;
+            child = self.nodes[child].next_sibling;
+            Some(current)
+        })
+    }
+
+    /// Gets an iterator over the specified node and all its descendants, in depth-first
+    /// pre-order. The iterator is empty if the node does not exist.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::NodePool;
+    ///
+    /// let mut pool = NodePool::<u32, u8>::default();
+    /// let root = pool.add_root(0);
+    /// let a = pool.add_child(root, 1);
+    /// let b = pool.add_child(root, 2);
+    /// let c = pool.add_child(a, 3);
+    /// assert_eq!(pool.descendants(root).collect::<Vec<_>>(), [root, a, c, b]);
+    /// ```
+    pub fn descendants(&self, node: TIndex) -> impl Iterator<Item = TIndex> + '_ {
+        let mut next = if self.nodes.contains(node) {
+            node
+        } else {
+            Self::SENTINEL
+        };
+        std::iter::from_fn(move || {
+            if next == Self::SENTINEL {
+                return None;
+            }
+            let current = next;
+            next = self.successor(current, node);
+            Some(current)
+        })
+    }
+
+    /// Gets the node following `current` in a pre-order traversal of the subtree of `root`.
+    fn successor(&self, current: TIndex, root: TIndex) -> TIndex {
+        let first_child = self.nodes[current].first_child;
+        if first_child != Self::SENTINEL {
+            return first_child;
+        }
+        let mut node = current;
+        while node != root {
+            let sibling = self.nodes[node].next_sibling;
+            if sibling != Self::SENTINEL {
+                return sibling;
+            }
+            node = self.nodes[node].parent;
+        }
+        Self::SENTINEL
+    }
+
+    /// Detaches the specified node from the child list of its parent.
+    fn unlink(&mut self, node: TIndex) {
+        let parent = self.nodes[node].parent;
+        if parent == Self::SENTINEL {
+            return;
+        }
+        let next = self.nodes[node].next_sibling;
+        if self.nodes[parent].first_child == node {
+            self.nodes[parent].first_child = next;
+        } else {
+            let prev = self
+                .children(parent)
+                .find(|&child| self.nodes[child].next_sibling == node)
+                .expect("a child is linked from its parent");
+            self.nodes[prev].next_sibling = next;
+        }
+    }
+
+    /// Frees the specified node and all its descendants without touching the links
+    /// that lead to the node, and returns the number of freed nodes.
+    fn free(&mut self, node: TIndex) -> usize {
+        let mut pending = vec![node];
+        let mut removed = 0;
+        while let Some(node) = pending.pop() {
+            let mut child = self.nodes[node].first_child;
+            while child != Self::SENTINEL {
+                pending.push(child);
+                child = self.nodes[child].next_sibling;
+            }
+            self.nodes.erase(node);
+            removed += 1;
+        }
+        removed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subtrees_are_freed_and_reused() {
+        let mut pool = NodePool::<u32, u8>::default();
+        let root = pool.add_root(0);
+        let a = pool.add_child(root, 1);
+        let b = pool.add_child(root, 2);
+        let c = pool.add_child(root, 3);
+        let a1 = pool.add_child(a, 11);
+        pool.add_child(a1, 111);
+        let b1 = pool.add_child(b, 21);
+
+        // Removing a middle child keeps its siblings linked.
+        assert_eq!(pool.remove_subtree(b), 2);
+        assert_eq!(pool.remove_subtree(b), 0);
+        assert!(!pool.contains(b1));
+        assert_eq!(pool.children(root).collect::<Vec<_>>(), [a, c]);
+        assert_eq!(pool.next_sibling(a), Some(c));
+
+        // Removing a first child updates the parent.
+        assert_eq!(pool.remove_subtree(a), 3);
+        assert_eq!(pool.first_child(root), Some(c));
+        assert_eq!(pool.len(), 2);
+
+        let d = pool.add_child(c, 4);
+        assert_eq!(pool.parent(d), Some(c));
+        assert_eq!(pool.descendants(root).collect::<Vec<_>>(), [root, c, d]);
+        assert_eq!(pool.get(d), Some(&4));
+
+        assert_eq!(pool.remove_subtree(root), 3);
+        assert!(pool.is_empty());
+    }
+
+    #[test]
+    fn deep_trees_do_not_overflow_the_stack() {
+        let mut pool = NodePool::<u32, u32>::default();
+        let root = pool.add_root(0);
+        let mut node = root;
+        for i in 1..100_000 {
+            node = pool.add_child(node, i);
+        }
+        assert_eq!(pool.descendants(root).count(), 100_000);
+        assert_eq!(pool.remove_children(root), 99_999);
+        assert!(pool.is_leaf(root));
+    }
+
+    #[test]
+    #[should_panic]
+    fn adding_to_a_removed_node_panics() {
+        let mut pool = NodePool::<u32, u8>::default();
+        let root = pool.add_root(0);
+        pool.remove_subtree(root);
+        pool.add_child(root, 1);
+    }
+}
@@ -0,0 +1,194 @@
+use crate::index_type::*;
+use std::cmp::Ordering;
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::num::{NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize};
+
+macro_rules! non_max {
+    ($(#[$meta:meta])* $name:ident, $primitive:ty, $non_zero:ty) => {
+        $(#[$meta])*
+        ///
+        /// The value is stored as its bitwise complement in a non-zero integer, so
+        /// that the unrepresentable primitive `MAX` value becomes a niche and
+        /// `Option<Self>` has the same size as the primitive type.
+        ///
+        /// When used as an index type, the largest representable value acts as the
+        /// sentinel, so at most `MAX - 2` elements can be stored in a list.
+        #[derive(Copy, Clone, PartialEq, Eq)]
+        pub struct $name($non_zero);
+
+        impl $name {
+            /// Creates a new value, or returns `None` if `value` is the primitive `MAX` value.
+            #[inline]
+            pub const fn new(value: $primitive) -> Option<Self> {
+                match <$non_zero>::new(!value) {
+                    Some(inner) => Some(Self(inner)),
+                    None => None,
+                }
+            }
+
+            /// Gets the primitive value.
+            #[inline]
+            pub const fn get(self) -> $primitive {
+                !self.0.get()
+            }
+        }
+
+        impl PartialOrd for $name {
+            #[inline]
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for $name {
+            #[inline]
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.get().cmp(&other.get())
+            }
+        }
+
+        impl Hash for $name {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.get().hash(state)
+            }
+        }
+
+        impl Debug for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                Debug::fmt(&self.get(), f)
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                Display::fmt(&self.get(), f)
+            }
+        }
+
+        impl From<$name> for $primitive {
+            #[inline]
+            fn from(value: $name) -> Self {
+                value.get()
+            }
+        }
+
+        impl MaxValue for $name {
+            const MAX: $name = match $name::new(<$primitive>::MAX - 1) {
+                Some(value) => value,
+                None => unreachable!(),
+            };
+        }
+
+        impl FromAndIntoUsize for $name {
+            #[inline]
+            fn try_from_usize(value: usize) -> Option<Self> {
+                <$primitive>::try_from_usize(value).and_then(Self::new)
+            }
+
+            #[inline]
+            fn into_usize(self) -> usize {
+                self.get().into_usize()
+            }
+        }
+    };
+}
+
+non_max!(
+    /// An unsigned 8-bit integer that cannot be `u8::MAX`.
+    NonMaxU8,
+    u8,
+    NonZeroU8
+);
+
+non_max!(
+    /// An unsigned 16-bit integer that cannot be `u16::MAX`.
+    NonMaxU16,
+    u16,
+    NonZeroU16
+);
+
+non_max!(
+    /// An unsigned 32-bit integer that cannot be `u32::MAX`.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::{FreeList, NonMaxU32};
+    ///
+    /// let mut list = FreeList::<&str, NonMaxU32>::default();
+    /// let index = list.push("first");
+    /// assert_eq!(index.get(), 0);
+    ///
+    /// // Optional indices do not take up any additional space.
+    /// assert_eq!(std::mem::size_of::<Option<NonMaxU32>>(), 4);
+    /// ```
+    NonMaxU32,
+    u32,
+    NonZeroU32
+);
+
+non_max!(
+    /// An unsigned 64-bit integer that cannot be `u64::MAX`.
+    NonMaxU64,
+    u64,
+    NonZeroU64
+);
+
+non_max!(
+    /// A pointer-sized unsigned integer that cannot be `usize::MAX`.
+    NonMaxUsize,
+    usize,
+    NonZeroUsize
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FreeList;
+    use std::mem::size_of;
+
+    #[test]
+    fn option_has_no_overhead() {
+        assert_eq!(size_of::<Option<NonMaxU8>>(), size_of::<u8>());
+        assert_eq!(size_of::<Option<NonMaxU16>>(), size_of::<u16>());
+        assert_eq!(size_of::<Option<NonMaxU32>>(), size_of::<u32>());
+        assert_eq!(size_of::<Option<NonMaxU64>>(), size_of::<u64>());
+        assert_eq!(size_of::<Option<NonMaxUsize>>(), size_of::<usize>());
+    }
+
+    #[test]
+    fn max_is_not_representable() {
+        assert!(NonMaxU8::new(u8::MAX).is_none());
+        assert_eq!(NonMaxU8::new(0).unwrap().get(), 0);
+        assert_eq!(<NonMaxU8 as MaxValue>::MAX.get(), 254);
+    }
+
+    #[test]
+    fn ordering_follows_value() {
+        let small = NonMaxU16::new(1).unwrap();
+        let large = NonMaxU16::new(1000).unwrap();
+        assert!(small < large);
+        assert!(<NonMaxU16 as MaxValue>::MAX > large);
+    }
+
+    #[test]
+    fn usable_as_index() {
+        let mut list = FreeList::<u32, NonMaxU8>::default();
+        for i in 0..253 {
+            assert_eq!(list.push(i).get(), i as u8);
+        }
+        list.erase(NonMaxU8::new(10).unwrap());
+        assert_eq!(list.push(0).get(), 10);
+        assert_eq!(list.keys().count(), 253);
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg_attr(not(any(debug_assertions, feature = "checked")), ignore)]
+    fn inserting_too_many_panics() {
+        let mut list = FreeList::<u32, NonMaxU8>::default();
+        for i in 0..254 {
+            list.push(i);
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use crate::index_type::*;
+use crate::Key;
+
+/// Receives notifications about the elements inserted into and removed from a
+/// [`FreeList`](crate::FreeList), e.g. to keep secondary indexes, caches or metrics
+/// in sync without wrapping every call site.
+///
+/// An observer is registered with [`FreeList::set_observer`](crate::FreeList::set_observer).
+/// It is notified after the list was updated; all methods do nothing by default.
+///
+/// ## Example
+/// ```rust
+/// use free_list::{FreeList, Key, Observer};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::sync::Arc;
+///
+/// struct Counter(Arc<AtomicUsize>);
+///
+/// impl Observer<u8> for Counter {
+///     fn on_insert(&mut self, _key: Key<u8>) {
+///         self.0.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let inserted = Arc::new(AtomicUsize::new(0));
+/// let mut list = FreeList::<&str, u8>::default();
+/// list.set_observer(Counter(inserted.clone()));
+/// list.push("first");
+/// list.push("second");
+/// assert_eq!(inserted.load(Ordering::Relaxed), 2);
+/// ```
+pub trait Observer<TIndex = DefaultIndex>: Send + Sync
+where
+    TIndex: IndexType,
+{
+    /// Called after an element was inserted, with the key of the element.
+    fn on_insert(&mut self, key: Key<TIndex>) {
+        let _ = key;
+    }
+
+    /// Called after an element was removed, with the key the element had.
+    ///
+    /// This includes elements moved into another list by
+    /// [`split_off`](crate::FreeList::split_off).
+    fn on_erase(&mut self, key: Key<TIndex>) {
+        let _ = key;
+    }
+
+    /// Called after all elements were removed at once by [`clear`](crate::FreeList::clear)
+    /// or [`reset`](crate::FreeList::reset), including when the list is dropped;
+    /// [`on_erase`](Self::on_erase) is not called for these elements.
+    fn on_clear(&mut self) {}
+}
@@ -0,0 +1,406 @@
+use crate::bitset::BitSet;
+use crate::index_type::*;
+use crate::macros::safety_check;
+use crate::{FreeElement, Keys, SlotStatus};
+use std::mem::ManuallyDrop;
+
+/// An indexed free list that stores its slots in fixed-size pages.
+///
+/// Unlike [`FreeList`](crate::FreeList), inserting elements never moves existing
+/// elements in memory: once a page is allocated, it stays in place until the list is
+/// cleared or dropped. References obtained through raw pointers remain valid across
+/// calls to [`push`](Self::push) for as long as the element is not erased.
+///
+/// ## Type parameters
+/// * `T` - The type of the element.
+/// * `TIndex` - The type of the index; see [`FreeList`](crate::FreeList) for details.
+/// * `PAGE_SIZE` - The number of slots per page. Must not be zero.
+pub struct PagedFreeList<T, TIndex = DefaultIndex, const PAGE_SIZE: usize = 256>
+where
+    TIndex: IndexType,
+{
+    /// The pages; each page holds exactly `PAGE_SIZE` slots.
+    pages: Vec<Box<[FreeElement<T, TIndex>]>>,
+    /// The number of slots that were handed out so far.
+    slots: usize,
+    /// The set of indices of all slots that currently hold an element.
+    occupied: BitSet,
+    /// The index of the the most recently freed element, or `SENTINEL` if no
+    /// element is free.
+    first_free: TIndex,
+}
+
+impl<T, TIndex, const PAGE_SIZE: usize> Default for PagedFreeList<T, TIndex, PAGE_SIZE>
+where
+    TIndex: IndexType,
+{
+    /// Creates an empty list.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::PagedFreeList;
+    ///
+    /// let list = PagedFreeList::<&str, u8>::default();
+    /// assert_eq!(list.slot_count(), 0);
+    /// ```
+    fn default() -> Self {
+        assert_ne!(PAGE_SIZE, 0, "The page size must not be zero");
+        Self {
+            pages: Vec::new(),
+            slots: 0,
+            occupied: BitSet::default(),
+            first_free: Self::SENTINEL,
+        }
+    }
+}
+
+impl<T, TIndex, const PAGE_SIZE: usize> PagedFreeList<T, TIndex, PAGE_SIZE>
+where
+    TIndex: IndexType,
+{
+    /// The sentinel value indicates the absence of a valid value.
+    pub(crate) const SENTINEL: TIndex = TIndex::MAX;
+
+    /// Inserts an element to the list and returns an index to it.
+    ///
+    /// Existing elements are never moved by this operation.
+    ///
+    /// ## Panics
+    /// Panics if the index type cannot address any more elements.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::PagedFreeList;
+    ///
+    /// let mut list = PagedFreeList::<u32, u8, 2>::default();
+    /// let first = list.push(1);
+    /// let address = unsafe { list.at(first) } as *const u32;
+    ///
+    /// // Adding more elements allocates new pages but does not move the first one.
+    /// for i in 0..10 {
+    ///     list.push(i);
+    /// }
+    /// assert_eq!(unsafe { list.at(first) } as *const u32, address);
+    /// ```
+    pub fn push(&mut self, element: T) -> TIndex {
+        if self.first_free != Self::SENTINEL {
+            let index = self.first_free;
+            let slot = self.slot_mut(index.into_usize());
+
+            // Set the "first free" pointer to the next free index.
+            let next = unsafe { slot.next };
+
+            // Place the element into the previously free location.
+            slot.element = ManuallyDrop::new(element);
+            self.first_free = next;
+            self.occupied.insert(index.into_usize());
+            index
+        } else {
+            let index = self.slots;
+            assert!(
+                index < Self::SENTINEL.into_usize() - 1,
+                "Attempted to insert more elements than can be addressed by the provided index type ({:?} allowed)",
+                TIndex::MAX
+            );
+
+            if index == self.pages.len() * PAGE_SIZE {
+                self.pages.push(Self::new_page());
+            }
+
+            self.slots += 1;
+            self.slot_mut(index).element = ManuallyDrop::new(element);
+            self.occupied.insert(index);
+            index_from_usize(index)
+        }
+    }
+
+    /// Removes the nth element from the list.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::PagedFreeList;
+    ///
+    /// let mut list = PagedFreeList::<&str, u8>::default();
+    /// list.push("uses one slot");
+    /// list.erase(0);
+    ///
+    /// // The slot is reused by the next insertion.
+    /// assert_eq!(list.push("uses the same slot"), 0);
+    /// assert_eq!(list.slot_count(), 1);
+    /// ```
+    pub fn erase(&mut self, n: TIndex) {
+        let n_usize = n.into_usize();
+        safety_check!(
+            self.occupied.contains(n_usize),
+            "Attempted to erase a slot that is not occupied ({:?})",
+            n
+        );
+        if !self.occupied.contains(n_usize) {
+            return;
+        }
+
+        let first_free = self.first_free;
+        let slot = self.slot_mut(n_usize);
+        unsafe { ManuallyDrop::drop(&mut slot.element) };
+        slot.next = first_free;
+        self.occupied.remove(n_usize);
+        self.first_free = n;
+    }
+
+    /// Removes all elements from the list and frees all pages.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::PagedFreeList;
+    ///
+    /// let mut list = PagedFreeList::<&str, u8>::default();
+    /// list.push("one");
+    /// list.push("two");
+    ///
+    /// list.clear();
+    /// assert_eq!(list.slot_count(), 0);
+    /// ```
+    pub fn clear(&mut self) {
+        // Only occupied slots hold an element that needs to be dropped.
+        for index in self.occupied.ones() {
+            let slot = &mut self.pages[index / PAGE_SIZE][index % PAGE_SIZE];
+            unsafe { ManuallyDrop::drop(&mut slot.element) };
+        }
+
+        self.pages.clear();
+        self.occupied.clear();
+        self.slots = 0;
+        self.first_free = Self::SENTINEL;
+    }
+
+    /// Gets a reference to the value at the specified index.
+    ///
+    /// # Safety
+    ///
+    /// If the element at the specified index was erased, the slot now acts
+    /// as a pointer to the next free element. Accessing the same index again after that
+    /// is undefined behavior.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::PagedFreeList;
+    ///
+    /// let mut list = PagedFreeList::<&str, u8>::default();
+    /// assert_eq!(list.push("first"), 0);
+    /// assert_eq!(unsafe { list.at(0) }, &"first");
+    /// ```
+    #[inline]
+    pub unsafe fn at(&self, index: TIndex) -> &T {
+        let index = index.into_usize();
+        safety_check!(
+            self.occupied.contains(index),
+            "Attempted to access a slot that is not occupied ({:?})",
+            index
+        );
+        &self.pages[index / PAGE_SIZE][index % PAGE_SIZE].element
+    }
+
+    /// Gets a mutable reference to the value at the specified index.
+    ///
+    /// # Safety
+    ///
+    /// If the element at the specified index was erased, the slot now acts
+    /// as a pointer to the next free element. Accessing the same index again after that
+    /// is undefined behavior.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::PagedFreeList;
+    ///
+    /// let mut list = PagedFreeList::<&str, u8>::default();
+    /// list.push("first");
+    /// *unsafe { list.at_mut(0) } = "changed";
+    /// assert_eq!(unsafe { list.at(0) }, &"changed");
+    /// ```
+    #[inline]
+    pub unsafe fn at_mut(&mut self, index: TIndex) -> &mut T {
+        let index = index.into_usize();
+        safety_check!(
+            self.occupied.contains(index),
+            "Attempted to access a slot that is not occupied ({:?})",
+            index
+        );
+        &mut self.pages[index / PAGE_SIZE][index % PAGE_SIZE].element
+    }
+
+    /// Determines the state of the slot at the specified index.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::{PagedFreeList, SlotStatus};
+    ///
+    /// let mut list = PagedFreeList::<&str, u8>::default();
+    /// list.push("first");
+    /// list.push("second");
+    /// list.erase(0);
+    ///
+    /// assert_eq!(list.status(0), SlotStatus::Free);
+    /// assert_eq!(list.status(1), SlotStatus::Occupied);
+    /// assert_eq!(list.status(2), SlotStatus::OutOfRange);
+    /// ```
+    pub fn status(&self, index: TIndex) -> SlotStatus {
+        let index = index.into_usize();
+        if index >= self.slots {
+            SlotStatus::OutOfRange
+        } else if self.occupied.contains(index) {
+            SlotStatus::Occupied
+        } else {
+            SlotStatus::Free
+        }
+    }
+
+    /// Gets an iterator over the indices of all occupied slots, in ascending order.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::PagedFreeList;
+    ///
+    /// let mut list = PagedFreeList::<&str, u8>::default();
+    /// list.push("first");
+    /// list.push("second");
+    /// list.erase(0);
+    /// assert_eq!(list.keys().collect::<Vec<_>>(), [1]);
+    /// ```
+    pub fn keys(&self) -> Keys<'_, TIndex> {
+        Keys::new(self.occupied.ones())
+    }
+
+    /// Gets the number of slots that were handed out so far.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::PagedFreeList;
+    ///
+    /// let mut list = PagedFreeList::<&str, u8>::default();
+    /// list.push("first");
+    /// list.push("second");
+    /// list.erase(0);
+    /// assert_eq!(list.slot_count(), 2);
+    /// ```
+    pub fn slot_count(&self) -> usize {
+        self.slots
+    }
+
+    /// Gets the number of allocated pages.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::PagedFreeList;
+    ///
+    /// let mut list = PagedFreeList::<&str, u8, 2>::default();
+    /// list.push("first");
+    /// list.push("second");
+    /// list.push("third");
+    /// assert_eq!(list.page_count(), 2);
+    /// ```
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Allocates a new page with all slots unused.
+    fn new_page() -> Box<[FreeElement<T, TIndex>]> {
+        (0..PAGE_SIZE)
+            .map(|_| FreeElement {
+                next: Self::SENTINEL,
+            })
+            .collect()
+    }
+
+    /// Gets the slot at the specified index.
+    #[inline]
+    fn slot_mut(&mut self, index: usize) -> &mut FreeElement<T, TIndex> {
+        &mut self.pages[index / PAGE_SIZE][index % PAGE_SIZE]
+    }
+}
+
+impl<T, TIndex, const PAGE_SIZE: usize> Drop for PagedFreeList<T, TIndex, PAGE_SIZE>
+where
+    TIndex: IndexType,
+{
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn push_allocates_pages_lazily() {
+        let mut list = PagedFreeList::<u32, u8, 4>::default();
+        assert_eq!(list.page_count(), 0);
+
+        for i in 0..5 {
+            assert_eq!(list.push(i), i as u8);
+        }
+        assert_eq!(list.page_count(), 2);
+        assert_eq!(list.slot_count(), 5);
+    }
+
+    #[test]
+    fn elements_do_not_move() {
+        let mut list = PagedFreeList::<u32, u16, 4>::default();
+        let first = list.push(42);
+        let address = unsafe { list.at(first) } as *const u32;
+
+        for i in 0..100 {
+            list.push(i);
+        }
+
+        assert_eq!(unsafe { list.at(first) } as *const u32, address);
+        assert_eq!(unsafe { *address }, 42);
+    }
+
+    #[test]
+    fn erase_reuses_slots() {
+        let mut list = PagedFreeList::<u32, u8, 4>::default();
+        for i in 0..6 {
+            list.push(i);
+        }
+
+        list.erase(1);
+        list.erase(5);
+        assert_eq!(list.status(5), SlotStatus::Free);
+        assert_eq!(list.keys().collect::<Vec<_>>(), vec![0, 2, 3, 4]);
+
+        assert_eq!(list.push(10), 5);
+        assert_eq!(list.push(11), 1);
+        assert_eq!(list.push(12), 6);
+        assert_eq!(unsafe { *list.at(1) }, 11);
+    }
+
+    #[test]
+    fn clear_and_drop_drop_live_elements() {
+        let value = Rc::new(());
+        let mut list = PagedFreeList::<Rc<()>, u8, 2>::default();
+        for _ in 0..5 {
+            list.push(value.clone());
+        }
+        list.erase(2);
+        assert_eq!(Rc::strong_count(&value), 5);
+
+        list.clear();
+        assert_eq!(Rc::strong_count(&value), 1);
+        assert_eq!(list.slot_count(), 0);
+
+        list.push(value.clone());
+        drop(list);
+        assert_eq!(Rc::strong_count(&value), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn inserting_too_many_panics() {
+        let mut list = PagedFreeList::<u32, u8, 16>::default();
+        for i in 0..255 {
+            list.push(i);
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use crate::index_type::*;
+use crate::FreeList;
+use allocator_api2::alloc::Allocator;
+use rayon::iter::Either;
+use rayon::prelude::*;
+use std::ptr::NonNull;
+
+impl<T, TIndex, A> FreeList<T, TIndex, A>
+where
+    T: Default,
+    TIndex: IndexType,
+    A: Allocator,
+{
+    /// Gets a parallel iterator over the indices and values of all occupied slots.
+    ///
+    /// The slot array is split across the rayon thread pool; free slots are skipped.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    /// use rayon::prelude::*;
+    ///
+    /// let mut list = FreeList::<u32, u16>::default();
+    /// for i in 0..1000 {
+    ///     list.push(i);
+    /// }
+    /// list.erase(0);
+    ///
+    /// let sum: u32 = list.par_iter().map(|(_, value)| *value).sum();
+    /// assert_eq!(sum, (1..1000).sum::<u32>());
+    /// ```
+    pub fn par_iter(&self) -> impl ParallelIterator<Item = (TIndex, &T)> + '_
+    where
+        T: Sync,
+        TIndex: Send + Sync,
+    {
+        let occupied = &self.occupied;
+        if Self::IS_ZST {
+            // Zero-sized elements are not stored, so the slots are enumerated instead.
+            return Either::Left(
+                (0..self.slot_count())
+                    .into_par_iter()
+                    .filter(move |index| occupied.contains(*index))
+                    .map(|index| {
+                        // SAFETY: References to zero-sized values are valid at any aligned address.
+                        let element = unsafe { &*NonNull::dangling().as_ptr() };
+                        (index_from_usize(index), element)
+                    }),
+            );
+        }
+
+        Either::Right(
+            self.data
+                .par_iter()
+                .enumerate()
+                .filter(move |(index, _)| occupied.contains(*index))
+                .map(|(index, element)| {
+                    // SAFETY: Only occupied slots are yielded.
+                    let element = unsafe { &*element.element };
+                    (index_from_usize(index), element)
+                }),
+        )
+    }
+
+    /// Gets a parallel iterator over the indices and mutable values of all occupied slots.
+    ///
+    /// The slot array is split across the rayon thread pool; free slots are skipped.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    /// use rayon::prelude::*;
+    ///
+    /// let mut list = FreeList::<u32, u16>::default();
+    /// for i in 0..1000 {
+    ///     list.push(i);
+    /// }
+    ///
+    /// list.par_iter_mut().for_each(|(_, value)| *value *= 2);
+    /// assert_eq!(unsafe { list.at(500) }, &1000);
+    /// ```
+    pub fn par_iter_mut(&mut self) -> impl ParallelIterator<Item = (TIndex, &mut T)> + '_
+    where
+        T: Send,
+        TIndex: Send + Sync,
+    {
+        self.ticks.changed(self.occupied.ones());
+        let occupied = &self.occupied;
+        if Self::IS_ZST {
+            // Zero-sized elements are not stored, so the slots are enumerated instead.
+            return Either::Left(
+                (0..self.slot_count())
+                    .into_par_iter()
+                    .filter(move |index| occupied.contains(*index))
+                    .map(|index| {
+                        // SAFETY: References to zero-sized values are valid at any aligned
+                        // address and never alias.
+                        let element = unsafe { &mut *NonNull::dangling().as_ptr() };
+                        (index_from_usize(index), element)
+                    }),
+            );
+        }
+
+        Either::Right(
+            self.data
+                .par_iter_mut()
+                .enumerate()
+                .filter(move |(index, _)| occupied.contains(*index))
+                .map(|(index, element)| {
+                    // SAFETY: Only occupied slots are yielded.
+                    let element = unsafe { &mut *element.element };
+                    (index_from_usize(index), element)
+                }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FreeList;
+    use rayon::prelude::*;
+
+    #[test]
+    fn par_iter_skips_free_slots() {
+        let mut list = FreeList::<usize, u16>::default();
+        for i in 0..500 {
+            list.push(i);
+        }
+        for i in (0..500).step_by(3) {
+            list.erase(i);
+        }
+
+        let mut keys: Vec<_> = list.par_iter().map(|(key, _)| key).collect();
+        keys.sort_unstable();
+        assert_eq!(keys, list.keys().collect::<Vec<_>>());
+        assert!(list.par_iter().all(|(key, value)| key as usize == *value));
+    }
+
+    #[test]
+    fn par_iter_mut_modifies_occupied_slots() {
+        let mut list = FreeList::<usize, u16>::default();
+        for i in 0..500 {
+            list.push(i);
+        }
+        list.erase(10);
+
+        list.par_iter_mut().for_each(|(_, value)| *value += 1);
+        for key in list.keys() {
+            assert_eq!(unsafe { *list.at(key) }, key as usize + 1);
+        }
+    }
+}
@@ -0,0 +1,388 @@
+use crate::index_type::*;
+use std::rc::Rc;
+
+/// The number of index bits consumed by every level of the trie.
+const BITS: usize = 5;
+/// The number of children or slots of every node.
+const WIDTH: usize = 1 << BITS;
+const MASK: usize = WIDTH - 1;
+
+/// An immutable indexed free list whose versions share their structure.
+///
+/// Every modification returns a new version of the list and leaves the original
+/// untouched, so older versions stay readable at no additional cost. The slots are
+/// stored in a trie of 32-way nodes; a modification copies only the path from the root
+/// to the affected leaf, which takes `O(log n)` time and memory. Cloning a version is
+/// constant-time. This makes the list suited for undo/redo stacks and for speculatively
+/// branching a simulation state.
+///
+/// ## Type parameters
+/// * `T` - The type of the element. Elements are cloned when their leaf is copied.
+/// * `TIndex` - The type of the index; see [`FreeList`](crate::FreeList) for details.
+///
+/// ## Example
+/// ```rust
+/// use free_list::PersistentFreeList;
+///
+/// let empty = PersistentFreeList::<&str, u8>::new();
+/// let (one, first) = empty.push("first");
+/// let (two, second) = one.push("second");
+/// let undone = two.erase(first).unwrap();
+///
+/// assert_eq!(one.get(first), Some(&"first"));
+/// assert_eq!(two.len(), 2);
+/// assert_eq!(undone.get(first), None);
+/// assert_eq!(undone.get(second), Some(&"second"));
+/// ```
+pub struct PersistentFreeList<T, TIndex = DefaultIndex>
+where
+    TIndex: IndexType,
+{
+    /// The root of the slot trie.
+    root: Rc<Node<T>>,
+    /// The number of trie levels above the leaves.
+    depth: u32,
+    /// The number of slots, occupied or free.
+    slots: usize,
+    /// The number of occupied slots.
+    len: usize,
+    /// The stack of free slots; the top is reused next.
+    free: Option<Rc<FreeNode<TIndex>>>,
+}
+
+/// A node of the slot trie.
+enum Node<T> {
+    /// An inner node with up to `WIDTH` children.
+    Branch(Vec<Rc<Node<T>>>),
+    /// A leaf with up to `WIDTH` slots; free slots are `None`.
+    Leaf(Vec<Option<T>>),
+}
+
+/// An entry of the persistent free stack.
+struct FreeNode<TIndex> {
+    index: TIndex,
+    next: Option<Rc<FreeNode<TIndex>>>,
+}
+
+impl<TIndex> Drop for FreeNode<TIndex> {
+    fn drop(&mut self) {
+        // Unlink iteratively so that long stacks do not overflow the call stack.
+        let mut next = self.next.take();
+        while let Some(node) = next {
+            match Rc::try_unwrap(node) {
+                Ok(mut node) => next = node.next.take(),
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+impl<T, TIndex> Clone for PersistentFreeList<T, TIndex>
+where
+    TIndex: IndexType,
+{
+    /// Creates another handle to the same version in constant time.
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+            depth: self.depth,
+            slots: self.slots,
+            len: self.len,
+            free: self.free.clone(),
+        }
+    }
+}
+
+impl<T, TIndex> Default for PersistentFreeList<T, TIndex>
+where
+    TIndex: IndexType,
+{
+    fn default() -> Self {
+        Self {
+            root: Rc::new(Node::Leaf(Vec::new())),
+            depth: 0,
+            slots: 0,
+            len: 0,
+            free: None,
+        }
+    }
+}
+
+impl<T, TIndex> PersistentFreeList<T, TIndex>
+where
+    TIndex: IndexType,
+{
+    /// Creates an empty list.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets a reference to the element at the specified index, or `None` if the
+    /// slot is not occupied.
+    pub fn get(&self, index: TIndex) -> Option<&T> {
+        let index = index.into_usize();
+        if index >= self.slots {
+            return None;
+        }
+
+        let mut node = &*self.root;
+        let mut level = self.depth;
+        loop {
+            match node {
+                Node::Branch(children) => {
+                    node = &children[(index >> (BITS * level as usize)) & MASK];
+                    level -= 1;
+                }
+                Node::Leaf(values) => return values[index & MASK].as_ref(),
+            }
+        }
+    }
+
+    /// Determines whether the specified index refers to an element.
+    #[inline]
+    pub fn contains(&self, index: TIndex) -> bool {
+        self.get(index).is_some()
+    }
+
+    /// Gets the number of elements in this version.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Determines whether this version contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Determines whether both lists are the same version, i.e. share their root.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::PersistentFreeList;
+    ///
+    /// let (list, _) = PersistentFreeList::<u32, u8>::new().push(1);
+    /// assert!(list.ptr_eq(&list.clone()));
+    /// assert!(!list.ptr_eq(&list.push(2).0));
+    /// ```
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.root, &other.root)
+    }
+
+    /// Gets an iterator over the indices and values of all elements, in ascending index order.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::PersistentFreeList;
+    ///
+    /// let (list, _) = PersistentFreeList::<&str, u8>::new().push("first");
+    /// let (list, _) = list.push("second");
+    /// let list = list.erase(0).unwrap();
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), [(1, &"second")]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (TIndex, &T)> + '_ {
+        let mut leaves = Vec::new();
+        Self::collect_leaves(&self.root, &mut leaves);
+        leaves
+            .into_iter()
+            .flatten()
+            .enumerate()
+            .filter_map(|(index, value)| Some((index_from_usize(index), value.as_ref()?)))
+    }
+
+    /// Collects the slot arrays of all leaves below the specified node, in index order.
+    fn collect_leaves<'a>(node: &'a Node<T>, leaves: &mut Vec<&'a [Option<T>]>) {
+        match node {
+            Node::Branch(children) => {
+                for child in children {
+                    Self::collect_leaves(child, leaves);
+                }
+            }
+            Node::Leaf(values) => leaves.push(values),
+        }
+    }
+}
+
+impl<T, TIndex> PersistentFreeList<T, TIndex>
+where
+    T: Clone,
+    TIndex: IndexType,
+{
+    /// Returns a new version with the element inserted, together with its index.
+    ///
+    /// ## Panics
+    /// Panics if the index type cannot address any more elements.
+    pub fn push(&self, element: T) -> (Self, TIndex) {
+        let mut next = self.clone();
+        let index = match &self.free {
+            Some(free) => {
+                next.free = free.next.clone();
+                free.index
+            }
+            None => {
+                assert!(
+                    self.slots < TIndex::MAX.into_usize() - 1,
+                    "Attempted to insert more elements than can be addressed by the provided index type ({:?} allowed)",
+                    TIndex::MAX
+                );
+                if self.slots == WIDTH << (BITS * self.depth as usize) {
+                    next.root = Rc::new(Node::Branch(vec![self.root.clone()]));
+                    next.depth += 1;
+                }
+                next.slots += 1;
+                index_from_usize(self.slots)
+            }
+        };
+
+        next.root = Rc::new(Self::with_slot(
+            &next.root,
+            next.depth,
+            index.into_usize(),
+            Some(element),
+        ));
+        next.len += 1;
+        (next, index)
+    }
+
+    /// Returns a new version without the element at the specified index, or `None`
+    /// if the slot is not occupied.
+    pub fn erase(&self, index: TIndex) -> Option<Self> {
+        if !self.contains(index) {
+            return None;
+        }
+
+        let mut next = self.clone();
+        next.root = Rc::new(Self::with_slot(
+            &self.root,
+            self.depth,
+            index.into_usize(),
+            None,
+        ));
+        next.free = Some(Rc::new(FreeNode {
+            index,
+            next: self.free.clone(),
+        }));
+        next.len -= 1;
+        Some(next)
+    }
+
+    /// Returns a new version with the element at the specified index replaced, or
+    /// `None` if the slot is not occupied.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::PersistentFreeList;
+    ///
+    /// let (before, index) = PersistentFreeList::<u32, u8>::new().push(1);
+    /// let after = before.set(index, 2).unwrap();
+    /// assert_eq!(before.get(index), Some(&1));
+    /// assert_eq!(after.get(index), Some(&2));
+    /// ```
+    pub fn set(&self, index: TIndex, element: T) -> Option<Self> {
+        if !self.contains(index) {
+            return None;
+        }
+
+        let mut next = self.clone();
+        next.root = Rc::new(Self::with_slot(
+            &self.root,
+            self.depth,
+            index.into_usize(),
+            Some(element),
+        ));
+        Some(next)
+    }
+
+    /// Copies the path to the specified slot, replacing the slot's value.
+    ///
+    /// Missing nodes along the path are created, which happens when a slot is appended.
+    fn with_slot(node: &Node<T>, level: u32, index: usize, value: Option<T>) -> Node<T> {
+        match node {
+            Node::Branch(children) => {
+                let mut children = children.clone();
+                let child = (index >> (BITS * level as usize)) & MASK;
+                if child == children.len() {
+                    let empty = if level == 1 {
+                        Node::Leaf(Vec::new())
+                    } else {
+                        Node::Branch(Vec::new())
+                    };
+                    children.push(Rc::new(Self::with_slot(&empty, level - 1, index, value)));
+                } else {
+                    children[child] =
+                        Rc::new(Self::with_slot(&children[child], level - 1, index, value));
+                }
+                Node::Branch(children)
+            }
+            Node::Leaf(values) => {
+                let mut values = values.clone();
+                let slot = index & MASK;
+                if slot == values.len() {
+                    values.push(value);
+                } else {
+                    values[slot] = value;
+                }
+                Node::Leaf(values)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_are_independent() {
+        let mut versions = vec![PersistentFreeList::<usize, u32>::new()];
+        for i in 0..2000 {
+            let (next, index) = versions.last().unwrap().push(i);
+            assert_eq!(index as usize, i);
+            versions.push(next);
+        }
+
+        for (count, version) in versions.iter().enumerate() {
+            assert_eq!(version.len(), count);
+            assert_eq!(version.get(count as u32), None);
+            if count > 0 {
+                assert_eq!(version.get(count as u32 - 1), Some(&(count - 1)));
+            }
+        }
+
+        let full = versions.last().unwrap();
+        let erased = full.erase(1500).unwrap().erase(33).unwrap();
+        assert!(erased.erase(33).is_none());
+        assert_eq!(full.get(33), Some(&33));
+        assert_eq!(erased.len(), 1998);
+        assert_eq!(erased.iter().count(), 1998);
+
+        // Free slots are reused in LIFO order.
+        let (reused, index) = erased.push(7);
+        assert_eq!(index, 33);
+        assert_eq!(reused.push(8).1, 1500);
+        assert_eq!(erased.get(33), None);
+        assert_eq!(reused.get(33), Some(&7));
+    }
+
+    #[test]
+    fn long_free_stacks_are_dropped() {
+        let mut list = PersistentFreeList::<u32, u32>::new();
+        for i in 0..100_000 {
+            list = list.push(i).0;
+        }
+        for i in 0..100_000 {
+            list = list.erase(i).unwrap();
+        }
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn inserting_too_many_panics() {
+        let mut list = PersistentFreeList::<u32, u8>::new();
+        for i in 0..255 {
+            list = list.push(i).0;
+        }
+    }
+}
@@ -0,0 +1,253 @@
+use crate::index_type::*;
+use crate::{Keys, PagedFreeList, SlotStatus};
+use std::pin::Pin;
+
+/// An indexed free list whose elements are pinned in memory.
+///
+/// Built on top of [`PagedFreeList`], elements are never moved from the moment they
+/// are inserted until they are erased (or the list is cleared or dropped), at which point
+/// they are dropped in place. This makes the list suitable for storing self-referential
+/// values such as futures or intrusive list nodes.
+///
+/// ## Type parameters
+/// * `T` - The type of the element.
+/// * `TIndex` - The type of the index; see [`FreeList`](crate::FreeList) for details.
+/// * `PAGE_SIZE` - The number of slots per page. Must not be zero.
+pub struct PinnedFreeList<T, TIndex = DefaultIndex, const PAGE_SIZE: usize = 256>
+where
+    TIndex: IndexType,
+{
+    inner: PagedFreeList<T, TIndex, PAGE_SIZE>,
+}
+
+impl<T, TIndex, const PAGE_SIZE: usize> Default for PinnedFreeList<T, TIndex, PAGE_SIZE>
+where
+    TIndex: IndexType,
+{
+    /// Creates an empty list.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::PinnedFreeList;
+    ///
+    /// let list = PinnedFreeList::<&str, u8>::default();
+    /// assert_eq!(list.slot_count(), 0);
+    /// ```
+    fn default() -> Self {
+        Self {
+            inner: PagedFreeList::default(),
+        }
+    }
+}
+
+impl<T, TIndex, const PAGE_SIZE: usize> PinnedFreeList<T, TIndex, PAGE_SIZE>
+where
+    TIndex: IndexType,
+{
+    /// Inserts an element to the list and returns an index to it.
+    ///
+    /// From this point on, the element will not be moved until it is erased.
+    ///
+    /// ## Panics
+    /// Panics if the index type cannot address any more elements.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::PinnedFreeList;
+    ///
+    /// let mut list = PinnedFreeList::<&str, u8>::default();
+    /// assert_eq!(list.push("first"), 0);
+    /// ```
+    pub fn push(&mut self, element: T) -> TIndex {
+        self.inner.push(element)
+    }
+
+    /// Removes the nth element from the list, dropping it in place.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::{PinnedFreeList, SlotStatus};
+    ///
+    /// let mut list = PinnedFreeList::<&str, u8>::default();
+    /// list.push("first");
+    /// list.erase(0);
+    /// assert_eq!(list.status(0), SlotStatus::Free);
+    /// ```
+    pub fn erase(&mut self, n: TIndex) {
+        self.inner.erase(n)
+    }
+
+    /// Removes all elements from the list, dropping them in place.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::PinnedFreeList;
+    ///
+    /// let mut list = PinnedFreeList::<&str, u8>::default();
+    /// list.push("first");
+    /// list.clear();
+    /// assert_eq!(list.slot_count(), 0);
+    /// ```
+    pub fn clear(&mut self) {
+        self.inner.clear()
+    }
+
+    /// Gets a reference to the value at the specified index.
+    ///
+    /// # Safety
+    ///
+    /// Accessing an index whose element was erased is undefined behavior.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::PinnedFreeList;
+    ///
+    /// let mut list = PinnedFreeList::<&str, u8>::default();
+    /// list.push("first");
+    /// assert_eq!(unsafe { list.at(0) }, &"first");
+    /// ```
+    #[inline]
+    pub unsafe fn at(&self, index: TIndex) -> &T {
+        self.inner.at(index)
+    }
+
+    /// Gets a pinned mutable reference to the value at the specified index.
+    ///
+    /// # Safety
+    ///
+    /// Accessing an index whose element was erased is undefined behavior.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::PinnedFreeList;
+    /// use std::future::Future;
+    /// use std::task::{Context, Poll, Waker};
+    ///
+    /// let mut list = PinnedFreeList::<_, u8>::default();
+    /// let index = list.push(async { 42 });
+    ///
+    /// let mut context = Context::from_waker(Waker::noop());
+    /// let future = unsafe { list.at_pin(index) };
+    /// assert_eq!(future.poll(&mut context), Poll::Ready(42));
+    /// ```
+    #[inline]
+    pub unsafe fn at_pin(&mut self, index: TIndex) -> Pin<&mut T> {
+        // SAFETY: Elements are never moved until they are dropped in place.
+        Pin::new_unchecked(self.inner.at_mut(index))
+    }
+
+    /// Gets a pinned mutable reference to the value at the specified index,
+    /// or `None` if the slot is not occupied.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::PinnedFreeList;
+    ///
+    /// let mut list = PinnedFreeList::<u32, u8>::default();
+    /// list.push(1);
+    /// list.erase(0);
+    /// assert!(list.get_pin(0).is_none());
+    /// assert!(list.get_pin(1).is_none());
+    /// ```
+    pub fn get_pin(&mut self, index: TIndex) -> Option<Pin<&mut T>> {
+        if self.status(index).is_occupied() {
+            Some(unsafe { self.at_pin(index) })
+        } else {
+            None
+        }
+    }
+
+    /// Determines the state of the slot at the specified index.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::{PinnedFreeList, SlotStatus};
+    ///
+    /// let mut list = PinnedFreeList::<&str, u8>::default();
+    /// list.push("first");
+    /// assert_eq!(list.status(0), SlotStatus::Occupied);
+    /// assert_eq!(list.status(1), SlotStatus::OutOfRange);
+    /// ```
+    pub fn status(&self, index: TIndex) -> SlotStatus {
+        self.inner.status(index)
+    }
+
+    /// Gets an iterator over the indices of all occupied slots, in ascending order.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::PinnedFreeList;
+    ///
+    /// let mut list = PinnedFreeList::<&str, u8>::default();
+    /// list.push("first");
+    /// list.push("second");
+    /// assert_eq!(list.keys().collect::<Vec<_>>(), [0, 1]);
+    /// ```
+    pub fn keys(&self) -> Keys<'_, TIndex> {
+        self.inner.keys()
+    }
+
+    /// Gets the number of slots that were handed out so far.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::PinnedFreeList;
+    ///
+    /// let mut list = PinnedFreeList::<&str, u8>::default();
+    /// list.push("first");
+    /// assert_eq!(list.slot_count(), 1);
+    /// ```
+    pub fn slot_count(&self) -> usize {
+        self.inner.slot_count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::marker::PhantomPinned;
+
+    /// A value that stores a pointer to itself.
+    struct SelfReferential {
+        value: u32,
+        this: *const SelfReferential,
+        _pinned: PhantomPinned,
+    }
+
+    impl SelfReferential {
+        fn new(value: u32) -> Self {
+            Self {
+                value,
+                this: std::ptr::null(),
+                _pinned: PhantomPinned,
+            }
+        }
+
+        fn init(self: Pin<&mut Self>) {
+            let this = &*self as *const Self;
+            unsafe { self.get_unchecked_mut().this = this };
+        }
+    }
+
+    #[test]
+    fn self_references_stay_valid() {
+        let mut list = PinnedFreeList::<SelfReferential, u16, 4>::default();
+        for i in 0..3 {
+            let index = list.push(SelfReferential::new(i));
+            list.get_pin(index).unwrap().init();
+        }
+
+        list.erase(1);
+        for i in 3..50 {
+            let index = list.push(SelfReferential::new(i));
+            list.get_pin(index).unwrap().init();
+        }
+
+        for index in list.keys() {
+            let element = unsafe { list.at(index) };
+            assert!(std::ptr::eq(element.this, element));
+        }
+        assert_eq!(unsafe { list.at(0) }.value, 0);
+        assert_eq!(unsafe { list.at(2) }.value, 2);
+    }
+}
@@ -0,0 +1,132 @@
+use crate::index_type::*;
+use crate::{FreeElement, FreeList};
+use allocator_api2::alloc::Allocator;
+use bytemuck::Pod;
+use std::mem::ManuallyDrop;
+
+impl<T, TIndex, A> FreeList<T, TIndex, A>
+where
+    T: Pod + Default,
+    TIndex: IndexType,
+    A: Allocator,
+{
+    /// Gets the bytes of all slots, e.g. to upload them to a GPU buffer as they are.
+    ///
+    /// Slot `i` starts at byte `i * size_of::<T>()`. Occupied slots hold the bytes of their
+    /// element; free slots start with their link to the next free slot and are zero
+    /// otherwise, so the [`occupancy_words`](Self::occupancy_words) are needed to tell them
+    /// apart. Free slots are zeroed by this call, which is why it needs mutable access.
+    ///
+    /// Returns `None` if a slot is larger than an element, which is the case if the element
+    /// is smaller than the index type or less aligned than it.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<[f32; 2], u32>::default();
+    /// list.push([1.0, 2.0]);
+    /// list.push([3.0, 4.0]);
+    /// list.erase(0);
+    ///
+    /// let bytes = list.slot_bytes().unwrap();
+    /// assert_eq!(bytes.len(), 16);
+    /// assert_eq!(&bytes[8..], bytemuck::bytes_of(&[3.0f32, 4.0]));
+    /// assert_eq!(list.occupancy_words(), [0b10]);
+    /// ```
+    pub fn slot_bytes(&mut self) -> Option<&[u8]> {
+        if !Self::SLOTS_ARE_ELEMENTS {
+            return None;
+        }
+
+        // Free slots only initialize the bytes of their link; zero the rest.
+        let slots = self.data.len();
+        let mut index = self.occupied.next_zero(0);
+        while index < slots {
+            let next = self.next_free(index);
+            self.data[index] = FreeElement {
+                element: ManuallyDrop::new(T::zeroed()),
+            };
+            self.data[index].next = next;
+            index = self.occupied.next_zero(index + 1);
+        }
+
+        // SAFETY: Every slot has the size of an element and all of its bytes are
+        // initialized; elements have no padding.
+        Some(unsafe {
+            std::slice::from_raw_parts(
+                self.data.as_ptr().cast::<u8>(),
+                slots * std::mem::size_of::<T>(),
+            )
+        })
+    }
+
+    /// Gets the occupancy bitmap; slot `i` is occupied if bit `i % 64` of word `i / 64`
+    /// is set. The bitmap may end before the last slot, in which case the remaining
+    /// slots are free.
+    #[inline]
+    pub fn occupancy_words(&self) -> &[u64] {
+        self.occupied.words()
+    }
+
+    /// Appends the bytes of all elements, packed in ascending index order, to the buffer.
+    ///
+    /// Runs of adjacent elements are copied at once.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<u16, u8>::default();
+    /// list.extend_and_collect([1, 2, 3]);
+    /// list.erase(1);
+    ///
+    /// let mut bytes = Vec::new();
+    /// list.extend_packed_bytes(&mut bytes);
+    /// assert_eq!(bytes, bytemuck::cast_slice::<u16, u8>(&[1, 3]));
+    /// ```
+    pub fn extend_packed_bytes(&self, buffer: &mut Vec<u8>) {
+        buffer.reserve(self.len() * std::mem::size_of::<T>());
+        match self.iter_occupied_runs() {
+            Some(runs) => {
+                for (_, run) in runs {
+                    buffer.extend_from_slice(bytemuck::cast_slice(run));
+                }
+            }
+            None => {
+                for (_, element) in self.iter() {
+                    buffer.extend_from_slice(bytemuck::bytes_of(element));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FreeList;
+
+    #[test]
+    fn free_slots_hold_their_link() {
+        let mut list = FreeList::<u64, u32>::default();
+        list.reserve(3);
+        list.push(u64::MAX);
+        list.erase(0);
+        list.push(7);
+
+        // Slot 0 is reused, slot 1 links to slot 2, which ends the chain.
+        let bytes = list.slot_bytes().unwrap();
+        let slots: &[u64] = bytemuck::cast_slice(bytes);
+        assert_eq!(slots[0], 7);
+        assert_eq!(slots[1].to_ne_bytes()[..4], 2u32.to_ne_bytes());
+        assert_eq!(slots[1].to_ne_bytes()[4..], [0; 4]);
+        assert_eq!(slots[2].to_ne_bytes()[..4], u32::MAX.to_ne_bytes());
+
+        let mut list = FreeList::<u8, u32>::default();
+        list.push(1);
+        assert_eq!(list.slot_bytes(), None);
+        let mut bytes = vec![0];
+        list.extend_packed_bytes(&mut bytes);
+        assert_eq!(bytes, [0, 1]);
+    }
+}
@@ -0,0 +1,308 @@
+use crate::PagedFreeList;
+use std::cell::RefCell;
+use std::fmt::{Debug, Formatter};
+use std::ops::{Deref, DerefMut};
+use std::ptr::NonNull;
+
+/// A single-threaded object pool that recycles values instead of dropping them.
+///
+/// Values are borrowed from the pool with [`checkout`](Self::checkout), which returns a
+/// [`PoolGuard`]. When the guard is dropped, the value is optionally reset and returned
+/// to the pool, where it is handed out again by the next checkout. This allows expensive
+/// values such as buffers or parsers to keep their allocations across uses.
+///
+/// Values are stored in a [`PagedFreeList`], so any number of guards can be alive at the same time.
+pub struct ObjectPool<T>
+where
+    T: Default,
+{
+    state: RefCell<PoolState<T>>,
+    /// Called on every value that is returned to the pool.
+    reset: Option<ResetFn<T>>,
+}
+
+/// The function used to reset values returned to an [`ObjectPool`].
+type ResetFn<T> = Box<dyn Fn(&mut T)>;
+
+struct PoolState<T> {
+    /// All values owned by the pool, whether checked out or not.
+    values: PagedFreeList<T, usize>,
+    /// The indices of all values that are currently checked in, in LIFO order.
+    idle: Vec<usize>,
+}
+
+/// A value checked out from an [`ObjectPool`].
+///
+/// The value is returned to the pool when the guard is dropped.
+pub struct PoolGuard<'a, T>
+where
+    T: Default,
+{
+    pool: &'a ObjectPool<T>,
+    value: NonNull<T>,
+    index: usize,
+}
+
+impl<T> Default for ObjectPool<T>
+where
+    T: Default,
+{
+    /// Creates an empty pool that returns values without resetting them.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::ObjectPool;
+    ///
+    /// let pool = ObjectPool::<Vec<u8>>::default();
+    /// assert_eq!(pool.len(), 0);
+    /// ```
+    fn default() -> Self {
+        Self {
+            state: RefCell::new(PoolState {
+                values: PagedFreeList::default(),
+                idle: Vec::new(),
+            }),
+            reset: None,
+        }
+    }
+}
+
+impl<T> ObjectPool<T>
+where
+    T: Default,
+{
+    /// Creates an empty pool that calls the specified function on every value
+    /// that is returned to the pool.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::ObjectPool;
+    ///
+    /// let pool = ObjectPool::with_reset(|buffer: &mut Vec<u8>| buffer.clear());
+    /// {
+    ///     let mut buffer = pool.checkout();
+    ///     buffer.extend_from_slice(b"data");
+    /// }
+    ///
+    /// // The same buffer is handed out again, cleared but with its allocation intact.
+    /// let buffer = pool.checkout();
+    /// assert!(buffer.is_empty());
+    /// assert!(buffer.capacity() >= 4);
+    /// ```
+    pub fn with_reset(reset: impl Fn(&mut T) + 'static) -> Self {
+        Self {
+            reset: Some(Box::new(reset)),
+            ..Self::default()
+        }
+    }
+
+    /// Borrows a value from the pool, creating a new one with [`Default`] if no
+    /// idle value is available.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::ObjectPool;
+    ///
+    /// let pool = ObjectPool::<String>::default();
+    /// let mut first = pool.checkout();
+    /// let second = pool.checkout();
+    /// first.push_str("hello");
+    ///
+    /// assert_eq!(pool.len(), 2);
+    /// assert_eq!(pool.idle_count(), 0);
+    /// ```
+    pub fn checkout(&self) -> PoolGuard<'_, T> {
+        self.checkout_with(T::default)
+    }
+
+    /// Borrows a value from the pool, creating a new one with the specified function
+    /// if no idle value is available.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::ObjectPool;
+    ///
+    /// let pool = ObjectPool::<Vec<u8>>::default();
+    /// let buffer = pool.checkout_with(|| Vec::with_capacity(1024));
+    /// assert!(buffer.capacity() >= 1024);
+    /// ```
+    pub fn checkout_with(&self, create: impl FnOnce() -> T) -> PoolGuard<'_, T> {
+        let idle = self.state.borrow_mut().idle.pop();
+
+        // The value is created without holding the borrow so that the function may use the pool.
+        let mut state;
+        let index = match idle {
+            Some(index) => {
+                state = self.state.borrow_mut();
+                index
+            }
+            None => {
+                let value = create();
+                state = self.state.borrow_mut();
+                state.values.push(value)
+            }
+        };
+
+        // SAFETY: The paged storage never moves the value, and the pool only
+        // erases values that are not checked out.
+        let value = NonNull::from(unsafe { state.values.at_mut(index) });
+        PoolGuard {
+            pool: self,
+            value,
+            index,
+        }
+    }
+
+    /// Gets the total number of values owned by the pool, including checked out ones.
+    pub fn len(&self) -> usize {
+        self.state.borrow().values.keys().count()
+    }
+
+    /// Determines whether the pool owns no values.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Gets the number of values that are available for checkout.
+    pub fn idle_count(&self) -> usize {
+        self.state.borrow().idle.len()
+    }
+
+    /// Drops all idle values, keeping checked out values untouched.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::ObjectPool;
+    ///
+    /// let pool = ObjectPool::<String>::default();
+    /// let kept = pool.checkout();
+    /// drop(pool.checkout());
+    /// assert_eq!(pool.len(), 2);
+    ///
+    /// pool.shrink();
+    /// assert_eq!(pool.len(), 1);
+    /// assert_eq!(pool.idle_count(), 0);
+    /// ```
+    pub fn shrink(&self) {
+        let mut state = self.state.borrow_mut();
+        let PoolState { values, idle } = &mut *state;
+        for index in idle.drain(..) {
+            values.erase(index);
+        }
+    }
+
+    /// Returns a value to the pool.
+    fn checkin(&self, index: usize, value: &mut T) {
+        if let Some(reset) = &self.reset {
+            reset(value);
+        }
+        self.state.borrow_mut().idle.push(index);
+    }
+}
+
+impl<'a, T> PoolGuard<'a, T>
+where
+    T: Default,
+{
+    /// Gets the index of the value within the pool.
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl<'a, T> Deref for PoolGuard<'a, T>
+where
+    T: Default,
+{
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: The guard has exclusive access to the value until it is dropped.
+        unsafe { self.value.as_ref() }
+    }
+}
+
+impl<'a, T> DerefMut for PoolGuard<'a, T>
+where
+    T: Default,
+{
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // SAFETY: The guard has exclusive access to the value until it is dropped.
+        unsafe { self.value.as_mut() }
+    }
+}
+
+impl<'a, T> Drop for PoolGuard<'a, T>
+where
+    T: Default,
+{
+    fn drop(&mut self) {
+        // SAFETY: The guard has exclusive access to the value until it is dropped.
+        let value = unsafe { self.value.as_mut() };
+        self.pool.checkin(self.index, value);
+    }
+}
+
+impl<'a, T> Debug for PoolGuard<'a, T>
+where
+    T: Default + Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PoolGuard")
+            .field("index", &self.index)
+            .field("value", &**self)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_are_recycled() {
+        let pool = ObjectPool::<Vec<u32>>::default();
+        let index = {
+            let mut guard = pool.checkout();
+            guard.push(1);
+            guard.index()
+        };
+        assert_eq!(pool.idle_count(), 1);
+
+        let guard = pool.checkout();
+        assert_eq!(guard.index(), index);
+        assert_eq!(*guard, vec![1]);
+        assert_eq!(pool.len(), 1);
+    }
+
+    #[test]
+    fn reset_is_applied_on_checkin() {
+        let pool = ObjectPool::with_reset(|value: &mut u32| *value = 0);
+        drop({
+            let mut guard = pool.checkout();
+            *guard = 42;
+            guard
+        });
+        assert_eq!(*pool.checkout(), 0);
+    }
+
+    #[test]
+    fn many_guards_can_be_alive() {
+        let pool = ObjectPool::<usize>::default();
+        let mut guards: Vec<_> = (0..1000).map(|_| pool.checkout()).collect();
+        for (i, guard) in guards.iter_mut().enumerate() {
+            **guard = i;
+        }
+        for (i, guard) in guards.iter().enumerate() {
+            assert_eq!(**guard, i);
+        }
+
+        drop(guards);
+        assert_eq!(pool.idle_count(), 1000);
+        pool.shrink();
+        assert!(pool.is_empty());
+    }
+}
@@ -0,0 +1,243 @@
+use crate::bitset::BitSet;
+use crate::index_type::*;
+use crate::macros::safety_check;
+use crate::ticks::ChangeTicks;
+use crate::zst::ZstSlots;
+use crate::{DropOrder, FreeElement, FreeList, GenerationOverflow, ReusePolicy};
+use allocator_api2::alloc::Allocator;
+use std::collections::VecDeque;
+use std::mem::ManuallyDrop;
+
+/// The components of a [`FreeList`], obtained with [`FreeList::into_raw_parts`].
+///
+/// The parts own the elements of all occupied slots but do not drop them; dropping
+/// the parts without passing them to [`FreeList::from_raw_parts`] leaks the elements.
+pub struct RawParts<T, TIndex, A>
+where
+    TIndex: IndexType,
+    A: Allocator,
+{
+    /// The slots. Occupied slots hold an element, free slots a link to the next free slot.
+    pub data: allocator_api2::vec::Vec<FreeElement<T, TIndex>, A>,
+    /// The occupancy bitmap; slot `i` is occupied if bit `i % 64` of word `i / 64` is set.
+    pub occupied: Vec<u64>,
+    /// The generation of every slot that was ever handed out; may be longer than `data`.
+    pub generations: Vec<u32>,
+    /// The first slot of the free chain, or `TIndex::MAX` if the chain is empty.
+    pub first_free: TIndex,
+    /// The last slot of the free chain, or `TIndex::MAX` if the chain is empty.
+    pub last_free: TIndex,
+    /// The order in which free slots are reused.
+    pub policy: ReusePolicy,
+    /// The free slots that are held back from the free chain, oldest first.
+    pub quarantine: VecDeque<TIndex>,
+    /// The number of erases a slot is held back for before it can be reused.
+    pub quarantine_len: usize,
+    /// What happens when the generation of a slot is exhausted. With
+    /// [`GenerationOverflow::Retire`], free slots with generation `u32::MAX` are neither
+    /// linked nor quarantined.
+    pub overflow: GenerationOverflow,
+    /// Whether live elements are reported when the list is dropped.
+    pub report_leaks: bool,
+    /// The order in which live elements are dropped.
+    pub drop_order: DropOrder,
+    /// Whether the rank index over the occupied slots is enabled; the index itself is
+    /// rebuilt by [`FreeList::from_raw_parts`].
+    pub rank_index: bool,
+}
+
+impl<T, TIndex, A> FreeList<T, TIndex, A>
+where
+    T: Default,
+    TIndex: IndexType,
+    A: Allocator,
+{
+    /// Decomposes the list into its components without dropping any element.
+    ///
+    /// The high-water marks reported by [`stats`](Self::stats) are not part of the
+    /// components; a list rebuilt with [`from_raw_parts`](Self::from_raw_parts) starts
+    /// with its current size as the peak, and the number of
+    /// [`generation_wraps`](Self::generation_wraps) starts at zero. A registered [`Observer`](crate::Observer)
+    /// is dropped.
+    ///
+    /// Lists of zero-sized elements do not store their slots; for them, the slots are
+    /// allocated here.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// list.push("first");
+    /// list.push("second");
+    /// list.erase(0);
+    ///
+    /// let parts = list.into_raw_parts();
+    /// assert_eq!(parts.data.len(), 2);
+    /// assert_eq!(parts.first_free, 0);
+    ///
+    /// let list = unsafe { FreeList::from_raw_parts(parts) };
+    /// assert_eq!(list.get(1), Some(&"second"));
+    /// ```
+    pub fn into_raw_parts(self) -> RawParts<T, TIndex, A> {
+        let mut this = ManuallyDrop::new(self);
+
+        // Zero-sized elements are not stored, so their slots are materialized here;
+        // the element of an occupied slot needs no bytes.
+        if Self::IS_ZST {
+            for index in 0..this.zst.len {
+                let next = this.zst.next(index);
+                this.data.push(FreeElement { next });
+            }
+        }
+
+        // SAFETY: Every field is read exactly once and the list itself is never dropped.
+        unsafe {
+            std::ptr::drop_in_place(&mut this.zst);
+            std::ptr::drop_in_place(&mut this.observer);
+            std::ptr::drop_in_place(&mut this.ticks);
+            RawParts {
+                data: std::ptr::read(&this.data),
+                rank_index: this.occupied.has_rank_index(),
+                occupied: std::ptr::read(&this.occupied).into_words(),
+                generations: std::ptr::read(&this.generations),
+                first_free: this.first_free,
+                last_free: this.last_free,
+                policy: this.policy,
+                quarantine: std::ptr::read(&this.quarantine),
+                quarantine_len: this.quarantine_len,
+                overflow: this.overflow,
+                report_leaks: this.report_leaks,
+                drop_order: this.drop_order,
+            }
+        }
+    }
+
+    /// Reassembles a list from its components.
+    ///
+    /// With debug assertions or the `checked` feature enabled, the components are
+    /// validated with [`check_invariants`](Self::check_invariants).
+    ///
+    /// # Safety
+    /// The components must describe a consistent list, as if obtained from
+    /// [`into_raw_parts`](Self::into_raw_parts): exactly the slots marked in the
+    /// occupancy bitmap hold an initialized element, every other slot is either linked
+    /// into the free chain exactly once, quarantined or retired, the chain is terminated by
+    /// `TIndex::MAX`, and there is a generation for every slot.
+    pub unsafe fn from_raw_parts(parts: RawParts<T, TIndex, A>) -> Self {
+        let mut occupied = BitSet::from_words(parts.occupied);
+        occupied.set_rank_index(parts.rank_index);
+        let length = occupied.count();
+        let mut data = parts.data;
+        let mut zst = ZstSlots::new();
+        if Self::IS_ZST {
+            zst.len = data.len();
+            for (index, slot) in data.iter().enumerate() {
+                if !occupied.contains(index) {
+                    zst.set_next(index, unsafe { slot.next });
+                }
+            }
+            data.clear();
+            data.shrink_to_fit();
+        }
+
+        let list = Self {
+            peak_length: length,
+            peak_slots: zst.len.max(data.len()),
+            length,
+            data,
+            zst,
+            occupied,
+            generations: parts.generations,
+            first_free: parts.first_free,
+            last_free: parts.last_free,
+            policy: parts.policy,
+            overflow: parts.overflow,
+            generation_wraps: 0,
+            quarantine: parts.quarantine,
+            quarantine_len: parts.quarantine_len,
+            report_leaks: parts.report_leaks,
+            drop_order: parts.drop_order,
+            observer: None,
+            ticks: ChangeTicks::new(),
+        };
+
+        safety_check!(
+            list.check_invariants().is_ok(),
+            "Attempted to assemble a list from inconsistent parts ({:?})",
+            list.check_invariants()
+        );
+        list
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn round_trip_keeps_elements_alive() {
+        let value = Rc::new(());
+        let mut list = FreeList::<Rc<()>, u8>::with_reuse_policy(ReusePolicy::Fifo);
+        list.set_quarantine_len(1);
+        for _ in 0..4 {
+            list.push(value.clone());
+        }
+        list.erase(1);
+        list.erase(2);
+        let key = list.key(3).unwrap();
+
+        let parts = list.into_raw_parts();
+        assert_eq!(Rc::strong_count(&value), 3);
+        assert_eq!(parts.quarantine, [2]);
+        assert_eq!(parts.occupied, [0b1001]);
+
+        let mut list = unsafe { FreeList::from_raw_parts(parts) };
+        assert_eq!(list.len(), 2);
+        assert!(list.contains_key(key));
+        assert_eq!(list.push(value.clone()), 1);
+
+        drop(list);
+        assert_eq!(Rc::strong_count(&value), 1);
+    }
+
+    #[test]
+    fn round_trip_keeps_settings() {
+        let mut list = FreeList::<u32, u8>::default();
+        list.set_report_leaks(true);
+        list.set_drop_order(DropOrder::Descending);
+        list.set_rank_index(true);
+        list.extend_and_collect(0..100);
+        list.erase(10);
+
+        let list = unsafe { FreeList::from_raw_parts(list.into_raw_parts()) };
+        assert!(list.report_leaks());
+        assert_eq!(list.drop_order(), DropOrder::Descending);
+        assert!(list.has_rank_index());
+        assert_eq!(list.rank(50), 49);
+        assert_eq!(list.select(49), Some(50));
+
+        let mut list = list;
+        list.set_report_leaks(false);
+        list.set_rank_index(false);
+        let list = unsafe { FreeList::from_raw_parts(list.into_raw_parts()) };
+        assert!(!list.report_leaks());
+        assert!(!list.has_rank_index());
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg_attr(not(any(debug_assertions, feature = "checked")), ignore)]
+    fn inconsistent_parts_are_detected() {
+        let mut list = FreeList::<u32, u8>::default();
+        list.push(1);
+        list.push(2);
+        list.erase(0);
+
+        let mut parts = list.into_raw_parts();
+        parts.first_free = u8::MAX;
+        parts.last_free = u8::MAX;
+        let _ = unsafe { FreeList::from_raw_parts(parts) };
+    }
+}
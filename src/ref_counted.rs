@@ -0,0 +1,201 @@
+use crate::index_type::*;
+use crate::FreeList;
+
+/// A free list whose slots are shared by reference counting.
+///
+/// Every element starts with a reference count of one. Additional owners register
+/// themselves with [`retain`](Self::retain), and [`erase`](Self::erase) only removes the
+/// element once the last owner released it. Indices stay plain `Copy` values, so
+/// sharing an element costs a counter increment instead of an allocation.
+///
+/// ## Type parameters
+/// * `T` - The type of the element.
+/// * `TIndex` - The type of the index; see [`FreeList`] for details.
+///
+/// ## Example
+/// ```rust
+/// use free_list::RefCountedFreeList;
+///
+/// let mut list = RefCountedFreeList::<&str, u8>::default();
+/// let texture = list.push("texture");
+/// list.retain(texture);
+///
+/// // The first owner releases the element, which stays alive for the second.
+/// assert_eq!(list.erase(texture), None);
+/// assert_eq!(list.get(texture), Some(&"texture"));
+///
+/// // The last owner releases the element.
+/// assert_eq!(list.erase(texture), Some("texture"));
+/// assert!(!list.contains(texture));
+/// ```
+pub struct RefCountedFreeList<T, TIndex = DefaultIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    list: FreeList<T, TIndex>,
+    /// The reference count of every slot; zero for free slots.
+    counts: Vec<u32>,
+}
+
+impl<T, TIndex> Default for RefCountedFreeList<T, TIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    fn default() -> Self {
+        Self {
+            list: FreeList::default(),
+            counts: Vec::new(),
+        }
+    }
+}
+
+impl<T, TIndex> RefCountedFreeList<T, TIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    /// Inserts an element with a reference count of one and returns an index to it.
+    pub fn push(&mut self, element: T) -> TIndex {
+        let index = self.list.push(element);
+        let slot = index.into_usize();
+        if slot >= self.counts.len() {
+            self.counts.resize(slot + 1, 0);
+        }
+        self.counts[slot] = 1;
+        index
+    }
+
+    /// Increments the reference count of the element at the specified index.
+    ///
+    /// ## Panics
+    /// Panics if the slot is not occupied or the reference count overflows.
+    pub fn retain(&mut self, index: TIndex) {
+        assert!(
+            self.list.contains(index),
+            "Attempted to retain a slot that is not occupied ({index:?})"
+        );
+        let count = &mut self.counts[index.into_usize()];
+        *count = count.checked_add(1).expect("Reference count overflowed");
+    }
+
+    /// Decrements the reference count of the element at the specified index and
+    /// removes the element once the count reaches zero.
+    ///
+    /// Returns the element if it was removed, or `None` if it is still referenced.
+    ///
+    /// ## Panics
+    /// Panics if the slot is not occupied.
+    pub fn erase(&mut self, index: TIndex) -> Option<T> {
+        assert!(
+            self.list.contains(index),
+            "Attempted to erase a slot that is not occupied ({index:?})"
+        );
+        let count = &mut self.counts[index.into_usize()];
+        *count -= 1;
+        if *count == 0 {
+            Some(self.list.remove(index))
+        } else {
+            None
+        }
+    }
+
+    /// Gets the reference count of the slot at the specified index; zero if it is not occupied.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::RefCountedFreeList;
+    ///
+    /// let mut list = RefCountedFreeList::<u32, u8>::default();
+    /// let index = list.push(1);
+    /// list.retain(index);
+    /// assert_eq!(list.ref_count(index), 2);
+    /// assert_eq!(list.ref_count(index + 1), 0);
+    /// ```
+    #[inline]
+    pub fn ref_count(&self, index: TIndex) -> u32 {
+        self.counts.get(index.into_usize()).copied().unwrap_or(0)
+    }
+
+    /// Gets a reference to the element at the specified index, or `None` if the
+    /// slot is not occupied.
+    #[inline]
+    pub fn get(&self, index: TIndex) -> Option<&T> {
+        self.list.get(index)
+    }
+
+    /// Gets a mutable reference to the element at the specified index, or `None` if
+    /// the slot is not occupied. The change is visible to all owners.
+    #[inline]
+    pub fn get_mut(&mut self, index: TIndex) -> Option<&mut T> {
+        self.list.get_mut(index)
+    }
+
+    /// Determines whether the specified index refers to an element.
+    #[inline]
+    pub fn contains(&self, index: TIndex) -> bool {
+        self.list.contains(index)
+    }
+
+    /// Gets the number of elements in the list, regardless of their reference counts.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Determines whether the list contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Gets an iterator over the indices, values and reference counts of all elements,
+    /// in ascending index order.
+    pub fn iter(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = (TIndex, &T, u32)> + ExactSizeIterator + '_ {
+        self.list
+            .iter()
+            .map(|(index, value)| (index, value, self.counts[index.into_usize()]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slots_are_freed_by_the_last_owner() {
+        let mut list = RefCountedFreeList::<String, u8>::default();
+        let shared = list.push("shared".into());
+        let single = list.push("single".into());
+        list.retain(shared);
+        list.retain(shared);
+
+        assert_eq!(list.erase(single).as_deref(), Some("single"));
+        assert_eq!(list.erase(shared), None);
+        assert_eq!(list.erase(shared), None);
+        assert_eq!(
+            list.iter()
+                .map(|(index, _, count)| (index, count))
+                .collect::<Vec<_>>(),
+            [(shared, 1)]
+        );
+        assert_eq!(list.erase(shared).as_deref(), Some("shared"));
+        assert!(list.is_empty());
+
+        // Reused slots start over with a single reference.
+        let reused = list.push("reused".into());
+        assert_eq!(list.ref_count(reused), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn retaining_a_free_slot_panics() {
+        let mut list = RefCountedFreeList::<u32, u8>::default();
+        let index = list.push(1);
+        list.erase(index);
+        list.retain(index);
+    }
+}
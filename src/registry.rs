@@ -0,0 +1,170 @@
+use crate::{FreeList, Key};
+
+/// A registry of values addressed by plain `u32` handles, e.g. for handing stable
+/// IDs to JavaScript from a WebAssembly module.
+///
+/// A handle combines the slot index in its lower [`INDEX_BITS`](Self::INDEX_BITS) bits
+/// with the low bits of the slot generation in its upper bits. Handles are validated on
+/// every access, so handles to removed values, handles made up on the JavaScript side
+/// and handles of reused slots are rejected, the latter unless the slot was reused a
+/// multiple of 256 times in between.
+///
+/// ## Example
+/// ```rust
+/// use free_list::HandleRegistry;
+///
+/// let mut registry = HandleRegistry::<String>::new();
+/// let handle = registry.insert("canvas".into());
+/// assert_eq!(registry.get(handle).map(String::as_str), Some("canvas"));
+///
+/// assert_eq!(registry.remove(handle).as_deref(), Some("canvas"));
+/// let reused = registry.insert("context".into());
+/// assert_ne!(reused, handle);
+/// assert_eq!(registry.get(handle), None);
+/// ```
+pub struct HandleRegistry<T>
+where
+    T: Default,
+{
+    list: FreeList<T, u32>,
+}
+
+impl<T> Default for HandleRegistry<T>
+where
+    T: Default,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> HandleRegistry<T>
+where
+    T: Default,
+{
+    /// The number of handle bits that encode the slot index.
+    pub const INDEX_BITS: u32 = 24;
+
+    /// The maximum number of values the registry can hold at once.
+    pub const MAX_LEN: usize = (1 << Self::INDEX_BITS) - 1;
+
+    const INDEX_MASK: u32 = (1 << Self::INDEX_BITS) - 1;
+
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self {
+            list: FreeList::default(),
+        }
+    }
+
+    /// Inserts a value and returns its handle.
+    ///
+    /// ## Panics
+    /// Panics if the registry already holds [`MAX_LEN`](Self::MAX_LEN) values.
+    pub fn insert(&mut self, value: T) -> u32 {
+        assert!(
+            self.list.len() < Self::MAX_LEN,
+            "Attempted to register more values than can be addressed by a handle ({} allowed)",
+            Self::MAX_LEN
+        );
+        Self::handle(self.list.push_key(value))
+    }
+
+    /// Gets a reference to the value of the specified handle.
+    pub fn get(&self, handle: u32) -> Option<&T> {
+        let index = self.validate(handle)?;
+        self.list.get(index)
+    }
+
+    /// Gets a mutable reference to the value of the specified handle.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::HandleRegistry;
+    ///
+    /// let mut registry = HandleRegistry::<u32>::new();
+    /// let handle = registry.insert(1);
+    /// *registry.get_mut(handle).unwrap() += 1;
+    /// assert_eq!(registry.get(handle), Some(&2));
+    /// ```
+    pub fn get_mut(&mut self, handle: u32) -> Option<&mut T> {
+        let index = self.validate(handle)?;
+        self.list.get_mut(index)
+    }
+
+    /// Determines whether the specified handle refers to a value.
+    #[inline]
+    pub fn contains(&self, handle: u32) -> bool {
+        self.validate(handle).is_some()
+    }
+
+    /// Removes the value of the specified handle and returns it.
+    pub fn remove(&mut self, handle: u32) -> Option<T> {
+        let index = self.validate(handle)?;
+        self.list.try_remove(index)
+    }
+
+    /// Gets the number of values in the registry.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Determines whether the registry holds no values.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Gets an iterator over the handles and values of all entries, in ascending index order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (u32, &T)> + ExactSizeIterator + '_ {
+        self.list.iter().map(|(index, value)| {
+            let key = self.list.key(index).expect("iterated slots are occupied");
+            (Self::handle(key), value)
+        })
+    }
+
+    /// Removes all values. Handles issued before remain invalid.
+    pub fn clear(&mut self) {
+        self.list.clear();
+    }
+
+    /// Converts a key into a handle.
+    fn handle(key: Key<u32>) -> u32 {
+        (key.generation() << Self::INDEX_BITS) | key.index()
+    }
+
+    /// Gets the slot index of the specified handle if it refers to a value.
+    fn validate(&self, handle: u32) -> Option<u32> {
+        let key = self.list.key(handle & Self::INDEX_MASK)?;
+        (Self::handle(key) == handle).then_some(key.index())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stale_and_forged_handles_are_rejected() {
+        let mut registry = HandleRegistry::<u32>::new();
+        let first = registry.insert(1);
+        let second = registry.insert(2);
+        assert!(!registry.contains(first ^ (1 << HandleRegistry::<u32>::INDEX_BITS)));
+        assert!(!registry.contains(u32::MAX));
+
+        assert_eq!(registry.remove(first), Some(1));
+        assert_eq!(registry.remove(first), None);
+        let third = registry.insert(3);
+        assert_eq!(third & 0xFF_FFFF, first & 0xFF_FFFF);
+        assert!(!registry.contains(first));
+        assert_eq!(
+            registry.iter().collect::<Vec<_>>(),
+            [(third, &3), (second, &2)]
+        );
+
+        registry.clear();
+        assert!(registry.is_empty());
+        assert!(!registry.contains(second));
+    }
+}
@@ -0,0 +1,23 @@
+/// The order in which a [`FreeList`](crate::FreeList) reuses erased slots.
+///
+/// The policy is chosen when the list is created, see
+/// [`FreeList::with_reuse_policy`](crate::FreeList::with_reuse_policy).
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ReusePolicy {
+    /// The most recently erased slot is reused first.
+    ///
+    /// This is the cheapest policy and keeps recently touched memory hot.
+    #[default]
+    Lifo,
+    /// The least recently erased slot is reused first.
+    ///
+    /// Slots stay free for as long as possible, which makes it more likely that
+    /// stale indices are detected instead of silently referring to new elements.
+    Fifo,
+    /// The free slot with the lowest index is reused first.
+    ///
+    /// This keeps the occupied slots packed towards the front of the list, which
+    /// improves locality. Erasing is no longer constant-time since the slot has to be
+    /// linked into the free chain in ascending order.
+    LowestIndexFirst,
+}
@@ -0,0 +1,308 @@
+use crate::index_type::*;
+use crate::{Allocator, FreeList, Key};
+use std::marker::PhantomData;
+
+/// Associates additional data with the elements of a [`FreeList`], keyed by their [`Key`].
+///
+/// The values are stored densely by slot index, so lookups are as cheap as in the list
+/// itself, and the map grows lazily as values are inserted for higher indices. Every value
+/// remembers the generation of the key it was inserted with: once the element in the
+/// primary list is removed and its slot reused, the new key no longer matches and the
+/// stale value is replaced on the next insertion. Stale values can also be dropped eagerly
+/// with [`remove_stale`](Self::remove_stale).
+///
+/// ## Type parameters
+/// * `V` - The type of the associated value.
+/// * `TIndex` - The index type of the primary list.
+///
+/// ## Example
+/// ```rust
+/// use free_list::{FreeList, SecondaryMap};
+///
+/// let mut names = FreeList::<&str, u8>::default();
+/// let mut health = SecondaryMap::<u32, u8>::new();
+///
+/// let player = names.push_key("player");
+/// health.insert(player, 100);
+/// assert_eq!(health.get(player), Some(&100));
+///
+/// // The slot is reused by a new element, which does not inherit the value.
+/// names.erase(player.index());
+/// let enemy = names.push_key("enemy");
+/// assert_eq!(enemy.index(), player.index());
+/// assert_eq!(health.get(enemy), None);
+/// ```
+pub struct SecondaryMap<V, TIndex = DefaultIndex>
+where
+    TIndex: IndexType,
+{
+    /// The values, indexed by slot.
+    slots: Vec<Option<SecondaryEntry<V>>>,
+    /// The number of stored values.
+    len: usize,
+    _index: PhantomData<TIndex>,
+}
+
+/// A value of a [`SecondaryMap`] together with the generation of its key.
+struct SecondaryEntry<V> {
+    generation: u32,
+    value: V,
+}
+
+impl<V, TIndex> Default for SecondaryMap<V, TIndex>
+where
+    TIndex: IndexType,
+{
+    /// Creates an empty map.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::SecondaryMap;
+    ///
+    /// let map = SecondaryMap::<u32, u8>::default();
+    /// assert!(map.is_empty());
+    /// ```
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V, TIndex> SecondaryMap<V, TIndex>
+where
+    TIndex: IndexType,
+{
+    /// Creates an empty map.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::SecondaryMap;
+    ///
+    /// let map = SecondaryMap::<u32, u8>::new();
+    /// assert_eq!(map.len(), 0);
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            len: 0,
+            _index: PhantomData,
+        }
+    }
+
+    /// Associates a value with the specified key and returns the value previously
+    /// associated with the same key.
+    ///
+    /// A stale value stored for an older generation of the slot is dropped. If the key
+    /// itself is older than the stored value, nothing is inserted and `None` is returned.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::{Key, SecondaryMap};
+    ///
+    /// let mut map = SecondaryMap::<&str, u8>::new();
+    /// assert_eq!(map.insert(Key::new(2, 0), "first"), None);
+    /// assert_eq!(map.insert(Key::new(2, 0), "second"), Some("first"));
+    ///
+    /// // A newer generation replaces the stale value.
+    /// assert_eq!(map.insert(Key::new(2, 1), "third"), None);
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn insert(&mut self, key: Key<TIndex>, value: V) -> Option<V> {
+        let index = key.index().into_usize();
+        if index >= self.slots.len() {
+            self.slots.resize_with(index + 1, || None);
+        }
+
+        let entry = SecondaryEntry {
+            generation: key.generation(),
+            value,
+        };
+        match &mut self.slots[index] {
+            Some(existing) if existing.generation == key.generation() => {
+                Some(std::mem::replace(existing, entry).value)
+            }
+            Some(existing) if Self::is_newer(existing.generation, key.generation()) => None,
+            Some(existing) => {
+                *existing = entry;
+                None
+            }
+            slot @ None => {
+                *slot = Some(entry);
+                self.len += 1;
+                None
+            }
+        }
+    }
+
+    /// Gets a reference to the value associated with the specified key.
+    pub fn get(&self, key: Key<TIndex>) -> Option<&V> {
+        match self.slots.get(key.index().into_usize()) {
+            Some(Some(entry)) if entry.generation == key.generation() => Some(&entry.value),
+            _ => None,
+        }
+    }
+
+    /// Gets a mutable reference to the value associated with the specified key.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::{Key, SecondaryMap};
+    ///
+    /// let mut map = SecondaryMap::<u32, u8>::new();
+    /// let key = Key::new(0, 0);
+    /// map.insert(key, 1);
+    /// *map.get_mut(key).unwrap() += 1;
+    /// assert_eq!(map.get(key), Some(&2));
+    /// ```
+    pub fn get_mut(&mut self, key: Key<TIndex>) -> Option<&mut V> {
+        match self.slots.get_mut(key.index().into_usize()) {
+            Some(Some(entry)) if entry.generation == key.generation() => Some(&mut entry.value),
+            _ => None,
+        }
+    }
+
+    /// Determines whether a value is associated with the specified key.
+    #[inline]
+    pub fn contains_key(&self, key: Key<TIndex>) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Removes the value associated with the specified key and returns it.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::{Key, SecondaryMap};
+    ///
+    /// let mut map = SecondaryMap::<u32, u8>::new();
+    /// map.insert(Key::new(0, 0), 1);
+    /// assert_eq!(map.remove(Key::new(0, 1)), None);
+    /// assert_eq!(map.remove(Key::new(0, 0)), Some(1));
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn remove(&mut self, key: Key<TIndex>) -> Option<V> {
+        let slot = self.slots.get_mut(key.index().into_usize())?;
+        match slot {
+            Some(entry) if entry.generation == key.generation() => {
+                self.len -= 1;
+                slot.take().map(|entry| entry.value)
+            }
+            _ => None,
+        }
+    }
+
+    /// Drops all values whose key no longer refers to an element of the specified list.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::{FreeList, SecondaryMap};
+    ///
+    /// let mut list = FreeList::<&str, u8>::default();
+    /// let mut map = SecondaryMap::<u32, u8>::new();
+    /// let first = list.push_key("first");
+    /// let second = list.push_key("second");
+    /// map.insert(first, 1);
+    /// map.insert(second, 2);
+    ///
+    /// list.erase(first.index());
+    /// map.remove_stale(&list);
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn remove_stale<T, A>(&mut self, list: &FreeList<T, TIndex, A>)
+    where
+        T: Default,
+        A: Allocator,
+    {
+        for (index, slot) in self.slots.iter_mut().enumerate() {
+            let stale = match slot {
+                Some(entry) => {
+                    let key = Key::new(index_from_usize(index), entry.generation);
+                    !list.contains_key(key)
+                }
+                None => false,
+            };
+            if stale {
+                *slot = None;
+                self.len -= 1;
+            }
+        }
+    }
+
+    /// Gets the number of stored values, including stale values that were
+    /// not yet replaced or removed.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Determines whether the map stores no values.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes all values.
+    pub fn clear(&mut self) {
+        self.slots.clear();
+        self.len = 0;
+    }
+
+    /// Determines whether generation `a` is newer than generation `b`, taking
+    /// wraparound into account.
+    #[inline]
+    fn is_newer(a: u32, b: u32) -> bool {
+        (a.wrapping_sub(b) as i32) > 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_follow_generations() {
+        let mut list = FreeList::<u32, u8>::default();
+        let mut map = SecondaryMap::<&str, u8>::new();
+
+        let a = list.push_key(1);
+        let b = list.push_key(2);
+        map.insert(a, "a");
+        map.insert(b, "b");
+
+        list.erase(a.index());
+        let c = list.push_key(3);
+        assert_eq!(c.index(), a.index());
+        assert_eq!(map.get(c), None);
+        assert_eq!(map.get(a), Some(&"a"));
+
+        assert_eq!(map.insert(c, "c"), None);
+        assert_eq!(map.get(a), None);
+        assert_eq!(map.get(c), Some(&"c"));
+        assert_eq!(map.len(), 2);
+
+        // Outdated keys do not overwrite newer values.
+        assert_eq!(map.insert(a, "a"), None);
+        assert_eq!(map.get(c), Some(&"c"));
+    }
+
+    #[test]
+    fn clearing_the_list_invalidates_keys() {
+        let mut list = FreeList::<u32, u8>::default();
+        let mut map = SecondaryMap::<u32, u8>::new();
+        let key = list.push_key(1);
+        map.insert(key, 10);
+
+        list.clear();
+        let new_key = list.push_key(2);
+        assert_ne!(key, new_key);
+        assert!(!map.contains_key(new_key));
+
+        map.remove_stale(&list);
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn generations_wrap_around() {
+        assert!(SecondaryMap::<(), u8>::is_newer(1, 0));
+        assert!(SecondaryMap::<(), u8>::is_newer(0, u32::MAX));
+        assert!(!SecondaryMap::<(), u8>::is_newer(u32::MAX, 0));
+    }
+}
@@ -0,0 +1,275 @@
+use crate::FreeList;
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// A thread-safe free list that spreads its elements over multiple independently locked shards.
+///
+/// Each thread is assigned a preferred shard into which its insertions go, which
+/// reduces lock contention when many threads insert at the same time. All shards
+/// share a single logical index space: the shard of an element is encoded in the lower
+/// part of its index, so elements can be accessed and erased from any thread.
+pub struct ShardedFreeList<T>
+where
+    T: Default,
+{
+    shards: Box<[Mutex<FreeList<T, usize>>]>,
+}
+
+thread_local! {
+    /// The shard hint of the current thread; assigned round-robin on first use.
+    static SHARD_HINT: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+/// The source of shard hints for new threads.
+static NEXT_SHARD_HINT: AtomicUsize = AtomicUsize::new(0);
+
+impl<T> Default for ShardedFreeList<T>
+where
+    T: Default,
+{
+    /// Creates an empty list with one shard per available CPU.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::ShardedFreeList;
+    ///
+    /// let list = ShardedFreeList::<&str>::default();
+    /// assert!(list.shard_count() >= 1);
+    /// ```
+    fn default() -> Self {
+        let shards = std::thread::available_parallelism().map_or(1, |n| n.get());
+        Self::new(shards)
+    }
+}
+
+impl<T> ShardedFreeList<T>
+where
+    T: Default,
+{
+    /// Creates an empty list with the specified number of shards.
+    ///
+    /// ## Panics
+    /// Panics if `shards` is zero.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::ShardedFreeList;
+    ///
+    /// let list = ShardedFreeList::<&str>::new(4);
+    /// assert_eq!(list.shard_count(), 4);
+    /// ```
+    pub fn new(shards: usize) -> Self {
+        assert_ne!(shards, 0, "At least one shard is required");
+        Self {
+            shards: (0..shards).map(|_| Mutex::default()).collect(),
+        }
+    }
+
+    /// Gets the number of shards.
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Inserts an element into the shard of the current thread and returns an index to it.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::ShardedFreeList;
+    ///
+    /// let list = ShardedFreeList::<&str>::new(4);
+    /// let index = list.push("first");
+    /// assert_eq!(list.with(index, |value| *value), Some("first"));
+    /// ```
+    pub fn push(&self, element: T) -> usize {
+        let shard = self.current_shard();
+        let local = self.lock(shard).push(element);
+        local * self.shards.len() + shard
+    }
+
+    /// Removes the element at the specified index.
+    ///
+    /// Returns `true` if the slot was occupied, or `false` otherwise.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::ShardedFreeList;
+    ///
+    /// let list = ShardedFreeList::<&str>::new(4);
+    /// let index = list.push("first");
+    /// assert!(list.erase(index));
+    /// assert!(!list.erase(index));
+    /// ```
+    pub fn erase(&self, index: usize) -> bool {
+        let (shard, local) = self.split(index);
+        let mut list = self.lock(shard);
+        if list.status(local).is_occupied() {
+            list.erase(local);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Determines whether the specified index refers to an occupied slot.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::ShardedFreeList;
+    ///
+    /// let list = ShardedFreeList::<&str>::new(4);
+    /// let index = list.push("first");
+    /// assert!(list.contains(index));
+    /// assert!(!list.contains(index + 4));
+    /// ```
+    pub fn contains(&self, index: usize) -> bool {
+        let (shard, local) = self.split(index);
+        self.lock(shard).status(local).is_occupied()
+    }
+
+    /// Calls the specified function with a reference to the value at the specified index.
+    ///
+    /// Returns `None` if the slot is not occupied. The shard of the element is
+    /// locked while the function executes.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::ShardedFreeList;
+    ///
+    /// let list = ShardedFreeList::<String>::new(2);
+    /// let index = list.push("first".to_string());
+    /// assert_eq!(list.with(index, |value| value.len()), Some(5));
+    /// ```
+    pub fn with<R>(&self, index: usize, f: impl FnOnce(&T) -> R) -> Option<R> {
+        let (shard, local) = self.split(index);
+        let list = self.lock(shard);
+        if list.status(local).is_occupied() {
+            Some(f(unsafe { list.at(local) }))
+        } else {
+            None
+        }
+    }
+
+    /// Calls the specified function with a mutable reference to the value at the specified index.
+    ///
+    /// Returns `None` if the slot is not occupied. The shard of the element is
+    /// locked while the function executes.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::ShardedFreeList;
+    ///
+    /// let list = ShardedFreeList::<u32>::new(2);
+    /// let index = list.push(1);
+    /// list.with_mut(index, |value| *value += 1);
+    /// assert_eq!(list.with(index, |value| *value), Some(2));
+    /// ```
+    pub fn with_mut<R>(&self, index: usize, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        let (shard, local) = self.split(index);
+        let mut list = self.lock(shard);
+        if list.status(local).is_occupied() {
+            Some(f(unsafe { list.at_mut(local) }))
+        } else {
+            None
+        }
+    }
+
+    /// Gets the indices of all occupied slots, ordered by shard.
+    ///
+    /// The result is a snapshot; shards are locked one after another.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::ShardedFreeList;
+    ///
+    /// let list = ShardedFreeList::<&str>::new(2);
+    /// let first = list.push("first");
+    /// let second = list.push("second");
+    /// list.erase(first);
+    /// assert_eq!(list.keys(), [second]);
+    /// ```
+    pub fn keys(&self) -> Vec<usize> {
+        let shards = self.shards.len();
+        (0..shards)
+            .flat_map(|shard| {
+                self.lock(shard)
+                    .keys()
+                    .map(|local| local * shards + shard)
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// Gets the shard preferred by the current thread.
+    fn current_shard(&self) -> usize {
+        let hint = SHARD_HINT.with(|hint| match hint.get() {
+            Some(value) => value,
+            None => {
+                let value = NEXT_SHARD_HINT.fetch_add(1, Ordering::Relaxed);
+                hint.set(Some(value));
+                value
+            }
+        });
+        hint % self.shards.len()
+    }
+
+    /// Splits a global index into the shard and the index within that shard.
+    #[inline]
+    fn split(&self, index: usize) -> (usize, usize) {
+        (index % self.shards.len(), index / self.shards.len())
+    }
+
+    /// Locks the specified shard.
+    ///
+    /// Poisoning is ignored since a panic in a user function cannot leave
+    /// the list itself in an inconsistent state.
+    fn lock(&self, shard: usize) -> MutexGuard<'_, FreeList<T, usize>> {
+        self.shards[shard]
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn indices_encode_shards() {
+        let list = ShardedFreeList::<u32>::new(3);
+        let a = list.push(1);
+        let b = list.push(2);
+        assert_eq!(a % 3, b % 3);
+        assert_eq!(b, a + 3);
+        assert_eq!(list.with(a, |v| *v), Some(1));
+        assert_eq!(list.with(b, |v| *v), Some(2));
+        assert_eq!(list.with(b + 3, |v| *v), None);
+    }
+
+    #[test]
+    fn concurrent_pushes_yield_unique_indices() {
+        let list = ShardedFreeList::<usize>::new(4);
+        let indices: Vec<Vec<usize>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..8)
+                .map(|thread| {
+                    let list = &list;
+                    scope.spawn(move || (0..100).map(|i| list.push(thread * 100 + i)).collect())
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        let unique: HashSet<_> = indices.iter().flatten().copied().collect();
+        assert_eq!(unique.len(), 800);
+        assert_eq!(list.keys().len(), 800);
+
+        for (thread, indices) in indices.iter().enumerate() {
+            for (i, &index) in indices.iter().enumerate() {
+                assert_eq!(list.with(index, |v| *v), Some(thread * 100 + i));
+                assert!(list.erase(index));
+            }
+        }
+        assert!(list.keys().is_empty());
+    }
+}
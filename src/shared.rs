@@ -0,0 +1,244 @@
+use crate::PagedFreeList;
+use std::fmt::{Debug, Formatter};
+use std::ops::Deref;
+use std::ptr::NonNull;
+use std::sync::atomic::{fence, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// A thread-safe, reference-counted object pool.
+///
+/// Values inserted into the pool are accessed through [`SharedHandle`]s. Handles can be
+/// cloned cheaply; the slot of a value is only returned to the free chain once the
+/// last handle to it is dropped. Since the pool is backed by a [`PagedFreeList`], values
+/// never move while handles to them exist.
+pub struct SharedPool<T> {
+    inner: Arc<Mutex<PagedFreeList<SharedSlot<T>, usize>>>,
+}
+
+/// The element type of a [`SharedPool`].
+struct SharedSlot<T> {
+    /// The number of handles referring to this slot.
+    refs: AtomicUsize,
+    /// The pooled value.
+    value: T,
+}
+
+/// A strong, reference-counted handle to a value stored in a [`SharedPool`].
+///
+/// This type is created by [`SharedPool::insert`].
+pub struct SharedHandle<T> {
+    pool: Arc<Mutex<PagedFreeList<SharedSlot<T>, usize>>>,
+    slot: NonNull<SharedSlot<T>>,
+    index: usize,
+}
+
+unsafe impl<T: Send + Sync> Send for SharedHandle<T> {}
+unsafe impl<T: Send + Sync> Sync for SharedHandle<T> {}
+
+impl<T> Default for SharedPool<T> {
+    /// Creates an empty pool.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::SharedPool;
+    ///
+    /// let pool = SharedPool::<&str>::default();
+    /// assert_eq!(pool.slot_count(), 0);
+    /// ```
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(PagedFreeList::default())),
+        }
+    }
+}
+
+impl<T> Clone for SharedPool<T> {
+    /// Creates another reference to the same pool.
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> SharedPool<T> {
+    /// Inserts a value into the pool and returns the first handle to it.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::SharedPool;
+    ///
+    /// let pool = SharedPool::default();
+    /// let handle = pool.insert("first");
+    /// assert_eq!(*handle, "first");
+    ///
+    /// // The slot is released once the last handle is dropped.
+    /// let other = handle.clone();
+    /// drop(handle);
+    /// assert!(pool.contains(other.index()));
+    /// let index = other.index();
+    /// drop(other);
+    /// assert!(!pool.contains(index));
+    /// ```
+    pub fn insert(&self, value: T) -> SharedHandle<T> {
+        let mut list = lock(&self.inner);
+        let index = list.push(SharedSlot {
+            refs: AtomicUsize::new(1),
+            value,
+        });
+
+        // SAFETY: The paged storage never moves the slot until it is erased,
+        // which only happens once the last handle is dropped.
+        let slot = NonNull::from(unsafe { list.at(index) });
+        SharedHandle {
+            pool: self.inner.clone(),
+            slot,
+            index,
+        }
+    }
+
+    /// Determines whether the specified index refers to a live value.
+    pub fn contains(&self, index: usize) -> bool {
+        lock(&self.inner).status(index).is_occupied()
+    }
+
+    /// Gets the number of slots that were handed out so far.
+    pub fn slot_count(&self) -> usize {
+        lock(&self.inner).slot_count()
+    }
+}
+
+impl<T> SharedHandle<T> {
+    /// Gets the index of the slot this handle refers to.
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Gets the number of handles referring to the same value.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::SharedPool;
+    ///
+    /// let pool = SharedPool::default();
+    /// let handle = pool.insert(42);
+    /// let other = handle.clone();
+    /// assert_eq!(handle.strong_count(), 2);
+    /// ```
+    pub fn strong_count(&self) -> usize {
+        self.slot().refs.load(Ordering::Relaxed)
+    }
+
+    #[inline]
+    fn slot(&self) -> &SharedSlot<T> {
+        // SAFETY: The slot stays alive and in place while this handle exists.
+        unsafe { self.slot.as_ref() }
+    }
+}
+
+impl<T> Clone for SharedHandle<T> {
+    fn clone(&self) -> Self {
+        self.slot().refs.fetch_add(1, Ordering::Relaxed);
+        Self {
+            pool: self.pool.clone(),
+            slot: self.slot,
+            index: self.index,
+        }
+    }
+}
+
+impl<T> Deref for SharedHandle<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.slot().value
+    }
+}
+
+impl<T> Drop for SharedHandle<T> {
+    fn drop(&mut self) {
+        if self.slot().refs.fetch_sub(1, Ordering::Release) != 1 {
+            return;
+        }
+
+        // Synchronize with the decrements of all other handles before dropping the value.
+        fence(Ordering::Acquire);
+        lock(&self.pool).erase(self.index);
+    }
+}
+
+impl<T: Debug> Debug for SharedHandle<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SharedHandle")
+            .field("index", &self.index)
+            .field("value", &**self)
+            .finish()
+    }
+}
+
+/// Locks the pool, ignoring poisoning since the pool is never left in an inconsistent state.
+fn lock<T>(
+    pool: &Mutex<PagedFreeList<SharedSlot<T>, usize>>,
+) -> MutexGuard<'_, PagedFreeList<SharedSlot<T>, usize>> {
+    pool.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slots_are_reused_after_last_handle_is_dropped() {
+        let pool = SharedPool::default();
+        let a = pool.insert(1);
+        let b = pool.insert(2);
+        assert_eq!((a.index(), b.index()), (0, 1));
+
+        let a2 = a.clone();
+        drop(a);
+        assert_eq!(pool.insert(3).index(), 2);
+
+        drop(a2);
+        let c = pool.insert(4);
+        assert_eq!(c.index(), 0);
+        assert_eq!(*c, 4);
+        assert_eq!(*b, 2);
+    }
+
+    #[test]
+    fn handles_keep_pool_alive() {
+        let value = Arc::new(());
+        let handle = {
+            let pool = SharedPool::default();
+            pool.insert(value.clone())
+        };
+        assert_eq!(Arc::strong_count(&value), 2);
+        drop(handle);
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+
+    #[test]
+    fn handles_can_be_shared_across_threads() {
+        let pool = SharedPool::default();
+        let handle = pool.insert(String::from("shared"));
+
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                let handle = handle.clone();
+                scope.spawn(move || {
+                    for _ in 0..100 {
+                        let copy = handle.clone();
+                        assert_eq!(copy.as_str(), "shared");
+                    }
+                });
+            }
+        });
+
+        assert_eq!(handle.strong_count(), 1);
+        let index = handle.index();
+        drop(handle);
+        assert!(!pool.contains(index));
+    }
+}
@@ -0,0 +1,372 @@
+use crate::ShmError;
+use std::cell::UnsafeCell;
+use std::mem::{align_of, size_of, MaybeUninit};
+use std::ptr::NonNull;
+use std::sync::atomic::{fence, AtomicU32, Ordering};
+
+/// A fixed-capacity free list that lives in a caller-provided memory region, e.g. a
+/// shared memory segment mapped by several processes.
+///
+/// The region only holds a `#[repr(C)]` header followed by the slots, and all links are
+/// slot indices, so every process can map the region at a different address. Elements
+/// are addressed by plain `u64` handles that combine the slot index and its generation
+/// and can be exchanged over any IPC channel.
+///
+/// One process writes with [`push`](Self::push) and [`erase`](Self::erase); any number of
+/// processes read with [`get`](Self::get). Readers never block the writer: a read copies
+/// the element and is rejected if the slot changed while it was copied.
+///
+/// ## Type parameters
+/// * `T` - The type of the element. It must not contain pointers or references, since
+///   they are meaningless in other processes, and must have the same layout in all of
+///   them, which `#[repr(C)]` types of primitives have.
+///
+/// ## Example
+/// ```rust
+/// use free_list::ShmFreeList;
+/// use std::ptr::NonNull;
+///
+/// // Stands in for a shared memory segment.
+/// let mut segment = vec![0u64; ShmFreeList::<[f32; 3]>::required_len(16) / 8];
+/// let region = NonNull::new(segment.as_mut_ptr().cast::<u8>()).unwrap();
+/// let len = segment.len() * 8;
+///
+/// let mut writer = unsafe { ShmFreeList::<[f32; 3]>::create(region, len) }.unwrap();
+/// let handle = writer.push([1.0, 2.0, 3.0]).unwrap();
+///
+/// let reader = unsafe { ShmFreeList::<[f32; 3]>::open(region, len) }.unwrap();
+/// assert_eq!(reader.capacity(), 16);
+/// assert_eq!(reader.get(handle), Some([1.0, 2.0, 3.0]));
+///
+/// writer.erase(handle);
+/// assert_eq!(reader.get(handle), None);
+/// ```
+pub struct ShmFreeList<T>
+where
+    T: Copy,
+{
+    header: NonNull<ShmHeader>,
+    slots: NonNull<ShmSlot<T>>,
+}
+
+/// The header at the start of the region of a [`ShmFreeList`].
+#[repr(C)]
+struct ShmHeader {
+    /// Identifies the region as a list; [`ShmHeader::MAGIC`].
+    magic: u32,
+    /// The version of the layout; [`ShmHeader::VERSION`].
+    version: u32,
+    /// The size of a slot, to reject regions created for another element type.
+    slot_size: u32,
+    /// The number of slots that fit into the region.
+    capacity: u32,
+    /// The number of live elements.
+    length: AtomicU32,
+    /// The number of slots that were ever used; higher slots are uninitialized.
+    slot_count: AtomicU32,
+    /// The first slot of the free chain, or `SENTINEL` if the chain is empty.
+    first_free: AtomicU32,
+    /// Keeps the header size a multiple of eight bytes.
+    _reserved: u32,
+}
+
+impl ShmHeader {
+    const MAGIC: u32 = u32::from_le_bytes(*b"FLSM");
+    const VERSION: u32 = 1;
+}
+
+/// A slot of a [`ShmFreeList`].
+#[repr(C)]
+struct ShmSlot<T> {
+    /// The generation of the slot; odd while the slot holds an element.
+    generation: AtomicU32,
+    /// The next free slot, or `SENTINEL` for the last free slot.
+    next: AtomicU32,
+    /// The element; only initialized while the generation is odd.
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+impl<T> ShmFreeList<T>
+where
+    T: Copy,
+{
+    /// The sentinel value indicates the absence of a valid index.
+    const SENTINEL: u32 = u32::MAX;
+
+    /// The offset of the first slot from the start of the region.
+    const SLOTS_OFFSET: usize = size_of::<ShmHeader>().next_multiple_of(align_of::<ShmSlot<T>>());
+
+    /// The alignment the region must have.
+    const ALIGN: usize = if align_of::<ShmHeader>() > align_of::<ShmSlot<T>>() {
+        align_of::<ShmHeader>()
+    } else {
+        align_of::<ShmSlot<T>>()
+    };
+
+    /// Gets the number of bytes a region needs to hold the specified number of elements.
+    pub const fn required_len(capacity: usize) -> usize {
+        Self::SLOTS_OFFSET + capacity * size_of::<ShmSlot<T>>()
+    }
+
+    /// Initializes an empty list in the region, overwriting its contents, and returns
+    /// the writer of the list. As many slots as fit into the region are used.
+    ///
+    /// # Safety
+    /// The region must be valid for reads and writes of `len` bytes for as long as the
+    /// returned list or any list opened on it is used, and no other writer may exist.
+    pub unsafe fn create(region: NonNull<u8>, len: usize) -> Result<Self, ShmError> {
+        Self::check_region(region, len)?;
+        let capacity = (len - Self::SLOTS_OFFSET) / size_of::<ShmSlot<T>>();
+        let capacity = capacity.min(Self::SENTINEL as usize - 1) as u32;
+
+        let header = region.cast::<ShmHeader>();
+        unsafe {
+            header.as_ptr().write(ShmHeader {
+                magic: ShmHeader::MAGIC,
+                version: ShmHeader::VERSION,
+                slot_size: size_of::<ShmSlot<T>>() as u32,
+                capacity,
+                length: AtomicU32::new(0),
+                slot_count: AtomicU32::new(0),
+                first_free: AtomicU32::new(Self::SENTINEL),
+                _reserved: 0,
+            });
+        }
+        fence(Ordering::Release);
+        Ok(unsafe { Self::from_region(region) })
+    }
+
+    /// Opens a list that was created in the region, e.g. by another process.
+    ///
+    /// # Safety
+    /// The region must be valid for reads of `len` bytes for as long as the returned
+    /// list is used, and for writes as well if the list is modified through it. At most
+    /// one of all lists opened on the region may be modified.
+    pub unsafe fn open(region: NonNull<u8>, len: usize) -> Result<Self, ShmError> {
+        Self::check_region(region, len)?;
+        let header = unsafe { region.cast::<ShmHeader>().as_ref() };
+        fence(Ordering::Acquire);
+        if header.magic != ShmHeader::MAGIC
+            || header.version != ShmHeader::VERSION
+            || header.slot_size as usize != size_of::<ShmSlot<T>>()
+        {
+            return Err(ShmError::Incompatible);
+        }
+        if Self::required_len(header.capacity as usize) > len {
+            return Err(ShmError::TooSmall);
+        }
+        Ok(unsafe { Self::from_region(region) })
+    }
+
+    fn check_region(region: NonNull<u8>, len: usize) -> Result<(), ShmError> {
+        if !(region.as_ptr() as usize).is_multiple_of(Self::ALIGN) {
+            Err(ShmError::Misaligned)
+        } else if len < Self::SLOTS_OFFSET {
+            Err(ShmError::TooSmall)
+        } else {
+            Ok(())
+        }
+    }
+
+    unsafe fn from_region(region: NonNull<u8>) -> Self {
+        Self {
+            header: region.cast(),
+            slots: unsafe { region.add(Self::SLOTS_OFFSET).cast() },
+        }
+    }
+
+    #[inline]
+    fn header(&self) -> &ShmHeader {
+        // SAFETY: The header was validated when the list was created or opened.
+        unsafe { self.header.as_ref() }
+    }
+
+    /// Gets the slot at the specified index, which must be below the capacity.
+    #[inline]
+    fn slot(&self, index: u32) -> &ShmSlot<T> {
+        // SAFETY: The region holds `capacity` slots.
+        unsafe { self.slots.add(index as usize).as_ref() }
+    }
+
+    /// Splits a handle into its slot index and generation.
+    #[inline]
+    fn split(handle: u64) -> (u32, u32) {
+        (handle as u32, (handle >> 32) as u32)
+    }
+
+    /// Inserts an element and returns a handle to it, or `None` if the list is full.
+    pub fn push(&mut self, value: T) -> Option<u64> {
+        let header = self.header();
+        let first_free = header.first_free.load(Ordering::Relaxed);
+        let index = if first_free != Self::SENTINEL {
+            let next = self.slot(first_free).next.load(Ordering::Relaxed);
+            header.first_free.store(next, Ordering::Relaxed);
+            first_free
+        } else {
+            let count = header.slot_count.load(Ordering::Relaxed);
+            if count == header.capacity {
+                return None;
+            }
+            let slot = self.slot(count);
+            slot.generation.store(0, Ordering::Relaxed);
+            slot.next.store(Self::SENTINEL, Ordering::Relaxed);
+            header.slot_count.store(count + 1, Ordering::Release);
+            count
+        };
+
+        // The slot is free, so no reader accepts its value until the generation is odd.
+        // Like the writer of a seqlock, the even generation stored when the slot was freed
+        // is ordered before the new bytes, so that a reader that copies any of them also
+        // sees the changed generation afterwards and rejects the copy.
+        let slot = self.slot(index);
+        fence(Ordering::Release);
+        unsafe { std::ptr::write_volatile(slot.value.get(), MaybeUninit::new(value)) };
+        let generation = slot.generation.load(Ordering::Relaxed).wrapping_add(1);
+        slot.generation.store(generation, Ordering::Release);
+        header.length.fetch_add(1, Ordering::Release);
+        Some(u64::from(generation) << 32 | u64::from(index))
+    }
+
+    /// Removes the element of the handle and returns it, or `None` if the handle does
+    /// not refer to an element.
+    pub fn erase(&mut self, handle: u64) -> Option<T> {
+        let (index, generation) = Self::split(handle);
+        if !self.contains(handle) {
+            return None;
+        }
+
+        let header = self.header();
+        let slot = self.slot(index);
+        // SAFETY: The generation is odd, so the slot holds an element.
+        let value = unsafe { (*slot.value.get()).assume_init() };
+        slot.generation
+            .store(generation.wrapping_add(1), Ordering::Release);
+        slot.next
+            .store(header.first_free.load(Ordering::Relaxed), Ordering::Relaxed);
+        header.first_free.store(index, Ordering::Relaxed);
+        header.length.fetch_sub(1, Ordering::Release);
+        Some(value)
+    }
+
+    /// Gets a copy of the element of the handle, or `None` if the handle does not refer
+    /// to an element, also if the element is removed while it is copied.
+    pub fn get(&self, handle: u64) -> Option<T> {
+        let (index, generation) = Self::split(handle);
+        if !self.contains(handle) {
+            return None;
+        }
+
+        // The writer may reuse the slot while the element is copied; the copy is only
+        // trusted if the generation is unchanged afterwards.
+        let slot = self.slot(index);
+        let value = unsafe { std::ptr::read_volatile(slot.value.get()) };
+        fence(Ordering::Acquire);
+        if slot.generation.load(Ordering::Relaxed) != generation {
+            return None;
+        }
+        // SAFETY: The slot held the element of the handle during the whole copy.
+        Some(unsafe { value.assume_init() })
+    }
+
+    /// Determines whether the handle refers to an element.
+    pub fn contains(&self, handle: u64) -> bool {
+        let (index, generation) = Self::split(handle);
+        generation % 2 == 1
+            && index < self.header().slot_count.load(Ordering::Acquire)
+            && self.slot(index).generation.load(Ordering::Acquire) == generation
+    }
+
+    /// Gets the number of elements in the list.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.header().length.load(Ordering::Acquire) as usize
+    }
+
+    /// Determines whether the list contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Gets the number of elements the region can hold.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.header().capacity as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reader_sees_the_changes_of_the_writer() {
+        let mut segment = vec![0u64; ShmFreeList::<u64>::required_len(2) / 8];
+        let region = NonNull::new(segment.as_mut_ptr().cast::<u8>()).unwrap();
+        let len = segment.len() * 8;
+
+        let mut writer = unsafe { ShmFreeList::<u64>::create(region, len) }.unwrap();
+        let reader = unsafe { ShmFreeList::<u64>::open(region, len) }.unwrap();
+        let first = writer.push(1).unwrap();
+        let second = writer.push(2).unwrap();
+        assert_eq!(writer.push(3), None);
+        assert_eq!(reader.len(), 2);
+
+        // Reusing a slot invalidates the old handle.
+        assert_eq!(writer.erase(first), Some(1));
+        let third = writer.push(3).unwrap();
+        assert_eq!(third as u32, first as u32);
+        assert_eq!(reader.get(first), None);
+        assert_eq!(reader.get(third), Some(3));
+        assert_eq!(reader.get(second), Some(2));
+        assert_eq!(writer.erase(first), None);
+
+        assert_eq!(
+            unsafe { ShmFreeList::<u32>::open(region, len) }.err(),
+            Some(ShmError::Incompatible)
+        );
+        assert_eq!(
+            unsafe { ShmFreeList::<u64>::open(region, 8) }.err(),
+            Some(ShmError::TooSmall)
+        );
+    }
+
+    #[test]
+    fn readers_never_accept_values_of_a_reused_slot() {
+        use std::sync::atomic::{AtomicBool, AtomicU64};
+
+        const ROUNDS: u64 = 20_000;
+
+        let mut segment = vec![0u64; ShmFreeList::<[u64; 4]>::required_len(1) / 8];
+        let region = NonNull::new(segment.as_mut_ptr().cast::<u8>()).unwrap();
+        let (address, len) = (region.as_ptr() as usize, segment.len() * 8);
+
+        let mut writer = unsafe { ShmFreeList::<[u64; 4]>::create(region, len) }.unwrap();
+        let latest = AtomicU64::new(writer.push([0; 4]).unwrap());
+        let done = AtomicBool::new(false);
+        std::thread::scope(|scope| {
+            for _ in 0..2 {
+                let (latest, done) = (&latest, &done);
+                scope.spawn(move || {
+                    let region = NonNull::new(address as *mut u8).unwrap();
+                    let reader = unsafe { ShmFreeList::<[u64; 4]>::open(region, len) }.unwrap();
+                    while !done.load(Ordering::Acquire) {
+                        // The element of round `r` has the generation `2 * r + 1`.
+                        let handle = latest.load(Ordering::Acquire);
+                        let round = u64::from((handle >> 32) as u32 / 2);
+                        if let Some(value) = reader.get(handle) {
+                            assert_eq!(value, [round; 4]);
+                        }
+                    }
+                });
+            }
+
+            for round in 1..ROUNDS {
+                writer.erase(latest.load(Ordering::Relaxed)).unwrap();
+                let handle = writer.push([round; 4]).unwrap();
+                latest.store(handle, Ordering::Release);
+            }
+            done.store(true, Ordering::Release);
+        });
+    }
+}
@@ -0,0 +1,334 @@
+use crate::index_type::*;
+use crate::{FreeList, Key};
+use std::cell::UnsafeCell;
+use std::fmt::{Debug, Formatter};
+use std::ops::{Deref, DerefMut};
+#[cfg(debug_assertions)]
+use std::sync::atomic::{AtomicIsize, Ordering};
+
+/// A free list whose elements can be mutated through a shared reference, from multiple
+/// threads at once, as long as the caller guarantees that no two accesses to the same
+/// element overlap.
+///
+/// This suits parallel systems that partition keys among workers themselves: every
+/// worker obtains the [`SlotCell`]s of its own keys through [`slot_cell`](Self::slot_cell)
+/// and accesses them without any locking or borrow tracking.
+///
+/// ## Contract
+/// [`SlotCell::borrow_mut`] must not be called while any other access to the same
+/// element is alive, and [`SlotCell::borrow`] must not be called while a mutable access
+/// to it is alive, on any thread. Accesses to different elements never conflict.
+///
+/// In debug builds, every cell carries an access counter and violations of the contract
+/// panic instead of causing undefined behavior. Release builds do not check anything.
+///
+/// ## Type parameters
+/// * `T` - The type of the element.
+/// * `TIndex` - The type of the index; see [`FreeList`] for details.
+///
+/// ## Example
+/// ```rust
+/// use free_list::SlotCellFreeList;
+///
+/// let mut list = SlotCellFreeList::<u64>::default();
+/// let keys: Vec<_> = (0..8).map(|value| list.push(value)).collect();
+///
+/// let list = &list;
+/// std::thread::scope(|scope| {
+///     for chunk in keys.chunks(2) {
+///         scope.spawn(move || {
+///             for &key in chunk {
+///                 // SAFETY: Every key is only accessed by the thread of its chunk.
+///                 *unsafe { list.slot_cell(key).unwrap().borrow_mut() } *= 10;
+///             }
+///         });
+///     }
+/// });
+///
+/// assert_eq!(unsafe { *list.slot_cell(keys[7]).unwrap().borrow() }, 70);
+/// ```
+pub struct SlotCellFreeList<T, TIndex = DefaultIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    list: FreeList<SlotCell<T>, TIndex>,
+}
+
+/// An element of a [`SlotCellFreeList`] that can be accessed through a shared reference.
+pub struct SlotCell<T> {
+    value: UnsafeCell<T>,
+    /// The number of shared accesses, or `-1` during a mutable access.
+    #[cfg(debug_assertions)]
+    accesses: AtomicIsize,
+}
+
+/// A shared access to the element of a [`SlotCell`], created by [`SlotCell::borrow`].
+pub struct SlotCellRef<'a, T> {
+    cell: &'a SlotCell<T>,
+}
+
+/// A mutable access to the element of a [`SlotCell`], created by [`SlotCell::borrow_mut`].
+pub struct SlotCellMut<'a, T> {
+    cell: &'a SlotCell<T>,
+}
+
+unsafe impl<T: Send + Sync> Sync for SlotCell<T> {}
+
+impl<T, TIndex> Default for SlotCellFreeList<T, TIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    fn default() -> Self {
+        Self {
+            list: FreeList::default(),
+        }
+    }
+}
+
+impl<T, TIndex> SlotCellFreeList<T, TIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    /// Inserts an element and returns its key.
+    #[inline]
+    pub fn push(&mut self, element: T) -> Key<TIndex> {
+        self.list.push_key(SlotCell::new(element))
+    }
+
+    /// Removes the element of the specified key and returns it, or returns `None` if the
+    /// key does not refer to an element.
+    pub fn remove(&mut self, key: Key<TIndex>) -> Option<T> {
+        self.list
+            .contains_key(key)
+            .then(|| self.list.remove(key.index()).into_inner())
+    }
+
+    /// Gets the cell of the element of the specified key, or `None` if the key does not
+    /// refer to an element.
+    #[inline]
+    pub fn slot_cell(&self, key: Key<TIndex>) -> Option<&SlotCell<T>> {
+        self.list.contains_key(key).then(|| &self.list[key.index()])
+    }
+
+    /// Gets a mutable reference to the element of the specified key, or `None` if the
+    /// key does not refer to an element; no contract applies since the access is exclusive.
+    #[inline]
+    pub fn get_mut(&mut self, key: Key<TIndex>) -> Option<&mut T> {
+        self.list
+            .contains_key(key)
+            .then(|| self.list[key.index()].get_mut())
+    }
+
+    /// Determines whether the specified key refers to an element.
+    #[inline]
+    pub fn contains_key(&self, key: Key<TIndex>) -> bool {
+        self.list.contains_key(key)
+    }
+
+    /// Gets the number of elements.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Determines whether the list is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Gets an iterator over the indices and cells of all elements in ascending index order.
+    pub fn cells(&self) -> impl Iterator<Item = (TIndex, &SlotCell<T>)> + '_ {
+        self.list.iter()
+    }
+}
+
+impl<T, TIndex> Debug for SlotCellFreeList<T, TIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SlotCellFreeList")
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: Default> Default for SlotCell<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> SlotCell<T> {
+    /// Creates a cell holding the specified value.
+    #[inline]
+    pub fn new(value: T) -> Self {
+        Self {
+            value: UnsafeCell::new(value),
+            #[cfg(debug_assertions)]
+            accesses: AtomicIsize::new(0),
+        }
+    }
+
+    /// Gets shared access to the element.
+    ///
+    /// ## Safety
+    /// No mutable access to the element may be alive at the same time; see the
+    /// contract of [`SlotCellFreeList`].
+    ///
+    /// ## Panics
+    /// Panics in debug builds if the element is accessed mutably.
+    #[inline]
+    pub unsafe fn borrow(&self) -> SlotCellRef<'_, T> {
+        #[cfg(debug_assertions)]
+        {
+            let previous = self.accesses.fetch_add(1, Ordering::Acquire);
+            if previous < 0 {
+                self.accesses.fetch_sub(1, Ordering::Relaxed);
+                panic!("Attempted to access a slot that is accessed mutably");
+            }
+        }
+        SlotCellRef { cell: self }
+    }
+
+    /// Gets mutable access to the element.
+    ///
+    /// ## Safety
+    /// No other access to the element may be alive at the same time; see the contract
+    /// of [`SlotCellFreeList`].
+    ///
+    /// ## Panics
+    /// Panics in debug builds if the element is accessed elsewhere.
+    #[inline]
+    pub unsafe fn borrow_mut(&self) -> SlotCellMut<'_, T> {
+        #[cfg(debug_assertions)]
+        if self
+            .accesses
+            .compare_exchange(0, -1, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            panic!("Attempted to mutably access a slot that is already accessed");
+        }
+        SlotCellMut { cell: self }
+    }
+
+    /// Gets a mutable reference to the element; no contract applies since the access
+    /// is exclusive.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+
+    /// Unwraps the element.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+impl<'a, T> Deref for SlotCellRef<'a, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: The caller of `borrow` guarantees that no mutable access is alive.
+        unsafe { &*self.cell.value.get() }
+    }
+}
+
+impl<'a, T> Drop for SlotCellRef<'a, T> {
+    #[inline]
+    fn drop(&mut self) {
+        #[cfg(debug_assertions)]
+        self.cell.accesses.fetch_sub(1, Ordering::Release);
+    }
+}
+
+impl<'a, T: Debug> Debug for SlotCellRef<'a, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<'a, T> Deref for SlotCellMut<'a, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: The caller of `borrow_mut` guarantees that no other access is alive.
+        unsafe { &*self.cell.value.get() }
+    }
+}
+
+impl<'a, T> DerefMut for SlotCellMut<'a, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // SAFETY: The caller of `borrow_mut` guarantees that no other access is alive.
+        unsafe { &mut *self.cell.value.get() }
+    }
+}
+
+impl<'a, T> Drop for SlotCellMut<'a, T> {
+    #[inline]
+    fn drop(&mut self) {
+        #[cfg(debug_assertions)]
+        self.cell.accesses.store(0, Ordering::Release);
+    }
+}
+
+impl<'a, T: Debug> Debug for SlotCellMut<'a, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disjoint_slots_are_mutated_in_parallel() {
+        const THREADS: usize = 4;
+        const ROUNDS: usize = 1000;
+
+        let mut list = SlotCellFreeList::<usize, u16>::default();
+        let keys: Vec<_> = (0..THREADS * 2).map(|_| list.push(0)).collect();
+        std::thread::scope(|scope| {
+            for chunk in keys.chunks(2) {
+                let list = &list;
+                scope.spawn(move || {
+                    for _ in 0..ROUNDS {
+                        for &key in chunk {
+                            // SAFETY: The chunks are disjoint.
+                            *unsafe { list.slot_cell(key).unwrap().borrow_mut() } += 1;
+                        }
+                    }
+                });
+            }
+        });
+
+        assert!(list
+            .cells()
+            .all(|(_, cell)| unsafe { *cell.borrow() } == ROUNDS));
+        assert_eq!(list.remove(keys[0]), Some(ROUNDS));
+        assert!(list.slot_cell(keys[0]).is_none());
+        *list.get_mut(keys[1]).unwrap() = 0;
+        assert_eq!(list.len(), THREADS * 2 - 1);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Attempted to mutably access a slot that is already accessed")]
+    fn overlapping_access_panics_in_debug_builds() {
+        let mut list = SlotCellFreeList::<u32>::default();
+        let key = list.push(1);
+        let cell = list.slot_cell(key).unwrap();
+        let _value = unsafe { cell.borrow() };
+        let _ = unsafe { cell.borrow_mut() };
+    }
+}
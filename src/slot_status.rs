@@ -0,0 +1,29 @@
+/// The state of a slot in a [`FreeList`](crate::FreeList).
+///
+/// This type is returned by [`FreeList::status`](crate::FreeList::status).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SlotStatus {
+    /// The slot holds an element and can be safely accessed.
+    Occupied,
+    /// The slot was erased and does not hold an element.
+    Free,
+    /// The index does not refer to any slot of the list.
+    OutOfRange,
+}
+
+impl SlotStatus {
+    /// Determines whether the slot holds an element.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::SlotStatus;
+    ///
+    /// assert!(SlotStatus::Occupied.is_occupied());
+    /// assert!(!SlotStatus::Free.is_occupied());
+    /// assert!(!SlotStatus::OutOfRange.is_occupied());
+    /// ```
+    #[inline]
+    pub fn is_occupied(self) -> bool {
+        self == SlotStatus::Occupied
+    }
+}
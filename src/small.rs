@@ -0,0 +1,307 @@
+use crate::index_type::*;
+use crate::{ArrayFreeList, FreeList};
+use std::fmt::{Debug, Formatter};
+
+/// A free list that stores its first `N` slots inline and spills over to the heap when
+/// more are needed.
+///
+/// Lists that stay small never allocate, which suits workloads with many tiny,
+/// short-lived lists; see [`ArrayFreeList`] for a list that never grows. Once an element
+/// does not fit inline anymore, all elements are moved into a [`FreeList`] on the heap
+/// at their current indices, so indices remain valid across the spill. The list does not
+/// move back inline when it shrinks again, except through [`clear`](Self::clear).
+///
+/// ## Type parameters
+/// * `T` - The type of the element.
+/// * `N` - The number of inline slots.
+/// * `TIndex` - The type of the index; see [`FreeList`] for details.
+///
+/// ## Example
+/// ```rust
+/// use free_list::SmallFreeList;
+///
+/// let mut list = SmallFreeList::<&str, 2>::default();
+/// let first = list.push("first");
+/// list.push("second");
+/// assert!(!list.spilled());
+///
+/// list.push("third");
+/// assert!(list.spilled());
+/// assert_eq!(list.get(first), Some(&"first"));
+/// ```
+pub struct SmallFreeList<T, const N: usize, TIndex = DefaultIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    storage: Storage<T, N, TIndex>,
+    /// The number of elements.
+    len: usize,
+}
+
+/// The slots of a [`SmallFreeList`].
+///
+/// The inline variant is meant to be large, since boxing it would defeat its purpose.
+#[allow(clippy::large_enum_variant)]
+enum Storage<T, const N: usize, TIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    Inline(ArrayFreeList<T, N, TIndex>),
+    Heap(FreeList<T, TIndex>),
+}
+
+impl<T, const N: usize, TIndex> Default for SmallFreeList<T, N, TIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    /// Creates an empty list that stores its elements inline.
+    fn default() -> Self {
+        Self {
+            storage: Storage::Inline(ArrayFreeList::new()),
+            len: 0,
+        }
+    }
+}
+
+impl<T, const N: usize, TIndex> SmallFreeList<T, N, TIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    /// Inserts an element to the list and returns an index to it, moving all elements
+    /// to the heap if the inline slots are exhausted.
+    pub fn push(&mut self, element: T) -> TIndex {
+        let index = match &mut self.storage {
+            Storage::Inline(list) => match list.push(element) {
+                Ok(index) => index,
+                Err(element) => self.spill().push(element),
+            },
+            Storage::Heap(list) => list.push(element),
+        };
+        self.len += 1;
+        index
+    }
+
+    /// Removes the element at the specified index and returns it, or returns `None` if
+    /// the slot is not occupied.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::SmallFreeList;
+    ///
+    /// let mut list = SmallFreeList::<&str, 4>::default();
+    /// let index = list.push("first");
+    /// assert_eq!(list.remove(index), Some("first"));
+    /// assert_eq!(list.remove(index), None);
+    /// ```
+    pub fn remove(&mut self, index: TIndex) -> Option<T> {
+        let element = match &mut self.storage {
+            Storage::Inline(list) => list.remove(index)?,
+            Storage::Heap(list) => {
+                list.get(index)?;
+                list.remove(index)
+            }
+        };
+        self.len -= 1;
+        Some(element)
+    }
+
+    /// Gets a reference to the element at the specified index, or `None` if the slot is
+    /// not occupied.
+    pub fn get(&self, index: TIndex) -> Option<&T> {
+        match &self.storage {
+            // SAFETY: The slot is occupied.
+            Storage::Inline(list) => list
+                .status(index)
+                .is_occupied()
+                .then(|| unsafe { list.at(index) }),
+            Storage::Heap(list) => list.get(index),
+        }
+    }
+
+    /// Gets a mutable reference to the element at the specified index, or `None` if the
+    /// slot is not occupied.
+    pub fn get_mut(&mut self, index: TIndex) -> Option<&mut T> {
+        match &mut self.storage {
+            // SAFETY: The slot is occupied.
+            Storage::Inline(list) => list
+                .status(index)
+                .is_occupied()
+                .then(|| unsafe { list.at_mut(index) }),
+            Storage::Heap(list) => list.get_mut(index),
+        }
+    }
+
+    /// Determines whether the specified index refers to an occupied slot.
+    #[inline]
+    pub fn contains(&self, index: TIndex) -> bool {
+        self.get(index).is_some()
+    }
+
+    /// Removes all elements and moves the list back inline.
+    pub fn clear(&mut self) {
+        // The length is reset first, since dropping the elements may panic.
+        self.len = 0;
+        self.storage = Storage::Inline(ArrayFreeList::new());
+    }
+
+    /// Gets the number of elements.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Determines whether the list is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Determines whether the elements were moved to the heap.
+    #[inline]
+    pub fn spilled(&self) -> bool {
+        matches!(self.storage, Storage::Heap(_))
+    }
+
+    /// Gets an iterator over the indices and values of all elements in ascending index order.
+    pub fn iter(&self) -> Box<dyn Iterator<Item = (TIndex, &T)> + '_> {
+        match &self.storage {
+            // SAFETY: The keys refer to occupied slots.
+            Storage::Inline(list) => {
+                Box::new(list.keys().map(|index| (index, unsafe { list.at(index) })))
+            }
+            Storage::Heap(list) => Box::new(list.iter()),
+        }
+    }
+
+    /// Moves the inline elements to the heap at their current indices.
+    fn spill(&mut self) -> &mut FreeList<T, TIndex> {
+        let Storage::Inline(inline) = &mut self.storage else {
+            unreachable!("the list is stored inline");
+        };
+
+        // Spilling only happens when all inline slots are occupied, so the elements
+        // keep their indices when pushed in order.
+        // Only the moved elements are reserved for, since the index type may not be able
+        // to address another slot.
+        let slots = inline.slot_count();
+        let mut heap = FreeList::default();
+        heap.reserve(slots);
+        for slot in 0..slots {
+            let element = inline
+                .remove(index_from_usize(slot))
+                .expect("all inline slots are occupied");
+            heap.push(element);
+        }
+
+        self.storage = Storage::Heap(heap);
+        let Storage::Heap(heap) = &mut self.storage else {
+            unreachable!("the list was just spilled");
+        };
+        heap
+    }
+}
+
+impl<T, const N: usize, TIndex> Debug for SmallFreeList<T, N, TIndex>
+where
+    T: Default + Debug,
+    TIndex: IndexType + Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indices_survive_the_spill() {
+        let mut list = SmallFreeList::<String, 3, u8>::default();
+        let a = list.push("a".into());
+        let b = list.push("b".into());
+        let c = list.push("c".into());
+        assert_eq!(list.remove(b).as_deref(), Some("b"));
+        assert_eq!(list.push("d".into()), b);
+        assert!(!list.spilled());
+
+        // The fourth element does not fit inline anymore.
+        let e = list.push("e".into());
+        assert!(list.spilled());
+        assert_eq!(e, 3);
+        assert_eq!(list.get(a).map(String::as_str), Some("a"));
+        assert_eq!(list.get(c).map(String::as_str), Some("c"));
+        list.get_mut(b).unwrap().push('!');
+        assert_eq!(
+            list.iter()
+                .map(|(index, value)| (index, value.as_str()))
+                .collect::<Vec<_>>(),
+            [(0, "a"), (1, "d!"), (2, "c"), (3, "e")]
+        );
+        assert_eq!(list.len(), 4);
+
+        list.clear();
+        assert!(!list.spilled());
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn spilling_respects_the_limit_of_the_index_type() {
+        let mut list = SmallFreeList::<u8, 255, u8>::default();
+        for value in 0..254 {
+            assert_eq!(list.push(value), value);
+        }
+        assert!(!list.spilled());
+
+        // The elements are moved, but the index type cannot address another one.
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| list.push(254)));
+        let message = result.unwrap_err().downcast::<String>().unwrap();
+        assert!(message.starts_with("Attempted to insert more elements"));
+        assert!(list.spilled());
+        assert_eq!(list.len(), 254);
+        assert_eq!(list.get(253), Some(&253));
+
+        let mut list = SmallFreeList::<u8, 253, u8>::default();
+        for value in 0..254 {
+            assert_eq!(list.push(value), value);
+        }
+        assert!(list.spilled());
+    }
+
+    #[test]
+    fn clear_drops_every_inline_element_once_if_a_destructor_panics() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        #[derive(Default)]
+        struct Bomb(u32, Rc<Cell<u32>>);
+
+        impl Drop for Bomb {
+            fn drop(&mut self) {
+                self.1.set(self.1.get() + 1);
+                if self.0 == 0 {
+                    panic!("destructor failed");
+                }
+            }
+        }
+
+        let drops = Rc::new(Cell::new(0));
+        let mut list = SmallFreeList::<Bomb, 4, u8>::default();
+        for value in 0..2 {
+            list.push(Bomb(value, drops.clone()));
+        }
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| list.clear()));
+        assert!(result.is_err());
+        assert_eq!(drops.get(), 2);
+        assert!(list.is_empty());
+        assert!(!list.spilled());
+
+        drop(list);
+        assert_eq!(drops.get(), 2);
+    }
+}
@@ -0,0 +1,315 @@
+use crate::index_type::*;
+use crate::{FreeElement, FreeList, ReusePolicy};
+use allocator_api2::alloc::Allocator;
+use std::io::{self, Read, Write};
+use std::mem::ManuallyDrop;
+
+/// The magic bytes at the start of every snapshot.
+const MAGIC: [u8; 4] = *b"FLST";
+
+/// The version of the snapshot format written by [`FreeList::save_to`].
+const VERSION: u8 = 1;
+
+/// The encoding of `SENTINEL` links, independent of the index type.
+const NO_INDEX: u64 = u64::MAX;
+
+impl<T, TIndex, A> FreeList<T, TIndex, A>
+where
+    T: Default,
+    TIndex: IndexType,
+    A: Allocator,
+{
+    /// Writes a binary snapshot of the list, which can be restored with [`load_from`](Self::load_from).
+    ///
+    /// The snapshot preserves the indices of all elements as well as the free chain,
+    /// the quarantine and the slot generations, so that the restored list hands out
+    /// exactly the same indices and keys as this one. Elements are encoded by the
+    /// specified function; all other values are written in little-endian byte order.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    /// use std::io::{Read, Write};
+    ///
+    /// let mut list = FreeList::<u32, u8>::default();
+    /// list.push(1);
+    /// list.push(2);
+    /// list.erase(0);
+    ///
+    /// let mut snapshot = Vec::new();
+    /// list.save_to(&mut snapshot, |value, writer| writer.write_all(&value.to_le_bytes()))
+    ///     .unwrap();
+    ///
+    /// let mut restored = FreeList::<u32, u8>::load_from(snapshot.as_slice(), |reader| {
+    ///     let mut bytes = [0; 4];
+    ///     reader.read_exact(&mut bytes)?;
+    ///     Ok(u32::from_le_bytes(bytes))
+    /// })
+    /// .unwrap();
+    /// assert_eq!(restored.iter().collect::<Vec<_>>(), [(1, &2)]);
+    /// assert_eq!(restored.push(3), 0);
+    /// ```
+    pub fn save_to<W: Write>(
+        &self,
+        mut writer: W,
+        mut write_element: impl FnMut(&T, &mut W) -> io::Result<()>,
+    ) -> io::Result<()> {
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&[VERSION, encode_policy(self.policy)])?;
+
+        for value in [
+            self.slot_count(),
+            self.generations.len(),
+            self.length,
+            self.peak_length,
+            self.peak_slots,
+            self.quarantine_len,
+        ] {
+            write_u64(&mut writer, value as u64)?;
+        }
+        write_index(&mut writer, self.first_free)?;
+        write_index(&mut writer, self.last_free)?;
+
+        write_u64(&mut writer, self.quarantine.len() as u64)?;
+        for &index in &self.quarantine {
+            write_index(&mut writer, index)?;
+        }
+
+        for &generation in &self.generations {
+            writer.write_all(&generation.to_le_bytes())?;
+        }
+
+        for index in 0..self.slot_count() {
+            if self.occupied.contains(index) {
+                writer.write_all(&[1])?;
+                write_element(unsafe { self.element(index) }, &mut writer)?;
+            } else {
+                writer.write_all(&[0])?;
+                write_index(&mut writer, self.next_free(index))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Restores a list from a snapshot written by [`save_to`](Self::save_to).
+    ///
+    /// Elements are decoded by the specified function, which must read exactly the
+    /// bytes written by the encoder. The restored list is validated with
+    /// [`check_invariants`](Self::check_invariants); malformed or inconsistent
+    /// snapshots result in an error of kind [`io::ErrorKind::InvalidData`].
+    pub fn load_from<R: Read>(
+        mut reader: R,
+        mut read_element: impl FnMut(&mut R) -> io::Result<T>,
+    ) -> io::Result<Self>
+    where
+        A: Default,
+    {
+        let mut header = [0; 6];
+        reader.read_exact(&mut header)?;
+        if header[..4] != MAGIC {
+            return Err(invalid_data("not a free list snapshot"));
+        }
+        if header[4] != VERSION {
+            return Err(invalid_data("unsupported snapshot version"));
+        }
+
+        let mut list = Self::with_reuse_policy(decode_policy(header[5])?);
+        let slots = read_usize(&mut reader)?;
+        let generations = read_usize(&mut reader)?;
+        if slots > Self::SENTINEL.into_usize() - 1 || generations < slots {
+            return Err(invalid_data("slot count out of range"));
+        }
+
+        let length = read_usize(&mut reader)?;
+        list.peak_length = read_usize(&mut reader)?;
+        list.peak_slots = read_usize(&mut reader)?;
+        list.quarantine_len = read_usize(&mut reader)?;
+        let first_free = read_index(&mut reader)?;
+        let last_free = read_index(&mut reader)?;
+
+        for _ in 0..read_usize(&mut reader)? {
+            let index = read_index(&mut reader)?;
+            list.quarantine.push_back(index);
+        }
+
+        for _ in 0..generations {
+            let mut bytes = [0; 4];
+            reader.read_exact(&mut bytes)?;
+            list.generations.push(u32::from_le_bytes(bytes));
+        }
+
+        for index in 0..slots {
+            let mut tag = [0; 1];
+            reader.read_exact(&mut tag)?;
+            list.reserve_slots(1);
+            match tag[0] {
+                0 => {
+                    let next = read_index(&mut reader)?;
+                    list.append_slot(FreeElement {
+                        next: Self::SENTINEL,
+                    });
+                    list.set_next_free(index, next);
+                }
+                1 => {
+                    let element = read_element(&mut reader)?;
+                    list.append_slot(FreeElement {
+                        element: ManuallyDrop::new(element),
+                    });
+                    list.occupied.insert(index);
+                }
+                _ => return Err(invalid_data("invalid slot tag")),
+            }
+        }
+
+        list.length = length;
+        list.first_free = first_free;
+        list.last_free = last_free;
+        if let Err(error) = list.check_invariants() {
+            // The links cannot be trusted, so the list must not attempt to follow them.
+            list.first_free = Self::SENTINEL;
+            list.last_free = Self::SENTINEL;
+            return Err(io::Error::new(io::ErrorKind::InvalidData, error));
+        }
+        Ok(list)
+    }
+}
+
+pub(crate) fn encode_policy(policy: ReusePolicy) -> u8 {
+    match policy {
+        ReusePolicy::Lifo => 0,
+        ReusePolicy::Fifo => 1,
+        ReusePolicy::LowestIndexFirst => 2,
+    }
+}
+
+pub(crate) fn decode_policy(value: u8) -> io::Result<ReusePolicy> {
+    match value {
+        0 => Ok(ReusePolicy::Lifo),
+        1 => Ok(ReusePolicy::Fifo),
+        2 => Ok(ReusePolicy::LowestIndexFirst),
+        _ => Err(invalid_data("invalid reuse policy")),
+    }
+}
+
+pub(crate) fn write_u64(writer: &mut impl Write, value: u64) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+pub(crate) fn write_index<TIndex: IndexType>(
+    writer: &mut impl Write,
+    index: TIndex,
+) -> io::Result<()> {
+    if index == TIndex::MAX {
+        write_u64(writer, NO_INDEX)
+    } else {
+        write_u64(writer, index.into_usize() as u64)
+    }
+}
+
+pub(crate) fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+pub(crate) fn read_usize(reader: &mut impl Read) -> io::Result<usize> {
+    usize::try_from(read_u64(reader)?).map_err(|_| invalid_data("value out of range"))
+}
+
+pub(crate) fn read_index<TIndex: IndexType>(reader: &mut impl Read) -> io::Result<TIndex> {
+    match read_u64(reader)? {
+        NO_INDEX => Ok(TIndex::MAX),
+        value => usize::try_from(value)
+            .ok()
+            .and_then(TIndex::try_from_usize)
+            .filter(|&index| index != TIndex::MAX)
+            .ok_or_else(|| invalid_data("index out of range")),
+    }
+}
+
+pub(crate) fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn save(list: &FreeList<String, u16>) -> Vec<u8> {
+        let mut snapshot = Vec::new();
+        list.save_to(&mut snapshot, |element, writer| {
+            write_u64(writer, element.len() as u64)?;
+            writer.write_all(element.as_bytes())
+        })
+        .unwrap();
+        snapshot
+    }
+
+    fn load(snapshot: &[u8]) -> io::Result<FreeList<String, u16>> {
+        FreeList::load_from(snapshot, |reader| {
+            let mut bytes = vec![0; read_usize(reader)?];
+            reader.read_exact(&mut bytes)?;
+            String::from_utf8(bytes).map_err(|_| invalid_data("invalid string"))
+        })
+    }
+
+    #[test]
+    fn snapshots_preserve_the_state() {
+        let mut list = FreeList::<String, u16>::with_reuse_policy(ReusePolicy::Fifo);
+        list.set_quarantine_len(1);
+        for i in 0..8 {
+            list.push(i.to_string());
+        }
+        for i in [5, 2, 6] {
+            list.erase(i);
+        }
+        let key = list.push_key("new".into());
+
+        let mut restored = load(&save(&list)).unwrap();
+        assert_eq!(restored.reuse_policy(), ReusePolicy::Fifo);
+        assert_eq!(restored.quarantine_len(), 1);
+        assert_eq!(restored.stats(), list.stats());
+        assert!(restored.contains_key(key));
+        assert!(restored.iter().eq(list.iter()));
+
+        // Both lists hand out the same indices from here on.
+        for i in [1, 3, 7] {
+            list.erase(i);
+            restored.erase(i);
+        }
+        for _ in 0..4 {
+            assert_eq!(restored.push(String::new()), list.push(String::new()));
+        }
+    }
+
+    #[test]
+    fn malformed_snapshots_are_rejected() {
+        let mut list = FreeList::<String, u16>::default();
+        list.push("first".into());
+        list.push("second".into());
+        list.erase(0);
+        let snapshot = save(&list);
+
+        let mut bad_magic = snapshot.clone();
+        bad_magic[0] = b'X';
+        assert_eq!(
+            load(&bad_magic).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        // Link the free slot to the occupied one.
+        let mut bad_link = snapshot.clone();
+        let free_slot = bad_link.len() - (1 + 8 + 6) - (1 + 8);
+        bad_link[free_slot + 1..free_slot + 9].copy_from_slice(&1u64.to_le_bytes());
+        assert_eq!(
+            load(&bad_link).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        let truncated = &snapshot[..snapshot.len() - 1];
+        assert_eq!(
+            load(truncated).unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+}
@@ -0,0 +1,413 @@
+use crate::bitset::BitSet;
+use crate::index_type::*;
+use crate::{Keys, SlotStatus};
+
+/// A tuple of values that can be stored column-wise in a [`SoaFreeList`].
+///
+/// This trait is implemented for tuples of up to eight elements whose types
+/// implement [`Default`]; free slots hold default values so that every column
+/// is a plain, gap-free slice.
+pub trait SoaColumns: Sized {
+    /// The column storage, a tuple of vectors.
+    type Storage: Default;
+    /// A tuple of references to the fields of one row.
+    type Refs<'a>
+    where
+        Self: 'a;
+    /// A tuple of mutable references to the fields of one row.
+    type Muts<'a>
+    where
+        Self: 'a;
+    /// A tuple of slices over all columns.
+    type Slices<'a>
+    where
+        Self: 'a;
+    /// A tuple of mutable slices over all columns.
+    type SlicesMut<'a>
+    where
+        Self: 'a;
+
+    /// Appends a row.
+    fn push(storage: &mut Self::Storage, row: Self);
+
+    /// Replaces the row at the specified position and returns the previous one.
+    fn replace(storage: &mut Self::Storage, position: usize, row: Self) -> Self;
+
+    /// Gets references to the fields of the row at the specified position.
+    fn get(storage: &Self::Storage, position: usize) -> Self::Refs<'_>;
+
+    /// Gets mutable references to the fields of the row at the specified position.
+    fn get_mut(storage: &mut Self::Storage, position: usize) -> Self::Muts<'_>;
+
+    /// Gets slices over all columns.
+    fn slices(storage: &Self::Storage) -> Self::Slices<'_>;
+
+    /// Gets mutable slices over all columns.
+    fn slices_mut(storage: &mut Self::Storage) -> Self::SlicesMut<'_>;
+
+    /// Removes all rows.
+    fn clear(storage: &mut Self::Storage);
+}
+
+macro_rules! soa_columns {
+    ($($name:ident: $index:tt),+) => {
+        impl<$($name),+> SoaColumns for ($($name,)+)
+        where
+            $($name: Default),+
+        {
+            type Storage = ($(Vec<$name>,)+);
+            type Refs<'a> = ($(&'a $name,)+) where Self: 'a;
+            type Muts<'a> = ($(&'a mut $name,)+) where Self: 'a;
+            type Slices<'a> = ($(&'a [$name],)+) where Self: 'a;
+            type SlicesMut<'a> = ($(&'a mut [$name],)+) where Self: 'a;
+
+            #[inline]
+            fn push(storage: &mut Self::Storage, row: Self) {
+                $(storage.$index.push(row.$index);)+
+            }
+
+            #[inline]
+            fn replace(storage: &mut Self::Storage, position: usize, row: Self) -> Self {
+                ($(std::mem::replace(&mut storage.$index[position], row.$index),)+)
+            }
+
+            #[inline]
+            fn get(storage: &Self::Storage, position: usize) -> Self::Refs<'_> {
+                ($(&storage.$index[position],)+)
+            }
+
+            #[inline]
+            fn get_mut(storage: &mut Self::Storage, position: usize) -> Self::Muts<'_> {
+                ($(&mut storage.$index[position],)+)
+            }
+
+            #[inline]
+            fn slices(storage: &Self::Storage) -> Self::Slices<'_> {
+                ($(&storage.$index[..],)+)
+            }
+
+            #[inline]
+            fn slices_mut(storage: &mut Self::Storage) -> Self::SlicesMut<'_> {
+                ($(&mut storage.$index[..],)+)
+            }
+
+            fn clear(storage: &mut Self::Storage) {
+                $(storage.$index.clear();)+
+            }
+        }
+    };
+}
+
+soa_columns!(A: 0);
+soa_columns!(A: 0, B: 1);
+soa_columns!(A: 0, B: 1, C: 2);
+soa_columns!(A: 0, B: 1, C: 2, D: 3);
+soa_columns!(A: 0, B: 1, C: 2, D: 3, E: 4);
+soa_columns!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5);
+soa_columns!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6);
+soa_columns!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7);
+
+/// An indexed free list that stores each field of its tuple elements in a separate column.
+///
+/// All columns share the same slots and free chain, so an index refers to the same
+/// row in every column. Since each column is a contiguous slice, per-field loops over
+/// [`columns`](Self::columns) are cache friendly and can be vectorized. Free slots
+/// hold default values; use [`keys`](Self::keys) or [`status`](Self::status) to tell
+/// them apart from live rows.
+///
+/// ## Type parameters
+/// * `C` - The tuple type of a row, e.g. `(f32, f32, u8)`.
+/// * `TIndex` - The type of the index; see [`FreeList`](crate::FreeList) for details.
+///
+/// ## Example
+/// ```rust
+/// use free_list::SoaFreeList;
+///
+/// let mut particles = SoaFreeList::<(f32, f32), u16>::default();
+/// particles.push((0.0, 1.0));
+/// particles.push((5.0, -1.0));
+///
+/// let (positions, velocities) = particles.columns_mut();
+/// for (position, velocity) in positions.iter_mut().zip(velocities.iter()) {
+///     *position += *velocity;
+/// }
+/// assert_eq!(particles.get(1), Some((&4.0, &-1.0)));
+/// ```
+pub struct SoaFreeList<C, TIndex = DefaultIndex>
+where
+    C: SoaColumns,
+    TIndex: IndexType,
+{
+    /// The columns.
+    columns: C::Storage,
+    /// The number of slots that were handed out so far.
+    slots: usize,
+    /// The set of indices of all slots that currently hold a row.
+    occupied: BitSet,
+    /// The free slots, most recently freed last.
+    free: Vec<TIndex>,
+}
+
+impl<C, TIndex> Default for SoaFreeList<C, TIndex>
+where
+    C: SoaColumns,
+    TIndex: IndexType,
+{
+    /// Creates an empty list.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::SoaFreeList;
+    ///
+    /// let list = SoaFreeList::<(u32, bool), u8>::default();
+    /// assert!(list.is_empty());
+    /// ```
+    fn default() -> Self {
+        Self {
+            columns: C::Storage::default(),
+            slots: 0,
+            occupied: BitSet::default(),
+            free: Vec::new(),
+        }
+    }
+}
+
+impl<C, TIndex> SoaFreeList<C, TIndex>
+where
+    C: SoaColumns + Default,
+    TIndex: IndexType,
+{
+    /// Removes the row at the specified index and returns it, or returns `None`
+    /// if the slot is not occupied.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::SoaFreeList;
+    ///
+    /// let mut list = SoaFreeList::<(u32, char), u8>::default();
+    /// let index = list.push((1, 'a'));
+    /// assert_eq!(list.erase(index), Some((1, 'a')));
+    /// assert_eq!(list.erase(index), None);
+    /// ```
+    pub fn erase(&mut self, index: TIndex) -> Option<C> {
+        let position = index.into_usize();
+        if !self.occupied.contains(position) {
+            return None;
+        }
+
+        self.occupied.remove(position);
+        self.free.push(index);
+        Some(C::replace(&mut self.columns, position, C::default()))
+    }
+}
+
+impl<C, TIndex> SoaFreeList<C, TIndex>
+where
+    C: SoaColumns,
+    TIndex: IndexType,
+{
+    /// Inserts a row to the list and returns an index to it.
+    ///
+    /// ## Panics
+    /// Panics if the index type cannot address any more elements.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::SoaFreeList;
+    ///
+    /// let mut list = SoaFreeList::<(u32, char), u8>::default();
+    /// assert_eq!(list.push((1, 'a')), 0);
+    /// assert_eq!(list.push((2, 'b')), 1);
+    /// ```
+    pub fn push(&mut self, row: C) -> TIndex {
+        let index = match self.free.pop() {
+            Some(index) => {
+                drop(C::replace(&mut self.columns, index.into_usize(), row));
+                index
+            }
+            None => {
+                let index = self.slots;
+                assert!(
+                    index < TIndex::MAX.into_usize() - 1,
+                    "Attempted to insert more elements than can be addressed by the provided index type ({:?} allowed)",
+                    TIndex::MAX
+                );
+                C::push(&mut self.columns, row);
+                self.slots += 1;
+                index_from_usize(index)
+            }
+        };
+
+        self.occupied.insert(index.into_usize());
+        index
+    }
+
+    /// Removes all rows from the list.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::SoaFreeList;
+    ///
+    /// let mut list = SoaFreeList::<(u32, char), u8>::default();
+    /// list.push((1, 'a'));
+    /// list.clear();
+    /// assert_eq!(list.slot_count(), 0);
+    /// ```
+    pub fn clear(&mut self) {
+        C::clear(&mut self.columns);
+        self.slots = 0;
+        self.occupied.clear();
+        self.free.clear();
+    }
+
+    /// Gets references to the fields of the row at the specified index, or `None`
+    /// if the slot is not occupied.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::SoaFreeList;
+    ///
+    /// let mut list = SoaFreeList::<(u32, char), u8>::default();
+    /// let index = list.push((1, 'a'));
+    /// assert_eq!(list.get(index), Some((&1, &'a')));
+    /// ```
+    pub fn get(&self, index: TIndex) -> Option<C::Refs<'_>> {
+        if self.contains(index) {
+            Some(C::get(&self.columns, index.into_usize()))
+        } else {
+            None
+        }
+    }
+
+    /// Gets mutable references to the fields of the row at the specified index,
+    /// or `None` if the slot is not occupied.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::SoaFreeList;
+    ///
+    /// let mut list = SoaFreeList::<(u32, char), u8>::default();
+    /// let index = list.push((1, 'a'));
+    /// let (number, _) = list.get_mut(index).unwrap();
+    /// *number += 1;
+    /// assert_eq!(list.get(index), Some((&2, &'a')));
+    /// ```
+    pub fn get_mut(&mut self, index: TIndex) -> Option<C::Muts<'_>> {
+        if self.contains(index) {
+            Some(C::get_mut(&mut self.columns, index.into_usize()))
+        } else {
+            None
+        }
+    }
+
+    /// Gets slices over all columns, indexed by slot.
+    ///
+    /// Free slots hold default values.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::SoaFreeList;
+    ///
+    /// let mut list = SoaFreeList::<(u32, char), u8>::default();
+    /// list.push((1, 'a'));
+    /// list.push((2, 'b'));
+    /// let (numbers, chars) = list.columns();
+    /// assert_eq!(numbers, [1, 2]);
+    /// assert_eq!(chars, ['a', 'b']);
+    /// ```
+    pub fn columns(&self) -> C::Slices<'_> {
+        C::slices(&self.columns)
+    }
+
+    /// Gets mutable slices over all columns, indexed by slot.
+    ///
+    /// Free slots hold default values; changing them has no effect on later insertions.
+    pub fn columns_mut(&mut self) -> C::SlicesMut<'_> {
+        C::slices_mut(&mut self.columns)
+    }
+
+    /// Determines whether the specified index refers to an occupied slot.
+    #[inline]
+    pub fn contains(&self, index: TIndex) -> bool {
+        self.occupied.contains(index.into_usize())
+    }
+
+    /// Determines the state of the slot at the specified index.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::{SoaFreeList, SlotStatus};
+    ///
+    /// let mut list = SoaFreeList::<(u32,), u8>::default();
+    /// let index = list.push((1,));
+    /// list.erase(index);
+    /// assert_eq!(list.status(index), SlotStatus::Free);
+    /// assert_eq!(list.status(index + 1), SlotStatus::OutOfRange);
+    /// ```
+    pub fn status(&self, index: TIndex) -> SlotStatus {
+        let index = index.into_usize();
+        if index >= self.slots {
+            SlotStatus::OutOfRange
+        } else if self.occupied.contains(index) {
+            SlotStatus::Occupied
+        } else {
+            SlotStatus::Free
+        }
+    }
+
+    /// Gets an iterator over the indices of all occupied slots, in ascending order.
+    pub fn keys(&self) -> Keys<'_, TIndex> {
+        Keys::new(self.occupied.ones())
+    }
+
+    /// Gets the number of rows in the list.
+    pub fn len(&self) -> usize {
+        self.slots - self.free.len()
+    }
+
+    /// Determines whether the list contains no rows.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Gets the number of slots that were handed out so far, i.e. the length of each column.
+    pub fn slot_count(&self) -> usize {
+        self.slots
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn columns_share_slots() {
+        let mut list = SoaFreeList::<(u32, String, bool), u8>::default();
+        for i in 0..4 {
+            list.push((i, i.to_string(), i % 2 == 0));
+        }
+
+        assert_eq!(list.erase(1), Some((1, "1".to_string(), false)));
+        assert_eq!(list.erase(1), None);
+        let (numbers, strings, flags) = list.columns();
+        assert_eq!(numbers, [0, 0, 2, 3]);
+        assert_eq!(strings[1], "");
+        assert_eq!(flags, [true, false, true, false]);
+
+        assert_eq!(list.push((10, "ten".to_string(), true)), 1);
+        assert_eq!(list.get(1), Some((&10, &"ten".to_string(), &true)));
+        assert_eq!(list.keys().collect::<Vec<_>>(), [0, 1, 2, 3]);
+        assert_eq!(list.len(), 4);
+    }
+
+    #[test]
+    fn clear_resets_columns() {
+        let mut list = SoaFreeList::<(u32, u64), u8>::default();
+        list.push((1, 2));
+        list.erase(0);
+        list.clear();
+        assert!(list.is_empty());
+        assert_eq!(list.push((3, 4)), 0);
+        assert_eq!(list.columns(), (&[3][..], &[4][..]));
+    }
+}
@@ -0,0 +1,48 @@
+/// A snapshot of the slot usage of a [`FreeList`](crate::FreeList).
+///
+/// This type is returned by [`FreeList::stats`](crate::FreeList::stats).
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct FreeListStats {
+    /// The number of slots that hold an element.
+    pub live: usize,
+    /// The number of slots that were erased and do not hold an element.
+    pub free: usize,
+    /// The total number of slots, i.e. the sum of live and free slots.
+    pub total: usize,
+    /// The number of slots up to and including the highest occupied one.
+    pub span: usize,
+    /// The highest number of simultaneously live elements.
+    pub peak_live: usize,
+    /// The highest total number of slots.
+    pub peak_total: usize,
+}
+
+impl FreeListStats {
+    /// Gets the fraction of slots within the [`span`](Self::span) that are free,
+    /// ranging from `0.0` (densely packed) to almost `1.0` (highly fragmented).
+    ///
+    /// A high value indicates that compacting the list would free up a large
+    /// portion of its memory.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeListStats;
+    ///
+    /// let stats = FreeListStats {
+    ///     live: 1,
+    ///     free: 3,
+    ///     total: 4,
+    ///     span: 4,
+    ///     peak_live: 4,
+    ///     peak_total: 4,
+    /// };
+    /// assert_eq!(stats.fragmentation(), 0.75);
+    /// ```
+    pub fn fragmentation(&self) -> f64 {
+        if self.span == 0 {
+            0.0
+        } else {
+            1.0 - self.live as f64 / self.span as f64
+        }
+    }
+}
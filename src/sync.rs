@@ -0,0 +1,321 @@
+use crate::index_type::*;
+use crate::{FreeList, Key};
+use std::fmt::{Debug, Formatter};
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// A thread-safe free list behind a reader-writer lock, addressed by [`Key`]s.
+///
+/// Elements are accessed through guards returned by [`read`](Self::read) and
+/// [`write`](Self::write). A guard keeps the lock for as long as it lives, so the element
+/// it refers to cannot be removed or replaced in the meantime; any number of read guards
+/// can exist at the same time, while a write guard is exclusive. The number of elements is
+/// tracked separately, so [`len`](Self::len) does not take the lock.
+///
+/// Since a guard holds the lock of the whole list, acquiring a write guard (or inserting or
+/// removing an element) while the same thread still holds another guard deadlocks.
+///
+/// ## Type parameters
+/// * `T` - The type of the element.
+/// * `TIndex` - The type of the index; see [`FreeList`] for details.
+///
+/// ## Example
+/// ```rust
+/// use free_list::SyncFreeList;
+///
+/// let list = SyncFreeList::<String>::default();
+/// let key = list.push("first".into());
+///
+/// std::thread::scope(|scope| {
+///     scope.spawn(|| list.write(key).unwrap().push_str(" value"));
+/// });
+///
+/// assert_eq!(*list.read(key).unwrap(), "first value");
+/// assert_eq!(list.remove(key).as_deref(), Some("first value"));
+/// assert!(list.read(key).is_none());
+/// ```
+pub struct SyncFreeList<T, TIndex = DefaultIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    list: RwLock<FreeList<T, TIndex>>,
+    /// The number of elements, readable without taking the lock.
+    len: AtomicUsize,
+}
+
+/// Shared access to an element of a [`SyncFreeList`], created by [`SyncFreeList::read`].
+///
+/// The list stays locked for reading until the guard is dropped.
+pub struct SyncReadGuard<'a, T, TIndex = DefaultIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    list: RwLockReadGuard<'a, FreeList<T, TIndex>>,
+    key: Key<TIndex>,
+}
+
+/// Exclusive access to an element of a [`SyncFreeList`], created by [`SyncFreeList::write`].
+///
+/// The list stays locked for writing until the guard is dropped.
+pub struct SyncWriteGuard<'a, T, TIndex = DefaultIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    list: RwLockWriteGuard<'a, FreeList<T, TIndex>>,
+    key: Key<TIndex>,
+}
+
+impl<T, TIndex> Default for SyncFreeList<T, TIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    fn default() -> Self {
+        Self::new(FreeList::default())
+    }
+}
+
+impl<T, TIndex> SyncFreeList<T, TIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    /// Wraps an existing list.
+    pub fn new(list: FreeList<T, TIndex>) -> Self {
+        Self {
+            len: AtomicUsize::new(list.len()),
+            list: RwLock::new(list),
+        }
+    }
+
+    /// Inserts an element and returns its key.
+    pub fn push(&self, element: T) -> Key<TIndex> {
+        let key = self.lock_write().push_key(element);
+        self.len.fetch_add(1, Ordering::Relaxed);
+        key
+    }
+
+    /// Removes the element of the specified key and returns it, or returns `None` if the
+    /// key does not refer to an element.
+    pub fn remove(&self, key: Key<TIndex>) -> Option<T> {
+        let mut list = self.lock_write();
+        if !list.contains_key(key) {
+            return None;
+        }
+        let element = list.remove(key.index());
+        self.len.fetch_sub(1, Ordering::Relaxed);
+        Some(element)
+    }
+
+    /// Locks the list for reading and returns a guard to the element of the specified
+    /// key, or returns `None` if the key does not refer to an element.
+    pub fn read(&self, key: Key<TIndex>) -> Option<SyncReadGuard<'_, T, TIndex>> {
+        let list = self.lock_read();
+        list.contains_key(key)
+            .then_some(SyncReadGuard { list, key })
+    }
+
+    /// Locks the list for writing and returns a guard to the element of the specified
+    /// key, or returns `None` if the key does not refer to an element.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::SyncFreeList;
+    ///
+    /// let list = SyncFreeList::<u32>::default();
+    /// let key = list.push(1);
+    /// *list.write(key).unwrap() += 1;
+    /// assert_eq!(*list.read(key).unwrap(), 2);
+    /// ```
+    pub fn write(&self, key: Key<TIndex>) -> Option<SyncWriteGuard<'_, T, TIndex>> {
+        let list = self.lock_write();
+        list.contains_key(key)
+            .then_some(SyncWriteGuard { list, key })
+    }
+
+    /// Determines whether the specified key refers to an element.
+    pub fn contains_key(&self, key: Key<TIndex>) -> bool {
+        self.lock_read().contains_key(key)
+    }
+
+    /// Gets the number of elements without taking the lock.
+    ///
+    /// The value may be outdated as soon as it is returned if other threads modify the
+    /// list concurrently.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    /// Determines whether the list is empty without taking the lock.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Gets a mutable reference to the list; no locking is needed since the access is
+    /// exclusive.
+    pub fn get_mut(&mut self) -> &mut FreeList<T, TIndex> {
+        self.list.get_mut().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Unwraps the list.
+    pub fn into_inner(self) -> FreeList<T, TIndex> {
+        self.list
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Locks the list for reading; a poisoned lock is recovered since the free chain
+    /// is only modified by methods that do not panic halfway.
+    fn lock_read(&self) -> RwLockReadGuard<'_, FreeList<T, TIndex>> {
+        self.list.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Locks the list for writing; see [`lock_read`](Self::lock_read).
+    fn lock_write(&self) -> RwLockWriteGuard<'_, FreeList<T, TIndex>> {
+        self.list.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T, TIndex> Debug for SyncFreeList<T, TIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SyncFreeList")
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a, T, TIndex> SyncReadGuard<'a, T, TIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    /// Gets the key of the element.
+    #[inline]
+    pub fn key(guard: &Self) -> Key<TIndex> {
+        guard.key
+    }
+}
+
+impl<'a, T, TIndex> Deref for SyncReadGuard<'a, T, TIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.list[self.key.index()]
+    }
+}
+
+impl<'a, T, TIndex> Debug for SyncReadGuard<'a, T, TIndex>
+where
+    T: Default + Debug,
+    TIndex: IndexType,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<'a, T, TIndex> SyncWriteGuard<'a, T, TIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    /// Gets the key of the element.
+    #[inline]
+    pub fn key(guard: &Self) -> Key<TIndex> {
+        guard.key
+    }
+}
+
+impl<'a, T, TIndex> Deref for SyncWriteGuard<'a, T, TIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.list[self.key.index()]
+    }
+}
+
+impl<'a, T, TIndex> DerefMut for SyncWriteGuard<'a, T, TIndex>
+where
+    T: Default,
+    TIndex: IndexType,
+{
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.list[self.key.index()]
+    }
+}
+
+impl<'a, T, TIndex> Debug for SyncWriteGuard<'a, T, TIndex>
+where
+    T: Default + Debug,
+    TIndex: IndexType,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stale_keys_are_rejected_after_reuse() {
+        let list = SyncFreeList::<u32, u8>::default();
+        let first = list.push(1);
+        assert_eq!(list.remove(first), Some(1));
+        assert_eq!(list.remove(first), None);
+
+        let second = list.push(2);
+        assert_eq!(second.index(), first.index());
+        assert!(list.read(first).is_none());
+        assert!(list.write(first).is_none());
+        assert_eq!(SyncReadGuard::key(&list.read(second).unwrap()), second);
+        assert_eq!(list.len(), 1);
+    }
+
+    #[test]
+    fn concurrent_readers_and_writers() {
+        const THREADS: usize = 4;
+        const ROUNDS: usize = 500;
+
+        let list = SyncFreeList::<usize>::default();
+        let counter = list.push(0);
+        std::thread::scope(|scope| {
+            for _ in 0..THREADS {
+                scope.spawn(|| {
+                    for _ in 0..ROUNDS {
+                        let key = list.push(1);
+                        let value = *list.read(key).unwrap();
+                        *list.write(counter).unwrap() += value;
+                        assert_eq!(list.remove(key), Some(1));
+                    }
+                });
+            }
+        });
+
+        assert_eq!(*list.read(counter).unwrap(), THREADS * ROUNDS);
+        assert_eq!(list.len(), 1);
+        assert_eq!(list.into_inner().len(), 1);
+    }
+}
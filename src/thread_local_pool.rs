@@ -0,0 +1,300 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::mem::ManuallyDrop;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// A thread-safe object pool that serves most checkouts from a cache of the current
+/// thread, without taking a lock.
+///
+/// Every thread keeps its own free list of idle values. A checkout takes a value from
+/// the cache of the current thread; only if the cache is empty, a batch of values is
+/// moved over from a shared global list. Returned values go into the cache of the
+/// returning thread, and once it holds more than two batches, one batch is moved to the
+/// global list, so that values freed on one thread become available to the others.
+///
+/// Values cached by a thread are dropped when the thread exits; the cache of the thread
+/// that drops the pool is released with the pool.
+///
+/// ## Example
+/// ```rust
+/// use free_list::ThreadLocalPool;
+///
+/// let pool = ThreadLocalPool::<Vec<u8>>::new(16);
+/// std::thread::scope(|scope| {
+///     for _ in 0..4 {
+///         scope.spawn(|| {
+///             let mut buffer = pool.checkout();
+///             buffer.clear();
+///             buffer.extend_from_slice(b"data");
+///         });
+///     }
+/// });
+///
+/// // Values are handed back on the thread that returned them.
+/// let buffer = pool.checkout();
+/// drop(buffer);
+/// assert_eq!(pool.local_len(), 1);
+/// ```
+pub struct ThreadLocalPool<T>
+where
+    T: Default + Send + 'static,
+{
+    /// Identifies the caches of this pool in the thread-local storage.
+    id: usize,
+    /// The number of values moved between a thread cache and the global list at once.
+    batch: usize,
+    /// The idle values that are not cached by any thread.
+    global: Mutex<Vec<T>>,
+}
+
+/// A value checked out from a [`ThreadLocalPool`].
+///
+/// The value is returned to the cache of the current thread when the guard is dropped.
+pub struct ThreadLocalPoolGuard<'a, T>
+where
+    T: Default + Send + 'static,
+{
+    pool: &'a ThreadLocalPool<T>,
+    value: ManuallyDrop<T>,
+}
+
+thread_local! {
+    /// The caches of the current thread, by pool.
+    static CACHES: RefCell<HashMap<usize, Box<dyn Any>>> = RefCell::new(HashMap::new());
+}
+
+/// The source of pool identifiers.
+static NEXT_POOL_ID: AtomicUsize = AtomicUsize::new(0);
+
+impl<T> Default for ThreadLocalPool<T>
+where
+    T: Default + Send + 'static,
+{
+    /// Creates an empty pool that moves 32 values at once.
+    fn default() -> Self {
+        Self::new(32)
+    }
+}
+
+impl<T> ThreadLocalPool<T>
+where
+    T: Default + Send + 'static,
+{
+    /// Creates an empty pool that moves the specified number of values at once between
+    /// the thread caches and the global list.
+    ///
+    /// ## Panics
+    /// Panics if `batch` is zero.
+    pub fn new(batch: usize) -> Self {
+        assert_ne!(batch, 0, "At least one value must be moved at once");
+        Self {
+            id: NEXT_POOL_ID.fetch_add(1, Ordering::Relaxed),
+            batch,
+            global: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Borrows a value from the pool, creating a new one with [`Default`] if no idle
+    /// value is available.
+    pub fn checkout(&self) -> ThreadLocalPoolGuard<'_, T> {
+        ThreadLocalPoolGuard {
+            pool: self,
+            value: ManuallyDrop::new(self.take()),
+        }
+    }
+
+    /// Takes a value out of the pool, creating a new one with [`Default`] if no idle
+    /// value is available.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::ThreadLocalPool;
+    ///
+    /// let pool = ThreadLocalPool::<String>::default();
+    /// pool.give("recycled".into());
+    /// assert_eq!(pool.take(), "recycled");
+    /// assert_eq!(pool.take(), "");
+    /// ```
+    pub fn take(&self) -> T {
+        self.with_cache(|cache| {
+            if cache.is_empty() {
+                let mut global = self.lock_global();
+                let start = global.len().saturating_sub(self.batch);
+                cache.extend(global.drain(start..));
+            }
+            cache.pop()
+        })
+        .unwrap_or_default()
+    }
+
+    /// Returns a value to the cache of the current thread.
+    pub fn give(&self, value: T) {
+        self.with_cache(|cache| {
+            cache.push(value);
+            if cache.len() > 2 * self.batch {
+                self.lock_global().extend(cache.drain(..self.batch));
+            }
+        });
+    }
+
+    /// Moves all values cached by the current thread to the global list, e.g. before
+    /// the thread exits.
+    pub fn flush(&self) {
+        self.with_cache(|cache| self.lock_global().append(cache));
+    }
+
+    /// Gets the number of idle values cached by the current thread.
+    pub fn local_len(&self) -> usize {
+        self.with_cache(|cache| cache.len())
+    }
+
+    /// Gets the number of idle values in the global list.
+    pub fn global_len(&self) -> usize {
+        self.lock_global().len()
+    }
+
+    /// Calls a function with the cache of the current thread.
+    fn with_cache<R>(&self, f: impl FnOnce(&mut Vec<T>) -> R) -> R {
+        CACHES.with(|caches| {
+            let mut caches = caches.borrow_mut();
+            let cache = caches
+                .entry(self.id)
+                .or_insert_with(|| Box::new(Vec::<T>::new()))
+                .downcast_mut::<Vec<T>>()
+                .expect("pool identifiers are unique");
+            f(cache)
+        })
+    }
+
+    /// Locks the global list; a poisoned lock is recovered since the list of idle
+    /// values stays valid when a thread panics.
+    fn lock_global(&self) -> MutexGuard<'_, Vec<T>> {
+        self.global.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T> Drop for ThreadLocalPool<T>
+where
+    T: Default + Send + 'static,
+{
+    fn drop(&mut self) {
+        // The thread-local storage may already be gone if the pool is dropped
+        // during thread teardown.
+        let _ = CACHES.try_with(|caches| caches.borrow_mut().remove(&self.id));
+    }
+}
+
+impl<T> Debug for ThreadLocalPool<T>
+where
+    T: Default + Send + 'static,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ThreadLocalPool")
+            .field("batch", &self.batch)
+            .field("global_len", &self.global_len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a, T> ThreadLocalPoolGuard<'a, T>
+where
+    T: Default + Send + 'static,
+{
+    /// Takes the value out of the guard, so that it is not returned to the pool.
+    pub fn into_inner(guard: Self) -> T {
+        let mut guard = ManuallyDrop::new(guard);
+        // SAFETY: The guard is not dropped, so the value is taken out exactly once.
+        unsafe { ManuallyDrop::take(&mut guard.value) }
+    }
+}
+
+impl<'a, T> Deref for ThreadLocalPoolGuard<'a, T>
+where
+    T: Default + Send + 'static,
+{
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<'a, T> DerefMut for ThreadLocalPoolGuard<'a, T>
+where
+    T: Default + Send + 'static,
+{
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
+
+impl<'a, T> Drop for ThreadLocalPoolGuard<'a, T>
+where
+    T: Default + Send + 'static,
+{
+    fn drop(&mut self) {
+        // SAFETY: The value is taken out only here and in `into_inner`, which skips the drop.
+        let value = unsafe { ManuallyDrop::take(&mut self.value) };
+        self.pool.give(value);
+    }
+}
+
+impl<'a, T> Debug for ThreadLocalPoolGuard<'a, T>
+where
+    T: Default + Send + Debug + 'static,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ThreadLocalPoolGuard")
+            .field(&*self.value)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn surplus_values_are_balanced_through_the_global_list() {
+        let pool = ThreadLocalPool::<u32>::new(2);
+        for value in 0..5 {
+            pool.give(value);
+        }
+        // The fifth value exceeded two batches, so the two oldest were moved.
+        assert_eq!((pool.local_len(), pool.global_len()), (3, 2));
+
+        // Another thread refills its empty cache with a batch from the global list.
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                assert_eq!(pool.local_len(), 0);
+                assert_eq!(pool.take(), 1);
+                assert_eq!(pool.local_len(), 1);
+                assert_eq!(pool.global_len(), 0);
+                pool.flush();
+            });
+        });
+        assert_eq!(pool.global_len(), 1);
+
+        let guard = pool.checkout();
+        assert_eq!(*guard, 4);
+        assert_eq!(ThreadLocalPoolGuard::into_inner(guard), 4);
+        assert_eq!(pool.local_len(), 2);
+    }
+
+    #[test]
+    fn pools_have_separate_caches() {
+        let first = ThreadLocalPool::<String>::default();
+        let second = ThreadLocalPool::<String>::default();
+        first.give("first".into());
+        assert_eq!(second.take(), "");
+        assert_eq!(first.take(), "first");
+        drop(first);
+        assert_eq!(second.local_len(), 0);
+    }
+}
@@ -0,0 +1,197 @@
+//! Per-slot change detection, which stamps inserted and mutably accessed elements with
+//! a tick if the `change-ticks` feature is enabled, and does nothing otherwise.
+
+#[cfg(feature = "change-ticks")]
+use crate::index_type::*;
+#[cfg(feature = "change-ticks")]
+use crate::FreeList;
+#[cfg(feature = "change-ticks")]
+use allocator_api2::alloc::Allocator;
+
+/// The ticks at which the element of a slot was inserted and last accessed mutably.
+///
+/// Ticks are obtained with [`FreeList::slot_ticks`]; see [`FreeList::change_tick`].
+#[cfg(feature = "change-ticks")]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct SlotTicks {
+    /// The tick at which the element was inserted.
+    pub added: u64,
+    /// The tick at which the element was last accessed mutably, or inserted.
+    pub changed: u64,
+}
+
+/// The current tick of a list and the ticks of all slots.
+#[cfg(feature = "change-ticks")]
+#[derive(Debug, Default)]
+pub(crate) struct ChangeTicks {
+    /// The tick of the most recent change.
+    tick: u64,
+    /// The ticks of every slot that ever held an element; may be shorter than the slots.
+    slots: Vec<SlotTicks>,
+}
+
+#[cfg(feature = "change-ticks")]
+impl ChangeTicks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stamps an inserted element with a new tick.
+    #[inline]
+    pub fn added(&mut self, index: usize) {
+        self.tick += 1;
+        if self.slots.len() <= index {
+            self.slots.resize(index + 1, SlotTicks::default());
+        }
+        self.slots[index] = SlotTicks {
+            added: self.tick,
+            changed: self.tick,
+        };
+    }
+
+    /// Stamps the elements of the specified occupied slots with a new tick.
+    #[inline]
+    pub fn changed(&mut self, indices: impl IntoIterator<Item = usize>) {
+        self.tick += 1;
+        for index in indices {
+            if self.slots.len() <= index {
+                self.slots.resize(index + 1, SlotTicks::default());
+            }
+            self.slots[index].changed = self.tick;
+        }
+    }
+
+    /// Gets the ticks of a slot; elements that were never stamped, e.g. because they
+    /// were restored from a snapshot, have tick zero.
+    #[inline]
+    pub fn get(&self, index: usize) -> SlotTicks {
+        self.slots.get(index).copied().unwrap_or_default()
+    }
+}
+
+/// Stand-in for the ticks if the `change-ticks` feature is disabled.
+#[cfg(not(feature = "change-ticks"))]
+pub(crate) struct ChangeTicks;
+
+#[cfg(not(feature = "change-ticks"))]
+impl ChangeTicks {
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self
+    }
+
+    #[inline(always)]
+    pub fn added(&mut self, _index: usize) {}
+
+    #[inline(always)]
+    pub fn changed(&mut self, _indices: impl IntoIterator<Item = usize>) {}
+}
+
+#[cfg(feature = "change-ticks")]
+impl<T, TIndex, A> FreeList<T, TIndex, A>
+where
+    T: Default,
+    TIndex: IndexType,
+    A: Allocator,
+{
+    /// Gets the tick of the most recent change.
+    ///
+    /// Every insertion and every mutable access, e.g. through [`get_mut`](Self::get_mut)
+    /// or [`iter_mut`](Self::iter_mut), advances the tick and stamps the affected slots
+    /// with it. Mutable iterators stamp all occupied slots up front, whether or not they
+    /// are visited. Remembering the tick after processing the list allows to visit only
+    /// the elements that changed since.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::FreeList;
+    ///
+    /// let mut list = FreeList::<u32, u8>::default();
+    /// list.push(1);
+    /// list.push(2);
+    /// let seen = list.change_tick();
+    ///
+    /// *list.get_mut(0).unwrap() += 10;
+    /// list.push(3);
+    ///
+    /// let changed: Vec<_> = list.iter_changed_since(seen).collect();
+    /// assert_eq!(changed, [(0, &11), (2, &3)]);
+    /// let added: Vec<_> = list.iter_added_since(seen).collect();
+    /// assert_eq!(added, [(2, &3)]);
+    /// ```
+    #[inline]
+    pub fn change_tick(&self) -> u64 {
+        self.ticks.tick
+    }
+
+    /// Gets the ticks of the element at the specified index, or `None` if the slot is
+    /// not occupied.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use free_list::{FreeList, SlotTicks};
+    ///
+    /// let mut list = FreeList::<u32, u8>::default();
+    /// list.push(1);
+    /// list[0] += 1;
+    /// assert_eq!(list.slot_ticks(0), Some(SlotTicks { added: 1, changed: 2 }));
+    /// assert_eq!(list.slot_ticks(1), None);
+    /// ```
+    pub fn slot_ticks(&self, index: TIndex) -> Option<SlotTicks> {
+        self.contains(index)
+            .then(|| self.ticks.get(index.into_usize()))
+    }
+
+    /// Gets an iterator over the indices and values of all elements that were inserted
+    /// or accessed mutably after the specified tick, in ascending index order.
+    pub fn iter_changed_since(
+        &self,
+        tick: u64,
+    ) -> impl DoubleEndedIterator<Item = (TIndex, &T)> + '_ {
+        self.iter()
+            .filter(move |(index, _)| self.ticks.get(index.into_usize()).changed > tick)
+    }
+
+    /// Gets an iterator over the indices and values of all elements that were inserted
+    /// after the specified tick, in ascending index order.
+    pub fn iter_added_since(
+        &self,
+        tick: u64,
+    ) -> impl DoubleEndedIterator<Item = (TIndex, &T)> + '_ {
+        self.iter()
+            .filter(move |(index, _)| self.ticks.get(index.into_usize()).added > tick)
+    }
+}
+
+#[cfg(all(test, feature = "change-ticks"))]
+mod tests {
+    use crate::FreeList;
+
+    #[test]
+    fn mutable_access_stamps_slots() {
+        let mut list = FreeList::<u32, u8>::default();
+        for value in 0..4 {
+            list.push(value);
+        }
+        list.erase(1);
+        let seen = list.change_tick();
+        assert_eq!(seen, 4);
+
+        let [a, b] = list.get_disjoint_mut([0, 2]).unwrap();
+        std::mem::swap(a, b);
+        list.push(10);
+        assert_eq!(
+            list.iter_changed_since(seen).collect::<Vec<_>>(),
+            [(0, &2), (1, &10), (2, &0)]
+        );
+        assert_eq!(list.iter_added_since(seen).collect::<Vec<_>>(), [(1, &10)]);
+
+        let seen = list.change_tick();
+        assert_eq!(list.iter_changed_since(seen).count(), 0);
+        for (_, value) in list.iter_mut() {
+            *value += 1;
+        }
+        assert_eq!(list.iter_changed_since(seen).count(), 4);
+        assert_eq!(list.iter_added_since(seen).count(), 0);
+    }
+}
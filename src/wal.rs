@@ -0,0 +1,325 @@
+use crate::index_type::*;
+use crate::snapshot::{decode_policy, encode_policy, invalid_data, read_index, write_index};
+use crate::{FreeList, Iter, ReusePolicy};
+use allocator_api2::alloc::Allocator;
+use std::io::{self, Read, Write};
+
+/// The magic bytes at the start of every log.
+const MAGIC: [u8; 4] = *b"FLWL";
+
+/// The version of the log format written by [`LoggedFreeList`].
+const VERSION: u8 = 1;
+
+/// The record tags.
+const PUSH: u8 = 0;
+const ERASE: u8 = 1;
+const REPLACE: u8 = 2;
+const CLEAR: u8 = 3;
+
+/// Encodes an element into the log.
+type WriteElement<T, W> = Box<dyn FnMut(&T, &mut W) -> io::Result<()>>;
+
+/// A free list that appends every mutation to a write-ahead log before applying it.
+///
+/// The log starts with a header and holds one record per [`push`](Self::push),
+/// [`erase`](Self::erase), [`replace`](Self::replace) and [`clear`](Self::clear).
+/// [`FreeList::replay`] rebuilds the list from it, with the same indices, e.g. after a
+/// crash or on a mirror that receives the log. Elements are encoded by the function
+/// passed on construction; all other values are written in little-endian byte order.
+///
+/// Elements cannot be modified in place, since such changes would bypass the log.
+/// Flushing the writer, e.g. to make records durable, is up to the caller.
+///
+/// ## Type parameters
+/// * `T` - The type of the element.
+/// * `W` - The writer of the log.
+/// * `TIndex` - The type of the index; see [`FreeList`] for details.
+///
+/// ## Example
+/// ```rust
+/// use free_list::{FreeList, LoggedFreeList};
+/// use std::io::{Read, Write};
+///
+/// let mut list = LoggedFreeList::<u32, Vec<u8>, u8>::new(Vec::new(), |value, writer| {
+///     writer.write_all(&value.to_le_bytes())
+/// })
+/// .unwrap();
+/// list.push(1).unwrap();
+/// list.push(2).unwrap();
+/// list.erase(0).unwrap();
+///
+/// let (_, log) = list.into_inner();
+/// let mut replayed = FreeList::<u32, u8>::replay(log.as_slice(), |reader| {
+///     let mut bytes = [0; 4];
+///     reader.read_exact(&mut bytes)?;
+///     Ok(u32::from_le_bytes(bytes))
+/// })
+/// .unwrap();
+/// assert_eq!(replayed.iter().collect::<Vec<_>>(), [(1, &2)]);
+/// assert_eq!(replayed.push(3), 0);
+/// ```
+pub struct LoggedFreeList<T, W, TIndex = DefaultIndex>
+where
+    T: Default,
+    W: Write,
+    TIndex: IndexType,
+{
+    list: FreeList<T, TIndex>,
+    writer: W,
+    write_element: WriteElement<T, W>,
+}
+
+impl<T, W, TIndex> LoggedFreeList<T, W, TIndex>
+where
+    T: Default,
+    W: Write,
+    TIndex: IndexType,
+{
+    /// Creates an empty list and writes the header of its log.
+    pub fn new(
+        writer: W,
+        write_element: impl FnMut(&T, &mut W) -> io::Result<()> + 'static,
+    ) -> io::Result<Self> {
+        Self::with_reuse_policy(ReusePolicy::default(), writer, write_element)
+    }
+
+    /// Creates an empty list with the specified reuse policy and writes the header of
+    /// its log.
+    pub fn with_reuse_policy(
+        policy: ReusePolicy,
+        mut writer: W,
+        write_element: impl FnMut(&T, &mut W) -> io::Result<()> + 'static,
+    ) -> io::Result<Self> {
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&[VERSION, encode_policy(policy)])?;
+        Ok(Self {
+            list: FreeList::with_reuse_policy(policy),
+            writer,
+            write_element: Box::new(write_element),
+        })
+    }
+
+    /// Logs and inserts an element and returns its index.
+    ///
+    /// If the record cannot be written, the list is not changed.
+    pub fn push(&mut self, element: T) -> io::Result<TIndex> {
+        let index = self.list.next_index();
+        self.writer.write_all(&[PUSH])?;
+        write_index(&mut self.writer, index)?;
+        (self.write_element)(&element, &mut self.writer)?;
+        let pushed = self.list.push(element);
+        debug_assert_eq!(pushed, index);
+        Ok(index)
+    }
+
+    /// Logs and removes the element at the specified index and returns it.
+    ///
+    /// If the record cannot be written, the list is not changed.
+    ///
+    /// ## Panics
+    /// Panics if the slot is not occupied.
+    pub fn erase(&mut self, index: TIndex) -> io::Result<T> {
+        assert!(
+            self.list.contains(index),
+            "Attempted to erase a slot that is not occupied ({index:?})"
+        );
+        self.writer.write_all(&[ERASE])?;
+        write_index(&mut self.writer, index)?;
+        Ok(self.list.remove(index))
+    }
+
+    /// Logs and replaces the element at the specified index and returns the previous one.
+    ///
+    /// If the record cannot be written, the list is not changed.
+    ///
+    /// ## Panics
+    /// Panics if the slot is not occupied.
+    pub fn replace(&mut self, index: TIndex, element: T) -> io::Result<T> {
+        assert!(
+            self.list.contains(index),
+            "Attempted to replace an element in a slot that is not occupied ({index:?})"
+        );
+        self.writer.write_all(&[REPLACE])?;
+        write_index(&mut self.writer, index)?;
+        (self.write_element)(&element, &mut self.writer)?;
+        Ok(std::mem::replace(&mut self.list[index], element))
+    }
+
+    /// Logs and removes all elements.
+    ///
+    /// If the record cannot be written, the list is not changed.
+    pub fn clear(&mut self) -> io::Result<()> {
+        self.writer.write_all(&[CLEAR])?;
+        self.list.clear();
+        Ok(())
+    }
+
+    /// Gets the list.
+    #[inline]
+    pub fn list(&self) -> &FreeList<T, TIndex> {
+        &self.list
+    }
+
+    /// Gets the writer of the log, e.g. to flush it.
+    #[inline]
+    pub fn writer_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Gets a reference to the element at the specified index, or `None` if the
+    /// slot is not occupied.
+    #[inline]
+    pub fn get(&self, index: TIndex) -> Option<&T> {
+        self.list.get(index)
+    }
+
+    /// Gets the number of elements in the list.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Determines whether the list contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Gets an iterator over the indices and values of all elements, in ascending
+    /// index order.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T, TIndex> {
+        self.list.iter()
+    }
+
+    /// Decomposes the logged list into the list and the writer of its log.
+    pub fn into_inner(self) -> (FreeList<T, TIndex>, W) {
+        (self.list, self.writer)
+    }
+}
+
+impl<T, TIndex, A> FreeList<T, TIndex, A>
+where
+    T: Default,
+    TIndex: IndexType,
+    A: Allocator,
+{
+    /// Rebuilds a list from the log written by a [`LoggedFreeList`].
+    ///
+    /// Elements are decoded by the specified function, which must read exactly the
+    /// bytes written by the encoder. An incomplete last record, as left behind by a crash
+    /// while it was written, is ignored. Records that do not match the list result in an
+    /// error of kind [`io::ErrorKind::InvalidData`].
+    pub fn replay<R: Read>(
+        mut reader: R,
+        mut read_element: impl FnMut(&mut R) -> io::Result<T>,
+    ) -> io::Result<Self>
+    where
+        A: Default,
+    {
+        let mut header = [0; 6];
+        reader.read_exact(&mut header)?;
+        if header[..4] != MAGIC {
+            return Err(invalid_data("not a free list log"));
+        }
+        if header[4] != VERSION {
+            return Err(invalid_data("unsupported log version"));
+        }
+
+        let mut list = Self::with_reuse_policy(decode_policy(header[5])?);
+        loop {
+            let mut tag = [0; 1];
+            if reader.read(&mut tag)? == 0 {
+                return Ok(list);
+            }
+            let record = match tag[0] {
+                PUSH => read_index::<TIndex>(&mut reader).and_then(|index| {
+                    let element = read_element(&mut reader)?;
+                    if index != list.next_index() {
+                        return Err(invalid_data("logged index does not match the list"));
+                    }
+                    list.push(element);
+                    Ok(())
+                }),
+                ERASE => read_index::<TIndex>(&mut reader).and_then(|index| {
+                    list.try_erase(index)
+                        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+                }),
+                REPLACE => read_index::<TIndex>(&mut reader).and_then(|index| {
+                    let element = read_element(&mut reader)?;
+                    let slot = list
+                        .get_mut(index)
+                        .ok_or_else(|| invalid_data("logged index does not refer to an element"))?;
+                    *slot = element;
+                    Ok(())
+                }),
+                CLEAR => {
+                    list.clear();
+                    Ok(())
+                }
+                _ => Err(invalid_data("invalid record tag")),
+            };
+            match record {
+                Ok(()) => {}
+                Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => return Ok(list),
+                Err(error) => return Err(error),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replay(log: &[u8]) -> io::Result<FreeList<String, u16>> {
+        FreeList::replay(log, |reader| {
+            let mut len = [0; 1];
+            reader.read_exact(&mut len)?;
+            let mut bytes = vec![0; len[0] as usize];
+            reader.read_exact(&mut bytes)?;
+            String::from_utf8(bytes).map_err(|_| invalid_data("invalid string"))
+        })
+    }
+
+    #[test]
+    fn replay_reproduces_indices_and_ignores_torn_records() {
+        let mut list = LoggedFreeList::<String, Vec<u8>, u16>::with_reuse_policy(
+            ReusePolicy::Fifo,
+            Vec::new(),
+            |element, writer| {
+                writer.write_all(&[element.len() as u8])?;
+                writer.write_all(element.as_bytes())
+            },
+        )
+        .unwrap();
+        for name in ["a", "b", "c"] {
+            list.push(name.into()).unwrap();
+        }
+        list.erase(0).unwrap();
+        list.erase(1).unwrap();
+        list.replace(2, "z".into()).unwrap();
+        list.push("d".into()).unwrap();
+        let complete = list.writer_mut().len();
+        list.push("e".into()).unwrap();
+
+        let (list, log) = list.into_inner();
+        let replayed = replay(&log).unwrap();
+        assert_eq!(
+            replayed.iter().collect::<Vec<_>>(),
+            list.iter().collect::<Vec<_>>()
+        );
+
+        // A crash in the middle of the last record loses only that record.
+        let replayed = replay(&log[..complete + 3]).unwrap();
+        assert_eq!(replayed.len(), 2);
+        assert_eq!(replayed.get(0).map(String::as_str), Some("d"));
+
+        let mut corrupt = log.clone();
+        corrupt[6] = ERASE;
+        assert_eq!(
+            replay(&corrupt).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+}
@@ -0,0 +1,160 @@
+//! Management of the slot storage that wipes released memory if the `zeroize`
+//! feature is enabled, and defers to the vector otherwise.
+
+use crate::index_type::IndexType;
+use crate::FreeElement;
+use allocator_api2::alloc::Allocator;
+use allocator_api2::vec::Vec;
+
+/// Overwrites the specified slots with zeros so that no bytes of removed elements remain.
+///
+/// The slots must not hold elements that are still alive.
+#[cfg(feature = "zeroize")]
+#[inline]
+pub(crate) fn wipe_slots<T, TIndex>(slots: &mut [FreeElement<T, TIndex>])
+where
+    TIndex: IndexType,
+{
+    use std::mem::MaybeUninit;
+    use zeroize::Zeroize;
+
+    // SAFETY: The slots hold no live elements, and any bytes are valid for `MaybeUninit`.
+    let slots = unsafe {
+        std::slice::from_raw_parts_mut(
+            slots
+                .as_mut_ptr()
+                .cast::<MaybeUninit<FreeElement<T, TIndex>>>(),
+            slots.len(),
+        )
+    };
+    slots.zeroize();
+}
+
+/// Overwrites the specified slots with zeros; a no-op without the `zeroize` feature.
+#[cfg(not(feature = "zeroize"))]
+#[inline(always)]
+pub(crate) fn wipe_slots<T, TIndex>(_slots: &mut [FreeElement<T, TIndex>])
+where
+    TIndex: IndexType,
+{
+}
+
+/// Reserves capacity for at least `additional` more slots, like [`Vec::reserve`].
+#[inline]
+pub(crate) fn reserve<T, TIndex, A>(data: &mut Vec<FreeElement<T, TIndex>, A>, additional: usize)
+where
+    TIndex: IndexType,
+    A: Allocator,
+{
+    #[cfg(feature = "zeroize")]
+    if data.capacity() - data.len() < additional {
+        let required = data
+            .len()
+            .checked_add(additional)
+            .expect("Attempted to reserve more slots than fit into memory");
+        reallocate(data, required.max(data.capacity().saturating_mul(2)).max(4));
+    }
+
+    #[cfg(not(feature = "zeroize"))]
+    data.reserve(additional);
+}
+
+/// Shrinks the capacity towards the specified value, like [`Vec::shrink_to`].
+#[inline]
+pub(crate) fn shrink_to<T, TIndex, A>(data: &mut Vec<FreeElement<T, TIndex>, A>, capacity: usize)
+where
+    TIndex: IndexType,
+    A: Allocator,
+{
+    #[cfg(feature = "zeroize")]
+    {
+        let capacity = capacity.max(data.len());
+        if capacity < data.capacity() {
+            reallocate(data, capacity);
+        }
+    }
+
+    #[cfg(not(feature = "zeroize"))]
+    data.shrink_to(capacity);
+}
+
+/// Moves the slots into a new allocation of exactly the specified capacity and wipes
+/// the old allocation before releasing it.
+#[cfg(feature = "zeroize")]
+fn reallocate<T, TIndex, A>(data: &mut Vec<FreeElement<T, TIndex>, A>, capacity: usize)
+where
+    TIndex: IndexType,
+    A: Allocator,
+{
+    use allocator_api2::alloc::Layout;
+    use std::mem::MaybeUninit;
+    use std::ptr::NonNull;
+    use zeroize::Zeroize;
+
+    debug_assert!(capacity >= data.len());
+    let len = data.len();
+    data.spare_capacity_mut().zeroize();
+
+    if capacity == 0 {
+        data.shrink_to(0);
+        return;
+    }
+
+    let layout = Layout::array::<FreeElement<T, TIndex>>(capacity)
+        .expect("Attempted to reserve more slots than fit into memory");
+    let Ok(new) = data.allocator().allocate(layout) else {
+        std::alloc::handle_alloc_error(layout);
+    };
+    let new = new.cast::<FreeElement<T, TIndex>>().as_ptr();
+
+    // SAFETY: The new allocation holds `capacity >= len` slots and does not overlap the
+    // old one. After the slots were copied, the old allocation is wiped and released
+    // with the layout it was allocated with; nothing can panic between reading and
+    // writing the vector.
+    unsafe {
+        std::ptr::copy_nonoverlapping(data.as_ptr(), new, len);
+        std::slice::from_raw_parts_mut(
+            data.as_mut_ptr()
+                .cast::<MaybeUninit<FreeElement<T, TIndex>>>(),
+            len,
+        )
+        .zeroize();
+
+        let (old, _, old_capacity, alloc) = std::ptr::read(data).into_raw_parts_with_alloc();
+        if old_capacity != 0 {
+            let old_layout = Layout::array::<FreeElement<T, TIndex>>(old_capacity)
+                .expect("the old layout was valid");
+            alloc.deallocate(NonNull::new_unchecked(old.cast()), old_layout);
+        }
+        std::ptr::write(data, Vec::from_raw_parts_in(new, len, capacity, alloc));
+    }
+}
+
+#[cfg(all(test, feature = "zeroize"))]
+mod tests {
+    use crate::FreeList;
+
+    #[test]
+    fn erased_slots_are_wiped() {
+        let mut list = FreeList::<[u8; 32], u8>::default();
+        for _ in 0..3 {
+            list.push([0xAA; 32]);
+        }
+        list.erase(1);
+
+        // Apart from the link to the next free slot, no byte of the element remains.
+        let slot = &list.data[1] as *const _ as *const u8;
+        let bytes = unsafe { std::slice::from_raw_parts(slot, 32) };
+        assert!(bytes[1..].iter().all(|&byte| byte == 0));
+
+        // Moving the slots into a larger allocation keeps the elements.
+        for _ in 0..100 {
+            list.push([0xBB; 32]);
+        }
+        assert_eq!(list[0], [0xAA; 32]);
+        assert_eq!(list[2], [0xAA; 32]);
+        assert_eq!(list[1], [0xBB; 32]);
+        list.shrink_to(0);
+        assert_eq!(list[101], [0xBB; 32]);
+    }
+}
@@ -0,0 +1,70 @@
+use crate::index_type::IndexType;
+use std::collections::HashMap;
+
+/// The slot bookkeeping of a [`FreeList`](crate::FreeList) of zero-sized elements,
+/// which replaces the slot storage.
+///
+/// Zero-sized elements occupy no memory, so only the number of slots and the links of
+/// the free chain are kept. A slot without a link ends the chain; links are dropped as
+/// soon as their slot is reused, so the memory scales with the number of free slots
+/// instead of the number of slots.
+pub(crate) struct ZstSlots<TIndex>
+where
+    TIndex: IndexType,
+{
+    /// The number of slots that were handed out.
+    pub len: usize,
+    /// The link of every free slot to the next free slot.
+    links: HashMap<usize, TIndex>,
+}
+
+impl<TIndex> ZstSlots<TIndex>
+where
+    TIndex: IndexType,
+{
+    pub fn new() -> Self {
+        Self {
+            len: 0,
+            links: HashMap::new(),
+        }
+    }
+
+    /// Gets the link of a free slot, or `TIndex::MAX` if the slot ends the chain.
+    #[inline]
+    pub fn next(&self, n: usize) -> TIndex {
+        self.links.get(&n).copied().unwrap_or(TIndex::MAX)
+    }
+
+    /// Sets the link of a free slot.
+    #[inline]
+    pub fn set_next(&mut self, n: usize, next: TIndex) {
+        if next == TIndex::MAX {
+            self.links.remove(&n);
+        } else {
+            self.links.insert(n, next);
+        }
+    }
+
+    /// Removes the link of a free slot that is about to be reused and returns it.
+    #[inline]
+    pub fn unlink(&mut self, n: usize) -> TIndex {
+        self.links.remove(&n).unwrap_or(TIndex::MAX)
+    }
+
+    /// Removes all slots at and above `len`.
+    pub fn truncate(&mut self, len: usize) {
+        self.len = self.len.min(len);
+        self.links.retain(|&index, _| index < len);
+    }
+
+    /// Removes all slots.
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.links.clear();
+    }
+
+    /// Gets the number of bytes allocated for the links.
+    pub fn memory_usage(&self) -> usize {
+        self.links.capacity() * std::mem::size_of::<(usize, TIndex)>()
+    }
+}
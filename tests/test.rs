@@ -1,4 +1,8 @@
-use free_list::FreeList;
+use free_list::{define_index_type, FreeList, ReusePolicy, SlotStatus};
+
+define_index_type! {
+    struct EntityId(u8);
+}
 
 #[derive(Default, Debug, PartialEq, PartialOrd)]
 struct Complex(f64, f64);
@@ -12,14 +16,14 @@ impl Drop for Complex {
 #[test]
 fn after_construction_has_no_first_free() {
     let list = FreeList::<Complex, u8>::default();
-    assert_eq!(list.capacity(), 0);
+    assert_eq!(list.slot_count(), 0);
 }
 
 #[test]
 fn after_insertion_has_no_first_free() {
     let mut list = FreeList::<Complex, u8>::default();
     assert_eq!(list.push(Complex::default()), 0);
-    assert_eq!(list.capacity(), 1);
+    assert_eq!(list.slot_count(), 1);
 }
 
 #[test]
@@ -27,7 +31,7 @@ fn after_deletion_has_a_first_free() {
     let mut list = FreeList::<Complex, u8>::default();
     list.push(Complex::default());
     list.erase(0);
-    assert_eq!(list.capacity(), 1);
+    assert_eq!(list.slot_count(), 1);
 }
 
 #[test]
@@ -36,7 +40,7 @@ fn insert_after_delete_has_no_free() {
     list.push(Complex::default());
     list.erase(0);
     list.push(Complex::default());
-    assert_eq!(list.capacity(), 1);
+    assert_eq!(list.slot_count(), 1);
 }
 
 #[test]
@@ -45,7 +49,7 @@ fn first_free_points_to_last_freed_index() {
     insert_some(&mut list, 2);
     list.erase(0);
     list.erase(1);
-    assert_eq!(list.capacity(), 2);
+    assert_eq!(list.slot_count(), 2);
 }
 
 #[test]
@@ -56,7 +60,7 @@ fn erase_in_ascending_order() {
     list.erase(1);
     list.erase(2);
     list.erase(3);
-    assert_eq!(list.capacity(), 4);
+    assert_eq!(list.slot_count(), 4);
 }
 
 #[test]
@@ -67,7 +71,7 @@ fn erase_in_descending_order() {
     list.erase(2);
     list.erase(1);
     list.erase(0);
-    assert_eq!(list.capacity(), 4);
+    assert_eq!(list.slot_count(), 4);
 }
 
 #[test]
@@ -78,7 +82,7 @@ fn erase_in_mixed_order() {
     list.erase(3);
     list.erase(1);
     list.erase(2);
-    assert_eq!(list.capacity(), 4);
+    assert_eq!(list.slot_count(), 4);
 }
 
 #[test]
@@ -88,7 +92,7 @@ fn clear_works() {
     list.erase(1);
     list.clear();
     list.clear();
-    assert_eq!(list.capacity(), 0);
+    assert_eq!(list.slot_count(), 0);
 }
 
 #[test]
@@ -115,8 +119,89 @@ fn at_mut_works() {
     assert_eq!(*element, Complex(0., 0.));
 }
 
+#[test]
+fn keys_works() {
+    let mut list = FreeList::<Complex, u8>::default();
+    insert_some(&mut list, 4);
+    list.erase(1);
+    list.erase(3);
+    assert_eq!(list.keys().collect::<Vec<_>>(), vec![0, 2]);
+}
+
+#[test]
+fn vacant_entry_works() {
+    let mut list = FreeList::<Complex, u8>::default();
+    insert_some(&mut list, 2);
+
+    let entry = list.vacant_entry();
+    let key = entry.key();
+    assert_eq!(key, 2);
+
+    let element = entry.insert(Complex(key as f64, 0.));
+    assert_eq!(*element, Complex(2., 0.));
+    assert_eq!(list.slot_count(), 3);
+}
+
+#[test]
+fn status_works() {
+    let mut list = FreeList::<Complex, u8>::default();
+    insert_some(&mut list, 3);
+    list.erase(1);
+    assert_eq!(list.status(0), SlotStatus::Occupied);
+    assert_eq!(list.status(1), SlotStatus::Free);
+    assert_eq!(list.status(3), SlotStatus::OutOfRange);
+    assert_eq!(list.status(u8::MAX), SlotStatus::OutOfRange);
+}
+
+#[test]
+fn get_disjoint_mut_works() {
+    let mut list = FreeList::<Complex, u8>::default();
+    list.push(Complex(1., 2.));
+    list.push(Complex(3., 4.));
+
+    let [a, b] = list.get_disjoint_mut([0, 1]).unwrap();
+    std::mem::swap(a, b);
+    assert_eq!(unsafe { list.at(0) }, &Complex(3., 4.));
+    assert_eq!(unsafe { list.at(1) }, &Complex(1., 2.));
+    assert!(list.get_disjoint_mut([1, 1]).is_err());
+}
+
+#[test]
+fn swap_works() {
+    let mut list = FreeList::<Complex, u8>::default();
+    list.push(Complex(1., 2.));
+    list.push(Complex(3., 4.));
+    list.swap(1, 0);
+    assert_eq!(unsafe { list.at(0) }, &Complex(3., 4.));
+    assert_eq!(unsafe { list.at(1) }, &Complex(1., 2.));
+}
+
+#[test]
+fn newtype_index_works() {
+    let mut list = FreeList::<Complex, EntityId>::default();
+    let first = list.push(Complex(1., 2.));
+    let second = list.push(Complex(3., 4.));
+    assert_eq!(first, EntityId(0));
+    assert_eq!(second, EntityId(1));
+
+    list.erase(first);
+    assert_eq!(list.keys().collect::<Vec<_>>(), vec![second]);
+    assert_eq!(list.push(Complex::default()), first);
+}
+
+#[test]
+#[should_panic]
+#[cfg_attr(not(any(debug_assertions, feature = "checked")), ignore)]
+fn newtype_index_inserting_too_many_panics() {
+    let mut list = FreeList::<Complex, EntityId>::default();
+    for i in 0..255 {
+        list.push(Complex(i as f64, 2.));
+    }
+}
+
 #[test]
 #[should_panic]
+#[cfg_attr(not(any(debug_assertions, feature = "checked")), ignore)]
 fn inserting_too_many_panics() {
     let mut list = FreeList::<Complex, u8>::default();
 
@@ -174,3 +259,67 @@ fn insert_some(list: &mut FreeList<Complex, u8>, n: usize) {
         list.push(Complex::default());
     }
 }
+
+#[test]
+fn reuse_policies_work() {
+    let mut fifo = FreeList::<Complex, u8>::with_reuse_policy(ReusePolicy::Fifo);
+    let mut lowest = FreeList::<Complex, u8>::with_reuse_policy(ReusePolicy::LowestIndexFirst);
+    for list in [&mut fifo, &mut lowest] {
+        insert_some(list, 5);
+        list.erase(3);
+        list.erase(1);
+    }
+    assert_eq!(fifo.push(Complex::default()), 3);
+    assert_eq!(lowest.push(Complex::default()), 1);
+}
+
+#[test]
+fn sort_free_chain_works() {
+    let mut list = FreeList::<Complex, u8>::default();
+    insert_some(&mut list, 5);
+    list.erase(4);
+    list.erase(1);
+    list.erase(2);
+    list.sort_free_chain();
+    assert_eq!(list.push(Complex::default()), 1);
+    assert_eq!(list.push(Complex::default()), 2);
+    assert_eq!(list.push(Complex::default()), 4);
+}
+
+#[test]
+fn stats_work() {
+    let mut list = FreeList::<Complex, u8>::default();
+    insert_some(&mut list, 4);
+    list.erase(3);
+    let stats = list.stats();
+    assert_eq!(
+        (stats.live, stats.free, stats.total, stats.span),
+        (3, 1, 4, 3)
+    );
+    assert_eq!(stats.fragmentation(), 0.0);
+}
+
+#[test]
+fn slab_api_works() {
+    let mut list = FreeList::<Complex, u8>::default();
+    let key = list.insert(Complex(1., 2.));
+    list.insert(Complex(3., 4.));
+    assert_eq!(list[key], Complex(1., 2.));
+    assert_eq!(list.remove(key), Complex(1., 2.));
+    assert_eq!(list.get(key), None);
+    assert!(list.contains(1));
+    assert_eq!(list.len(), 1);
+    assert_eq!(list.iter().map(|(key, _)| key).collect::<Vec<_>>(), vec![1]);
+}
+
+#[test]
+fn keys_detect_reused_slots() {
+    let mut list = FreeList::<Complex, u8>::default();
+    let first = list.push_key(Complex(1., 2.));
+    list.erase(first.index());
+    let second = list.push_key(Complex(3., 4.));
+    assert_eq!(first.index(), second.index());
+    assert_ne!(first, second);
+    assert!(!list.contains_key(first));
+    assert!(list.contains_key(second));
+}